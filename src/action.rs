@@ -0,0 +1,137 @@
+//! Actions for the shell wrapper: what the shell should do after `wt` exits
+//! (change directory, open an editor, run a command), since a child process
+//! can't change its parent shell's directory.
+//!
+//! Two protocols exist:
+//!
+//! - v1: a `cd|PATH`, `edit|PATH` or `run|COMMAND` line on stdout, which the
+//!   wrapper captures. Breaks on paths containing `|` or newlines.
+//! - v2: the wrapper exports `WT_SHELL_PROTOCOL` (the highest version it
+//!   understands) and `WT_ACTION_FILE` (an empty file it created), and `wt`
+//!   writes NUL-terminated fields to that file: the protocol version, the
+//!   action and its argument. Stdout is left alone.
+//!
+//! Without both variables (an older wrapper), actions use v1.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::error::WtError;
+
+/// Newest protocol version this binary speaks.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Environment variable with the highest protocol version the wrapper speaks.
+const PROTOCOL_ENV: &str = "WT_SHELL_PROTOCOL";
+
+/// Environment variable with the path of the file v2 actions are written to.
+const ACTION_FILE_ENV: &str = "WT_ACTION_FILE";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellAction {
+    /// Change into a directory
+    Cd(PathBuf),
+    /// Change into a directory and open `$EDITOR` there
+    Edit(PathBuf),
+    /// Evaluate a shell command
+    Run(String),
+}
+
+impl ShellAction {
+    fn name(&self) -> &'static str {
+        match self {
+            ShellAction::Cd(_) => "cd",
+            ShellAction::Edit(_) => "edit",
+            ShellAction::Run(_) => "run",
+        }
+    }
+
+    fn argument(&self) -> OsString {
+        match self {
+            ShellAction::Cd(path) | ShellAction::Edit(path) => path.clone().into_os_string(),
+            ShellAction::Run(command) => command.into(),
+        }
+    }
+
+    /// The v1 line, e.g. `cd|/path/to/worktree`.
+    fn line(&self) -> String {
+        format!("{}|{}", self.name(), self.argument().to_string_lossy())
+    }
+
+    /// The v2 record: version, action and argument, each ending in NUL.
+    fn record(&self) -> Vec<u8> {
+        let mut record = Vec::new();
+        for field in [
+            OsString::from(PROTOCOL_VERSION.to_string()),
+            OsString::from(self.name()),
+            self.argument(),
+        ] {
+            record.extend_from_slice(field.as_encoded_bytes());
+            record.push(0);
+        }
+        record
+    }
+}
+
+/// Hand `action` to the shell wrapper, in the newest protocol it speaks.
+pub fn emit(action: ShellAction) -> Result<()> {
+    let Some(file) = action_file(
+        std::env::var(PROTOCOL_ENV).ok().as_deref(),
+        std::env::var_os(ACTION_FILE_ENV),
+    ) else {
+        println!("{}", action.line());
+        return Ok(());
+    };
+    fs::write(&file, action.record()).map_err(|e| {
+        WtError::io_error_with_source(
+            format!("failed to write shell action to {}", file.display()),
+            e.into(),
+        )
+    })?;
+    Ok(())
+}
+
+/// The file to write v2 actions to, given the wrapper's `WT_SHELL_PROTOCOL`
+/// and `WT_ACTION_FILE`; `None` means v1.
+fn action_file(protocol: Option<&str>, file: Option<OsString>) -> Option<PathBuf> {
+    let version: u32 = protocol?.trim().parse().ok()?;
+    let file = file.filter(|f| !f.is_empty())?;
+    (version.min(PROTOCOL_VERSION) >= 2).then(|| PathBuf::from(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_protocol_version() {
+        let file = || Some(OsString::from("/tmp/wt-action"));
+        assert_eq!(action_file(None, file()), None);
+        assert_eq!(action_file(Some("1"), file()), None);
+        assert_eq!(action_file(Some("junk"), file()), None);
+        assert_eq!(action_file(Some("2"), None), None);
+        assert_eq!(
+            action_file(Some("2"), file()),
+            Some(PathBuf::from("/tmp/wt-action"))
+        );
+        // A newer wrapper gets the newest version this binary speaks
+        assert_eq!(
+            action_file(Some("7"), file()),
+            Some(PathBuf::from("/tmp/wt-action"))
+        );
+    }
+
+    #[test]
+    fn encodes_awkward_paths() {
+        let action = ShellAction::Cd(PathBuf::from("/repo/a|b\nc"));
+        assert_eq!(action.line(), "cd|/repo/a|b\nc");
+        assert_eq!(action.record(), b"2\0cd\0/repo/a|b\nc\0");
+        assert_eq!(
+            ShellAction::Run("vim .".to_string()).record(),
+            b"2\0run\0vim .\0"
+        );
+    }
+}
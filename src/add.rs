@@ -1,48 +1,122 @@
+use std::collections::HashSet;
 use std::fs;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::{Duration, UNIX_EPOCH};
 
 use anyhow::Result;
 use serde::Serialize;
 
 use crate::error::WtError;
-use crate::{config, git, process};
+use crate::{config, git, hooks, integrations, preview, process, state, vscode, worktree};
+
+/// A worktree created by [`create_worktree`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AddedWorktree {
+    pub branch: String,
+    pub path: PathBuf,
+    /// Remote branch the new branch tracks (`origin/feature-x`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracking: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beads_redirect: Option<String>,
+    /// Shared git hooks directory wired into the worktree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_hooks: Option<String>,
+    /// Whether `direnv allow` ran for the worktree's `.envrc`
+    pub direnv_allowed: bool,
+    /// The default path, when it was taken and a `-2` (`-3`, ...) suffixed
+    /// one was used instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occupied_path: Option<PathBuf>,
+}
 
 /// Result of adding a worktree (for JSON output)
 #[derive(Serialize)]
 struct AddResult {
     success: bool,
-    branch: String,
-    path: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    tracking: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    beads_redirect: Option<String>,
+    #[serde(flatten)]
+    worktree: AddedWorktree,
 }
 
 /// Interactive add: show fzf picker with available branches, then create worktree.
+/// With `remote`, only that remote's branches are offered besides local ones.
+/// Returns its path, or a cancellation error if the user backed out.
 pub fn interactive_add(
     path: Option<&str>,
     track: Option<&str>,
+    remote: Option<&str>,
     beads: bool,
+    force_path: bool,
     json: bool,
     quiet: bool,
-) -> Result<()> {
+) -> Result<PathBuf> {
     let repo_root = git::repo_root(None)?;
 
-    // Get available branches (local + remote, excluding ones that already have worktrees)
-    let mut branches = get_available_branches(&repo_root)?;
+    match pick_branch(&repo_root, remote)? {
+        Some(picked) => add_worktree(
+            &picked.name,
+            path,
+            track.or(picked.remote.as_deref()),
+            beads,
+            force_path,
+            json,
+            quiet,
+        ),
+        None => Err(WtError::cancelled().into()),
+    }
+}
+
+/// A branch picked for a new worktree.
+pub struct PickedBranch {
+    /// The local branch name
+    pub name: String,
+    /// Remote to create the branch from, when only a remote has it
+    pub remote: Option<String>,
+}
+
+/// Let the user pick a branch (or enter a new branch name) for a new worktree.
+/// With `remote`, other remotes' branches are left out; without, a branch on
+/// several remotes is only offered from `default_remote` if that has it.
+/// Returns `None` if the user cancelled.
+pub fn pick_branch(repo_root: &Path, remote: Option<&str>) -> Result<Option<PickedBranch>> {
+    // Branches without a worktree (local + remote), most recently committed first
+    let available: HashSet<String> = get_available_branches(repo_root)?.into_iter().collect();
+    let all_tips = git::branch_tips(repo_root)?;
+    let locals: HashSet<&str> = all_tips
+        .iter()
+        .filter(|tip| tip.refname.starts_with("refs/heads/"))
+        .map(|tip| tip.name())
+        .collect();
+    let default = default_remote(repo_root)?;
+    let on_default: HashSet<&str> = all_tips
+        .iter()
+        .filter(|tip| remote_of(&tip.refname) == Some(&default))
+        .map(|tip| local_name(&tip.refname))
+        .collect();
+    let tips: Vec<_> = all_tips
+        .iter()
+        .filter(|tip| available.contains(tip.name()))
+        .filter(|tip| match (remote_of(&tip.refname), remote) {
+            (None, _) => true,
+            (Some(from), Some(wanted)) => from == wanted,
+            (Some(from), None) => from == default || !on_default.contains(local_name(&tip.refname)),
+        })
+        .cloned()
+        .collect();
 
-    // Add option to create a new branch at the top
-    let create_new_option = "[+] Create new branch...";
-    branches.insert(0, create_new_option.to_string());
+    // Add option to create a new branch at the top; its preview shows HEAD,
+    // where the new branch starts
+    let create_new_option = "HEAD\t[+] Create new branch...";
+    let mut rows = vec![create_new_option.to_string()];
+    rows.extend(branch_rows(&tips));
 
     // Run fzf to select a branch
-    let selected = run_fzf_branch_picker(&branches)?;
+    let selected = run_fzf_branch_picker(repo_root, &rows)?;
 
     match selected {
-        Some(branch) if branch == create_new_option => {
+        Some(row) if row == create_new_option => {
             // Prompt for new branch name
             eprint!("Enter new branch name: ");
             std::io::stderr().flush()?;
@@ -52,71 +126,306 @@ pub fn interactive_add(
             let new_branch = new_branch.trim();
 
             if new_branch.is_empty() {
-                eprintln!("Cancelled.");
-                return Ok(());
+                return Ok(None);
             }
 
-            add_worktree(new_branch, path, track, beads, json, quiet)
-        }
-        Some(branch) => {
-            // Strip remote prefix if present (e.g., "origin/feature" -> "feature")
-            let branch_name = if let Some(stripped) = branch.strip_prefix("origin/") {
-                stripped
-            } else if let Some(pos) = branch.find('/') {
-                // Handle other remotes like "upstream/feature"
-                &branch[pos + 1..]
-            } else {
-                &branch
-            };
-
-            add_worktree(branch_name, path, track, beads, json, quiet)
+            Ok(Some(PickedBranch {
+                name: new_branch.to_string(),
+                remote: None,
+            }))
         }
-        None => {
-            // User cancelled
-            Ok(())
+        Some(row) => {
+            let refname = row.split('\t').next().unwrap_or(&row);
+            let name = local_name(refname);
+            Ok(Some(PickedBranch {
+                name: name.to_string(),
+                // A local branch of the same name is used as is
+                remote: remote_of(refname)
+                    .filter(|_| !locals.contains(name))
+                    .map(str::to_string),
+            }))
         }
+        None => Ok(None),
+    }
+}
+
+/// The remote to create `branch` from, for a branch only remotes have:
+/// `remote` if given, else the one remote that has it, else
+/// `default_remote` if it's among them. `None` for a local branch or one no
+/// remote has, and an `ambiguous_remote` error when several remotes have it
+/// and there's no telling which.
+pub fn tracking_remote(
+    repo_root: &Path,
+    branch: &str,
+    remote: Option<&str>,
+) -> Result<Option<String>> {
+    if git::local_branches(repo_root)?.iter().any(|b| b == branch) {
+        return Ok(None);
+    }
+    if let Some(remote) = remote {
+        return Ok(Some(remote.to_string()));
+    }
+    let names = git::remote_branches(repo_root)?;
+    let remotes: Vec<String> = git::remotes(repo_root)?
+        .into_iter()
+        .filter(|r| names.contains(&format!("{}/{}", r, branch)))
+        .collect();
+    if remotes.len() <= 1 {
+        return Ok(remotes.into_iter().next());
+    }
+    let default = default_remote(repo_root)?;
+    if remotes.contains(&default) {
+        return Ok(Some(default));
+    }
+    Err(WtError::user_error(format!(
+        "branch '{}' is on several remotes ({}); choose one with --remote",
+        branch,
+        remotes.join(", ")
+    ))
+    .with_reason("ambiguous_remote")
+    .with_branch(branch)
+    .with_hint("pass --remote NAME, or set default_remote in the config")
+    .with_candidates(
+        remotes
+            .iter()
+            .map(|r| format!("{}/{}", r, branch))
+            .collect(),
+    )
+    .into())
+}
+
+/// `default_remote` for the repository at `repo_root`: its `repos` entry's,
+/// else the global one; empty when neither is set.
+fn default_remote(repo_root: &Path) -> Result<String> {
+    let config = config::load()?;
+    let repo_remote = git::worktrees_porcelain(repo_root)
+        .ok()
+        .and_then(|worktrees| worktrees.into_iter().next())
+        .and_then(|main| config.repo(&main.path))
+        .map(|repo| repo.default_remote.clone());
+    Ok(repo_remote
+        .into_iter()
+        .chain(Some(config.default_remote))
+        .find(|r| !r.is_empty())
+        .unwrap_or_default())
+}
+
+/// Longest author name shown in the branch picker
+const MAX_AUTHOR_WIDTH: usize = 20;
+
+/// Branch picker rows: the ref, a tab, then the branch name, the age of its
+/// last commit, the author and the subject in aligned columns. fzf shows
+/// what follows the tab and previews the ref's log.
+fn branch_rows(tips: &[git::BranchTip]) -> Vec<String> {
+    let ages: Vec<String> = tips
+        .iter()
+        .map(|tip| preview::format_age(UNIX_EPOCH + Duration::from_secs(tip.time)))
+        .collect();
+    let authors: Vec<String> = tips
+        .iter()
+        .map(|tip| tip.author.chars().take(MAX_AUTHOR_WIDTH).collect())
+        .collect();
+    let name_width = tips
+        .iter()
+        .map(|tip| tip.name().chars().count())
+        .max()
+        .unwrap_or(0);
+    let age_width = ages
+        .iter()
+        .map(|age| age.chars().count())
+        .max()
+        .unwrap_or(0);
+    let author_width = authors
+        .iter()
+        .map(|author| author.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    tips.iter()
+        .zip(ages.iter().zip(&authors))
+        .map(|(tip, (age, author))| {
+            format!(
+                "{}\t{:name_width$}  {:age_width$}  {:author_width$}  {}",
+                tip.refname,
+                tip.name(),
+                age,
+                author,
+                tip.subject,
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect()
+}
+
+/// The remote of a remote-tracking ref: `origin` for
+/// `refs/remotes/origin/feature`, `None` for a local branch.
+fn remote_of(refname: &str) -> Option<&str> {
+    refname
+        .strip_prefix("refs/remotes/")
+        .and_then(|name| name.split_once('/'))
+        .map(|(remote, _)| remote)
+}
+
+/// The local branch for a picked ref: `refs/heads/feature/x` is
+/// `feature/x`, and `refs/remotes/origin/feature` is `feature`.
+fn local_name(refname: &str) -> &str {
+    if let Some(name) = refname.strip_prefix("refs/heads/") {
+        return name;
     }
+    refname
+        .strip_prefix("refs/remotes/")
+        .and_then(|name| name.split_once('/'))
+        .map_or(refname, |(_remote, name)| name)
 }
 
 /// Add a new worktree for the given branch.
 /// - branch: the branch name to create a worktree for
 /// - path: optional custom path (defaults to sibling directory named after branch)
 /// - track: optional remote to track (e.g., "origin")
+/// - force_path: use the default path even if it exists (git accepts an
+///   empty directory) instead of a `-2` suffixed one
 /// - json: output result as JSON
 /// - quiet: suppress non-essential output
+///
+/// Returns the path of the new worktree.
 pub fn add_worktree(
     branch: &str,
     path: Option<&str>,
     track: Option<&str>,
     beads: bool,
+    force_path: bool,
     json: bool,
     quiet: bool,
-) -> Result<()> {
+) -> Result<PathBuf> {
     // Get the current repository root
     let repo_root = git::repo_root(None)?;
+    // A given path is used as is, so --force-path passes the default one
+    let default_path = match path {
+        None if force_path => Some(
+            calculate_default_path(&repo_root, branch, &config::load()?.sanitize)?
+                .to_string_lossy()
+                .into_owned(),
+        ),
+        _ => None,
+    };
+    add_worktree_in(
+        &repo_root,
+        branch,
+        path.or(default_path.as_deref()),
+        track,
+        beads,
+        json,
+        quiet,
+    )
+}
+
+/// Add a worktree to the repository at `repo_root`; see [`add_worktree`].
+/// When the default path is taken, asks on a terminal whether to use a `-2`
+/// (`-3`, ...) suffixed one instead, and uses it without asking otherwise.
+/// Returns the path of the new worktree.
+pub fn add_worktree_in(
+    repo_root: &Path,
+    branch: &str,
+    path: Option<&str>,
+    track: Option<&str>,
+    beads: bool,
+    json: bool,
+    quiet: bool,
+) -> Result<PathBuf> {
+    let (path, occupied_path) = match path {
+        Some(path) => (PathBuf::from(path), None),
+        None => {
+            let default = calculate_default_path(repo_root, branch, &config::load()?.sanitize)?;
+            if default.exists() {
+                let free = free_path(&default);
+                let ask = !quiet && !json && std::io::stdin().is_terminal();
+                if ask && !confirm_free_path(&default, &free)? {
+                    return Err(WtError::cancelled().into());
+                }
+                if !ask && !quiet && !json {
+                    eprintln!(
+                        "{} already exists; using {}",
+                        default.display(),
+                        free.display()
+                    );
+                }
+                (free, Some(default))
+            } else {
+                (default, None)
+            }
+        }
+    };
+    // Display what we're doing (unless quiet or json)
+    if !quiet && !json {
+        eprintln!("Creating worktree at: {}", path.display());
+    }
+
+    let mut added = create_worktree(repo_root, branch, Some(&path), track, beads)?;
+    added.occupied_path = occupied_path;
+
+    if json {
+        let path = added.path.clone();
+        let result = AddResult {
+            success: true,
+            worktree: added,
+        };
+        println!("{}", serde_json::to_string(&result)?);
+        return Ok(path);
+    }
+    if !quiet {
+        eprintln!("Worktree created successfully");
+        if let Some(redirect) = &added.beads_redirect {
+            eprintln!("Configured beads redirect: {}", redirect);
+        }
+        if let Some(dir) = &added.git_hooks {
+            eprintln!("Configured shared git hooks: {}", dir);
+        }
+        if added.direnv_allowed {
+            eprintln!("Allowed .envrc with direnv");
+        }
+    }
+    Ok(added.path)
+}
 
+/// Create a worktree for `branch` in the repository at `repo_root`, without
+/// printing anything but warnings.
+/// - path: where to put it (defaults to [`calculate_default_path`])
+/// - track: remote to create the branch from and track (e.g. "origin")
+/// - beads: redirect the worktree's beads database to the main one
+///
+/// Existing branches are checked out; others are created from HEAD. Runs the
+/// `post-add` hooks and records the worktree in wt's state.
+pub fn create_worktree(
+    repo_root: &Path,
+    branch: &str,
+    path: Option<&Path>,
+    track: Option<&str>,
+    beads: bool,
+) -> Result<AddedWorktree> {
     // Determine the target path
-    let target_path = if let Some(custom_path) = path {
-        PathBuf::from(custom_path)
-    } else {
-        calculate_default_path(&repo_root, branch)?
+    let target_path = match path {
+        Some(custom_path) => custom_path.to_path_buf(),
+        None => calculate_default_path(repo_root, branch, &config::load()?.sanitize)?,
     };
 
-    // Check if the path already exists
-    if target_path.exists() {
+    // Check if the path already exists (git takes an empty directory)
+    if target_path.exists() && !is_empty_dir(&target_path) {
         return Err(WtError::user_error(format!(
             "path already exists: {}\nChoose a different path with --path",
             target_path.display()
         ))
+        .with_reason("path_exists")
+        .with_path(&target_path)
+        .with_branch(branch)
+        .with_hint("choose a different path with --path")
         .into());
     }
 
     // Check if a worktree for this branch already exists
-    check_existing_worktree(&repo_root, branch)?;
-
-    // Display what we're doing (unless quiet or json)
-    if !quiet && !json {
-        eprintln!("Creating worktree at: {}", target_path.display());
+    check_existing_worktree(repo_root, branch)?;
+    if let Some(remote) = track {
+        check_remote_branch(repo_root, remote, branch)?;
     }
 
     // Execute the git worktree add command
@@ -136,7 +445,7 @@ pub fn add_worktree(
                     .ok_or_else(|| WtError::io_error("invalid path encoding"))?,
                 &remote_branch,
             ],
-            Some(&repo_root),
+            Some(repo_root),
         )
         .map_err(|e| {
             WtError::git_error_with_source(
@@ -144,7 +453,7 @@ pub fn add_worktree(
                 e,
             )
         })?;
-    } else if branch_exists(&repo_root, branch)? {
+    } else if branch_exists(repo_root, branch)? {
         // Branch exists, just add worktree for it
         process::run(
             "git",
@@ -156,7 +465,7 @@ pub fn add_worktree(
                     .ok_or_else(|| WtError::io_error("invalid path encoding"))?,
                 branch,
             ],
-            Some(&repo_root),
+            Some(repo_root),
         )
         .map_err(|e| WtError::git_error_with_source("failed to add worktree", e))?;
     } else {
@@ -172,7 +481,7 @@ pub fn add_worktree(
                     .to_str()
                     .ok_or_else(|| WtError::io_error("invalid path encoding"))?,
             ],
-            Some(&repo_root),
+            Some(repo_root),
         )
         .map_err(|e| {
             WtError::git_error_with_source(
@@ -182,25 +491,66 @@ pub fn add_worktree(
         })?;
     }
 
-    let beads_redirect = configure_beads_redirect(&repo_root, &target_path, beads)?;
+    let beads_redirect = configure_beads_redirect(repo_root, &target_path, beads)?;
+    // The worktree exists at this point; missing hooks shouldn't fail the add
+    let git_hooks = configure_git_hooks(repo_root, &target_path).unwrap_or_else(|e| {
+        eprintln!("Warning: failed to set up shared git hooks: {:#}", e);
+        None
+    });
+    let direnv_allowed = integrations::direnv_allow(&target_path);
 
-    if json {
-        let result = AddResult {
-            success: true,
-            branch: branch.to_string(),
-            path: target_path.to_string_lossy().to_string(),
-            tracking: track.map(|r| format!("{}/{}", r, branch)),
-            beads_redirect,
-        };
-        println!("{}", serde_json::to_string(&result)?);
-    } else if !quiet {
-        eprintln!("Worktree created successfully");
-        if let Some(redirect) = beads_redirect {
-            eprintln!("Configured beads redirect: {}", redirect);
-        }
-    }
+    // Key state by the absolute path git reports, even for a relative --path
+    let state_path = target_path
+        .canonicalize()
+        .unwrap_or_else(|_| target_path.clone());
+    state::update_or_warn(|s| s.record_created(&state_path, state::now()));
+    integrations::zoxide_add(&state_path);
+    vscode::refresh(repo_root);
 
-    Ok(())
+    hooks::run(hooks::Event::PostAdd, &target_path, Some(branch), repo_root).map_err(|e| {
+        WtError::user_error(format!(
+            "{:#} (the worktree was created at {})",
+            e,
+            target_path.display()
+        ))
+    })?;
+
+    Ok(AddedWorktree {
+        branch: branch.to_string(),
+        path: target_path,
+        tracking: track.map(|r| format!("{}/{}", r, branch)),
+        beads_redirect,
+        git_hooks,
+        direnv_allowed,
+        occupied_path: None,
+    })
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+/// `path` with the first `-2`, `-3`, ... suffix that isn't taken.
+fn free_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    (2..)
+        .map(|n| path.with_file_name(format!("{}-{}", name, n)))
+        .find(|candidate| !candidate.exists())
+        .expect("some suffix is free")
+}
+
+/// Ask whether to create the worktree at `free` since `taken` exists.
+/// Anything but "n" is a yes.
+fn confirm_free_path(taken: &Path, free: &Path) -> Result<bool> {
+    eprint!(
+        "{} already exists. Create the worktree at {} instead? (Y/n): ",
+        taken.display(),
+        free.display()
+    );
+    std::io::stderr().flush()?;
+    let mut response = String::new();
+    std::io::stdin().read_line(&mut response)?;
+    Ok(!matches!(response.trim(), "n" | "N"))
 }
 
 fn configure_beads_redirect(
@@ -233,9 +583,151 @@ fn configure_beads_redirect(
     Ok(Some(redirect_path.display().to_string()))
 }
 
+/// Make the shared hooks directory from the `git_hooks` config active in the
+/// worktree at `target_path`. Returns the directory, or `None` when the
+/// feature is off or `core.hooksPath` already points elsewhere.
+fn configure_git_hooks(repo_root: &Path, target_path: &Path) -> Result<Option<String>> {
+    apply_git_hooks(&config::load()?.git_hooks, repo_root, target_path)
+}
+
+fn apply_git_hooks(
+    config: &config::GitHooksConfig,
+    repo_root: &Path,
+    target_path: &Path,
+) -> Result<Option<String>> {
+    if config.dir.is_empty() {
+        return Ok(None);
+    }
+    let dir = repo_root.join(&config.dir);
+    let dir = dir.canonicalize().map_err(|e| {
+        WtError::io_error_with_source(
+            format!("shared hooks directory {} not found", dir.display()),
+            e.into(),
+        )
+    })?;
+
+    match config.mode.as_str() {
+        "hooks-path" => {
+            // Git resolves a relative hooksPath against the worktree a hook
+            // runs in, so `.githooks` keeps working after the worktree wt add
+            // ran in is removed
+            let existing =
+                process::run_stdout("git", &["config", "core.hooksPath"], Some(target_path))
+                    .map(|v| v.trim().to_string())
+                    .unwrap_or_default();
+            if existing == config.dir {
+                return Ok(Some(config.dir.clone()));
+            }
+            if !existing.is_empty() {
+                eprintln!(
+                    "Warning: core.hooksPath is already set to {}; not changing it to {}",
+                    existing, config.dir
+                );
+                return Ok(None);
+            }
+            // Linked worktrees share the repository config, so the setting
+            // only becomes per-worktree when the repository opted into
+            // worktree-specific config
+            let per_worktree = process::run_stdout(
+                "git",
+                &["config", "--bool", "extensions.worktreeConfig"],
+                Some(target_path),
+            )
+            .is_ok_and(|v| v.trim() == "true");
+            let mut args = vec!["config"];
+            if per_worktree {
+                args.push("--worktree");
+            }
+            args.extend(["core.hooksPath", &config.dir]);
+            process::run("git", &args, Some(target_path))
+                .map_err(|e| WtError::git_error_with_source("failed to set core.hooksPath", e))?;
+            Ok(Some(config.dir.clone()))
+        }
+        "symlink" => {
+            link_hooks(&dir, target_path)?;
+            Ok(Some(dir.to_string_lossy().to_string()))
+        }
+        other => Err(WtError::user_error(format!(
+            "unknown git_hooks.mode '{}' (expected hooks-path or symlink)",
+            other
+        ))
+        .into()),
+    }
+}
+
+/// Symlink every file in `shared` into the hooks directory git uses for the
+/// worktree at `target_path`. Existing hooks that aren't such links are left
+/// alone with a warning.
+fn link_hooks(shared: &Path, target_path: &Path) -> Result<()> {
+    let hooks_dir = process::run_stdout(
+        "git",
+        &["rev-parse", "--git-path", "hooks"],
+        Some(target_path),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to find the hooks directory", e))?;
+    let hooks_dir = target_path.join(hooks_dir.trim());
+    fs::create_dir_all(&hooks_dir)?;
+
+    let entries = fs::read_dir(shared).map_err(|e| {
+        WtError::io_error_with_source(format!("failed to read {}", shared.display()), e.into())
+    })?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let source = entry.path();
+        if !source.is_file() {
+            continue;
+        }
+        let link = hooks_dir.join(entry.file_name());
+        if let Ok(existing) = fs::read_link(&link) {
+            if existing == source {
+                continue;
+            }
+            fs::remove_file(&link)?;
+        } else if link.exists() {
+            eprintln!(
+                "Warning: keeping existing hook {} (not linked to {})",
+                link.display(),
+                shared.display()
+            );
+            continue;
+        }
+        symlink(&source, &link).map_err(|e| {
+            WtError::io_error_with_source(format!("failed to link {}", link.display()), e.into())
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, link)
+}
+
+/// Needs Developer Mode or an elevated shell.
+#[cfg(windows)]
+fn symlink(source: &Path, link: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(source, link)
+    } else {
+        std::os::windows::fs::symlink_file(source, link)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_source: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "symlinked hooks aren't supported on this platform; use git_hooks.mode: hooks-path",
+    ))
+}
+
 /// Calculate the default path for a worktree based on the branch name.
-/// Pattern: <repo_root_parent>/<repo_name>-<branch_sanitized>
-fn calculate_default_path(repo_root: &Path, branch: &str) -> Result<PathBuf> {
+/// Pattern: <repo_root_parent>/<repo_name>-<branch_sanitized>, with the
+/// branch sanitized by `rules` (the `sanitize` config).
+pub fn calculate_default_path(
+    repo_root: &Path,
+    branch: &str,
+    rules: &config::SanitizeConfig,
+) -> Result<PathBuf> {
     // Get the parent directory of the repo root
     let repo_parent = repo_root
         .parent()
@@ -247,39 +739,115 @@ fn calculate_default_path(repo_root: &Path, branch: &str) -> Result<PathBuf> {
         .and_then(|n| n.to_str())
         .ok_or_else(|| WtError::io_error("failed to extract repository name"))?;
 
-    // Sanitize the branch name: replace / with -
-    let sanitized_branch = branch.replace('/', "-");
+    let sanitized_branch = sanitize_branch(branch, rules, cfg!(windows));
 
     // Construct the path: <parent>/<repo_name>-<branch_sanitized>
     let worktree_dir_name = format!("{}-{}", repo_name, sanitized_branch);
     Ok(repo_parent.join(worktree_dir_name))
 }
 
+/// Make a branch name usable in a directory name: applies the `rules`
+/// replacements, then `/` left over becomes `-`. On Windows, so do the
+/// characters file names can't contain (git allows `"`, `<`, `>` and `|` in
+/// branch names), and trailing dots are dropped. Finally lowercases and
+/// shortens it as configured.
+fn sanitize_branch(branch: &str, rules: &config::SanitizeConfig, windows: bool) -> String {
+    let mut name = rules
+        .replace
+        .iter()
+        .fold(branch.to_string(), |name, (from, to)| {
+            name.replace(from.as_str(), to)
+        });
+    name = if windows {
+        name.replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "-")
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        name.replace('/', "-")
+    };
+    if rules.lowercase {
+        name = name.to_lowercase();
+    }
+    if rules.max_length > 0 && name.chars().count() > rules.max_length {
+        name = name.chars().take(rules.max_length).collect();
+        name.truncate(name.trim_end_matches(['-', '.']).len());
+    }
+    name
+}
+
 /// Check if a branch exists (local or remote).
 fn branch_exists(repo_root: &Path, branch: &str) -> Result<bool> {
     // Check local branches
     let local_ref = format!("refs/heads/{}", branch);
-    let result = std::process::Command::new("git")
-        .args(["show-ref", "--verify", "--quiet", &local_ref])
-        .current_dir(repo_root)
-        .status()
-        .map_err(|e| WtError::git_error_with_source("failed to run git show-ref", e.into()))?;
+    let result = process::status(
+        std::process::Command::new("git")
+            .args(["show-ref", "--verify", "--quiet", &local_ref])
+            .current_dir(repo_root),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to run git show-ref", e.into()))?;
 
     if result.success() {
         return Ok(true);
     }
 
     // Check remote branches (any remote)
-    let output = std::process::Command::new("git")
-        .args(["branch", "-r", "--list", &format!("*/{}", branch)])
-        .current_dir(repo_root)
-        .output()
-        .map_err(|e| WtError::git_error_with_source("failed to run git branch -r", e.into()))?;
+    let output = process::output(
+        std::process::Command::new("git")
+            .args(["branch", "-r", "--list", &format!("*/{}", branch)])
+            .current_dir(repo_root),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to run git branch -r", e.into()))?;
 
     let remote_branches = String::from_utf8_lossy(&output.stdout);
     Ok(!remote_branches.trim().is_empty())
 }
 
+/// Make sure `<remote>/<branch>` is there to track, fetching the branch if
+/// it isn't yet. Fails before git does, suggesting close names, when
+/// `remote` has no branch `branch`.
+fn check_remote_branch(repo_root: &Path, remote: &str, branch: &str) -> Result<()> {
+    let prefix = format!("{}/", remote);
+    let remote_names = || -> Result<Vec<String>> {
+        Ok(git::remote_branches(repo_root)?
+            .iter()
+            .filter_map(|b| b.strip_prefix(&prefix))
+            .map(str::to_string)
+            .collect())
+    };
+    let mut names = remote_names()?;
+    if names.iter().any(|name| name == branch) {
+        return Ok(());
+    }
+
+    // Pushed since the last fetch, perhaps: fetch just that branch, which
+    // also updates <remote>/<branch>
+    tracing::debug!(%remote, %branch, "remote branch not fetched yet, fetching it");
+    match process::run("git", &["fetch", remote, branch], Some(repo_root)) {
+        Ok(_) => {
+            names = remote_names()?;
+            if names.iter().any(|name| name == branch) {
+                tracing::debug!(%remote, %branch, "fetched remote branch");
+                return Ok(());
+            }
+        }
+        Err(e) => tracing::debug!(error = %format!("{:#}", e), "fetching remote branch failed"),
+    }
+
+    let similar = worktree::similar_names(&names, branch);
+    let mut message = format!("no branch '{}' on remote '{}'", branch, remote);
+    if !similar.is_empty() {
+        message.push_str(&format!("; did you mean {}?", similar.join(", ")));
+    }
+    Err(WtError::not_found(message)
+        .with_branch(branch)
+        .with_hint(format!(
+            "`git ls-remote --heads {}` lists the remote's branches",
+            remote
+        ))
+        .with_candidates(similar.into_iter().map(str::to_string).collect())
+        .into())
+}
+
 /// Check if a worktree for the given branch already exists.
 fn check_existing_worktree(repo_root: &Path, branch: &str) -> Result<()> {
     let worktrees = git::worktrees_porcelain(repo_root)
@@ -294,6 +862,10 @@ fn check_existing_worktree(repo_root: &Path, branch: &str) -> Result<()> {
                 branch,
                 wt.path.display()
             ))
+            .with_reason("branch_checked_out")
+            .with_path(&wt.path)
+            .with_branch(branch)
+            .with_hint(format!("switch to it with `wt switch {}`", branch))
             .into());
         }
     }
@@ -303,7 +875,7 @@ fn check_existing_worktree(repo_root: &Path, branch: &str) -> Result<()> {
 
 /// Get available branches for creating new worktrees.
 /// Returns local and remote branches that don't already have worktrees.
-fn get_available_branches(repo_root: &Path) -> Result<Vec<String>> {
+pub fn get_available_branches(repo_root: &Path) -> Result<Vec<String>> {
     // Get existing worktree branches to exclude them
     let worktrees = git::worktrees_porcelain(repo_root)
         .map_err(|e| WtError::git_error_with_source("failed to list existing worktrees", e))?;
@@ -321,35 +893,18 @@ fn get_available_branches(repo_root: &Path) -> Result<Vec<String>> {
     let mut branches = Vec::new();
 
     // Get local branches
-    let output = Command::new("git")
-        .args(["branch", "--format=%(refname:short)"])
-        .current_dir(repo_root)
-        .output()
-        .map_err(|e| WtError::git_error_with_source("failed to list local branches", e.into()))?;
-
-    for line in String::from_utf8_lossy(&output.stdout).lines() {
-        let branch = line.trim();
-        if !branch.is_empty() && !existing_branches.contains(branch) {
-            branches.push(branch.to_string());
+    for branch in git::local_branches(repo_root)? {
+        if !existing_branches.contains(&branch) {
+            branches.push(branch);
         }
     }
 
     // Get remote branches
-    let output = Command::new("git")
-        .args(["branch", "-r", "--format=%(refname:short)"])
-        .current_dir(repo_root)
-        .output()
-        .map_err(|e| WtError::git_error_with_source("failed to list remote branches", e.into()))?;
-
-    for line in String::from_utf8_lossy(&output.stdout).lines() {
-        let branch = line.trim();
-        // Skip HEAD pointers and already existing worktrees
-        if !branch.is_empty() && !branch.contains("HEAD") {
-            // Extract just the branch name part for comparison
-            let branch_name = branch.split('/').skip(1).collect::<Vec<_>>().join("/");
-            if !existing_branches.contains(&branch_name) && !existing_branches.contains(branch) {
-                branches.push(branch.to_string());
-            }
+    for branch in git::remote_branches(repo_root)? {
+        // Extract just the branch name part for comparison
+        let branch_name = branch.split('/').skip(1).collect::<Vec<_>>().join("/");
+        if !existing_branches.contains(&branch_name) && !existing_branches.contains(&branch) {
+            branches.push(branch);
         }
     }
 
@@ -360,12 +915,25 @@ fn get_available_branches(repo_root: &Path) -> Result<Vec<String>> {
     Ok(branches)
 }
 
-/// Run fzf to let user pick a branch.
-fn run_fzf_branch_picker(branches: &[String]) -> Result<Option<String>> {
+/// Git log format for the branch picker's preview
+const BRANCH_LOG_FORMAT: &str = "%C(yellow)%h%C(reset) %s %C(dim)(%cr, %an)%C(reset)";
+
+/// Run fzf to let user pick a branch from `rows` (see [`branch_rows`]),
+/// previewing the recent commits of the highlighted one.
+fn run_fzf_branch_picker(repo_root: &Path, rows: &[String]) -> Result<Option<String>> {
+    let preview = format!(
+        "git -C {} log --color=always -n 50 --format={} {{1}} --",
+        process::shell_quote(&repo_root.to_string_lossy()),
+        process::shell_quote(BRANCH_LOG_FORMAT),
+    );
     let mut child = Command::new("fzf")
         .args([
             "--height=40%",
             "--layout=reverse",
+            "--delimiter=\t",
+            "--with-nth=2..",
+            "--preview-window=right:50%",
+            &format!("--preview={}", preview),
             "--prompt=Branch> ",
             "--header=Select branch to create worktree for (Esc to cancel)",
         ])
@@ -384,8 +952,8 @@ fn run_fzf_branch_picker(branches: &[String]) -> Result<Option<String>> {
             .as_mut()
             .ok_or_else(|| WtError::io_error("failed to open fzf stdin"))?;
 
-        for branch in branches {
-            writeln!(stdin, "{}", branch).map_err(|e| {
+        for row in rows {
+            writeln!(stdin, "{}", row).map_err(|e| {
                 WtError::io_error_with_source("failed to write to fzf stdin", e.into())
             })?;
         }
@@ -419,7 +987,8 @@ mod tests {
         let repo_root = PathBuf::from("/home/user/repos/my-project");
         let branch = "feature/new-ui";
 
-        let result = calculate_default_path(&repo_root, branch).unwrap();
+        let result =
+            calculate_default_path(&repo_root, branch, &config::SanitizeConfig::default()).unwrap();
         let expected = PathBuf::from("/home/user/repos/my-project-feature-new-ui");
 
         assert_eq!(result, expected);
@@ -430,20 +999,160 @@ mod tests {
         let repo_root = PathBuf::from("/home/user/repos/project");
         let branch = "bugfix/issue-123/part-2";
 
-        let result = calculate_default_path(&repo_root, branch).unwrap();
+        let result =
+            calculate_default_path(&repo_root, branch, &config::SanitizeConfig::default()).unwrap();
         let expected = PathBuf::from("/home/user/repos/project-bugfix-issue-123-part-2");
 
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn picks_the_first_free_suffix() {
+        let dir = tempfile::tempdir().unwrap();
+        let taken = dir.path().join("repo-feature");
+        fs::create_dir(&taken).unwrap();
+        assert_eq!(free_path(&taken), dir.path().join("repo-feature-2"));
+        fs::create_dir(dir.path().join("repo-feature-2")).unwrap();
+        assert_eq!(free_path(&taken), dir.path().join("repo-feature-3"));
+    }
+
+    #[test]
+    fn branch_rows_align_columns_after_the_ref() {
+        let now = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let tip = |refname: &str, author: &str, subject: &str| git::BranchTip {
+            refname: refname.to_string(),
+            time: now - 3 * 86400,
+            author: author.to_string(),
+            subject: subject.to_string(),
+        };
+        let rows = branch_rows(&[
+            tip("refs/heads/feature/x", "Ada", "Add x"),
+            tip("refs/remotes/origin/fix", "Bob Builder", ""),
+        ]);
+        assert_eq!(
+            rows,
+            [
+                "refs/heads/feature/x\tfeature/x   3d ago  Ada          Add x",
+                "refs/remotes/origin/fix\torigin/fix  3d ago  Bob Builder",
+            ]
+        );
+    }
+
+    #[test]
+    fn picked_refs_name_the_local_branch() {
+        assert_eq!(local_name("refs/heads/feature/x"), "feature/x");
+        assert_eq!(local_name("refs/remotes/origin/feature/x"), "feature/x");
+        assert_eq!(local_name("refs/remotes/upstream/fix"), "fix");
+        assert_eq!(
+            remote_of("refs/remotes/upstream/feature/x"),
+            Some("upstream")
+        );
+        assert_eq!(remote_of("refs/heads/upstream/x"), None);
+    }
+
+    #[test]
+    fn test_sanitize_branch_for_windows() {
+        let rules = config::SanitizeConfig::default();
+        assert_eq!(sanitize_branch("fix/a|b", &rules, false), "fix-a|b");
+        assert_eq!(sanitize_branch("fix/a|b<c>", &rules, true), "fix-a-b-c-");
+        assert_eq!(sanitize_branch("release/1.0.", &rules, true), "release-1.0");
+    }
+
+    #[test]
+    fn sanitizes_branches_by_the_configured_rules() {
+        let default = config::SanitizeConfig::default();
+        assert_eq!(
+            sanitize_branch("fix #12: Crash on start", &default, false),
+            "fix--12--Crash-on-start"
+        );
+
+        let rules = config::SanitizeConfig {
+            replace: [
+                ("#".to_string(), String::new()),
+                ("é".to_string(), "e".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            max_length: 12,
+            lowercase: true,
+        };
+        assert_eq!(
+            sanitize_branch("feat/Café#42", &rules, false),
+            "feat-cafe42"
+        );
+        assert_eq!(
+            sanitize_branch("feature/long-name", &rules, false),
+            "feature-long"
+        );
+        assert_eq!(
+            sanitize_branch("feature/a-b-c", &rules, false),
+            "feature-a-b"
+        );
+    }
+
     #[test]
     fn test_simple_branch_name() {
         let repo_root = PathBuf::from("/repos/app");
         let branch = "main";
 
-        let result = calculate_default_path(&repo_root, branch).unwrap();
+        let result =
+            calculate_default_path(&repo_root, branch, &config::SanitizeConfig::default()).unwrap();
         let expected = PathBuf::from("/repos/app-main");
 
         assert_eq!(result, expected);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks_path_outlives_the_worktree_it_was_set_from() {
+        use crate::git::test_support::{git, init_repo};
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let main = tmp.path().join("project");
+        init_repo(&main);
+        let hook = main.join(".githooks/pre-commit");
+        fs::create_dir_all(hook.parent().unwrap()).unwrap();
+        fs::write(&hook, "#!/bin/sh\ntouch hook-ran\n").unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        git(&main, &["add", "."]);
+        git(&main, &["commit", "-q", "-m", "hooks"]);
+        git(&main, &["worktree", "add", "-q", "../first"]);
+        git(&main, &["worktree", "add", "-q", "../second"]);
+        let first = tmp.path().join("first");
+        let second = tmp.path().join("second");
+
+        let config = config::GitHooksConfig {
+            dir: ".githooks".to_string(),
+            mode: "hooks-path".to_string(),
+        };
+        let dir = apply_git_hooks(&config, &first, &second).unwrap();
+        assert_eq!(dir.as_deref(), Some(".githooks"));
+        git(&main, &["worktree", "remove", "../first"]);
+
+        git(&second, &["commit", "-q", "--allow-empty", "-m", "check"]);
+        assert!(second.join("hook-ran").exists());
+    }
+
+    #[test]
+    fn an_existing_hooks_path_is_left_alone() {
+        use crate::git::test_support::{git, init_repo};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let main = tmp.path().join("project");
+        init_repo(&main);
+        fs::create_dir_all(main.join(".githooks")).unwrap();
+        git(&main, &["config", "core.hooksPath", ".husky"]);
+
+        let config = config::GitHooksConfig {
+            dir: ".githooks".to_string(),
+            mode: "hooks-path".to_string(),
+        };
+        assert_eq!(apply_git_hooks(&config, &main, &main).unwrap(), None);
+        let hooks_path = process::run_stdout("git", &["config", "core.hooksPath"], Some(&main));
+        assert_eq!(hooks_path.unwrap().trim(), ".husky");
+    }
 }
@@ -1,12 +1,26 @@
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
 use serde::Serialize;
 
 use crate::error::WtError;
-use crate::git;
+use crate::forge::{self, PullRequest};
+use crate::git::{CommitSummary, Upstream};
+use crate::state::{self, WorktreeState};
+use crate::worktree::Worktree;
+use crate::{git, preview, process, worktree};
+
+/// How many worktrees are inspected at once.
+const INSPECT_JOBS: usize = 8;
 
 #[derive(Serialize)]
 struct AgentContext {
     current_worktree: Option<WorktreeInfo>,
     other_worktrees: Vec<WorktreeInfo>,
+    /// Other worktrees left out to fit `--max-tokens`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    omitted_worktrees: Option<usize>,
     repository: RepositoryInfo,
     beads: BeadsInfo,
 }
@@ -17,6 +31,29 @@ struct WorktreeInfo {
     branch: Option<String>,
     head: Option<String>,
     dirty: bool,
+    /// Upstream branch with ahead/behind counts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream: Option<Upstream>,
+    /// Stashes made on the worktree's branch
+    stashes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_commit: Option<CommitSummary>,
+    /// Pull or merge request opened from the branch, when the forge CLI
+    /// knows of one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pull_request: Option<PullRequest>,
+    /// What wt recorded about the worktree (creation, visits, labels)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<WorktreeState>,
+}
+
+impl WorktreeInfo {
+    /// Drop the last commit and recorded state, keeping what an agent needs
+    /// to pick a worktree.
+    fn make_brief(&mut self) {
+        self.last_commit = None;
+        self.state = None;
+    }
 }
 
 #[derive(Serialize)]
@@ -31,8 +68,27 @@ struct BeadsInfo {
     canonical_dir: Option<String>,
 }
 
-/// Display compact context about current worktree state for agents.
-pub fn show_context(json: bool) -> Result<(), WtError> {
+/// What `git` reports about one worktree.
+#[derive(Default)]
+struct Inspection {
+    dirty: bool,
+    upstream: Option<Upstream>,
+    last_commit: Option<CommitSummary>,
+}
+
+/// How `wt agent context` trims its output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContextLimits {
+    /// Leave out last commits, recorded state and the quick command
+    /// reference
+    pub brief: bool,
+    /// Approximate token budget for the whole output
+    pub max_tokens: Option<usize>,
+}
+
+/// Display compact context about current worktree state for agents. With
+/// `prs`, each worktree's pull request is looked up on the forge as well.
+pub fn show_context(json: bool, prs: bool, limits: ContextLimits) -> Result<(), WtError> {
     let repo_root = git::repo_root(None)?;
     let worktrees = git::worktrees_porcelain(&repo_root)
         .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
@@ -42,27 +98,49 @@ pub fn show_context(json: bool) -> Result<(), WtError> {
         .map_err(|e| WtError::io_error_with_source("failed to get current directory", e.into()))?;
 
     // Find current worktree
-    let current_wt = worktrees
-        .iter()
-        .find(|wt| current_dir.starts_with(&wt.path));
+    let current_wt = worktree::containing(&worktrees, &current_dir);
+
+    // Inspect worktrees while the forge is asked about pull requests
+    let stashes = git::stashes(&repo_root);
+    let (inspections, prs) = thread::scope(|scope| {
+        let prs = scope.spawn(|| {
+            prs.then(|| forge::pull_requests(&repo_root).ok())
+                .flatten()
+                .unwrap_or_default()
+        });
+        let inspections = process::parallel_map(&worktrees, INSPECT_JOBS, inspect);
+        (inspections, prs.join().unwrap_or_default())
+    });
 
     // Separate current from others
+    let state = state::load();
     let mut other_wts = Vec::new();
     let mut current_info = None;
 
-    for wt in &worktrees {
+    for (wt, inspection) in worktrees.iter().zip(inspections) {
         let is_current = current_wt.is_some_and(|c| c.path == wt.path);
-        let info = WorktreeInfo {
+        let branch = wt.branch.as_ref().map(|b| {
+            b.strip_prefix("refs/heads/")
+                .or_else(|| b.strip_prefix("refs/remotes/"))
+                .unwrap_or(b)
+                .to_string()
+        });
+        let local_branch = wt.branch_name();
+        let mut info = WorktreeInfo {
             path: wt.path.display().to_string(),
-            branch: wt.branch.as_ref().map(|b| {
-                b.strip_prefix("refs/heads/")
-                    .or_else(|| b.strip_prefix("refs/remotes/"))
-                    .unwrap_or(b)
-                    .to_string()
-            }),
             head: wt.head.clone(),
-            dirty: is_worktree_dirty(&wt.path).unwrap_or(false),
+            dirty: inspection.dirty,
+            upstream: inspection.upstream,
+            stashes: local_branch
+                .map_or(0, |b| stashes.iter().filter(|s| s.is_on_branch(b)).count()),
+            last_commit: inspection.last_commit,
+            pull_request: local_branch.and_then(|b| prs.get(b).cloned()),
+            state: state.get(&wt.path).cloned(),
+            branch,
         };
+        if limits.brief {
+            info.make_brief();
+        }
 
         if is_current {
             current_info = Some(info);
@@ -71,83 +149,205 @@ pub fn show_context(json: bool) -> Result<(), WtError> {
         }
     }
 
+    let mut context = AgentContext {
+        current_worktree: current_info,
+        other_worktrees: other_wts,
+        omitted_worktrees: None,
+        repository: RepositoryInfo {
+            root: repo_root.display().to_string(),
+            total_worktrees: worktrees.len(),
+        },
+        beads: beads_info(&repo_root),
+    };
+    let output = match limits.max_tokens {
+        Some(budget) => fit_budget(&mut context, json, limits.brief, budget)?,
+        None => render_context(&context, json, limits.brief)?,
+    };
+    println!("{}", output);
+
+    Ok(())
+}
+
+/// Rough token count of `text`, at about four characters per token.
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Render `context` within about `budget` tokens. Other worktrees are made
+/// brief first, then the current one, then other worktrees are left out from
+/// the end of the list; the current worktree always stays, so the result may
+/// still be over budget.
+fn fit_budget(
+    context: &mut AgentContext,
+    json: bool,
+    brief: bool,
+    budget: usize,
+) -> Result<String, WtError> {
+    let mut output = render_context(context, json, brief)?;
+    if estimate_tokens(&output) <= budget {
+        return Ok(output);
+    }
+
+    // The quick command reference goes with the first cut
+    let brief = true;
+    context
+        .other_worktrees
+        .iter_mut()
+        .for_each(WorktreeInfo::make_brief);
+    output = render_context(context, json, brief)?;
+
+    if estimate_tokens(&output) > budget
+        && let Some(current) = &mut context.current_worktree
+    {
+        current.make_brief();
+        output = render_context(context, json, brief)?;
+    }
+
+    let mut omitted = 0;
+    while estimate_tokens(&output) > budget && context.other_worktrees.pop().is_some() {
+        omitted += 1;
+        context.omitted_worktrees = Some(omitted);
+        output = render_context(context, json, brief)?;
+    }
+    Ok(output)
+}
+
+fn render_context(context: &AgentContext, json: bool, brief: bool) -> Result<String, WtError> {
     if json {
-        let context = AgentContext {
-            current_worktree: current_info,
-            other_worktrees: other_wts,
-            repository: RepositoryInfo {
-                root: repo_root.display().to_string(),
-                total_worktrees: worktrees.len(),
-            },
-            beads: beads_info(&repo_root),
-        };
-        let json_str = serde_json::to_string_pretty(&context)
-            .map_err(|e| WtError::io_error_with_source("failed to serialize JSON", e.into()))?;
-        println!("{}", json_str);
+        serde_json::to_string_pretty(context)
+            .map_err(|e| WtError::io_error_with_source("failed to serialize JSON", e.into()))
     } else {
-        print_human_readable_context(current_info, other_wts, &repo_root, worktrees.len())?;
+        Ok(render_human_readable_context(context, brief))
     }
+}
 
-    Ok(())
+/// Status, divergence and last commit of a worktree; bare and missing
+/// worktrees aren't inspected.
+fn inspect(wt: &Worktree) -> Inspection {
+    if wt.bare || !wt.path.is_dir() {
+        return Inspection::default();
+    }
+    let status = git::worktree_status(&wt.path).ok();
+    Inspection {
+        dirty: status.as_ref().is_some_and(|s| s.is_dirty()),
+        upstream: status.and_then(|s| s.upstream),
+        last_commit: git::last_commit(&wt.path),
+    }
 }
 
-/// Print human-readable context output.
-fn print_human_readable_context(
-    current: Option<WorktreeInfo>,
-    others: Vec<WorktreeInfo>,
-    repo_root: &std::path::Path,
-    total: usize,
-) -> Result<(), WtError> {
-    println!("## Worktree Context");
-    println!();
+/// Human-readable context output; `brief` leaves out the quick command
+/// reference.
+fn render_human_readable_context(context: &AgentContext, brief: bool) -> String {
+    let mut lines = vec!["## Worktree Context".to_string(), String::new()];
 
-    if let Some(current) = current {
+    if let Some(current) = &context.current_worktree {
         let branch = current.branch.as_deref().unwrap_or("<detached>");
         let status = if current.dirty { "dirty" } else { "clean" };
-        println!("Current: {} @ {}", branch, current.path);
-        println!("Status: {}", status);
-        println!();
+        lines.push(format!("Current: {} @ {}", branch, current.path));
+        lines.push(format!("Status: {}", status));
+        if let Some(upstream) = &current.upstream {
+            lines.push(format!(
+                "Upstream: {} (ahead {}, behind {})",
+                upstream.name, upstream.ahead, upstream.behind
+            ));
+        }
+        if current.stashes > 0 {
+            lines.push(format!("Stashes: {}", current.stashes));
+        }
+        if let Some(commit) = &current.last_commit {
+            lines.push(format!("Last commit: {}", describe_commit(commit)));
+        }
+        if let Some(pr) = &current.pull_request {
+            lines.push(format!("Pull request: {} {}", pr.summary(), pr.url));
+        }
+        lines.push(String::new());
     } else {
-        println!("Not currently in a worktree");
-        println!();
+        lines.push("Not currently in a worktree".to_string());
+        lines.push(String::new());
     }
 
-    if !others.is_empty() {
-        println!("Other worktrees:");
-        for wt in others {
+    if !context.other_worktrees.is_empty() || context.omitted_worktrees.is_some() {
+        lines.push("Other worktrees:".to_string());
+        for wt in &context.other_worktrees {
             let branch = wt.branch.as_deref().unwrap_or("<detached>");
-            let status = if wt.dirty { " (dirty)" } else { "" };
-            println!("  - {} @ {}{}", branch, wt.path, status);
+            let mut notes = Vec::new();
+            if wt.dirty {
+                notes.push("dirty".to_string());
+            }
+            if let Some(upstream) = wt.upstream.as_ref().filter(|u| u.ahead + u.behind > 0) {
+                notes.push(format!("↑{} ↓{}", upstream.ahead, upstream.behind));
+            }
+            if wt.stashes > 0 {
+                notes.push(format!("{} stashed", wt.stashes));
+            }
+            if let Some(pr) = &wt.pull_request {
+                notes.push(pr.summary());
+            }
+            let notes = if notes.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", notes.join(", "))
+            };
+            lines.push(format!("  - {} @ {}{}", branch, wt.path, notes));
+            if let Some(commit) = &wt.last_commit {
+                lines.push(format!("      {}", describe_commit(commit)));
+            }
+        }
+        if let Some(omitted) = context.omitted_worktrees {
+            lines.push(format!(
+                "  ... and {} more (raise --max-tokens to see them)",
+                omitted
+            ));
         }
-        println!();
+        lines.push(String::new());
     }
 
-    println!("Repository: {}", repo_root.display());
-    println!("Total worktrees: {}", total);
-    println!();
-
-    let beads = beads_info(repo_root);
-    if beads.available {
-        println!("Beads: shared database available");
-        if let Some(path) = beads.canonical_dir {
-            println!("Canonical .beads: {}", path);
+    lines.push(format!("Repository: {}", context.repository.root));
+    lines.push(format!(
+        "Total worktrees: {}",
+        context.repository.total_worktrees
+    ));
+    lines.push(String::new());
+
+    if context.beads.available {
+        lines.push("Beads: shared database available".to_string());
+        if let Some(path) = &context.beads.canonical_dir {
+            lines.push(format!("Canonical .beads: {}", path));
         }
-        println!();
+        lines.push(String::new());
+    }
+
+    if !brief {
+        lines.extend(
+            [
+                "## Quick Commands",
+                "",
+                "  wt                  # Interactive picker",
+                "  wt list --json      # List all worktrees",
+                "  wt add <branch>     # Create new worktree",
+                "  wt remove <target>  # Remove worktree",
+                "  wt prune            # Clean stale worktrees",
+                "  bd where            # Verify shared beads database",
+            ]
+            .map(str::to_string),
+        );
     }
 
-    println!("## Quick Commands");
-    println!();
-    println!("  wt                  # Interactive picker");
-    println!("  wt list --json      # List all worktrees");
-    println!("  wt add <branch>     # Create new worktree");
-    println!("  wt remove <target>  # Remove worktree");
-    println!("  wt prune            # Clean stale worktrees");
-    println!("  bd where            # Verify shared beads database");
+    lines.join("\n").trim_end().to_string()
+}
 
-    Ok(())
+/// "abc1234 Fix login (3d ago)"
+fn describe_commit(commit: &CommitSummary) -> String {
+    let time = UNIX_EPOCH + Duration::from_secs(commit.time);
+    format!(
+        "{} {} ({})",
+        commit.hash,
+        commit.subject,
+        preview::format_age(time)
+    )
 }
 
-fn beads_info(repo_root: &std::path::Path) -> BeadsInfo {
+fn beads_info(repo_root: &Path) -> BeadsInfo {
     let canonical_dir = repo_root.join(".beads");
     BeadsInfo {
         available: canonical_dir.is_dir(),
@@ -158,12 +358,13 @@ fn beads_info(repo_root: &std::path::Path) -> BeadsInfo {
 }
 
 /// Check if a worktree has uncommitted changes.
-fn is_worktree_dirty(path: &std::path::Path) -> Result<bool, WtError> {
-    let output = std::process::Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(path)
-        .output()
-        .map_err(|e| WtError::git_error_with_source("failed to check git status", e.into()))?;
+fn is_worktree_dirty(path: &Path) -> Result<bool, WtError> {
+    let output = process::output(
+        std::process::Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(path),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to check git status", e.into()))?;
 
     Ok(!output.stdout.is_empty())
 }
@@ -176,9 +377,7 @@ pub fn show_status(json: bool) -> Result<(), WtError> {
 
     let current_dir = std::env::current_dir()
         .map_err(|e| WtError::io_error_with_source("failed to get current directory", e.into()))?;
-    let current_wt = worktrees
-        .iter()
-        .find(|wt| current_dir.starts_with(&wt.path));
+    let current_wt = worktree::containing(&worktrees, &current_dir);
 
     if json {
         #[derive(Serialize)]
@@ -196,10 +395,7 @@ pub fn show_status(json: bool) -> Result<(), WtError> {
 
         let current = current_wt.map(|wt| CurrentWorktree {
             path: wt.path.display().to_string(),
-            branch: wt
-                .branch
-                .as_ref()
-                .map(|b| b.strip_prefix("refs/heads/").unwrap_or(b).to_string()),
+            branch: wt.branch_name().map(str::to_string),
             dirty: is_worktree_dirty(&wt.path).unwrap_or(false),
         });
 
@@ -215,11 +411,7 @@ pub fn show_status(json: bool) -> Result<(), WtError> {
         );
     } else {
         if let Some(wt) = current_wt {
-            let branch = wt
-                .branch
-                .as_ref()
-                .and_then(|b| b.strip_prefix("refs/heads/"))
-                .unwrap_or("<detached>");
+            let branch = wt.branch_name().unwrap_or("<detached>");
             let dirty = if is_worktree_dirty(&wt.path).unwrap_or(false) {
                 " (dirty)"
             } else {
@@ -237,7 +429,68 @@ pub fn show_status(json: bool) -> Result<(), WtError> {
 
 /// Output onboarding instructions for AI agents.
 /// Similar to `bd prime` - outputs a compact workflow reference for context injection.
-pub fn show_onboard() -> Result<(), WtError> {
+/// With `json`, outputs the reference's commands as structured metadata instead.
+pub fn show_onboard(json: bool) -> Result<(), WtError> {
+    if json {
+        return crate::onboard::print_json();
+    }
     print!("{}", include_str!("agent_onboard.md"));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(path: &str) -> WorktreeInfo {
+        WorktreeInfo {
+            path: path.to_string(),
+            branch: path.rsplit('/').next().map(str::to_string),
+            head: Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+            dirty: false,
+            upstream: None,
+            stashes: 0,
+            last_commit: Some(CommitSummary {
+                hash: "0123456".to_string(),
+                subject: "A fairly long commit subject line".to_string(),
+                time: 1700000000,
+            }),
+            pull_request: None,
+            state: None,
+        }
+    }
+
+    #[test]
+    fn fit_budget_keeps_current_worktree() {
+        let mut context = AgentContext {
+            current_worktree: Some(info("/repo")),
+            other_worktrees: (0..20).map(|i| info(&format!("/repo-{}", i))).collect(),
+            omitted_worktrees: None,
+            repository: RepositoryInfo {
+                root: "/repo".to_string(),
+                total_worktrees: 21,
+            },
+            beads: BeadsInfo {
+                available: false,
+                canonical_dir: None,
+            },
+        };
+        let output = fit_budget(&mut context, true, false, 300).unwrap();
+        assert!(estimate_tokens(&output) <= 300);
+        assert!(context.current_worktree.is_some());
+        let kept = context.other_worktrees.len();
+        assert!(kept > 0 && kept < 20);
+        assert_eq!(context.omitted_worktrees, Some(20 - kept));
+        assert!(
+            context
+                .other_worktrees
+                .iter()
+                .all(|wt| wt.last_commit.is_none())
+        );
+
+        // The current worktree stays even when nothing fits
+        let output = fit_budget(&mut context, false, false, 1).unwrap();
+        assert!(output.contains("Current: repo @ /repo"));
+        assert!(context.other_worktrees.is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+//! `wt browse`: open a worktree's branch, compare view or pull request on
+//! its forge in a web browser.
+
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::Result;
+
+use crate::config::{self, BrowseConfig};
+use crate::error::WtError;
+use crate::forge;
+use crate::{git, preview, process};
+
+/// Which page of the branch to open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Branch,
+    Compare,
+    PullRequest,
+}
+
+/// Open the `view` page for the worktree matching `target` (branch name or
+/// path), or just print its URL with `print`.
+pub fn browse(target: &str, view: View, print: bool) -> Result<()> {
+    let path = preview::resolve_target(target)?;
+    let config = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?
+        .browse;
+    let url = page_url(&path, view, &config)?;
+
+    if print {
+        println!("{}", url);
+        return Ok(());
+    }
+    open_url(&config.browser, &url)?;
+    eprintln!("Opened {}", url);
+    Ok(())
+}
+
+/// The URL of the `view` page for the worktree at `path`.
+fn page_url(path: &Path, view: View, config: &BrowseConfig) -> Result<String> {
+    let branch = remote_branch(path)?;
+    if view == View::PullRequest {
+        return match forge::pull_request(path, &branch)? {
+            Some(pr) => Ok(pr.url),
+            None => Err(WtError::not_found(format!(
+                "no pull or merge request for branch '{}'; use --compare to open one",
+                branch
+            ))
+            .into()),
+        };
+    }
+
+    let url = forge::repo_web_url(path).ok_or_else(|| {
+        WtError::user_error("no web URL for this repository (its origin remote isn't a hosted URL)")
+    })?;
+    let kind = forge::kind(path)?;
+    let (template, base) = match view {
+        View::Compare => {
+            let base = git::main_branch(path).ok_or_else(|| {
+                WtError::not_found("could not determine the main branch to compare against")
+            })?;
+            (
+                or_default(&config.compare_url, kind.compare_template()),
+                base,
+            )
+        }
+        _ => (
+            or_default(&config.branch_url, kind.branch_template()),
+            String::new(),
+        ),
+    };
+    Ok(forge::render_url(template, &url, &branch, &base))
+}
+
+fn or_default<'a>(template: &'a str, default: &'a str) -> &'a str {
+    if template.trim().is_empty() {
+        default
+    } else {
+        template
+    }
+}
+
+/// The name of the worktree's branch on origin: its upstream branch when it
+/// tracks one there, else its local name.
+fn remote_branch(path: &Path) -> Result<String> {
+    let local = process::run_stdout(
+        "git",
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+        Some(path),
+    )
+    .map_err(|_| WtError::user_error("HEAD is detached; check out a branch to browse it"))?;
+    let upstream = process::run_stdout(
+        "git",
+        &[
+            "rev-parse",
+            "--abbrev-ref",
+            "--symbolic-full-name",
+            "@{upstream}",
+        ],
+        Some(path),
+    )
+    .ok();
+    Ok(upstream
+        .as_deref()
+        .and_then(|u| u.trim().strip_prefix("origin/"))
+        .unwrap_or(local.trim())
+        .to_string())
+}
+
+/// Open `url` with the `browser` command, `$BROWSER`, or the platform's
+/// opener. The browser's output goes to stderr.
+fn open_url(browser: &str, url: &str) -> Result<()> {
+    let browser = Some(browser.to_string())
+        .filter(|b| !b.trim().is_empty())
+        .or_else(|| env::var("BROWSER").ok().filter(|b| !b.trim().is_empty()))
+        .unwrap_or_else(|| {
+            if cfg!(target_os = "macos") {
+                "open".to_string()
+            } else if cfg!(windows) {
+                "rundll32 url.dll,FileProtocolHandler".to_string()
+            } else {
+                "xdg-open".to_string()
+            }
+        });
+    let command = format!("{} {}", browser, process::shell_quote(url));
+
+    let status = process::shell(&command)
+        .stdin(Stdio::null())
+        .stdout(io::stderr())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| WtError::io_error_with_source("failed to run browser", e.into()))?;
+    if !status.success() {
+        return Err(WtError::user_error(format!(
+            "failed to open {} (set `browse.browser` or $BROWSER)",
+            url
+        ))
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! CI status of worktree heads from the forge, cached briefly on disk so
+//! repeated `wt list --ci` runs and preview redraws don't hit the API.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::forge::{self, ChecksState};
+use crate::{config, process, state};
+
+/// How long a commit's CI status is reused before asking the forge again.
+const CACHE_TTL_SECS: u64 = 60;
+
+/// CI status of one commit, as cached.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// Unix timestamp (seconds) of the lookup
+    checked: u64,
+    checks: Option<ChecksState>,
+}
+
+/// The CI status of commit `sha` in the repository at `path`. Commits that
+/// aren't on any remote-tracking branch were never pushed, so they have no
+/// status and the forge isn't asked.
+pub fn status(path: &Path, sha: &str) -> Result<Option<ChecksState>> {
+    if let Some(entry) = load_cached(sha) {
+        return Ok(entry.checks);
+    }
+    if !is_pushed(path, sha) {
+        return Ok(None);
+    }
+    let checks = forge::commit_checks(path, sha)?;
+    store_cached(sha, checks);
+    Ok(checks)
+}
+
+fn is_pushed(path: &Path, sha: &str) -> bool {
+    process::run_stdout(
+        "git",
+        &[
+            "for-each-ref",
+            "--count=1",
+            "--contains",
+            sha,
+            "--format=%(refname)",
+            "refs/remotes",
+        ],
+        Some(path),
+    )
+    .is_ok_and(|out| !out.trim().is_empty())
+}
+
+fn cache_file(sha: &str) -> PathBuf {
+    config::cache_dir().join("ci").join(format!("{}.json", sha))
+}
+
+fn load_cached(sha: &str) -> Option<CacheEntry> {
+    let content = fs::read_to_string(cache_file(sha)).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+    (state::now().saturating_sub(entry.checked) < CACHE_TTL_SECS).then_some(entry)
+}
+
+fn store_cached(sha: &str, checks: Option<ChecksState>) {
+    let path = cache_file(sha);
+    let entry = CacheEntry {
+        checked: state::now(),
+        checks,
+    };
+    if let Some(dir) = path.parent()
+        && fs::create_dir_all(dir).is_ok()
+        && let Ok(content) = serde_json::to_string(&entry)
+    {
+        let _ = fs::write(path, content);
+    }
+}
@@ -0,0 +1,319 @@
+//! `wt clean`: remove worktrees whose branches are gone upstream or merged.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::worktree::Worktree;
+use crate::{git, process, state};
+
+/// Why a worktree is a candidate for removal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Reason {
+    /// The branch's upstream was deleted on the remote
+    Gone,
+    /// The branch is merged into the main branch
+    Merged,
+}
+
+impl Reason {
+    fn label(self) -> &'static str {
+        match self {
+            Reason::Gone => "gone upstream",
+            Reason::Merged => "merged",
+        }
+    }
+}
+
+/// A local branch as seen by `git for-each-ref`.
+#[derive(Debug, PartialEq, Eq)]
+struct BranchInfo {
+    commit: String,
+    /// The branch tracks an upstream that no longer exists
+    gone: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Candidate {
+    branch: String,
+    path: PathBuf,
+    reason: Reason,
+    /// Uncommitted changes; such worktrees are listed but never removed
+    dirty: bool,
+    removed: bool,
+    branch_deleted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Fetch with `--prune`, list linked worktrees whose branch is gone upstream
+/// or merged into the main branch, confirm, then remove them (and optionally
+/// their branches). With `dry_run`, only list them; with `force`, don't ask.
+/// With `json` or without a terminal to ask on, they're only listed unless
+/// `force`. `no_fetch` uses the last fetched state.
+pub fn clean(
+    dry_run: bool,
+    force: bool,
+    delete_branches: bool,
+    no_fetch: bool,
+    json: bool,
+) -> Result<()> {
+    let repo_root = git::repo_root(None)?;
+
+    if !no_fetch
+        && let Err(e) = process::run("git", &["fetch", "--prune", "--quiet"], Some(&repo_root))
+    {
+        eprintln!(
+            "Warning: fetch failed, using the last fetched state: {}",
+            process::error_summary(&format!("{:#}", e))
+        );
+    }
+
+    let worktrees = git::worktrees_porcelain(&repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+    let mut candidates = find_candidates(&repo_root, &worktrees)?;
+
+    if candidates.is_empty() {
+        if json {
+            println!("[]");
+        } else {
+            eprintln!("Nothing to clean.");
+        }
+        return Ok(());
+    }
+
+    if !json {
+        print_candidates(&candidates);
+    }
+    let removable = candidates.iter().filter(|c| !c.dirty).count();
+
+    // Never ask for an answer to a list the user can't see (--json), or
+    // where nobody can answer
+    let can_ask = !json && io::stdin().is_terminal();
+    let proceed = !dry_run && removable > 0 && (force || (can_ask && confirm(removable)?));
+    if !dry_run && removable > 0 && !force && !can_ask && !json {
+        eprintln!("Not removing anything without a terminal to confirm on; rerun with --force.");
+    }
+    if proceed {
+        for candidate in candidates.iter_mut().filter(|c| !c.dirty) {
+            remove(&repo_root, candidate, delete_branches);
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&candidates)?);
+    } else if proceed {
+        for candidate in candidates.iter().filter(|c| c.error.is_some()) {
+            eprintln!(
+                "Failed to clean {}: {}",
+                candidate.branch,
+                candidate.error.as_deref().unwrap_or_default()
+            );
+        }
+        let removed = candidates.iter().filter(|c| c.removed).count();
+        eprintln!("Removed {} of {} worktrees", removed, removable);
+    }
+
+    if candidates.iter().any(|c| c.error.is_some()) {
+        return Err(WtError::git_error("some worktrees could not be cleaned").into());
+    }
+    Ok(())
+}
+
+/// Linked worktrees whose branch is gone upstream or merged. The main
+/// worktree, the main branch, locked and detached worktrees are never
+/// candidates.
+fn find_candidates(repo_root: &Path, worktrees: &[Worktree]) -> Result<Vec<Candidate>> {
+    let Some(main) = git::main_branch(repo_root) else {
+        return Err(WtError::not_found("could not determine the main branch").into());
+    };
+    let branches = process::run_stdout(
+        "git",
+        &[
+            "for-each-ref",
+            "--format=%(refname:short)%09%(objectname)%09%(upstream:track)",
+            "refs/heads",
+        ],
+        Some(repo_root),
+    )
+    .map(|out| parse_branches(&out))
+    .map_err(|e| WtError::git_error_with_source("failed to list branches", e))?;
+
+    // Merged into the local main branch or into its upstream, which may be
+    // ahead after a fetch
+    let targets = [main.clone(), format!("{}@{{upstream}}", main)];
+    let mut merged = HashSet::new();
+    let mut main_tips = HashSet::new();
+    for target in &targets {
+        merged.extend(merged_into(repo_root, target));
+        if let Ok(commit) = process::run_stdout(
+            "git",
+            &["rev-parse", "--verify", "--quiet", target],
+            Some(repo_root),
+        ) {
+            main_tips.insert(commit.trim().to_string());
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for wt in worktrees.iter().skip(1) {
+        let Some(branch) = wt.branch_name() else {
+            continue;
+        };
+        if wt.bare || wt.locked || branch == main {
+            continue;
+        }
+        let Some(info) = branches.get(branch) else {
+            continue;
+        };
+        let Some(reason) = classify(info, merged.contains(branch), &main_tips) else {
+            continue;
+        };
+        candidates.push(Candidate {
+            branch: branch.to_string(),
+            path: wt.path.clone(),
+            reason,
+            dirty: wt.path.is_dir() && git::worktree_status(&wt.path).is_ok_and(|s| s.is_dirty()),
+            removed: false,
+            branch_deleted: false,
+            error: None,
+        });
+    }
+    Ok(candidates)
+}
+
+/// Branches merged into `target`; empty if `target` doesn't resolve.
+fn merged_into(repo_root: &Path, target: &str) -> HashSet<String> {
+    process::run_stdout(
+        "git",
+        &["branch", "--format=%(refname:short)", "--merged", target],
+        Some(repo_root),
+    )
+    .map(|out| out.lines().map(str::to_string).collect())
+    .unwrap_or_default()
+}
+
+/// Parse `git for-each-ref --format=%(refname:short)%09%(objectname)%09%(upstream:track)`.
+fn parse_branches(output: &str) -> HashMap<String, BranchInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next()?;
+            let commit = fields.next()?;
+            let track = fields.next().unwrap_or("");
+            Some((
+                name.to_string(),
+                BranchInfo {
+                    commit: commit.to_string(),
+                    gone: track == "[gone]",
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Decide whether a branch is a candidate. A merged branch still pointing at
+/// a main branch tip (local or upstream) is more likely new than finished, so
+/// it's kept.
+fn classify(info: &BranchInfo, merged: bool, main_tips: &HashSet<String>) -> Option<Reason> {
+    if info.gone {
+        Some(Reason::Gone)
+    } else if merged && !main_tips.contains(&info.commit) {
+        Some(Reason::Merged)
+    } else {
+        None
+    }
+}
+
+fn print_candidates(candidates: &[Candidate]) {
+    let width = candidates.iter().map(|c| c.branch.len()).max().unwrap_or(0);
+    for candidate in candidates {
+        let mut reason = candidate.reason.label().to_string();
+        if candidate.dirty {
+            reason.push_str(", uncommitted changes (skipped)");
+        }
+        println!(
+            "{:<width$}  {}  ({})",
+            candidate.branch,
+            candidate.path.display(),
+            reason
+        );
+    }
+}
+
+fn confirm(count: usize) -> Result<bool> {
+    eprint!(
+        "Remove {} worktree{}? (y/N): ",
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+    io::stderr().flush()?;
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    Ok(matches!(response.trim(), "y" | "Y"))
+}
+
+/// Remove one worktree and, with `delete_branch`, its branch. Merged branches
+/// are deleted with `-d`; gone branches need `-D` since squash or rebase
+/// merges leave their commits unmerged locally.
+fn remove(repo_root: &Path, candidate: &mut Candidate, delete_branch: bool) {
+    let path = candidate.path.to_string_lossy().to_string();
+    if let Err(e) = process::run("git", &["worktree", "remove", &path], Some(repo_root)) {
+        candidate.error = Some(process::error_summary(&format!("{:#}", e)));
+        return;
+    }
+    candidate.removed = true;
+    state::update_or_warn(|s| s.forget(&candidate.path));
+
+    if delete_branch {
+        let flag = match candidate.reason {
+            Reason::Gone => "-D",
+            Reason::Merged => "-d",
+        };
+        match process::run("git", &["branch", flag, &candidate.branch], Some(repo_root)) {
+            Ok(_) => candidate.branch_deleted = true,
+            Err(e) => candidate.error = Some(process::error_summary(&format!("{:#}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_branch_tracking() {
+        let branches = parse_branches("main\taaa\t\nold\tbbb\t[gone]\nwip\tccc\t[ahead 1]\n");
+        assert!(!branches["main"].gone);
+        assert!(branches["old"].gone);
+        assert!(!branches["wip"].gone);
+        assert_eq!(branches["wip"].commit, "ccc");
+    }
+
+    #[test]
+    fn classify_keeps_new_branches() {
+        let branch = |commit: &str, gone| BranchInfo {
+            commit: commit.to_string(),
+            gone,
+        };
+        let tips = HashSet::from(["a".to_string(), "u".to_string()]);
+        assert_eq!(
+            classify(&branch("b", true), false, &tips),
+            Some(Reason::Gone)
+        );
+        assert_eq!(
+            classify(&branch("b", false), true, &tips),
+            Some(Reason::Merged)
+        );
+        // Same commit as main or its upstream: just created, nothing merged yet
+        assert_eq!(classify(&branch("a", false), true, &tips), None);
+        assert_eq!(classify(&branch("u", false), true, &tips), None);
+        assert_eq!(classify(&branch("b", false), false, &tips), None);
+    }
+}
@@ -1,27 +1,93 @@
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
-#[command(name = "wt", about = "Git worktree manager", version)]
+#[command(
+    name = "wt",
+    about = "Git worktree manager",
+    version,
+    disable_version_flag = true
+)]
 #[command(after_help = "SHELL INTEGRATION:
   Run 'wt init' to set up shell integration (auto-detects your shell).")]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Command>,
+
+    /// Initial picker query (`wt feat` is short for `wt interactive feat`)
+    pub query: Option<String>,
+
+    /// Run as if wt was started in DIR instead of the current directory (like `git -C`; may be repeated)
+    #[arg(short = 'C', value_name = "DIR", action = clap::ArgAction::Append, global = true)]
+    pub directory: Vec<PathBuf>,
+
+    /// Log spawned commands to stderr (-vv for more detail; see also WT_LOG)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Format of the logs on stderr
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, global = true)]
+    pub log_format: LogFormat,
+
+    /// Print version
+    #[arg(short = 'V', long)]
+    pub version: bool,
+
+    /// With --version, print build metadata as JSON (commit, features, min git version, commands)
+    #[arg(long, requires = "version")]
+    pub json: bool,
+
+    /// Where --json commands print errors (default: the config's `json_errors`, else stdout)
+    #[arg(long, value_enum, value_name = "STREAM", global = true)]
+    pub json_errors: Option<ErrorStream>,
 }
 
 impl Cli {
     /// Check if the command has a --json flag set
     pub fn has_json_flag(&self) -> bool {
+        if self.json {
+            return true;
+        }
         match &self.command {
             Some(Command::List { json, .. }) => *json,
             Some(Command::Add { json, .. }) => *json,
+            Some(Command::Clone { json, .. }) => *json,
+            Some(Command::Migrate { json, .. }) => *json,
             Some(Command::Remove { json, .. }) => *json,
             Some(Command::Prune { json, .. }) => *json,
             Some(Command::Preview { json, .. }) => *json,
+            Some(Command::Interactive { json, .. }) => *json,
+            Some(Command::Recent { json, .. }) => *json,
+            Some(Command::Current { json }) => *json,
+            Some(Command::Each { json, .. }) => *json,
+            Some(Command::Fetch { json, .. }) => *json,
+            Some(Command::Sync { json, .. }) => *json,
+            Some(Command::Status { json, .. }) => *json,
+            Some(Command::Doctor { json }) => *json,
+            Some(Command::Clean { json, .. }) => *json,
+            Some(Command::Gc { json, .. }) => *json,
+            Some(Command::Note {
+                command: NoteCommand::Get { json, .. },
+            }) => *json,
+            Some(Command::Workspace {
+                command: WorkspaceCommand::Generate { json, .. },
+            }) => *json,
+            Some(Command::Repo {
+                command: RepoCommand::List { json },
+            }) => *json,
 
             Some(Command::Agent {
-                command: AgentCommand::Context { json } | AgentCommand::Status { json },
+                command:
+                    AgentCommand::Context { json, .. }
+                    | AgentCommand::Status { json }
+                    | AgentCommand::Diff { json, .. }
+                    | AgentCommand::Guard { json, .. }
+                    | AgentCommand::Onboard { json },
             }) => *json,
+            Some(Command::Agent {
+                command: AgentCommand::Watch { .. },
+            }) => true,
             _ => false,
         }
     }
@@ -35,6 +101,24 @@ pub enum Shell {
     Fish,
 }
 
+/// Where JSON errors are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorStream {
+    /// In place of the command's JSON output
+    Stdout,
+    /// Apart from it, so stdout only ever carries successful output
+    Stderr,
+}
+
+/// Formats for the logs enabled by `-v` or `WT_LOG`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// One human-readable line per event
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Set up shell integration for wt
@@ -47,9 +131,29 @@ pub enum Command {
     ///   wt init zsh       # Print zsh integration code
     ///   wt init bash      # Print bash integration code
     ///   wt init fish      # Print fish integration code
+    ///   wt init zsh --cmd w   # Name the shell function `w` instead of `wt`
     Init {
         /// Shell to generate integration for (optional - auto-detects if not provided)
         shell: Option<Shell>,
+
+        /// Name of the shell function and its completions (default: `shell.cmd` from the config, or wt)
+        #[arg(long, value_name = "NAME")]
+        cmd: Option<String>,
+    },
+
+    /// Print or install shell completions, without the shell function from `wt init`
+    #[command(long_about = include_str!("help/completions.md"))]
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+
+        /// Command to complete (default: `shell.cmd` from the config, or wt)
+        #[arg(long, value_name = "NAME")]
+        cmd: Option<String>,
+
+        /// Write the completions to their conventional file name in DIR (e.g. `_wt` for zsh)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
     },
 
     /// Interactive picker (fzf)
@@ -57,6 +161,34 @@ pub enum Command {
         /// Pick from all discovered repositories
         #[arg(long)]
         all: bool,
+
+        /// Pick a discovered repository first, then a worktree within it
+        #[arg(long)]
+        by_repo: bool,
+
+        /// Only repositories whose directory name matches GLOB, e.g. 'backend-*'
+        /// (with --all or --by-repo; a GLOB with `/` matches the whole path)
+        #[arg(long, value_name = "GLOB")]
+        repo: Option<String>,
+
+        /// Disable colored output (also honors NO_COLOR)
+        #[arg(long)]
+        no_color: bool,
+
+        /// Initial query; a single match is selected without opening the picker
+        query: Option<String>,
+
+        /// Fail with a JSON error instead of opening the picker (use `wt list --json`)
+        #[arg(long)]
+        json: bool,
+
+        /// Rediscover repositories instead of using the cached list (--all, --by-repo)
+        #[arg(long)]
+        refresh: bool,
+
+        /// Pick from recently visited worktrees across all repositories (see `wt recent`)
+        #[arg(long, conflicts_with_all = ["all", "by_repo"])]
+        recent: bool,
     },
 
     /// List worktrees
@@ -69,6 +201,19 @@ pub enum Command {
         /// Discover repos and list across all repos
         #[arg(long)]
         all: bool,
+
+        /// Only repositories whose directory name matches GLOB, e.g. 'backend-*'
+        /// (a GLOB with `/` matches the whole path)
+        #[arg(long, value_name = "GLOB", requires = "all")]
+        repo: Option<String>,
+
+        /// Show each branch's pull request (state, checks, review) via gh or glab
+        #[arg(long)]
+        pr: bool,
+
+        /// Show the CI status of each worktree's HEAD (✓ passing, ✗ failing, ● pending)
+        #[arg(long)]
+        ci: bool,
     },
 
     /// Add a new worktree
@@ -81,14 +226,30 @@ pub enum Command {
         #[arg(short, long)]
         path: Option<String>,
 
+        /// Use the default path even if it exists (it must be an empty directory) instead of <path>-2
+        #[arg(long, conflicts_with = "path")]
+        force_path: bool,
+
         /// Remote to track (e.g. origin)
         #[arg(long)]
         track: Option<String>,
 
+        /// Only consider branches on this remote (default: `default_remote` when several have the branch)
+        #[arg(long, value_name = "NAME", conflicts_with = "track")]
+        remote: Option<String>,
+
         /// Bootstrap a .beads/redirect in the new worktree
         #[arg(long)]
         beads: bool,
 
+        /// Change into the new worktree (requires shell integration)
+        #[arg(long)]
+        cd: bool,
+
+        /// Open the new worktree in the default editor, or the named one from `editors` (--open=NAME)
+        #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "", value_name = "EDITOR")]
+        open: Option<String>,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -98,12 +259,46 @@ pub enum Command {
         quiet: bool,
     },
 
+    /// Clone a repository as <dir>.git (bare) plus a worktree for the default branch
+    #[command(long_about = include_str!("help/clone.md"))]
+    Clone {
+        /// Repository URL
+        url: String,
+
+        /// Directory for the default branch worktree (defaults to the repository name)
+        dir: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Suppress non-essential output
+        #[arg(short, long)]
+        quiet: bool,
+    },
+
+    /// Convert the current clone into a bare repository plus worktree layout
+    #[command(long_about = include_str!("help/migrate.md"))]
+    Migrate {
+        /// Skip confirmation prompt
+        #[arg(long)]
+        force: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Remove a worktree (by branch name or path)
     #[command(long_about = include_str!("help/remove.md"))]
     Remove {
-        /// Worktree to remove (branch name or path) - optional, interactive picker if not provided
+        /// Worktree to remove (branch name or path, `.` for the current one) - optional, interactive picker if not provided
         target: Option<String>,
 
+        /// Remove the worktree you're in (same as `.`), then cd to the main worktree
+        #[arg(long, conflicts_with = "target")]
+        current: bool,
+
         /// Skip confirmation prompt
         #[arg(long)]
         force: bool,
@@ -120,6 +315,10 @@ pub enum Command {
     /// Prune stale worktrees
     #[command(long_about = include_str!("help/prune.md"))]
     Prune {
+        /// Unlock stale worktrees and delete orphaned directories without asking
+        #[arg(short, long)]
+        force: bool,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -129,14 +328,338 @@ pub enum Command {
         quiet: bool,
     },
 
+    /// Remove worktrees whose branches are gone upstream or merged
+    #[command(long_about = include_str!("help/clean.md"))]
+    Clean {
+        /// List candidates without removing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(long)]
+        force: bool,
+
+        /// Also delete the local branches of removed worktrees
+        #[arg(long)]
+        delete_branches: bool,
+
+        /// Don't fetch first; use the last fetched state
+        #[arg(long)]
+        no_fetch: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Prune stale worktrees, leftover directories, state entries and caches
+    #[command(long_about = include_str!("help/gc.md"))]
+    Gc {
+        /// Report what would be cleaned up without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Delete leftover worktree directories without asking
+        #[arg(short, long)]
+        force: bool,
+
+        /// Also clear the cache directory (preview and discovery caches)
+        #[arg(long)]
+        caches: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Print preview information for a worktree (used by fzf)
+    #[command(long_about = include_str!("help/preview.md"))]
     Preview {
+        /// Worktree to preview (branch name or path)
+        #[arg(required_unless_present = "path", conflicts_with = "path")]
+        target: Option<String>,
+
+        /// Path to the worktree (used by the fzf picker)
         #[arg(long)]
-        path: String,
+        path: Option<String>,
 
         /// Output as JSON for programmatic use
         #[arg(long)]
         json: bool,
+
+        /// Disable colored output (also honors NO_COLOR)
+        #[arg(long)]
+        no_color: bool,
+    },
+
+    /// Show the worktree containing the current directory
+    #[command(long_about = include_str!("help/current.md"))]
+    Current {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List recently visited worktrees across all repositories
+    #[command(long_about = include_str!("help/recent.md"))]
+    Recent {
+        /// Number of worktrees to show
+        #[arg(short = 'n', long, default_value_t = crate::recent::DEFAULT_LIMIT)]
+        limit: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Switch to a worktree by branch name or path
+    #[command(long_about = include_str!("help/switch.md"))]
+    Switch {
+        /// Worktree to switch to (branch name or path)
+        target: String,
+
+        /// Print the path instead of changing into it
+        #[arg(long)]
+        path_only: bool,
+    },
+
+    /// Switch to the worktree with the main branch checked out
+    #[command(long_about = include_str!("help/main.md"))]
+    Main {
+        /// Print the path instead of changing into it
+        #[arg(long)]
+        path_only: bool,
+    },
+
+    /// Move uncommitted changes into a new worktree on a new branch
+    #[command(long_about = include_str!("help/shift.md"))]
+    Shift {
+        /// Name of the new branch
+        branch: String,
+
+        /// Path to create the worktree in
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// Attach a short note or labels to a worktree
+    #[command(long_about = include_str!("help/note.md"))]
+    Note {
+        #[command(subcommand)]
+        command: NoteCommand,
+    },
+
+    /// Switch back to the previously visited worktree (like `cd -`)
+    #[command(long_about = include_str!("help/last.md"))]
+    Last,
+
+    /// Run a command inside a worktree (`wt exec <target> -- <cmd>`)
+    #[command(long_about = include_str!("help/exec.md"))]
+    Exec {
+        /// Worktree to run in (branch name or path)
+        target: String,
+
+        /// Command and arguments to run
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Show the diff between two worktrees' HEADs
+    #[command(long_about = include_str!("help/diff.md"))]
+    Diff {
+        /// Base worktree (branch name or path)
+        a: String,
+
+        /// Worktree to compare against the base (branch name or path)
+        b: String,
+
+        /// Show a diffstat instead of the full diff
+        #[arg(long)]
+        stat: bool,
+    },
+
+    /// Copy files to another worktree (or from it with --from)
+    #[command(long_about = include_str!("help/cp.md"))]
+    Cp {
+        /// Other worktree (branch name or path)
+        target: String,
+
+        /// Files, directories or globs, relative to the working directory
+        #[arg(required = true)]
+        paths: Vec<String>,
+
+        /// Copy from the other worktree into the current one
+        #[arg(long)]
+        from: bool,
+
+        /// List the files without copying
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Overwrite files that differ at the destination
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Run a command in every worktree (`wt each -- <cmd>`)
+    #[command(long_about = include_str!("help/each.md"))]
+    Each {
+        /// Run in the worktrees of all discovered repositories
+        #[arg(long)]
+        all: bool,
+
+        /// Only repositories whose directory name matches GLOB, e.g. 'backend-*'
+        /// (a GLOB with `/` matches the whole path)
+        #[arg(long, value_name = "GLOB", requires = "all")]
+        repo: Option<String>,
+
+        /// Run in up to N worktrees at once
+        #[arg(long, value_name = "N")]
+        parallel: Option<usize>,
+
+        /// Output results as JSON (command output goes to stderr)
+        #[arg(long)]
+        json: bool,
+
+        /// Command and arguments to run
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Fetch (with --prune) the current or all discovered repositories
+    #[command(long_about = include_str!("help/fetch.md"))]
+    Fetch {
+        /// Fetch every discovered repository
+        #[arg(long)]
+        all: bool,
+
+        /// Only repositories whose directory name matches GLOB, e.g. 'backend-*'
+        /// (a GLOB with `/` matches the whole path)
+        #[arg(long, value_name = "GLOB", requires = "all")]
+        repo: Option<String>,
+
+        /// Number of repositories to fetch at once
+        #[arg(short, long, default_value_t = 8)]
+        jobs: usize,
+
+        /// Output results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Fetch, then fast-forward clean worktrees that are behind their upstream
+    #[command(long_about = include_str!("help/sync.md"))]
+    Sync {
+        /// Sync the worktrees of all discovered repositories
+        #[arg(long)]
+        all: bool,
+
+        /// Only repositories whose directory name matches GLOB, e.g. 'backend-*'
+        /// (a GLOB with `/` matches the whole path)
+        #[arg(long, value_name = "GLOB", requires = "all")]
+        repo: Option<String>,
+
+        /// Number of repositories fetched and worktrees updated at once
+        #[arg(short, long, default_value_t = 8)]
+        jobs: usize,
+
+        /// Output results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show dirty state, ahead/behind, stashes and last commit age of every worktree
+    #[command(long_about = include_str!("help/status.md"))]
+    Status {
+        /// Include the worktrees of all discovered repositories
+        #[arg(long)]
+        all: bool,
+
+        /// Only repositories whose directory name matches GLOB, e.g. 'backend-*'
+        /// (a GLOB with `/` matches the whole path)
+        #[arg(long, value_name = "GLOB", requires = "all")]
+        repo: Option<String>,
+
+        /// Number of worktrees inspected at once
+        #[arg(short, long, default_value_t = 8)]
+        jobs: usize,
+
+        /// Output results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check git, fzf, shell integration, config and worktree health
+    #[command(long_about = include_str!("help/doctor.md"))]
+    Doctor {
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Open a worktree in its own tmux session
+    #[command(long_about = include_str!("help/tmux.md"))]
+    Tmux {
+        /// Worktree to open (branch name or path)
+        target: String,
+    },
+
+    /// Open a worktree in a zellij tab or session
+    #[command(long_about = include_str!("help/zellij.md"))]
+    Zellij {
+        /// Worktree to open (branch name or path)
+        target: String,
+    },
+
+    /// Open a worktree in VS Code
+    #[command(long_about = include_str!("help/code.md"))]
+    Code {
+        /// Worktree to open (branch name or path)
+        target: String,
+    },
+
+    /// Open a worktree with a launcher from the config (lazygit, a file manager, ...)
+    #[command(long_about = include_str!("help/open.md"))]
+    Open {
+        /// Worktree to open (branch name or path)
+        target: String,
+
+        /// Launcher name from the `launchers` config section
+        #[arg(short, long = "with", value_name = "LAUNCHER")]
+        with: String,
+    },
+
+    /// Open a worktree's branch, compare view or pull request in a browser
+    #[command(long_about = include_str!("help/browse.md"))]
+    Browse {
+        /// Worktree to browse (branch name or path)
+        target: String,
+
+        /// Open the compare view against the main branch
+        #[arg(long, conflicts_with = "pr")]
+        compare: bool,
+
+        /// Open the branch's pull or merge request (needs gh or glab)
+        #[arg(long)]
+        pr: bool,
+
+        /// Print the URL instead of opening it
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Register repositories for --all commands without scanning for them
+    #[command(long_about = include_str!("help/repo.md"))]
+    Repo {
+        #[command(subcommand)]
+        command: RepoCommand,
+    },
+
+    /// VS Code workspace files listing every worktree
+    #[command(long_about = include_str!("help/workspace.md"))]
+    Workspace {
+        #[command(subcommand)]
+        command: WorkspaceCommand,
     },
 
     /// Agent-friendly context and status commands
@@ -146,6 +669,35 @@ pub enum Command {
         command: AgentCommand,
     },
 
+    /// Print JSON Schemas for --json output
+    #[command(long_about = include_str!("help/schema.md"))]
+    Schema {
+        /// Command whose output to describe, e.g. `list` or `agent context`
+        /// (default: all)
+        #[arg(value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+
+    /// Print or install man pages generated from the CLI definition
+    #[command(long_about = include_str!("help/man.md"))]
+    Man {
+        /// Subcommand whose page to print, e.g. `add` or `agent context` (default: wt)
+        #[arg(value_name = "COMMAND")]
+        command: Vec<String>,
+
+        /// Write the pages for wt and every subcommand to DIR instead
+        #[arg(long, value_name = "DIR", conflicts_with = "command")]
+        dir: Option<PathBuf>,
+    },
+
+    /// Update wt to the latest GitHub release
+    #[command(name = "self-update", long_about = include_str!("help/self-update.md"))]
+    SelfUpdate {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+    },
+
     /// Configure auto-discovery paths for --all flag
     ///
     /// Set search paths for discovering git repositories when using --all.
@@ -161,6 +713,79 @@ pub enum Command {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum NoteCommand {
+    /// Set the note and/or labels of a worktree
+    Set {
+        /// Note text, e.g. "waiting on review"
+        #[arg(required_unless_present = "labels")]
+        text: Option<String>,
+
+        /// Label to attach (repeatable); replaces the existing labels
+        #[arg(short, long = "label")]
+        labels: Vec<String>,
+
+        /// Worktree to annotate (branch name or path); defaults to the current one
+        #[arg(short, long)]
+        worktree: Option<String>,
+    },
+
+    /// Show the note and labels of a worktree
+    Get {
+        /// Worktree to show (branch name or path); defaults to the current one
+        #[arg(short, long)]
+        worktree: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Remove the note and labels of a worktree
+    Clear {
+        /// Worktree to clear (branch name or path); defaults to the current one
+        #[arg(short, long)]
+        worktree: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RepoCommand {
+    /// Register repositories (default: the one you are in)
+    Add {
+        /// Paths inside the repositories to register
+        paths: Vec<String>,
+    },
+
+    /// Unregister repositories
+    Remove {
+        /// Registered paths, or paths inside the repositories
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+
+    /// List registered repositories
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceCommand {
+    /// Write a .code-workspace file with one folder per worktree
+    Generate {
+        /// File to write (default: <repo>.code-workspace next to the main worktree)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum AgentCommand {
     /// Display compact context about current worktree state
@@ -168,6 +793,18 @@ pub enum AgentCommand {
         /// Output as JSON instead of human-readable format
         #[arg(long)]
         json: bool,
+
+        /// Skip looking up pull requests on the forge
+        #[arg(long)]
+        no_pr: bool,
+
+        /// Leave out last commits, recorded state and the quick command reference
+        #[arg(long)]
+        brief: bool,
+
+        /// Trim the output to about this many tokens, keeping the current worktree
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        max_tokens: Option<u64>,
     },
 
     /// Display minimal status suitable for frequent injection
@@ -177,9 +814,51 @@ pub enum AgentCommand {
         json: bool,
     },
 
+    /// Show the files each worktree changed since the main branch, and overlaps
+    Diff {
+        /// Output as compact JSON instead of human-readable format
+        #[arg(long)]
+        json: bool,
+
+        /// Branch to compare against (default: the main branch)
+        #[arg(long, value_name = "BRANCH")]
+        base: Option<String>,
+    },
+
+    /// Check a destructive operation before running it; exits 1 when blocked
+    ///
+    /// Reports dirty trees, unpushed commits, stashes, locks and the main
+    /// branch's worktree for `remove <target>`, and what `prune` would forget.
+    Guard {
+        /// Operation to check: `remove <target>` or `prune`
+        #[arg(required = true, num_args = 1..=2, value_name = "OPERATION")]
+        operation: Vec<String>,
+
+        /// Output as JSON instead of human-readable format
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Stream worktree events (added, removed, branch or dirty changes) as NDJSON
+    Watch {
+        /// Seconds between checks of the git files that signal changes
+        #[arg(long, default_value_t = 2, value_name = "SECS",
+              value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
+        /// Seconds between full status polls (which catch edits to tracked files)
+        #[arg(long, default_value_t = 10, value_name = "SECS",
+              value_parser = clap::value_parser!(u64).range(1..))]
+        poll: u64,
+    },
+
     /// Output onboarding instructions for AI agents (similar to bd prime)
     ///
     /// Prints a compact workflow reference that can be injected into agent context.
     /// Includes CLI quick reference, JSON schemas, and common workflows.
-    Onboard,
+    Onboard {
+        /// Output command metadata (args, flags, output schema, examples) as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
@@ -0,0 +1,190 @@
+//! `wt clone`: clone a repository into a bare repository plus worktrees layout.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::progress::Progress;
+use crate::{process, state};
+
+/// Result of cloning (for JSON output)
+#[derive(Serialize)]
+struct CloneResult {
+    success: bool,
+    /// The bare repository, e.g. "/src/app.git"
+    bare: String,
+    /// The worktree of the default branch, e.g. "/src/app"
+    worktree: String,
+    branch: String,
+}
+
+/// Clone `url` as a bare repository `<dir>.git` and add a worktree for the
+/// default branch at `<dir>` (default: the repository name in the current
+/// directory). `wt add` run from that worktree then creates siblings like
+/// `<dir>-feature-x`.
+pub fn clone(url: &str, dir: Option<&str>, json: bool, quiet: bool) -> Result<()> {
+    let worktree_path = match dir {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(repo_name(url).ok_or_else(|| {
+            WtError::user_error(format!(
+                "can't derive a directory name from '{}'; pass one",
+                url
+            ))
+        })?),
+    };
+    let worktree_path = if worktree_path.is_absolute() {
+        worktree_path
+    } else {
+        std::env::current_dir()
+            .map_err(|e| {
+                WtError::io_error_with_source("failed to get current directory", e.into())
+            })?
+            .join(worktree_path)
+    };
+    let bare_path = bare_path_for(&worktree_path);
+
+    for path in [&worktree_path, &bare_path] {
+        if path.exists() {
+            return Err(
+                WtError::user_error(format!("path already exists: {}", path.display())).into(),
+            );
+        }
+    }
+
+    let bare = path_str(&bare_path)?;
+    if !quiet && !json {
+        eprintln!("Cloning {} into {}", url, bare_path.display());
+    }
+    let progress = Progress::spinner("Cloning", !quiet && !json);
+    if progress.is_live() {
+        process::run_streaming(
+            "git",
+            &["clone", "--bare", "--progress", url, bare],
+            None,
+            &mut |line| progress.message(line),
+        )
+    } else {
+        process::run("git", &["clone", "--bare", "--quiet", url, bare], None)
+    }
+    .map_err(|e| WtError::git_error_with_source(format!("failed to clone {}", url), e))?;
+
+    progress.message("setting up remote-tracking branches");
+    let branch = setup_bare(&bare_path)?;
+    progress.finish();
+
+    if !quiet && !json {
+        eprintln!("Creating worktree at: {}", worktree_path.display());
+    }
+    process::run(
+        "git",
+        &["worktree", "add", path_str(&worktree_path)?, &branch],
+        Some(&bare_path),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to add worktree", e))?;
+    // Best effort: without an upstream, status just won't show ahead/behind
+    let _ = process::run(
+        "git",
+        &[
+            "branch",
+            "--set-upstream-to",
+            &format!("origin/{}", branch),
+            &branch,
+        ],
+        Some(&worktree_path),
+    );
+    state::update_or_warn(|s| s.record_created(&worktree_path, state::now()));
+
+    if json {
+        let result = CloneResult {
+            success: true,
+            bare: bare.to_string(),
+            worktree: worktree_path.to_string_lossy().to_string(),
+            branch,
+        };
+        println!("{}", serde_json::to_string(&result)?);
+    } else if !quiet {
+        eprintln!(
+            "Cloned; run `wt add <branch>` inside {}",
+            worktree_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Make a fresh bare clone behave like a normal one and return its default
+/// branch. `git clone --bare` maps branches straight to `refs/heads` and sets
+/// no fetch refspec, so `git fetch` wouldn't update remote-tracking branches.
+fn setup_bare(bare_path: &Path) -> Result<String> {
+    let setup = [
+        &[
+            "config",
+            "remote.origin.fetch",
+            "+refs/heads/*:refs/remotes/origin/*",
+        ][..],
+        &["fetch", "--quiet", "origin"],
+        &["remote", "set-head", "origin", "--auto"],
+    ];
+    for args in setup {
+        process::run("git", args, Some(bare_path)).map_err(|e| {
+            WtError::git_error_with_source("failed to set up the bare repository", e)
+        })?;
+    }
+
+    let head = process::run_stdout("git", &["symbolic-ref", "--short", "HEAD"], Some(bare_path))
+        .map_err(|e| WtError::git_error_with_source("failed to read the default branch", e))?;
+    Ok(head.trim().to_string())
+}
+
+/// Repository name from a clone URL: the last path component without `.git`,
+/// e.g. "app" for "git@github.com:org/app.git".
+fn repo_name(url: &str) -> Option<String> {
+    let url = url.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    // "path/to/app/.git" names "app"
+    let url = url.trim_end_matches('/');
+    let name = url.rsplit(['/', ':']).next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// `<dir>.git` next to the worktree directory `<dir>`.
+fn bare_path_for(worktree_path: &Path) -> PathBuf {
+    let mut name = worktree_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".git");
+    worktree_path.with_file_name(name)
+}
+
+fn path_str(path: &Path) -> Result<&str> {
+    path.to_str()
+        .ok_or_else(|| WtError::io_error("invalid path encoding").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_name_from_urls() {
+        assert_eq!(
+            repo_name("git@github.com:org/app.git").as_deref(),
+            Some("app")
+        );
+        assert_eq!(
+            repo_name("https://github.com/org/app/").as_deref(),
+            Some("app")
+        );
+        assert_eq!(repo_name("/srv/git/app.git").as_deref(), Some("app"));
+        assert_eq!(repo_name("/srv/app/.git").as_deref(), Some("app"));
+        assert_eq!(repo_name("host:app").as_deref(), Some("app"));
+        assert_eq!(repo_name("/"), None);
+    }
+
+    #[test]
+    fn bare_path_sits_next_to_worktree() {
+        assert_eq!(
+            bare_path_for(Path::new("/src/app")),
+            PathBuf::from("/src/app.git")
+        );
+    }
+}
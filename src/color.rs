@@ -0,0 +1,72 @@
+//! Minimal ANSI styling for human-readable output.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Applies ANSI colors when enabled, and passes text through unchanged otherwise.
+#[derive(Debug, Clone, Copy)]
+pub struct Colors {
+    enabled: bool,
+}
+
+impl Colors {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn green(&self, text: &str) -> String {
+        self.paint(text, "32")
+    }
+
+    pub fn red(&self, text: &str) -> String {
+        self.paint(text, "31")
+    }
+
+    pub fn yellow(&self, text: &str) -> String {
+        self.paint(text, "33")
+    }
+
+    fn paint(&self, text: &str, code: &str) -> String {
+        if self.enabled && !text.is_empty() {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Whether colors are allowed at all: not disabled by `--no-color` or a
+/// non-empty `NO_COLOR` (see https://no-color.org).
+pub fn enabled(no_color: bool) -> bool {
+    !no_color && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Whether to color output written to stdout.
+///
+/// On top of [`enabled`], stdout must be a terminal or an fzf preview pane
+/// (fzf exports `FZF_PREVIEW_COLUMNS` to preview commands), so colors don't
+/// leak into files and pipes.
+pub fn stdout_enabled(no_color: bool) -> bool {
+    enabled(no_color)
+        && (io::stdout().is_terminal() || env::var_os("FZF_PREVIEW_COLUMNS").is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_colors_pass_text_through() {
+        let colors = Colors::new(false);
+        assert_eq!(colors.green("main"), "main");
+        assert_eq!(colors.red(" M src/main.rs"), " M src/main.rs");
+    }
+
+    #[test]
+    fn enabled_colors_wrap_text_in_escape_codes() {
+        let colors = Colors::new(true);
+        assert_eq!(colors.green("main"), "\x1b[32mmain\x1b[0m");
+        assert_eq!(colors.yellow("abc1234"), "\x1b[33mabc1234\x1b[0m");
+        assert_eq!(colors.red(""), "");
+    }
+}
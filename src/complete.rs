@@ -0,0 +1,468 @@
+//! `wt __complete`: context-aware completion candidates for the shell
+//! completions installed by `wt init`.
+//!
+//! The shell passes the words of the command line after the program name, up
+//! to and including the word being completed. They are matched against the CLI
+//! definition to find the argument being completed, and candidates that need
+//! the repository (worktrees, branches, remotes, discovered repositories) are
+//! printed one per line as `value<TAB>description`. Anything this doesn't know
+//! about prints nothing, and the shell falls back to the completions generated
+//! from the CLI definition.
+
+use anyhow::Result;
+use clap::{Arg, CommandFactory};
+
+use crate::cli::Cli;
+use crate::worktree::Worktree;
+use crate::{add, config, discovery, git, process, schema};
+
+/// The hidden command the shell completions call: `wt __complete -- <words>`.
+pub const COMMAND: &str = "__complete";
+
+/// A completion candidate, with an optional description shown next to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Candidate {
+    value: String,
+    description: Option<String>,
+}
+
+impl Candidate {
+    fn new(value: impl Into<String>) -> Self {
+        Candidate {
+            value: value.into(),
+            description: None,
+        }
+    }
+
+    fn described(value: impl Into<String>, description: impl Into<String>) -> Self {
+        Candidate {
+            value: value.into(),
+            description: Some(description.into()),
+        }
+    }
+}
+
+/// The argument the word under the cursor is a value for.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Slot {
+    /// Subcommands leading to the argument, e.g. `["agent", "guard"]`
+    path: Vec<String>,
+    /// Id of the argument (the field name in `cli.rs`)
+    arg: String,
+    /// Values already given for the argument, for ones taking several
+    previous: Vec<String>,
+    /// What the shell sees in front of the value: `--open=` when completing
+    /// `--open=NAME`, else empty
+    prefix: String,
+    /// `-C` directories given before the cursor, in order
+    directories: Vec<String>,
+}
+
+/// Print the candidates for the last of `words`. Failures print nothing:
+/// completion must not put errors into the user's prompt, so they only show in
+/// the logs (`WT_LOG=debug`).
+pub fn complete(words: &[String]) {
+    let Some(slot) = slot(words) else {
+        return;
+    };
+    match candidates(&slot) {
+        Ok(candidates) => {
+            for candidate in candidates {
+                let value = format!("{}{}", slot.prefix, candidate.value);
+                match candidate.description {
+                    Some(description) => println!("{}\t{}", value, description),
+                    None => println!("{}", value),
+                }
+            }
+        }
+        Err(e) => tracing::debug!(error = %format!("{:#}", e), "no completions"),
+    }
+}
+
+/// Find the argument the last word is a value for. `None` for subcommand
+/// names, flags and free-form values, which the generated completions handle.
+fn slot(words: &[String]) -> Option<Slot> {
+    let words = normalize(words);
+    let (current, words) = match words.split_last() {
+        Some((current, words)) => (current.as_str(), words),
+        None => ("", &words[..]),
+    };
+
+    let mut root = Cli::command();
+    root.build();
+    let mut command = &root;
+    let mut slot = Slot::default();
+    // Values given so far for each argument id
+    let mut values: Vec<(String, String)> = Vec::new();
+    let mut positional = 0;
+    let mut pending: Option<&Arg> = None;
+    let mut escaped = false;
+
+    let mut record = |arg: &Arg, value: &str, slot: &mut Slot| {
+        if arg.get_id() == "directory" {
+            slot.directories.push(value.to_string());
+        }
+        values.push((arg.get_id().to_string(), value.to_string()));
+    };
+
+    for word in words {
+        if let Some(arg) = pending.take() {
+            record(arg, word, &mut slot);
+            continue;
+        }
+        if !escaped && word == "--" {
+            escaped = true;
+            continue;
+        }
+        if !escaped && word.starts_with("--") {
+            let (name, value) = match word[2..].split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (&word[2..], None),
+            };
+            let arg = command.get_arguments().find(|a| a.get_long() == Some(name));
+            match (arg, value) {
+                (Some(arg), Some(value)) => record(arg, value, &mut slot),
+                (Some(arg), None) if takes_separate_value(arg) => pending = Some(arg),
+                _ => {}
+            }
+            continue;
+        }
+        if !escaped && word.starts_with('-') && word.len() > 1 {
+            for (i, c) in word.char_indices().skip(1) {
+                let Some(arg) = command.get_arguments().find(|a| a.get_short() == Some(c)) else {
+                    break;
+                };
+                if takes_separate_value(arg) {
+                    let rest = &word[i + c.len_utf8()..];
+                    if rest.is_empty() {
+                        pending = Some(arg);
+                    } else {
+                        record(arg, rest, &mut slot);
+                    }
+                    break;
+                }
+            }
+            continue;
+        }
+
+        if positional == 0
+            && !escaped
+            && let Some(sub) = command.find_subcommand(word)
+        {
+            command = sub;
+            slot.path.push(sub.get_name().to_string());
+            continue;
+        }
+        let Some(arg) = positional_arg(command, positional, escaped) else {
+            continue;
+        };
+        record(arg, word, &mut slot);
+        if !takes_many(arg) {
+            positional += 1;
+        }
+    }
+
+    let arg = if let Some(arg) = pending {
+        arg
+    } else if let Some(option) = current.strip_prefix("--")
+        && let Some((name, _)) = option.split_once('=')
+    {
+        slot.prefix = format!("--{}=", name);
+        command
+            .get_arguments()
+            .find(|a| a.get_long() == Some(name))?
+    } else if current.starts_with('-') && !escaped {
+        return None;
+    } else {
+        if positional == 0 && !escaped && command.has_subcommands() {
+            return None;
+        }
+        positional_arg(command, positional, escaped)?
+    };
+
+    slot.arg = arg.get_id().to_string();
+    slot.previous = values
+        .into_iter()
+        .filter(|(id, _)| *id == slot.arg)
+        .map(|(_, value)| value)
+        .collect();
+    Some(slot)
+}
+
+/// Undo bash's splitting of `--open=NAME` into `--open`, `=` and `NAME`
+/// (`=` is in `COMP_WORDBREAKS`).
+fn normalize(words: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    let mut joining = false;
+    for word in words {
+        match normalized.last_mut() {
+            Some(last) if word == "=" && last.starts_with("--") && !last.contains('=') => {
+                last.push('=');
+                joining = true;
+            }
+            Some(last) if joining => {
+                last.push_str(word);
+                joining = false;
+            }
+            _ => normalized.push(word.clone()),
+        }
+    }
+    normalized
+}
+
+/// The `index`th positional argument of `command`. Arguments only allowed
+/// after `--` (`wt exec <target> -- <cmd>`) are skipped until it's seen, and
+/// are the only ones left once it is.
+fn positional_arg(command: &clap::Command, index: usize, escaped: bool) -> Option<&Arg> {
+    if escaped && let Some(last) = command.get_positionals().find(|a| a.is_last_set()) {
+        return Some(last);
+    }
+    command
+        .get_positionals()
+        .filter(|a| !a.is_last_set())
+        .nth(index)
+}
+
+/// Whether the option's value can be the next word (`--track origin`), as
+/// opposed to a flag or an option like `--open[=NAME]`.
+fn takes_separate_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values() && !arg.is_require_equals_set()
+}
+
+/// Whether the argument takes several values (`wt schema agent context`).
+fn takes_many(arg: &Arg) -> bool {
+    arg.get_num_args().is_some_and(|n| n.max_values() > 1)
+}
+
+/// The candidates for `slot`, run in the directory the command would run in.
+fn candidates(slot: &Slot) -> Result<Vec<Candidate>> {
+    for dir in &slot.directories {
+        std::env::set_current_dir(dir)?;
+    }
+    let path: Vec<&str> = slot.path.iter().map(String::as_str).collect();
+    let previous: Vec<&str> = slot.previous.iter().map(String::as_str).collect();
+
+    Ok(
+        match (path.as_slice(), slot.arg.as_str(), previous.as_slice()) {
+            (_, "directory", _) => discovered_repos()?,
+            (["remove"], "target", _) => removable_worktrees()?,
+            (["agent", "guard"], "operation", []) => {
+                vec![Candidate::new("remove"), Candidate::new("prune")]
+            }
+            (["agent", "guard"], "operation", ["remove"]) => removable_worktrees()?,
+            (["add"], "branch", _) => {
+                let repo_root = git::repo_root(None)?;
+                add::get_available_branches(&repo_root)?
+                    .into_iter()
+                    .map(Candidate::new)
+                    .collect()
+            }
+            (["add"], "track" | "remote", _) => {
+                lines(&process::run_stdout("git", &["remote"], None)?)
+            }
+            (["add"], "open", _) => config::load()?
+                .editors
+                .into_iter()
+                .map(|(name, command)| Candidate::described(name, command))
+                .collect(),
+            (["open"], "with", _) => config::load()?
+                .launchers
+                .into_iter()
+                .map(|(name, command)| Candidate::described(name, command))
+                .collect(),
+            (["agent", "diff"], "base", _) => git::local_branches(&git::repo_root(None)?)?
+                .into_iter()
+                .map(Candidate::new)
+                .collect(),
+            (["schema"], "command", _) => schema_words(&previous),
+            (["man"], "command", _) => subcommand_words(&previous),
+            (_, "target" | "a" | "b" | "worktree", _) => {
+                let repo_root = git::repo_root(None)?;
+                git::worktrees_porcelain(&repo_root)?
+                    .iter()
+                    .filter(|wt| !wt.bare)
+                    .map(worktree_candidate)
+                    .collect()
+            }
+            _ => Vec::new(),
+        },
+    )
+}
+
+/// Worktrees `wt remove` accepts: all but the bare repository and the main
+/// branch's worktree.
+fn removable_worktrees() -> Result<Vec<Candidate>> {
+    let repo_root = git::repo_root(None)?;
+    Ok(git::worktrees_porcelain(&repo_root)?
+        .iter()
+        .filter(|wt| {
+            !wt.bare
+                && !wt
+                    .branch
+                    .as_ref()
+                    .is_some_and(|b| git::is_main_branch(&repo_root, b))
+        })
+        .map(worktree_candidate)
+        .collect())
+}
+
+/// A worktree by branch name (its path when detached), described by its path.
+fn worktree_candidate(wt: &Worktree) -> Candidate {
+    let path = wt.path.display().to_string();
+    let name = wt.branch.as_ref().map(|b| {
+        b.strip_prefix("refs/heads/")
+            .or_else(|| b.strip_prefix("refs/remotes/"))
+            .unwrap_or(b)
+            .to_string()
+    });
+    match name {
+        Some(name) => Candidate::described(name, path),
+        None => Candidate::new(path),
+    }
+}
+
+/// The repositories `--all` works on, i.e. the cached discovery results.
+fn discovered_repos() -> Result<Vec<Candidate>> {
+    let config = config::load()?;
+    Ok(discovery::discover_configured_repos(&config, false, None)?
+        .iter()
+        .map(|repo| Candidate::new(repo.display().to_string()))
+        .collect())
+}
+
+/// The subcommands (with their descriptions) of the command named by the
+/// `previous` words, e.g. `context` and `status` after `agent`.
+fn subcommand_words(previous: &[&str]) -> Vec<Candidate> {
+    let root = Cli::command();
+    let mut command = &root;
+    for name in previous {
+        match command.find_subcommand(name) {
+            Some(sub) => command = sub,
+            None => return Vec::new(),
+        }
+    }
+    command
+        .get_subcommands()
+        .filter(|s| !s.is_hide_set())
+        .map(|s| match s.get_about() {
+            Some(about) => Candidate::described(s.get_name(), about.to_string()),
+            None => Candidate::new(s.get_name()),
+        })
+        .collect()
+}
+
+/// The next word of the schema names starting with the `previous` words,
+/// e.g. `context` and `status` after `agent`.
+fn schema_words(previous: &[&str]) -> Vec<Candidate> {
+    let mut next: Vec<&str> = Vec::new();
+    for name in schema::names() {
+        let words: Vec<&str> = name.split(' ').collect();
+        if words.len() > previous.len()
+            && words[..previous.len()] == *previous
+            && !next.contains(&words[previous.len()])
+        {
+            next.push(words[previous.len()]);
+        }
+    }
+    next.into_iter().map(Candidate::new).collect()
+}
+
+fn lines(output: &str) -> Vec<Candidate> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(Candidate::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot_for(line: &str) -> Option<Slot> {
+        let mut words: Vec<String> = line.split_whitespace().map(String::from).collect();
+        if line.ends_with(' ') {
+            words.push(String::new());
+        }
+        slot(&words)
+    }
+
+    fn arg_of(line: &str) -> Option<(Vec<String>, String, Vec<String>)> {
+        slot_for(line).map(|s| (s.path, s.arg, s.previous))
+    }
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_the_argument_under_the_cursor() {
+        assert_eq!(
+            arg_of("remove "),
+            Some((strings(&["remove"]), "target".into(), vec![]))
+        );
+        assert_eq!(
+            arg_of("remove --force fe"),
+            Some((strings(&["remove"]), "target".into(), vec![]))
+        );
+        assert_eq!(
+            arg_of("add --track "),
+            Some((strings(&["add"]), "track".into(), vec![]))
+        );
+        assert_eq!(
+            arg_of("agent guard remove "),
+            Some((
+                strings(&["agent", "guard"]),
+                "operation".into(),
+                strings(&["remove"])
+            ))
+        );
+        assert_eq!(
+            arg_of("diff main "),
+            Some((strings(&["diff"]), "b".into(), vec![]))
+        );
+        assert_eq!(arg_of("-C "), Some((vec![], "directory".into(), vec![])));
+        // Subcommand names, flags and the command after `--` are left to the
+        // generated completions
+        assert_eq!(arg_of("agent "), None);
+        assert_eq!(arg_of("remove --"), None);
+        assert_eq!(
+            arg_of("exec main -- "),
+            Some((strings(&["exec"]), "command".into(), vec![]))
+        );
+    }
+
+    #[test]
+    fn handles_option_values_and_global_flags() {
+        let slot = slot_for("-C /a -v -C /b switch ").unwrap();
+        assert_eq!(slot.path, strings(&["switch"]));
+        assert_eq!(slot.arg, "target");
+        assert_eq!(slot.directories, strings(&["/a", "/b"]));
+
+        let slot = slot_for("add feat --open=co").unwrap();
+        assert_eq!(
+            (slot.arg.as_str(), slot.prefix.as_str()),
+            ("open", "--open=")
+        );
+        // bash splits at `=`
+        let words = strings(&["add", "--open", "=", "co"]);
+        assert_eq!(normalize(&words), strings(&["add", "--open=co"]));
+
+        let slot = slot_for("open main -w ").unwrap();
+        assert_eq!(slot.arg, "with");
+    }
+
+    #[test]
+    fn completes_schema_names_word_by_word() {
+        let words = |previous: &[&str]| -> Vec<String> {
+            schema_words(previous)
+                .into_iter()
+                .map(|c| c.value)
+                .collect()
+        };
+        assert!(words(&[]).contains(&"list".to_string()));
+        assert!(words(&["agent"]).contains(&"context".to_string()));
+        assert!(words(&["list"]).is_empty());
+    }
+}
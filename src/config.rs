@@ -1,7 +1,9 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -14,6 +16,70 @@ pub struct Config {
     pub auto_discovery: AutoDiscoveryConfig,
     #[serde(default)]
     pub beads: BeadsConfig,
+    #[serde(default)]
+    pub git_hooks: GitHooksConfig,
+    #[serde(default)]
+    pub preview: PreviewConfig,
+    #[serde(default)]
+    pub interactive: InteractiveConfig,
+    #[serde(default)]
+    pub tmux: TmuxConfig,
+    /// Where the picker's multiplexer action opens worktrees: tmux, zellij
+    /// or none
+    #[serde(default = "default_multiplexer")]
+    pub multiplexer: String,
+    #[serde(default)]
+    pub zellij: ZellijConfig,
+    /// Editor command template with `{path}` and `{branch}`, e.g.
+    /// "code --new-window {path}"; empty uses the shell's `$EDITOR`
+    #[serde(default)]
+    pub editor: String,
+    /// Named editor templates, picked with `wt add --open=NAME` or an
+    /// `edit:NAME` keybinding
+    #[serde(default)]
+    pub editors: BTreeMap<String, String>,
+    /// Named "open with" commands for `wt open --with NAME` and `open:NAME`
+    /// keybindings, with `{path}`, `{branch}` and `{url}` (the repository's
+    /// web URL)
+    #[serde(default)]
+    pub launchers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub browse: BrowseConfig,
+    /// Code hosting for pull requests and `wt browse`: github, gitlab or
+    /// gitea; empty guesses from the origin remote's host
+    #[serde(default)]
+    pub forge: String,
+    /// Remote `wt add` creates a branch from when several remotes have it;
+    /// empty asks for `--remote`
+    #[serde(default)]
+    pub default_remote: String,
+    /// Per-repository settings, keyed by the path of the repository's main
+    /// worktree (or bare repository); a leading `~/` is expanded
+    #[serde(default)]
+    pub repos: BTreeMap<String, RepoConfig>,
+    /// Picker keys mapped to actions (cd, edit, remove, lock, new, or a shell
+    /// command template using `{path}`); replaces the defaults when set
+    #[serde(default = "default_keybindings")]
+    pub keybindings: BTreeMap<String, String>,
+    /// Picker keys mapped to commands run inside the selected worktree
+    #[serde(default)]
+    pub actions: BTreeMap<String, String>,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub integrations: IntegrationsConfig,
+    #[serde(default)]
+    pub shell: ShellConfig,
+    #[serde(default)]
+    pub timeouts: TimeoutsConfig,
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub sanitize: SanitizeConfig,
+    /// Where `--json` commands print errors: stdout, in place of the output
+    /// (the default), or stderr; `--json-errors` overrides it
+    #[serde(default = "default_json_errors")]
+    pub json_errors: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -24,9 +90,37 @@ pub struct FzfConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
 pub struct AutoDiscoveryConfig {
     pub enabled: bool,
+    /// Directories searched for repositories; `PATH:DEPTH` overrides
+    /// `max_depth` for one of them, e.g. "~/work:5"
     pub paths: Vec<String>,
+    /// How many directory levels below each path are walked; 3 finds
+    /// `path/org/repo`
+    pub max_depth: usize,
+    /// Globs for directories the walk never enters. A pattern without `/`
+    /// matches a directory name anywhere; one with `/` matches the path
+    /// relative to the search path
+    pub exclude: Vec<String>,
+    /// Don't walk into hidden directories such as `~/.local`
+    pub skip_hidden: bool,
+    /// Walk into symlinked directories, e.g. a farm of links to projects
+    pub follow_symlinks: bool,
+    /// Repositories registered with `wt repo add`, used by `--all` commands
+    /// along with the ones found under `paths`
+    pub repos: Vec<String>,
+}
+
+/// Deepest walk `auto_discovery` accepts; anything deeper crawls most of the
+/// file system.
+pub const MAX_DISCOVERY_DEPTH: usize = 16;
+
+/// One auto-discovery path with `~` expanded and its walk depth resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPath {
+    pub path: PathBuf,
+    pub max_depth: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -35,6 +129,241 @@ pub struct BeadsConfig {
     pub redirect_mode: String,
 }
 
+/// Shared git hooks wired into every worktree `wt add` creates.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GitHooksConfig {
+    /// Directory holding the hooks, relative to the worktree `wt add` runs in
+    /// or absolute; empty disables the feature
+    pub dir: String,
+    /// `hooks-path` sets `core.hooksPath` to the directory (unless already
+    /// set), `symlink` links each hook into the repository's hooks directory
+    pub mode: String,
+}
+
+/// Settings for one repository in `repos`, overriding the global ones.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RepoConfig {
+    /// Editor command template used instead of `editor`
+    pub editor: String,
+    /// Forge used instead of `forge`
+    pub forge: String,
+    /// Remote used instead of `default_remote`
+    pub default_remote: String,
+}
+
+/// Interactive picker behavior.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct InteractiveConfig {
+    /// Order candidates by frecency (frequently and recently visited first)
+    pub frecency: bool,
+}
+
+/// How `wt tmux` (and the picker's tmux action) opens a worktree.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TmuxConfig {
+    /// Shell command template run instead of the per-worktree session;
+    /// `{path}` is the worktree path, `{name}` its directory name and
+    /// `{session}` its session name. Empty uses sessions
+    pub command: String,
+    /// Shell command templates (same tokens) run once in a new session,
+    /// e.g. "tmux split-window -h -t {session} -c {path}"
+    pub layout: Vec<String>,
+}
+
+/// Shell commands run at worktree lifecycle events (see `hooks.rs`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// After `wt add` creates a worktree, inside it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_add: Option<String>,
+    /// Before `wt remove` removes a worktree, inside it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_remove: Option<String>,
+    /// After a worktree is removed, in the main worktree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_remove: Option<String>,
+    /// When the picker, `wt main` or `wt last` switches to a worktree, inside it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_switch: Option<String>,
+    pub on_failure: HookFailurePolicy,
+}
+
+/// What a failing hook (non-zero exit) does to the operation that ran it.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailurePolicy {
+    /// Print a warning and carry on
+    #[default]
+    Warn,
+    /// Fail the command; a failing `pre_remove` keeps the worktree
+    Abort,
+}
+
+/// Other tools `wt` drives when worktrees are created or opened.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct IntegrationsConfig {
+    /// Run `direnv allow` in new worktrees that have an `.envrc`
+    pub direnv: bool,
+    /// Add worktrees to zoxide when they are created or opened from the
+    /// picker (skipped if zoxide isn't installed)
+    pub zoxide: bool,
+}
+
+/// The shell integration `wt init` generates.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ShellConfig {
+    /// Name of the shell function (and its completions), e.g. "w"; the
+    /// `wt` binary keeps its name
+    pub cmd: String,
+}
+
+impl Default for ShellConfig {
+    fn default() -> Self {
+        Self {
+            cmd: "wt".to_string(),
+        }
+    }
+}
+
+/// The environment of git commands whose output `wt` reads. By default they
+/// can't prompt for credentials (nobody would see the prompt) and print
+/// untranslated messages, which `wt` parses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Let git prompt for credentials on the terminal
+    pub prompts: bool,
+    /// Keep the locale, so git's messages are translated
+    pub localized: bool,
+}
+
+/// How long spawned commands may run before wt stops them, in seconds; 0
+/// waits forever. Covers git and the other tools wt runs for their output
+/// (gh, glab, ...), not interactive ones like fzf, editors or hooks.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TimeoutsConfig {
+    pub default: u64,
+    /// Overrides by git subcommand (fetch, clone, ...) or program (gh, glab)
+    pub commands: BTreeMap<String, u64>,
+}
+
+impl Default for TimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            default: 120,
+            commands: BTreeMap::from([("clone".to_string(), 0), ("fetch".to_string(), 600)]),
+        }
+    }
+}
+
+impl TimeoutsConfig {
+    /// The timeout for `command`: a git subcommand or program name.
+    pub fn for_command(&self, command: &str) -> Option<std::time::Duration> {
+        let seconds = self.commands.get(command).copied().unwrap_or(self.default);
+        (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+    }
+}
+
+/// How a branch name becomes the `<branch>` part of a new worktree's
+/// directory (`<repo>-<branch>`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SanitizeConfig {
+    /// Characters (or strings) replaced in the branch name, each by the
+    /// string it maps to; "" drops it. Unmapped `/` still becomes `-`, as do
+    /// the characters Windows file names can't contain there
+    pub replace: BTreeMap<String, String>,
+    /// Longest the branch part may get, in characters; 0 for no limit
+    pub max_length: usize,
+    pub lowercase: bool,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        Self {
+            replace: ["/", "\\", ":", "#", " "]
+                .iter()
+                .map(|c| (c.to_string(), "-".to_string()))
+                .collect(),
+            max_length: 0,
+            lowercase: false,
+        }
+    }
+}
+
+impl SanitizeConfig {
+    /// An error for a replacement that would nest directories or an empty
+    /// key.
+    pub fn validate(&self) -> Result<()> {
+        for (from, to) in &self.replace {
+            if from.is_empty() {
+                anyhow::bail!("sanitize.replace can't replace an empty string");
+            }
+            if to.contains(['/', '\\']) {
+                anyhow::bail!(
+                    "sanitize.replace maps '{}' to '{}', but a directory name can't contain / or \\",
+                    from,
+                    to
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How `wt browse` builds forge URLs and opens them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct BrowseConfig {
+    /// Command that opens a URL, e.g. "firefox"; empty uses `$BROWSER`,
+    /// then `open` (macOS) or `xdg-open`
+    pub browser: String,
+    /// Branch page template with `{url}` (the repository's web URL) and
+    /// `{branch}`; empty picks the forge's pattern from the remote's host
+    pub branch_url: String,
+    /// Compare view template with `{url}`, `{base}` and `{branch}`
+    pub compare_url: String,
+}
+
+/// How `wt zellij` opens a worktree.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ZellijConfig {
+    /// Layout (a file path or the name of a zellij layout) for new tabs and
+    /// sessions; empty uses zellij's default
+    pub layout: String,
+}
+
+/// Which sections `wt preview` renders, in order, and how long each may get.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PreviewConfig {
+    /// Section names (status, commits, changed_files, diffstat, stashes,
+    /// submodules, and the opt-in pull_request and ci); unknown names are
+    /// ignored with a warning
+    pub sections: Vec<String>,
+    pub limits: PreviewLimits,
+}
+
+/// Maximum number of entries shown per preview section.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PreviewLimits {
+    pub commits: usize,
+    pub changed_files: usize,
+    /// Files listed per staged/unstaged diffstat (git's summary line is always kept)
+    pub diffstat: usize,
+    pub stashes: usize,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -42,14 +371,69 @@ impl Default for Config {
             fzf: FzfConfig::default(),
             auto_discovery: AutoDiscoveryConfig::default(),
             beads: BeadsConfig::default(),
+            git_hooks: GitHooksConfig::default(),
+            preview: PreviewConfig::default(),
+            interactive: InteractiveConfig::default(),
+            tmux: TmuxConfig::default(),
+            multiplexer: default_multiplexer(),
+            zellij: ZellijConfig::default(),
+            editor: String::new(),
+            editors: BTreeMap::new(),
+            launchers: BTreeMap::new(),
+            browse: BrowseConfig::default(),
+            forge: String::new(),
+            default_remote: String::new(),
+            repos: BTreeMap::new(),
+            keybindings: default_keybindings(),
+            actions: BTreeMap::new(),
+            hooks: HooksConfig::default(),
+            integrations: IntegrationsConfig::default(),
+            shell: ShellConfig::default(),
+            timeouts: TimeoutsConfig::default(),
+            git: GitConfig::default(),
+            sanitize: SanitizeConfig::default(),
+            json_errors: default_json_errors(),
         }
     }
 }
 
+impl Config {
+    /// The `repos` entry for the repository whose main worktree (or bare
+    /// repository) is at `main`.
+    pub fn repo(&self, main: &Path) -> Option<&RepoConfig> {
+        let main = main.canonicalize().unwrap_or_else(|_| main.to_path_buf());
+        self.repos.iter().find_map(|(key, repo)| {
+            let key = expand_home(key);
+            let key = key.canonicalize().unwrap_or(key);
+            (key == main).then_some(repo)
+        })
+    }
+}
+
 fn default_version() -> String {
     "1.0.0".to_string()
 }
 
+fn default_multiplexer() -> String {
+    "tmux".to_string()
+}
+
+fn default_json_errors() -> String {
+    "stdout".to_string()
+}
+
+fn default_keybindings() -> BTreeMap<String, String> {
+    [
+        ("ctrl-d", "remove"),
+        ("ctrl-e", "edit"),
+        ("ctrl-n", "new"),
+        ("ctrl-t", "multiplexer"),
+    ]
+    .iter()
+    .map(|(key, action)| (key.to_string(), action.to_string()))
+    .collect()
+}
+
 impl Default for FzfConfig {
     fn default() -> Self {
         Self {
@@ -65,10 +449,85 @@ impl Default for AutoDiscoveryConfig {
         Self {
             enabled: true,
             paths: Vec::new(),
+            max_depth: 3,
+            exclude: ["node_modules", "target", ".cache", "vendor"]
+                .map(String::from)
+                .to_vec(),
+            skip_hidden: true,
+            follow_symlinks: false,
+            repos: Vec::new(),
+        }
+    }
+}
+
+impl AutoDiscoveryConfig {
+    /// Reject bad depths and unparseable exclude globs.
+    pub fn validate(&self) -> Result<()> {
+        self.search_paths()?;
+        self.exclude_set()?;
+        Ok(())
+    }
+
+    /// `exclude` compiled to match paths relative to a search path.
+    pub fn exclude_set(&self) -> Result<GlobSet> {
+        let mut set = GlobSetBuilder::new();
+        for pattern in &self.exclude {
+            let trimmed = pattern.trim_end_matches('/');
+            let full = if trimmed.contains('/') {
+                trimmed.trim_start_matches('/').to_string()
+            } else {
+                format!("**/{}", trimmed)
+            };
+            let glob = GlobBuilder::new(&full)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("invalid auto_discovery.exclude pattern '{}'", pattern))?;
+            set.add(glob);
         }
+        Ok(set.build()?)
+    }
+
+    /// The configured paths with their depths, or an error for a depth of 0
+    /// or over [`MAX_DISCOVERY_DEPTH`].
+    pub fn search_paths(&self) -> Result<Vec<SearchPath>> {
+        check_depth("auto_discovery.max_depth", self.max_depth)?;
+        self.paths
+            .iter()
+            .map(|entry| {
+                // A trailing `:DIGITS` is a depth; any other colon is part of the path
+                let (path, max_depth) = match entry.rsplit_once(':') {
+                    Some((path, depth))
+                        if !path.is_empty()
+                            && !depth.is_empty()
+                            && depth.bytes().all(|b| b.is_ascii_digit()) =>
+                    {
+                        let depth = depth.parse().unwrap_or(usize::MAX);
+                        check_depth(&format!("depth of '{}'", entry), depth)?;
+                        (path, depth)
+                    }
+                    _ => (entry.as_str(), self.max_depth),
+                };
+                Ok(SearchPath {
+                    path: expand_home(path),
+                    max_depth,
+                })
+            })
+            .collect()
     }
 }
 
+fn check_depth(what: &str, depth: usize) -> Result<()> {
+    if depth == 0 || depth > MAX_DISCOVERY_DEPTH {
+        anyhow::bail!(
+            "{} must be between 1 and {}, got {}",
+            what,
+            MAX_DISCOVERY_DEPTH,
+            depth
+        );
+    }
+    Ok(())
+}
+
 impl Default for BeadsConfig {
     fn default() -> Self {
         Self {
@@ -78,6 +537,51 @@ impl Default for BeadsConfig {
     }
 }
 
+impl Default for GitHooksConfig {
+    fn default() -> Self {
+        Self {
+            dir: String::new(),
+            mode: "hooks-path".to_string(),
+        }
+    }
+}
+
+impl Default for InteractiveConfig {
+    fn default() -> Self {
+        Self { frecency: true }
+    }
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            sections: [
+                "status",
+                "commits",
+                "changed_files",
+                "diffstat",
+                "stashes",
+                "submodules",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            limits: PreviewLimits::default(),
+        }
+    }
+}
+
+impl Default for PreviewLimits {
+    fn default() -> Self {
+        Self {
+            commits: 5,
+            changed_files: 50,
+            diffstat: 20,
+            stashes: 10,
+        }
+    }
+}
+
 /// Returns the config directory: `~/.config/worktree-manager`
 pub fn config_dir() -> PathBuf {
     let base = directories::BaseDirs::new()
@@ -87,6 +591,39 @@ pub fn config_dir() -> PathBuf {
     base.join("worktree-manager")
 }
 
+/// Returns the cache directory: `~/.cache/worktree-manager`
+pub fn cache_dir() -> PathBuf {
+    let base = directories::BaseDirs::new()
+        .expect("failed to determine home directory")
+        .cache_dir()
+        .to_path_buf();
+    base.join("worktree-manager")
+}
+
+/// Returns the data directory: `~/.local/share/worktree-manager`
+pub fn data_dir() -> PathBuf {
+    let base = directories::BaseDirs::new()
+        .expect("failed to determine home directory")
+        .data_dir()
+        .to_path_buf();
+    base.join("worktree-manager")
+}
+
+/// Expand a leading `~/` (`~\` too on Windows), or a lone `~`, to the home
+/// directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = || directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    let rest = path
+        .strip_prefix("~/")
+        .or_else(|| path.strip_prefix("~\\").filter(|_| cfg!(windows)));
+    match rest {
+        Some(rest) => home().map(|h| h.join(rest)),
+        None if path == "~" => home(),
+        None => None,
+    }
+    .unwrap_or_else(|| PathBuf::from(path))
+}
+
 /// Returns the config file path: `~/.config/worktree-manager/config.yaml`
 pub fn config_path() -> PathBuf {
     config_dir().join("config.yaml")
@@ -105,6 +642,11 @@ pub fn load() -> Result<Config> {
 
     let config: Config = serde_yaml::from_str(&content)
         .with_context(|| format!("failed to parse config file: {}", path.display()))?;
+    config
+        .auto_discovery
+        .validate()
+        .and_then(|_| config.sanitize.validate())
+        .with_context(|| format!("invalid config file: {}", path.display()))?;
 
     Ok(config)
 }
@@ -143,6 +685,34 @@ mod tests {
         assert!(config.auto_discovery.paths.is_empty());
         assert!(!config.beads.enabled);
         assert_eq!(config.beads.redirect_mode, "off");
+        assert!(config.git_hooks.dir.is_empty());
+        assert_eq!(config.git_hooks.mode, "hooks-path");
+        assert_eq!(
+            config.preview.sections,
+            vec![
+                "status",
+                "commits",
+                "changed_files",
+                "diffstat",
+                "stashes",
+                "submodules"
+            ]
+        );
+        assert_eq!(config.preview.limits.commits, 5);
+        assert!(config.interactive.frecency);
+        assert!(!config.integrations.direnv);
+        assert!(!config.integrations.zoxide);
+        assert_eq!(config.keybindings["ctrl-e"], "edit");
+        assert_eq!(config.keybindings["ctrl-d"], "remove");
+        assert_eq!(config.keybindings["ctrl-n"], "new");
+        assert_eq!(config.keybindings["ctrl-t"], "multiplexer");
+        assert_eq!(config.multiplexer, "tmux");
+        assert_eq!(config.json_errors, "stdout");
+        assert_eq!(config.sanitize.replace["#"], "-");
+        assert_eq!(config.sanitize.max_length, 0);
+        assert!(config.zellij.layout.is_empty());
+        assert!(config.tmux.command.is_empty());
+        assert!(config.tmux.layout.is_empty());
     }
 
     #[test]
@@ -182,6 +752,82 @@ beads:
         assert_eq!(config.beads.redirect_mode, "shared-redirect");
     }
 
+    #[test]
+    fn preview_config_fills_missing_fields_with_defaults() {
+        let yaml = r#"
+preview:
+  sections: [commits, status]
+  limits:
+    commits: 12
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.preview.sections, vec!["commits", "status"]);
+        assert_eq!(config.preview.limits.commits, 12);
+        assert_eq!(config.preview.limits.changed_files, 50);
+        assert_eq!(config.preview.limits.stashes, 10);
+        assert_eq!(config.keybindings, default_keybindings());
+    }
+
+    #[test]
+    fn keybindings_replace_defaults() {
+        let yaml = r#"
+keybindings:
+  ctrl-e: edit
+  ctrl-o: "tmux new-window -c {path}"
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.keybindings.len(), 2);
+        assert_eq!(config.keybindings["ctrl-o"], "tmux new-window -c {path}");
+        assert!(!config.keybindings.contains_key("ctrl-d"));
+        assert!(config.actions.is_empty());
+    }
+
+    #[test]
+    fn actions_deserialize_from_yaml() {
+        let yaml = r#"
+actions:
+  ctrl-o: "gh pr view --web"
+  ctrl-b: "cargo build"
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.actions["ctrl-b"], "cargo build");
+        assert_eq!(config.keybindings, default_keybindings());
+    }
+
+    #[test]
+    fn hooks_deserialize_from_yaml() {
+        let yaml = r#"
+hooks:
+  post_add: "npm install"
+  pre_remove: "test -z \"$(git status --porcelain)\""
+  on_failure: abort
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.hooks.post_add.as_deref(), Some("npm install"));
+        assert!(config.hooks.post_remove.is_none());
+        assert_eq!(config.hooks.on_failure, HookFailurePolicy::Abort);
+        assert_eq!(Config::default().hooks.on_failure, HookFailurePolicy::Warn);
+    }
+
+    #[test]
+    fn repo_settings_match_main_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "repos:\n  {}/:\n    editor: \"idea {{path}}\"\n",
+            dir.path().display()
+        );
+        let config: Config = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(config.repo(dir.path()).unwrap().editor, "idea {path}");
+        assert!(config.repo(&dir.path().join("other")).is_none());
+
+        let home = directories::BaseDirs::new()
+            .unwrap()
+            .home_dir()
+            .to_path_buf();
+        assert_eq!(expand_home("~/src/app"), home.join("src/app"));
+        assert_eq!(expand_home("/src/app"), PathBuf::from("/src/app"));
+    }
+
     #[test]
     fn config_dir_returns_path() {
         let dir = config_dir();
@@ -0,0 +1,283 @@
+//! `wt cp`: copy files between worktrees at the same repository paths.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+use walkdir::WalkDir;
+
+use crate::error::WtError;
+use crate::{git, preview, process};
+
+/// Copy `paths` (files, directories or globs, relative to the working
+/// directory) from the current worktree to the same repository paths in
+/// `target`, or from `target` into the current worktree with `from`. Files
+/// that differ at the destination are skipped unless `force`.
+pub fn copy(target: &str, paths: &[String], from: bool, dry_run: bool, force: bool) -> Result<()> {
+    let current_root = git::repo_root(None)?;
+    let cwd = std::env::current_dir()
+        .map_err(|e| WtError::io_error_with_source("failed to get current directory", e.into()))?;
+    // Paths are given relative to the working directory, which is at the same
+    // place in both worktrees
+    let prefix = cwd
+        .canonicalize()
+        .ok()
+        .and_then(|cwd| {
+            let root = current_root.canonicalize().ok()?;
+            cwd.strip_prefix(root).ok().map(Path::to_path_buf)
+        })
+        .unwrap_or_default();
+    let other_root = git::repo_root(Some(&preview::resolve_target(target)?))?;
+
+    let (src_root, dst_root) = if from {
+        (&other_root, &current_root)
+    } else {
+        (&current_root, &other_root)
+    };
+    if src_root == dst_root {
+        return Err(WtError::user_error("source and destination are the same worktree").into());
+    }
+
+    let mut files = BTreeSet::new();
+    for path in paths {
+        let rel = normalize(&prefix.join(path)).ok_or_else(|| {
+            WtError::user_error(format!(
+                "'{}' is outside the worktree; paths must be relative to it",
+                path
+            ))
+        })?;
+        let matched = if is_glob(path) {
+            glob_files(src_root, &rel)?
+        } else {
+            literal_files(src_root, &rel)
+        };
+        if matched.is_empty() {
+            return Err(WtError::not_found(format!(
+                "'{}' matches no files in {}",
+                path,
+                src_root.display()
+            ))
+            .into());
+        }
+        files.extend(matched);
+    }
+
+    let copied = copy_files(src_root, dst_root, &files, dry_run, force)?;
+    let mut summary = format!(
+        "{} {} from {} to {}",
+        if dry_run { "Would copy" } else { "Copied" },
+        count(copied.copied, "file"),
+        src_root.display(),
+        dst_root.display()
+    );
+    if copied.unchanged > 0 {
+        summary.push_str(&format!("; {} already up to date", copied.unchanged));
+    }
+    if !copied.skipped.is_empty() {
+        summary.push_str(&format!(
+            "; skipped {} differing at the destination (use --force to overwrite)",
+            count(copied.skipped.len(), "file")
+        ));
+    }
+    eprintln!("{}", summary);
+    Ok(())
+}
+
+/// What [`copy_files`] did.
+#[derive(Debug, Default)]
+struct Copied {
+    copied: usize,
+    /// Files already identical at the destination
+    unchanged: usize,
+    /// Files left alone because the destination has different contents
+    skipped: Vec<PathBuf>,
+}
+
+/// Copy the repository-relative `files` from `src_root` to `dst_root`,
+/// printing each. Files that already exist at the destination with other
+/// contents are only overwritten with `force`.
+fn copy_files(
+    src_root: &Path,
+    dst_root: &Path,
+    files: &BTreeSet<PathBuf>,
+    dry_run: bool,
+    force: bool,
+) -> Result<Copied> {
+    let mut result = Copied::default();
+    for rel in files {
+        let src = src_root.join(rel);
+        let dst = dst_root.join(rel);
+        if dst.symlink_metadata().is_ok() {
+            if same_contents(&src, &dst) {
+                result.unchanged += 1;
+                continue;
+            }
+            if !force {
+                println!("Skipped {} (differs at the destination)", rel.display());
+                result.skipped.push(rel.clone());
+                continue;
+            }
+        }
+        result.copied += 1;
+        if dry_run {
+            println!("Would copy {}", rel.display());
+            continue;
+        }
+        if let Some(dir) = dst.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                WtError::io_error_with_source(
+                    format!("failed to create {}", dir.display()),
+                    e.into(),
+                )
+            })?;
+        }
+        fs::copy(&src, &dst).map_err(|e| {
+            WtError::io_error_with_source(format!("failed to copy {}", rel.display()), e.into())
+        })?;
+        println!("Copied {}", rel.display());
+    }
+    Ok(result)
+}
+
+fn same_contents(a: &Path, b: &Path) -> bool {
+    matches!((fs::read(a), fs::read(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+fn count(n: usize, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
+
+/// Resolve `.` and `..` in a relative path without touching the file system.
+/// Returns `None` for absolute paths and paths that climb above the root.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// Files under `root` matching the repository-relative glob `pattern`,
+/// tracked or not (ignored files included, e.g. local config).
+fn glob_files(root: &Path, pattern: &Path) -> Result<Vec<PathBuf>> {
+    let pathspec = format!(":(glob){}", pattern.display());
+    let out = process::run_stdout(
+        "git",
+        &["ls-files", "-z", "--cached", "--others", "--", &pathspec],
+        Some(root),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to expand glob", e))?;
+    Ok(out
+        .split('\0')
+        .filter(|p| !p.is_empty() && root.join(p).is_file())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// The file at the repository-relative `path`, or every file below it if it
+/// is a directory (skipping `.git`).
+fn literal_files(root: &Path, path: &Path) -> Vec<PathBuf> {
+    let full = root.join(path);
+    if full.is_file() {
+        return vec![path.to_path_buf()];
+    }
+    if !full.is_dir() {
+        return Vec::new();
+    }
+    WalkDir::new(&full)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_files_walks_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("fixtures/nested")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join("fixtures/a.json"), "{}").unwrap();
+        fs::write(root.join("fixtures/nested/b.json"), "{}").unwrap();
+        fs::write(root.join(".git/config"), "").unwrap();
+
+        let mut files = literal_files(root, Path::new("fixtures"));
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("fixtures/a.json"),
+                PathBuf::from("fixtures/nested/b.json")
+            ]
+        );
+        assert_eq!(
+            literal_files(root, Path::new("fixtures/a.json")),
+            vec![PathBuf::from("fixtures/a.json")]
+        );
+        assert!(literal_files(root, Path::new("missing")).is_empty());
+        assert!(is_glob("src/*.rs") && !is_glob("src/main.rs"));
+    }
+
+    #[test]
+    fn normalize_stays_inside_the_worktree() {
+        assert_eq!(
+            normalize(Path::new("src/./../config/a.toml")),
+            Some(PathBuf::from("config/a.toml"))
+        );
+        assert_eq!(normalize(Path::new("src/..")), Some(PathBuf::new()));
+        assert_eq!(normalize(Path::new("../other")), None);
+        assert_eq!(normalize(Path::new("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn differing_destination_files_are_kept_without_force() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        for name in ["new", "same", "modified"] {
+            fs::write(src.path().join(name), "source").unwrap();
+        }
+        fs::write(dst.path().join("same"), "source").unwrap();
+        fs::write(dst.path().join("modified"), "local edit").unwrap();
+        let files: BTreeSet<_> = ["new", "same", "modified"].map(PathBuf::from).into();
+
+        let copied = copy_files(src.path(), dst.path(), &files, false, false).unwrap();
+        assert_eq!(copied.copied, 1);
+        assert_eq!(copied.unchanged, 1);
+        assert_eq!(copied.skipped, vec![PathBuf::from("modified")]);
+        assert_eq!(
+            fs::read_to_string(dst.path().join("new")).unwrap(),
+            "source"
+        );
+        assert_eq!(
+            fs::read_to_string(dst.path().join("modified")).unwrap(),
+            "local edit"
+        );
+
+        let copied = copy_files(src.path(), dst.path(), &files, false, true).unwrap();
+        assert_eq!((copied.copied, copied.unchanged), (1, 2));
+        assert_eq!(
+            fs::read_to_string(dst.path().join("modified")).unwrap(),
+            "source"
+        );
+    }
+}
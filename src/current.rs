@@ -0,0 +1,62 @@
+//! `wt current`: the worktree containing the working directory.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::{git, worktree};
+
+#[derive(Serialize)]
+struct CurrentWorktree {
+    path: String,
+    branch: Option<String>,
+    head: Option<String>,
+    /// Whether this is the repository's main worktree (the original checkout)
+    main: bool,
+    dirty: bool,
+    repo_root: String,
+}
+
+/// Print the branch, path, kind (main or linked) and dirty state of the
+/// worktree containing the current directory.
+pub fn show_current(json: bool) -> Result<()> {
+    let repo_root = git::repo_root(None)?;
+    let worktrees = git::worktrees_porcelain(&repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+    let current_dir = std::env::current_dir()
+        .map_err(|e| WtError::io_error_with_source("failed to get current directory", e.into()))?;
+
+    let Some(wt) = worktree::containing(&worktrees, &current_dir) else {
+        return Err(WtError::not_found("not inside a worktree").into());
+    };
+
+    // `git worktree list` always lists the main worktree first
+    let main = worktrees.first().is_some_and(|first| first.path == wt.path);
+    let current = CurrentWorktree {
+        path: wt.path.display().to_string(),
+        branch: wt.branch_name().map(str::to_string),
+        head: wt.head.clone(),
+        main,
+        dirty: git::worktree_status(&wt.path).is_ok_and(|s| s.is_dirty()),
+        repo_root: worktrees
+            .first()
+            .map(|first| first.path.display().to_string())
+            .unwrap_or_else(|| repo_root.display().to_string()),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&current)?);
+        return Ok(());
+    }
+
+    let kind = if current.main { "main" } else { "linked" };
+    let status = if current.dirty { "dirty" } else { "clean" };
+    println!(
+        "{} @ {} ({}, {})",
+        current.branch.as_deref().unwrap_or("<detached>"),
+        current.path,
+        kind,
+        status
+    );
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! `wt diff`: compare the HEADs of two worktrees.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::error::WtError;
+use crate::{exec, git, preview};
+
+/// Uncommitted changes listed per side before the diff.
+const MAX_LISTED_CHANGES: usize = 10;
+
+/// Show the diff (or `--stat` with `stat`) from worktree `a`'s HEAD to
+/// worktree `b`'s HEAD. Each side's uncommitted changes, which the diff
+/// doesn't include, are summarized on stderr first, so stdout stays a clean
+/// patch.
+pub fn diff(a: &str, b: &str, stat: bool) -> Result<()> {
+    let path_a = preview::resolve_target(a)?;
+    let path_b = preview::resolve_target(b)?;
+    let (status_a, head_a) = status_of(&path_a, a)?;
+    let (status_b, head_b) = status_of(&path_b, b)?;
+
+    summarize(a, &status_a, &head_a);
+    summarize(b, &status_b, &head_b);
+
+    let mut command = vec!["git".to_string(), "diff".to_string()];
+    if stat {
+        command.push("--stat".to_string());
+    }
+    command.push(head_a);
+    command.push(head_b);
+    // Both worktrees share the object database, so either can run the diff
+    let status = exec::run_in(&path_a, &command)?;
+    if !status.success() {
+        std::process::exit(exec::exit_code(status));
+    }
+    Ok(())
+}
+
+/// The status of the worktree at `path` and its HEAD commit.
+fn status_of(path: &Path, target: &str) -> Result<(git::WorktreeStatus, String)> {
+    let status = git::worktree_status(path).map_err(|e| {
+        WtError::git_error_with_source(format!("failed to read the status of '{}'", target), e)
+    })?;
+    let head = status
+        .head
+        .clone()
+        .ok_or_else(|| WtError::git_error(format!("'{}' has no commits", target)))?;
+    Ok((status, head))
+}
+
+/// Print "<target>: <branch> (<short hash>)" and its uncommitted changes.
+fn summarize(target: &str, status: &git::WorktreeStatus, head: &str) {
+    let branch = status.branch.as_deref().unwrap_or("detached");
+    let short = &head[..head.len().min(7)];
+    match status.changes.len() {
+        0 => eprintln!("{}: {} ({}), clean", target, branch, short),
+        n => {
+            eprintln!(
+                "{}: {} ({}), {} uncommitted change{} not in the diff:",
+                target,
+                branch,
+                short,
+                n,
+                if n == 1 { "" } else { "s" }
+            );
+            for change in status.changes.iter().take(MAX_LISTED_CHANGES) {
+                eprintln!("  {}", change);
+            }
+            if n > MAX_LISTED_CHANGES {
+                eprintln!("  ... and {} more", n - MAX_LISTED_CHANGES);
+            }
+        }
+    }
+}
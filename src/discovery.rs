@@ -1,22 +1,149 @@
 use std::collections::HashSet;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
-use walkdir::WalkDir;
+use globset::GlobBuilder;
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
 
-use crate::git;
+use crate::config::{AutoDiscoveryConfig, SearchPath};
+use crate::error::WtError;
+use crate::{config, git};
 
-/// Discover git repositories under the given search paths.
+/// How long a cached repository list is reused before walking the search paths again.
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Discovered repositories for a discovery configuration, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct RepoCache {
+    discovery: AutoDiscoveryConfig,
+    /// Unix timestamp (seconds) of the discovery
+    created: u64,
+    repos: Vec<PathBuf>,
+}
+
+impl RepoCache {
+    /// Whether this cache answers a discovery with `discovery` at `now`.
+    fn is_fresh(&self, discovery: &AutoDiscoveryConfig, now: u64) -> bool {
+        self.discovery == *discovery && now.saturating_sub(self.created) < CACHE_TTL.as_secs()
+    }
+}
+
+/// Like [`discover`], but reuses the last result for the same discovery
+/// settings for up to an hour. With `refresh`, always walks the search paths
+/// again. Cached repositories that no longer exist are dropped.
+pub fn discover_repos_cached(
+    discovery: &AutoDiscoveryConfig,
+    refresh: bool,
+) -> Result<Vec<PathBuf>> {
+    let cache_file = config::cache_dir().join("repos.json");
+    let now = unix_now();
+
+    if !refresh
+        && let Some(cache) = fs::read_to_string(&cache_file)
+            .ok()
+            .and_then(|content| serde_json::from_str::<RepoCache>(&content).ok())
+        && cache.is_fresh(discovery, now)
+    {
+        return Ok(cache
+            .repos
+            .into_iter()
+            .filter(|repo| repo.exists())
+            .collect());
+    }
+
+    let repos = discover(discovery)?;
+
+    // Caching is best-effort; discovery already succeeded
+    let cache = RepoCache {
+        discovery: discovery.clone(),
+        created: now,
+        repos: repos.clone(),
+    };
+    if let Ok(content) = serde_json::to_string(&cache) {
+        let _ = fs::create_dir_all(config::cache_dir());
+        let _ = fs::write(&cache_file, content);
+    }
+
+    Ok(repos)
+}
+
+/// Discover repositories under the configured auto-discovery paths, plus the
+/// registered ones, reusing the cached list unless `refresh` is set. With
+/// `repo`, keeps only those matching that glob (see [`filter_repos`]). Fails
+/// if neither is configured or no repositories are found.
+pub fn discover_configured_repos(
+    config: &config::Config,
+    refresh: bool,
+    repo: Option<&str>,
+) -> Result<Vec<PathBuf>> {
+    // Check that discovery paths or repositories are configured
+    if config.auto_discovery.paths.is_empty() && config.auto_discovery.repos.is_empty() {
+        return Err(WtError::user_error(
+            "No auto-discovery paths configured. Run: wt config <paths...> or wt repo add",
+        )
+        .into());
+    }
+
+    // Discover all repos
+    let repos = discover_repos_cached(&config.auto_discovery, refresh)?;
+    if repos.is_empty() {
+        return Err(
+            WtError::not_found("No git repositories found in configured discovery paths.").into(),
+        );
+    }
+    match repo {
+        Some(pattern) => filter_repos(repos, pattern),
+        None => Ok(repos),
+    }
+}
+
+/// Keep the repositories whose directory name matches the glob `pattern`,
+/// e.g. `backend-*`; a pattern with `/` is matched against the whole path
+/// instead. Fails if the pattern is invalid or matches none of them.
+pub fn filter_repos(repos: Vec<PathBuf>, pattern: &str) -> Result<Vec<PathBuf>> {
+    let by_path = pattern.contains('/');
+    let expanded = config::expand_home(pattern);
+    let matcher = GlobBuilder::new(&expanded.to_string_lossy())
+        .literal_separator(true)
+        .build()
+        .map_err(|e| {
+            WtError::user_error_with_source(format!("invalid --repo pattern '{}'", pattern), e)
+        })?
+        .compile_matcher();
+
+    let matched: Vec<PathBuf> = repos
+        .into_iter()
+        .filter(|repo| {
+            if by_path {
+                matcher.is_match(repo)
+            } else {
+                repo.file_name().is_some_and(|name| matcher.is_match(name))
+            }
+        })
+        .collect();
+    if matched.is_empty() {
+        return Err(
+            WtError::not_found(format!("no discovered repository matches '{}'", pattern)).into(),
+        );
+    }
+    Ok(matched)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Discover git repositories under the given search paths, each walked to
+/// the default depth of 3 unless it carries a `PATH:DEPTH` override.
 /// Returns a list of repository root paths (deduplicated).
 ///
-/// # Implementation Details
-///
-/// - Walks each search path up to 3 levels deep
-/// - Looks for `.git` entries (either directory or file)
-/// - For worktrees (`.git` file), resolves to the main repo root
-/// - Deduplicates results so each main repo appears only once
-/// - Skips paths that don't exist or can't be read
-///
 /// # Arguments
 ///
 /// * `search_paths` - List of directory paths to search for git repositories
@@ -30,7 +157,7 @@ use crate::git;
 /// ```no_run
 /// use worktree_manager::discovery;
 ///
-/// let paths = vec!["/home/user/projects".to_string()];
+/// let paths = vec!["/home/user/projects".to_string(), "/home/user/work:5".to_string()];
 /// let repos = discovery::discover_repos(&paths)?;
 /// for repo in repos {
 ///     println!("Found repo: {}", repo.display());
@@ -38,11 +165,32 @@ use crate::git;
 /// # Ok::<(), anyhow::Error>(())
 /// ```
 pub fn discover_repos(search_paths: &[String]) -> Result<Vec<PathBuf>> {
-    let mut repo_roots = HashSet::new();
+    discover(&AutoDiscoveryConfig {
+        paths: search_paths.to_vec(),
+        ..AutoDiscoveryConfig::default()
+    })
+}
 
-    for search_path in search_paths {
-        let path = PathBuf::from(search_path);
+/// Discover git repositories under the search paths of an `auto_discovery`
+/// config section. Fails if a depth is out of range.
+///
+/// # Implementation Details
+///
+/// - Walks each search path `max_depth` levels deep, or its own `PATH:DEPTH`
+/// - Never enters directories matching `exclude`, e.g. `node_modules`
+/// - Looks for directories holding a `.git` entry (either directory or file)
+/// - Skips what `.gitignore` files ignore and, with `skip_hidden`, hidden
+///   directories
+/// - Follows symlinks only with `follow_symlinks`, walking each directory once
+/// - For worktrees (`.git` file), resolves to the main repo root
+/// - Adds the repositories registered with `wt repo add`
+/// - Deduplicates results so each main repo appears only once
+/// - Skips paths that don't exist or can't be read
+pub fn discover(discovery: &AutoDiscoveryConfig) -> Result<Vec<PathBuf>> {
+    let mut repo_roots = HashSet::new();
+    let exclude = discovery.exclude_set()?;
 
+    for SearchPath { path, max_depth } in discovery.search_paths()? {
         // Skip if path doesn't exist
         if !path.exists() {
             eprintln!("Warning: search path does not exist: {}", path.display());
@@ -58,36 +206,76 @@ pub fn discover_repos(search_paths: &[String]) -> Result<Vec<PathBuf>> {
             continue;
         }
 
-        // Walk the directory tree up to 3 levels deep
-        for entry in WalkDir::new(&path)
-            .max_depth(3)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let entry_path = entry.path();
-
-            // Look for .git entries
-            if entry_path.file_name().and_then(|s| s.to_str()) == Some(".git") {
-                // Parent directory is the potential repo root
-                if let Some(parent) = entry_path.parent() {
-                    match resolve_repo_root(parent) {
-                        Ok(repo_root) => {
-                            repo_roots.insert(repo_root);
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "Warning: failed to resolve repo root for {}: {}",
-                                parent.display(),
-                                e
-                            );
-                        }
-                    }
+        let root = path.clone();
+        let exclude = exclude.clone();
+        let follow = discovery.follow_symlinks;
+        let visited = Mutex::new(HashSet::new());
+        // A repository is a directory holding `.git`, so walk one level less
+        // than the depth of the `.git` entries to find
+        let walker = WalkBuilder::new(&path)
+            .max_depth(Some(max_depth - 1))
+            .hidden(discovery.skip_hidden)
+            .follow_links(follow)
+            .filter_entry(move |e| {
+                if !e.file_type().is_some_and(|t| t.is_dir()) {
+                    return true;
+                }
+                if e.file_name() == ".git"
+                    || e.path()
+                        .strip_prefix(&root)
+                        .is_ok_and(|rel| exclude.is_match(rel))
+                {
+                    return false;
+                }
+                // Through symlinks the same directory can turn up again, even
+                // as its own descendant; walk it once
+                !follow || dir_id(e.path()).is_none_or(|id| visited.lock().unwrap().insert(id))
+            })
+            .build();
+
+        for entry in walker.filter_map(|e| e.ok()) {
+            let dir = entry.path();
+            if !entry.file_type().is_some_and(|t| t.is_dir()) || !dir.join(".git").exists() {
+                continue;
+            }
+            match resolve_repo_root(dir) {
+                Ok(repo_root) => {
+                    repo_roots.insert(repo_root);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Warning: failed to resolve repo root for {}: {}",
+                        dir.display(),
+                        e
+                    );
                 }
             }
         }
     }
 
+    for entry in &discovery.repos {
+        let path = config::expand_home(entry);
+        if !path.is_dir() {
+            eprintln!(
+                "Warning: registered repository does not exist: {}",
+                path.display()
+            );
+            continue;
+        }
+        match resolve_repo_root(&path) {
+            Ok(repo_root) => {
+                repo_roots.insert(repo_root);
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: failed to resolve repo root for {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+
     // Convert HashSet to sorted Vec for consistent output
     let mut repos: Vec<PathBuf> = repo_roots.into_iter().collect();
     repos.sort();
@@ -95,6 +283,23 @@ pub fn discover_repos(search_paths: &[String]) -> Result<Vec<PathBuf>> {
     Ok(repos)
 }
 
+/// What identifies a directory however it's reached: its device and inode.
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+#[cfg(unix)]
+fn dir_id(path: &Path) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_id(path: &Path) -> Option<DirId> {
+    path.canonicalize().ok()
+}
+
 /// Resolves the true repository root for a given path.
 ///
 /// For normal repos with `.git` directory, this returns the parent directory.
@@ -118,8 +323,53 @@ fn resolve_repo_root(path: &Path) -> Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::test_support::init_repo;
     use std::fs;
 
+    fn discovery(paths: &[&str]) -> AutoDiscoveryConfig {
+        AutoDiscoveryConfig {
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            ..AutoDiscoveryConfig::default()
+        }
+    }
+
+    #[test]
+    fn repo_cache_freshness() {
+        let projects = discovery(&["/home/user/projects"]);
+        let cache = RepoCache {
+            discovery: projects.clone(),
+            created: 1_000,
+            repos: Vec::new(),
+        };
+        assert!(cache.is_fresh(&projects, 1_000 + 60));
+        assert!(!cache.is_fresh(&projects, 1_000 + CACHE_TTL.as_secs()));
+        assert!(!cache.is_fresh(&discovery(&["/home/user/work"]), 1_060));
+        let deeper = AutoDiscoveryConfig {
+            max_depth: 5,
+            ..projects
+        };
+        assert!(!cache.is_fresh(&deeper, 1_060));
+    }
+
+    #[test]
+    fn filter_repos_by_name_or_path() {
+        let repos: Vec<PathBuf> = ["/src/backend-api", "/src/backend-jobs", "/work/frontend"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let backends = filter_repos(repos.clone(), "backend-*").unwrap();
+        assert_eq!(backends, repos[..2].to_vec());
+        assert_eq!(
+            filter_repos(repos.clone(), "/work/*").unwrap(),
+            repos[2..].to_vec()
+        );
+        // `*` doesn't cross directories in a path pattern
+        assert!(filter_repos(repos.clone(), "/*/frontend").is_ok());
+        assert!(filter_repos(repos.clone(), "/*").is_err());
+        assert!(filter_repos(repos, "[").is_err());
+    }
+
     #[test]
     fn discover_repos_returns_empty_for_empty_paths() {
         let repos = discover_repos(&[]).unwrap();
@@ -200,4 +450,109 @@ mod tests {
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn discover_walks_deeper_with_configured_depth() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo = temp_dir.path().join("org").join("team").join("repo");
+        init_repo(&repo);
+        let root = temp_dir.path().to_string_lossy().to_string();
+        let repo = repo.canonicalize().unwrap();
+
+        assert!(
+            !discover_repos(std::slice::from_ref(&root))
+                .unwrap()
+                .contains(&repo)
+        );
+        assert!(
+            discover_repos(&[format!("{}:4", root)])
+                .unwrap()
+                .contains(&repo)
+        );
+        let deeper = AutoDiscoveryConfig {
+            max_depth: 4,
+            ..discovery(&[&root])
+        };
+        assert!(discover(&deeper).unwrap().contains(&repo));
+        assert!(discover(&discovery(&[&format!("{}:0", root)])).is_err());
+    }
+
+    #[test]
+    fn discover_skips_excluded_directories() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let app = root.join("app");
+        let dependency = root.join("node_modules").join("dep");
+        let fixture = root.join("tests").join("fixtures");
+        init_repo(&app);
+        init_repo(&dependency);
+        init_repo(&fixture);
+
+        let defaults = discovery(&[&root.to_string_lossy()]);
+        let repos = discover(&defaults).unwrap();
+        assert!(repos.contains(&app) && repos.contains(&fixture));
+        assert!(!repos.contains(&dependency));
+
+        let custom = AutoDiscoveryConfig {
+            exclude: vec!["tests/*".to_string()],
+            ..defaults
+        };
+        let repos = discover(&custom).unwrap();
+        assert!(repos.contains(&app) && repos.contains(&dependency));
+        assert!(!repos.contains(&fixture));
+
+        let invalid = AutoDiscoveryConfig {
+            exclude: vec!["[".to_string()],
+            ..custom
+        };
+        assert!(discover(&invalid).is_err());
+    }
+
+    #[test]
+    fn discover_skips_ignored_and_hidden_directories() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let app = root.join("app");
+        let artifact = app.join("build").join("checkout");
+        let dotfiles = root.join(".dotfiles");
+        init_repo(&app);
+        init_repo(&artifact);
+        init_repo(&dotfiles);
+        fs::write(app.join(".gitignore"), "build/\n").unwrap();
+
+        let defaults = discovery(&[&root.to_string_lossy()]);
+        assert_eq!(discover(&defaults).unwrap(), vec![app.clone()]);
+
+        let with_hidden = AutoDiscoveryConfig {
+            skip_hidden: false,
+            ..defaults
+        };
+        assert_eq!(discover(&with_hidden).unwrap(), vec![dotfiles, app]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn discover_follows_symlinks_when_asked() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let farm = root.join("farm");
+        let app = root.join("elsewhere").join("app");
+        init_repo(&app);
+        fs::create_dir_all(farm.join("group")).unwrap();
+        std::os::unix::fs::symlink(&app, farm.join("group").join("app")).unwrap();
+        // A cycle back up the tree must not be walked forever
+        std::os::unix::fs::symlink(&farm, farm.join("group").join("loop")).unwrap();
+
+        let plain = AutoDiscoveryConfig {
+            max_depth: 10,
+            ..discovery(&[&farm.to_string_lossy()])
+        };
+        assert!(discover(&plain).unwrap().is_empty());
+
+        let following = AutoDiscoveryConfig {
+            follow_symlinks: true,
+            ..plain
+        };
+        assert_eq!(discover(&following).unwrap(), vec![app]);
+    }
 }
@@ -0,0 +1,299 @@
+//! `wt doctor`: diagnose the environment and the current repository's worktrees.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{config, git, init, process};
+
+/// Result of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Ok,
+    /// Something works worse than it could
+    Warning,
+    /// Something is broken
+    Error,
+    /// The check doesn't apply here (e.g. outside a repository)
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    message: String,
+    /// What to do about a warning or error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, message: impl Into<String>) -> Self {
+        Check {
+            name,
+            status,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Run all checks and print a report with fixes for every problem. Exits with
+/// status 1 if any check failed; warnings don't change the exit status.
+pub fn doctor(json: bool) -> Result<()> {
+    let mut checks = vec![check_git(), check_fzf(), check_shell_integration()];
+    checks.extend(check_config());
+    checks.extend(check_worktrees());
+
+    let failed = checks.iter().any(|c| c.status == Status::Error);
+    if json {
+        let report = serde_json::json!({
+            "ok": !failed,
+            "checks": checks,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&checks);
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn check_git() -> Check {
+    let version = match process::run_stdout("git", &["--version"], None) {
+        Ok(out) => out.trim().to_string(),
+        Err(_) => {
+            return Check::new("git", Status::Error, "git not found on PATH")
+                .fix("install git and make sure it is on your PATH");
+        }
+    };
+    match git::parse_version(&version) {
+        Some(found) if found < git::MIN_VERSION => Check::new(
+            "git",
+            Status::Error,
+            format!(
+                "{} is too old; wt needs git {}.{} or newer",
+                version,
+                git::MIN_VERSION.0,
+                git::MIN_VERSION.1
+            ),
+        )
+        .fix("upgrade git"),
+        Some(_) => Check::new("git", Status::Ok, version),
+        None => Check::new(
+            "git",
+            Status::Warning,
+            format!("could not parse git version from '{}'", version),
+        ),
+    }
+}
+
+fn check_fzf() -> Check {
+    match process::run_stdout("fzf", &["--version"], None) {
+        Ok(out) => Check::new(
+            "fzf",
+            Status::Ok,
+            format!("fzf {}", out.split_whitespace().next().unwrap_or("")),
+        ),
+        Err(_) => Check::new(
+            "fzf",
+            Status::Warning,
+            "fzf not found on PATH; the interactive picker is unavailable",
+        )
+        .fix("install fzf (https://github.com/junegunn/fzf)"),
+    }
+}
+
+fn check_shell_integration() -> Check {
+    match init::integration_status() {
+        Ok((path, true)) => Check::new(
+            "shell integration",
+            Status::Ok,
+            format!("configured in {}", path.display()),
+        ),
+        Ok((path, false)) => Check::new(
+            "shell integration",
+            Status::Warning,
+            format!(
+                "not found in {}; cd and edit actions won't change your shell",
+                path.display()
+            ),
+        )
+        .fix("run `wt init` (or add `eval \"$(wt init <shell>)\"` to your shell config)"),
+        Err(e) => Check::new("shell integration", Status::Skipped, format!("{:#}", e)),
+    }
+}
+
+/// Config file validity and, if it loads, the discovery paths it lists.
+fn check_config() -> Vec<Check> {
+    let path = config::config_path();
+    let config = match config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![
+                Check::new("config", Status::Error, format!("{:#}", e))
+                    .fix(format!("fix or remove {}", path.display())),
+            ];
+        }
+    };
+
+    let config_check = if path.exists() {
+        Check::new("config", Status::Ok, format!("{} is valid", path.display()))
+    } else {
+        Check::new("config", Status::Ok, "no config file; using defaults")
+    };
+
+    // load() already rejected bad depths
+    let paths = config.auto_discovery.search_paths().unwrap_or_default();
+    let missing: Vec<String> = paths
+        .iter()
+        .filter(|p| !p.path.is_dir())
+        .map(|p| p.path.display().to_string())
+        .collect();
+    let discovery_check = if paths.is_empty() {
+        Check::new(
+            "discovery paths",
+            Status::Skipped,
+            "none configured; --all commands need them",
+        )
+    } else if missing.is_empty() {
+        Check::new(
+            "discovery paths",
+            Status::Ok,
+            format!("{} configured, all exist", paths.len()),
+        )
+    } else {
+        Check::new(
+            "discovery paths",
+            Status::Warning,
+            format!("not a directory: {}", missing.join(", ")),
+        )
+        .fix("update them with `wt config set-discovery-paths <paths...>`")
+    };
+
+    vec![config_check, discovery_check]
+}
+
+/// Orphaned `.git/worktrees` entries and worktrees whose `.git` link is
+/// broken, in the repository containing the working directory.
+fn check_worktrees() -> Vec<Check> {
+    let Some(dirs) = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| git::repo_dirs(&cwd).ok())
+    else {
+        return vec![Check::new(
+            "worktrees",
+            Status::Skipped,
+            "not in a git repository",
+        )];
+    };
+
+    let orphaned = orphaned_entries(&dirs.common_dir);
+    let orphan_check = if orphaned.is_empty() {
+        Check::new(
+            "orphaned worktree entries",
+            Status::Ok,
+            "every .git/worktrees entry has a worktree",
+        )
+    } else {
+        Check::new(
+            "orphaned worktree entries",
+            Status::Warning,
+            format!("no worktree directory for {}", orphaned.join(", ")),
+        )
+        .fix("run `wt prune`")
+    };
+
+    let broken = match git::worktrees_porcelain(&dirs.toplevel) {
+        Ok(worktrees) => worktrees
+            .iter()
+            .skip(1)
+            .filter(|wt| !wt.bare && wt.path.is_dir() && !git::has_valid_git_link(&wt.path))
+            .map(|wt| wt.path.clone())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let link_check = if broken.is_empty() {
+        Check::new(
+            "worktree links",
+            Status::Ok,
+            "every worktree links back to the repository",
+        )
+    } else {
+        let paths: Vec<String> = broken.iter().map(|p| p.display().to_string()).collect();
+        Check::new(
+            "worktree links",
+            Status::Error,
+            format!("broken .git link in {}", paths.join(", ")),
+        )
+        .fix(format!(
+            "run `git worktree repair {}` from the main worktree",
+            paths.join(" ")
+        ))
+    };
+
+    vec![orphan_check, link_check]
+}
+
+/// Names of `<common_dir>/worktrees/*` entries whose `gitdir` file points to a
+/// `.git` file that no longer exists.
+fn orphaned_entries(common_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(common_dir.join("worktrees")) else {
+        return Vec::new();
+    };
+    let mut orphaned: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            fs::read_to_string(e.path().join("gitdir"))
+                .is_ok_and(|gitdir| !Path::new(gitdir.trim()).exists())
+        })
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    orphaned.sort();
+    orphaned
+}
+
+fn print_report(checks: &[Check]) {
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in checks {
+        let mark = match check.status {
+            Status::Ok => "✓",
+            Status::Warning => "!",
+            Status::Error => "✗",
+            Status::Skipped => "-",
+        };
+        println!("{} {:<width$}  {}", mark, check.name, check.message);
+        if let Some(fix) = &check.fix {
+            println!("  {:<width$}  fix: {}", "", fix);
+        }
+    }
+
+    let errors = checks.iter().filter(|c| c.status == Status::Error).count();
+    let warnings = checks
+        .iter()
+        .filter(|c| c.status == Status::Warning)
+        .count();
+    if errors == 0 && warnings == 0 {
+        println!("\nNo problems found");
+    } else {
+        println!(
+            "\n{} error{}, {} warning{}",
+            errors,
+            if errors == 1 { "" } else { "s" },
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        );
+    }
+}
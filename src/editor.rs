@@ -0,0 +1,146 @@
+//! Editor command templates: the `editor` and `editors` config settings used
+//! by the picker's edit action and `wt add --open`.
+
+use std::env;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::action::ShellAction;
+use crate::config::{self, Config};
+use crate::error::WtError;
+use crate::{git, keymap, process, tmux};
+
+/// The command template for the editor `name` from `editors`, or for `None`
+/// the default: the `repos` entry's `editor` for the repository of the
+/// worktree at `path`, else `editor`. `Ok(None)` means no template is
+/// configured and the shell's `$EDITOR` should be used.
+pub fn template(config: &Config, path: &Path, name: Option<&str>) -> Result<Option<String>> {
+    match name {
+        Some(name) => config.editors.get(name).cloned().map(Some).ok_or_else(|| {
+            let known = config.editors.keys().cloned().collect::<Vec<_>>();
+            WtError::config_error(if known.is_empty() {
+                format!("unknown editor '{}'; no `editors` are configured", name)
+            } else {
+                format!(
+                    "unknown editor '{}' (configured: {})",
+                    name,
+                    known.join(", ")
+                )
+            })
+            .into()
+        }),
+        None => {
+            let repo_editor = git::worktrees_porcelain(path)
+                .ok()
+                .and_then(|worktrees| worktrees.into_iter().next())
+                .and_then(|main| config.repo(&main.path))
+                .map(|repo| repo.editor.clone());
+            Ok(repo_editor
+                .into_iter()
+                .chain(Some(config.editor.clone()))
+                .find(|t| !t.trim().is_empty()))
+        }
+    }
+}
+
+/// Fill in `{path}` (the worktree path) and `{branch}` (its branch, or its
+/// directory name on detached HEAD), both quoted with `quote` for the shell
+/// that will run the command.
+pub fn render(template: &str, path: &Path, quote: fn(&str) -> String) -> String {
+    let mut command = template.replace("{path}", &quote(&path.to_string_lossy()));
+    if command.contains("{branch}") {
+        command = command.replace("{branch}", &quote(&tmux::branch_name(path)));
+    }
+    command
+}
+
+/// The picker's shell action for editing the worktree at `path` with the
+/// editor `name` (default: `editor`): a command that changes into the
+/// worktree and runs the template, or an edit action for the shell's
+/// `$EDITOR`.
+pub fn picker_action(path: &Path, name: Option<&str>) -> Result<ShellAction> {
+    let config = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
+    Ok(match template(&config, path, name)? {
+        Some(template) => ShellAction::Run(format!(
+            "cd {} && {}",
+            keymap::shell_quote(&path.to_string_lossy()),
+            render(&template, path, keymap::shell_quote)
+        )),
+        None => ShellAction::Edit(path.to_path_buf()),
+    })
+}
+
+/// Open the worktree at `path` with the editor `name` (default: `editor`,
+/// then `$EDITOR`), running it in the worktree with the terminal attached.
+pub fn open(path: &Path, name: Option<&str>) -> Result<()> {
+    let config = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
+    let command = match template(&config, path, name)? {
+        Some(template) => render(&template, path, process::shell_quote),
+        None => {
+            let editor = env::var("EDITOR")
+                .ok()
+                .filter(|e| !e.trim().is_empty())
+                .ok_or_else(|| {
+                    WtError::config_error(
+                        "no editor configured; set `editor` in the config or $EDITOR",
+                    )
+                })?;
+            format!(
+                "{} {}",
+                editor,
+                process::shell_quote(&path.to_string_lossy())
+            )
+        }
+    };
+
+    let status = process::shell(&command)
+        .current_dir(path)
+        .status()
+        .map_err(|e| WtError::io_error_with_source("failed to run editor", e.into()))?;
+    if !status.success() {
+        return Err(WtError::user_error(format!("editor command failed: {}", command)).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_picks_named_or_default_editor() {
+        let mut config = Config::default();
+        let path = Path::new("/nonexistent/app");
+        assert_eq!(template(&config, path, None).unwrap(), None);
+
+        config.editor = "nvim {path}".to_string();
+        config
+            .editors
+            .insert("code".to_string(), "code --new-window {path}".to_string());
+        assert_eq!(
+            template(&config, path, None).unwrap().as_deref(),
+            Some("nvim {path}")
+        );
+        assert_eq!(
+            template(&config, path, Some("code")).unwrap().as_deref(),
+            Some("code --new-window {path}")
+        );
+        let err = template(&config, path, Some("zed")).unwrap_err();
+        assert!(err.to_string().contains("configured: code"));
+    }
+
+    #[test]
+    fn render_quotes_path() {
+        assert_eq!(
+            render(
+                "code --new-window {path}",
+                Path::new("/src/my app"),
+                keymap::shell_quote
+            ),
+            "code --new-window '/src/my app'"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use serde::Serialize;
 use std::fmt;
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Machine-readable error codes for programmatic error handling
@@ -16,6 +17,10 @@ pub enum ErrorCode {
     ConfigError,
     /// File system error
     IoError,
+    /// A spawned command ran past its timeout and was stopped
+    Timeout,
+    /// The user backed out of a picker or confirmation
+    Cancelled,
 }
 
 impl ErrorCode {
@@ -27,21 +32,50 @@ impl ErrorCode {
             ErrorCode::GitError => 3,
             ErrorCode::ConfigError => 4,
             ErrorCode::IoError => 5,
+            ErrorCode::Timeout => 6,
+            ErrorCode::Cancelled => 7,
         }
     }
 }
 
+/// What an error is about, added to its JSON next to the message so agents
+/// can react without parsing text. Unset fields are left out.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ErrorDetails {
+    /// Short machine-readable cause, e.g. "dirty" or "locked"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The worktree or file the error is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// What was asked for, e.g. the target given to `wt remove`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// What to do about it, e.g. a flag to retry with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// What the user may have meant, e.g. worktree paths
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+}
+
 /// Structured error type for wt commands
 #[derive(Error, Debug)]
 pub enum WtError {
     #[error("{message}")]
-    UserError { message: String },
+    UserError {
+        message: String,
+        details: Box<ErrorDetails>,
+    },
 
     #[error("{message}")]
     NotFound {
         message: String,
         #[source]
         source: Option<anyhow::Error>,
+        details: Box<ErrorDetails>,
     },
 
     #[error("{message}")]
@@ -49,6 +83,7 @@ pub enum WtError {
         message: String,
         #[source]
         source: Option<anyhow::Error>,
+        details: Box<ErrorDetails>,
     },
 
     #[error("{message}")]
@@ -56,6 +91,7 @@ pub enum WtError {
         message: String,
         #[source]
         source: Option<anyhow::Error>,
+        details: Box<ErrorDetails>,
     },
 
     #[error("{message}")]
@@ -63,6 +99,19 @@ pub enum WtError {
         message: String,
         #[source]
         source: Option<anyhow::Error>,
+        details: Box<ErrorDetails>,
+    },
+
+    #[error("{message}")]
+    Timeout {
+        message: String,
+        details: Box<ErrorDetails>,
+    },
+
+    #[error("{message}")]
+    Cancelled {
+        message: String,
+        details: Box<ErrorDetails>,
     },
 }
 
@@ -75,6 +124,8 @@ impl WtError {
             WtError::GitError { .. } => ErrorCode::GitError,
             WtError::ConfigError { .. } => ErrorCode::ConfigError,
             WtError::IoError { .. } => ErrorCode::IoError,
+            WtError::Timeout { .. } => ErrorCode::Timeout,
+            WtError::Cancelled { .. } => ErrorCode::Cancelled,
         }
     }
 
@@ -83,17 +134,110 @@ impl WtError {
         self.code().exit_code()
     }
 
-    /// Convert to JSON error output
+    /// The structured context attached with the `with_*` methods.
+    pub fn details(&self) -> &ErrorDetails {
+        match self {
+            WtError::UserError { details, .. }
+            | WtError::NotFound { details, .. }
+            | WtError::GitError { details, .. }
+            | WtError::ConfigError { details, .. }
+            | WtError::IoError { details, .. }
+            | WtError::Timeout { details, .. }
+            | WtError::Cancelled { details, .. } => details,
+        }
+    }
+
+    fn details_mut(&mut self) -> &mut ErrorDetails {
+        match self {
+            WtError::UserError { details, .. }
+            | WtError::NotFound { details, .. }
+            | WtError::GitError { details, .. }
+            | WtError::ConfigError { details, .. }
+            | WtError::IoError { details, .. }
+            | WtError::Timeout { details, .. }
+            | WtError::Cancelled { details, .. } => details,
+        }
+    }
+
+    /// Suggestions attached with [`WtError::with_candidates`].
+    pub fn candidates(&self) -> &[String] {
+        &self.details().candidates
+    }
+
+    /// Attach suggestions for what the user meant.
+    pub fn with_candidates(mut self, suggestions: Vec<String>) -> Self {
+        self.details_mut().candidates = suggestions;
+        self
+    }
+
+    /// Attach a machine-readable cause, e.g. "dirty" (see the error schema
+    /// for the ones in use).
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.details_mut().reason = Some(reason.to_string());
+        self
+    }
+
+    /// Attach the worktree or file the error is about.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.details_mut().path = Some(path.into());
+        self
+    }
+
+    /// Attach the branch the error is about, without `refs/heads/`.
+    pub fn with_branch(mut self, branch: &str) -> Self {
+        let branch = crate::git::short_branch(branch);
+        self.details_mut().branch = Some(branch.to_string());
+        self
+    }
+
+    /// Attach what the user asked for, e.g. a `wt remove` target.
+    pub fn with_target(mut self, target: &str) -> Self {
+        self.details_mut().target = Some(target.to_string());
+        self
+    }
+
+    /// Attach what to do about the error, e.g. "retry with --force".
+    pub fn with_hint(mut self, hint: impl fmt::Display) -> Self {
+        self.details_mut().hint = Some(hint.to_string());
+        self
+    }
+
+    /// Whether the user cancelled rather than something failing.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, WtError::Cancelled { .. })
+    }
+
+    /// Convert to JSON error output. A cancellation isn't an error, so it's
+    /// `{"cancelled": true, ...}` instead.
     pub fn to_json(&self) -> serde_json::Value {
-        serde_json::json!({
-            "error": true,
-            "code": self.code(),
-            "message": self.to_string(),
-        })
+        let mut json = if self.is_cancelled() {
+            serde_json::json!({
+                "cancelled": true,
+                "code": self.code(),
+                "message": self.to_string(),
+            })
+        } else {
+            serde_json::json!({
+                "error": true,
+                "code": self.code(),
+                "message": self.to_string(),
+            })
+        };
+        if let (Some(json), Ok(serde_json::Value::Object(details))) =
+            (json.as_object_mut(), serde_json::to_value(self.details()))
+        {
+            json.extend(details);
+        }
+        json
     }
 
-    /// Print error in human-readable structured format
+    /// Print error in human-readable structured format. A cancellation is
+    /// just its message.
     pub fn print_human(&self) {
+        if self.is_cancelled() {
+            eprintln!("{}", self);
+            return;
+        }
         eprintln!(
             "error[{}]: {}",
             format!("{:?}", self.code()).to_lowercase(),
@@ -111,6 +255,7 @@ impl WtError {
     pub fn user_error(message: impl fmt::Display) -> Self {
         WtError::UserError {
             message: message.to_string(),
+            details: Box::default(),
         }
     }
 
@@ -120,6 +265,7 @@ impl WtError {
     ) -> Self {
         WtError::UserError {
             message: format!("{}: {}", message, source.into()),
+            details: Box::default(),
         }
     }
 
@@ -127,15 +273,15 @@ impl WtError {
         WtError::NotFound {
             message: message.to_string(),
             source: None,
+            details: Box::default(),
         }
     }
 
-    // Not currently used, but kept for API completeness
-    #[allow(dead_code)]
     pub fn not_found_with_source(message: impl fmt::Display, source: anyhow::Error) -> Self {
         WtError::NotFound {
             message: message.to_string(),
             source: Some(source),
+            details: Box::default(),
         }
     }
 
@@ -145,6 +291,7 @@ impl WtError {
         WtError::GitError {
             message: message.to_string(),
             source: None,
+            details: Box::default(),
         }
     }
 
@@ -152,15 +299,15 @@ impl WtError {
         WtError::GitError {
             message: message.to_string(),
             source: Some(source),
+            details: Box::default(),
         }
     }
 
-    // Not currently used, but kept for API completeness
-    #[allow(dead_code)]
     pub fn config_error(message: impl fmt::Display) -> Self {
         WtError::ConfigError {
             message: message.to_string(),
             source: None,
+            details: Box::default(),
         }
     }
 
@@ -168,6 +315,7 @@ impl WtError {
         WtError::ConfigError {
             message: message.to_string(),
             source: Some(source),
+            details: Box::default(),
         }
     }
 
@@ -175,6 +323,7 @@ impl WtError {
         WtError::IoError {
             message: message.to_string(),
             source: None,
+            details: Box::default(),
         }
     }
 
@@ -182,6 +331,23 @@ impl WtError {
         WtError::IoError {
             message: message.to_string(),
             source: Some(source),
+            details: Box::default(),
+        }
+    }
+
+    pub fn timeout(message: impl fmt::Display) -> Self {
+        WtError::Timeout {
+            message: message.to_string(),
+            details: Box::default(),
+        }
+    }
+
+    /// The user dismissed a picker or answered no: "Cancelled." on stderr,
+    /// or `{"cancelled": true}` with `--json`, and exit code 7.
+    pub fn cancelled() -> Self {
+        WtError::Cancelled {
+            message: "Cancelled.".to_string(),
+            details: Box::default(),
         }
     }
 }
@@ -191,6 +357,7 @@ impl From<anyhow::Error> for WtError {
     fn from(err: anyhow::Error) -> Self {
         WtError::UserError {
             message: err.to_string(),
+            details: Box::default(),
         }
     }
 }
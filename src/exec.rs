@@ -0,0 +1,266 @@
+//! Run commands inside worktrees without changing directory.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::worktree::Worktree;
+use crate::{config, discovery, git, preview, process, worktree};
+
+/// Exit code reported when a command can't be started, as in shells.
+const NOT_STARTED: i32 = 127;
+
+/// Run `command` in the worktree matching `target` (branch name or path),
+/// streaming its output, and exit with its exit code.
+pub fn exec(target: &str, command: &[String]) -> Result<()> {
+    let path = preview::resolve_target(target)?;
+    let status = run_in(&path, command)?;
+    if !status.success() {
+        std::process::exit(exit_code(status));
+    }
+    Ok(())
+}
+
+/// Run `command` (program and arguments) with `dir` as working directory,
+/// inheriting stdin, stdout and stderr.
+pub fn run_in(dir: &Path, command: &[String]) -> Result<ExitStatus> {
+    command_in(dir, command)?.status().map_err(|e| {
+        WtError::user_error_with_source(
+            format!("failed to run '{}' in {}", command[0], dir.display()),
+            e,
+        )
+        .into()
+    })
+}
+
+/// Build a `Command` for `command` (program and arguments) running in `dir`.
+fn command_in(dir: &Path, command: &[String]) -> Result<Command> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| WtError::user_error("no command given"))?;
+    let mut cmd = Command::new(process::program(program));
+    cmd.args(args).current_dir(dir);
+    Ok(cmd)
+}
+
+/// Exit code to report for `status`; like shells, a command killed by signal N
+/// counts as 128 + N.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+/// A worktree `wt each` runs the command in.
+struct EachTarget {
+    repo: String,
+    worktree: Worktree,
+}
+
+/// Outcome of the command in one worktree.
+#[derive(Debug, Serialize)]
+struct EachResult {
+    repo: String,
+    path: PathBuf,
+    branch: Option<String>,
+    exit_code: i32,
+    success: bool,
+    duration_ms: u64,
+}
+
+/// Run `command` in every worktree of the current repository (or of all
+/// discovered repositories with `all`), one at a time or `parallel` at once,
+/// then print a summary. Exits with status 1 if the command failed anywhere.
+///
+/// Serial runs stream output as it comes; parallel runs print each worktree's
+/// output once its command finishes, so outputs don't interleave. With `json`,
+/// command output goes to stderr and stdout carries only the JSON results.
+pub fn each(
+    command: &[String],
+    all: bool,
+    repo: Option<&str>,
+    parallel: Option<usize>,
+    json: bool,
+) -> Result<()> {
+    if command.is_empty() {
+        return Err(WtError::user_error("no command given; usage: wt each -- <cmd>").into());
+    }
+    let targets = each_targets(all, repo)?;
+    if targets.is_empty() {
+        return Err(WtError::not_found("no worktrees to run in").into());
+    }
+
+    let results = match parallel {
+        // Each worktree's output is captured and printed when its command finishes
+        Some(jobs) => {
+            process::parallel_map(&targets, jobs, |target| run_captured(target, command, json))
+        }
+        None => targets
+            .iter()
+            .map(|target| run_streaming(target, command, json))
+            .collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        print_summary(&results, all);
+    }
+
+    if results.iter().any(|r| !r.success) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Worktrees to run in: those of the current repository, or of all discovered
+/// repositories (those matching the `repo` glob, if given). Bare and missing worktrees are skipped.
+fn each_targets(all: bool, repo: Option<&str>) -> Result<Vec<EachTarget>> {
+    let repos = if all {
+        let config = config::load()
+            .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
+        discovery::discover_configured_repos(&config, false, repo)?
+    } else {
+        vec![git::repo_root(None)?]
+    };
+
+    let targets = worktree::repos_worktrees(&repos, !all)?
+        .into_iter()
+        .flat_map(|repo| {
+            repo.worktrees.into_iter().map(move |worktree| EachTarget {
+                repo: repo.name.clone(),
+                worktree,
+            })
+        })
+        .collect();
+    Ok(targets)
+}
+
+/// Run the command in one worktree with output streamed to the terminal
+/// (stdout redirected to stderr with `json`).
+fn run_streaming(target: &EachTarget, command: &[String], json: bool) -> EachResult {
+    eprintln!("==> {}", heading(target));
+    let started = Instant::now();
+    let status = command_in(&target.worktree.path, command).and_then(|mut cmd| {
+        if json {
+            cmd.stdout(io::stderr());
+        }
+        Ok(cmd.status()?)
+    });
+    let exit_code = match status {
+        Ok(status) => exit_code(status),
+        Err(e) => {
+            eprintln!("Error: failed to run '{}': {:#}", command[0], e);
+            NOT_STARTED
+        }
+    };
+    result(target, exit_code, started.elapsed())
+}
+
+/// Run the command in one worktree with its output captured, then print the
+/// output under a heading.
+fn run_captured(target: &EachTarget, command: &[String], json: bool) -> EachResult {
+    let started = Instant::now();
+    let output = command_in(&target.worktree.path, command)
+        .and_then(|mut cmd| Ok(cmd.stdin(Stdio::null()).output()?));
+    let elapsed = started.elapsed();
+
+    // Hold the locks while printing so blocks from different worktrees don't mix
+    let mut stderr = io::stderr().lock();
+    let exit_code = match output {
+        Ok(output) => {
+            let _ = writeln!(stderr, "==> {}", heading(target));
+            if json {
+                let _ = stderr.write_all(&output.stdout);
+            } else {
+                let mut stdout = io::stdout().lock();
+                let _ = stdout.write_all(&output.stdout);
+                let _ = stdout.flush();
+            }
+            let _ = stderr.write_all(&output.stderr);
+            exit_code(output.status)
+        }
+        Err(e) => {
+            let _ = writeln!(stderr, "==> {}", heading(target));
+            let _ = writeln!(stderr, "Error: failed to run '{}': {:#}", command[0], e);
+            NOT_STARTED
+        }
+    };
+    result(target, exit_code, elapsed)
+}
+
+fn heading(target: &EachTarget) -> String {
+    format!(
+        "{} ({})",
+        target.worktree.branch_name().unwrap_or("(detached)"),
+        target.worktree.path.display()
+    )
+}
+
+fn result(target: &EachTarget, exit_code: i32, elapsed: Duration) -> EachResult {
+    EachResult {
+        repo: target.repo.clone(),
+        path: target.worktree.path.clone(),
+        branch: target.worktree.branch.clone(),
+        exit_code,
+        success: exit_code == 0,
+        duration_ms: elapsed.as_millis() as u64,
+    }
+}
+
+/// Print one line per worktree (status, branch, path, exit code, duration) and
+/// a count of successes and failures. With `all`, rows start with the repo.
+fn print_summary(results: &[EachResult], all: bool) {
+    let rows: Vec<(String, String, String)> = results
+        .iter()
+        .map(|r| {
+            let branch = r
+                .branch
+                .as_deref()
+                .map_or("(detached)", git::short_branch)
+                .to_string();
+            let name = if all {
+                format!("{}  {}", r.repo, branch)
+            } else {
+                branch
+            };
+            (
+                name,
+                r.path.display().to_string(),
+                format!(
+                    "exit {}  {:.1}s",
+                    r.exit_code,
+                    r.duration_ms as f64 / 1000.0
+                ),
+            )
+        })
+        .collect();
+    let name_width = rows.iter().map(|r| r.0.len()).max().unwrap_or(0);
+    let path_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0);
+
+    println!();
+    for (result, (name, path, outcome)) in results.iter().zip(&rows) {
+        let mark = if result.success { "✓" } else { "✗" };
+        println!("{mark} {name:<name_width$}  {path:<path_width$}  {outcome}");
+    }
+    let failed = results.iter().filter(|r| !r.success).count();
+    println!(
+        "{} worktrees: {} succeeded, {} failed",
+        results.len(),
+        results.len() - failed,
+        failed
+    );
+}
@@ -0,0 +1,230 @@
+//! `wt fetch`: fetch the current or all discovered repositories.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::progress::Progress;
+use crate::{config, discovery, git, process};
+
+/// Outcome of fetching one repository.
+#[derive(Debug, Serialize)]
+pub struct FetchResult {
+    pub repo: String,
+    pub path: PathBuf,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Remote branches that appeared, e.g. "origin/feature-x"
+    pub new_branches: Vec<String>,
+    /// Remote branches removed by `--prune`
+    pub pruned_branches: Vec<String>,
+}
+
+/// Run `git fetch --prune` in the current repository, or in every discovered
+/// repository with `all` (those matching the `repo` glob, if given; up to
+/// `jobs` at once), and report new branches.
+pub fn fetch(all: bool, repo: Option<&str>, jobs: usize, json: bool) -> Result<()> {
+    let repos = if all {
+        let config = config::load()
+            .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
+        discovery::discover_configured_repos(&config, false, repo)?
+    } else {
+        vec![git::repo_root(None)?]
+    };
+
+    let results = fetch_repos(&repos, jobs, !json);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        print_results(&results);
+    }
+
+    if let Some(err) = failure(&results) {
+        if json {
+            // The array already reports each failure; a second JSON document
+            // after it would break parsers
+            std::process::exit(err.exit_code());
+        }
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+/// The error to exit with when any repository failed to fetch.
+fn failure(results: &[FetchResult]) -> Option<WtError> {
+    let failed = results.iter().filter(|r| !r.success).count();
+    (failed > 0).then(|| {
+        WtError::git_error(format!(
+            "fetch failed in {} of {} repositories",
+            failed,
+            results.len()
+        ))
+    })
+}
+
+/// Fetch `repos` on up to `jobs` threads. With `progress`, show progress on
+/// stderr (with git's own progress on a terminal) and a line as each
+/// repository finishes.
+pub fn fetch_repos(repos: &[PathBuf], jobs: usize, progress: bool) -> Vec<FetchResult> {
+    let progress = Progress::new("Fetching", repos.len(), progress);
+    process::parallel_map(repos, jobs, |repo_root| {
+        let result = fetch_repo(repo_root, &progress);
+        progress.finish_item(&describe(&result));
+        result
+    })
+}
+
+/// Fetch one repository, comparing remote branches before and after.
+fn fetch_repo(repo_root: &Path, progress: &Progress) -> FetchResult {
+    let repo = repo_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "(unknown)".to_string());
+    let before = remote_branches(repo_root);
+    let outcome = if progress.is_live() {
+        process::run_streaming(
+            "git",
+            &["fetch", "--prune", "--progress"],
+            Some(repo_root),
+            &mut |line| progress.message(&format!("{}: {}", repo, line)),
+        )
+    } else {
+        process::run("git", &["fetch", "--prune", "--quiet"], Some(repo_root))
+    };
+    let after = remote_branches(repo_root);
+
+    FetchResult {
+        repo,
+        path: repo_root.to_path_buf(),
+        success: outcome.is_ok(),
+        error: outcome
+            .err()
+            .map(|e| process::error_summary(&format!("{:#}", e))),
+        new_branches: after.difference(&before).cloned().collect(),
+        pruned_branches: before.difference(&after).cloned().collect(),
+    }
+}
+
+/// Remote-tracking branches, e.g. "origin/main", without the `<remote>/HEAD`
+/// symbolic refs.
+fn remote_branches(repo_root: &Path) -> BTreeSet<String> {
+    process::run_stdout(
+        "git",
+        &["for-each-ref", "--format=%(refname:short)", "refs/remotes"],
+        Some(repo_root),
+    )
+    .map(|out| {
+        out.lines()
+            .filter(|name| name.contains('/') && !name.ends_with("/HEAD"))
+            .map(str::to_string)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// One-line summary of a fetch, e.g. "app: 2 new branches".
+fn describe(result: &FetchResult) -> String {
+    if let Some(error) = &result.error {
+        return format!("{}: failed: {}", result.repo, error);
+    }
+    let mut parts = Vec::new();
+    if !result.new_branches.is_empty() {
+        parts.push(plural(
+            result.new_branches.len(),
+            "new branch",
+            "new branches",
+        ));
+    }
+    if !result.pruned_branches.is_empty() {
+        parts.push(plural(
+            result.pruned_branches.len(),
+            "pruned branch",
+            "pruned branches",
+        ));
+    }
+    if parts.is_empty() {
+        format!("{}: no branch changes", result.repo)
+    } else {
+        format!("{}: {}", result.repo, parts.join(", "))
+    }
+}
+
+fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+/// Print repositories that gained or lost branches, then a one-line total.
+fn print_results(results: &[FetchResult]) {
+    for result in results {
+        if result.new_branches.is_empty() && result.pruned_branches.is_empty() {
+            continue;
+        }
+        println!("{}:", result.repo);
+        for branch in &result.new_branches {
+            println!("  + {}", branch);
+        }
+        for branch in &result.pruned_branches {
+            println!("  - {}", branch);
+        }
+    }
+    let new: usize = results.iter().map(|r| r.new_branches.len()).sum();
+    let failed = results.iter().filter(|r| !r.success).count();
+    println!(
+        "Fetched {} repositories: {}, {} failed",
+        results.len(),
+        plural(new, "new branch", "new branches"),
+        failed
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::{git, init_repo};
+
+    #[test]
+    fn describe_counts_branches() {
+        let mut result = FetchResult {
+            repo: "app".to_string(),
+            path: PathBuf::from("/src/app"),
+            success: true,
+            error: None,
+            new_branches: vec!["origin/a".to_string(), "origin/b".to_string()],
+            pruned_branches: vec!["origin/old".to_string()],
+        };
+        assert_eq!(describe(&result), "app: 2 new branches, 1 pruned branch");
+
+        result.new_branches.clear();
+        result.pruned_branches.clear();
+        assert_eq!(describe(&result), "app: no branch changes");
+    }
+
+    #[test]
+    fn unreachable_remote_fails_the_fetch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ok = tmp.path().join("ok");
+        let broken = tmp.path().join("broken");
+        init_repo(&ok);
+        init_repo(&broken);
+        let missing = tmp.path().join("missing");
+        git(
+            &broken,
+            &["remote", "add", "origin", missing.to_str().unwrap()],
+        );
+
+        let results = fetch_repos(&[ok, broken], 1, false);
+        assert!(results[0].success);
+        assert!(!results[1].success);
+        assert!(results[1].error.is_some());
+        assert_eq!(
+            failure(&results).unwrap().to_string(),
+            "fetch failed in 1 of 2 repositories"
+        );
+        assert!(failure(&results[..1]).is_none());
+    }
+}
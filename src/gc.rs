@@ -0,0 +1,245 @@
+//! `wt gc`: deep cleanup of stale worktree metadata, directories and caches.
+
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::{config, git, process, state};
+
+#[derive(Debug, Default, Serialize)]
+struct GcReport {
+    dry_run: bool,
+    /// Worktrees `git worktree prune` removed (or would remove) from the
+    /// current repository
+    pruned: Vec<PathBuf>,
+    /// Directories of former worktrees that git no longer knows about,
+    /// holding nothing but their stale `.git` file
+    removed_dirs: Vec<PathBuf>,
+    /// Such directories left alone because removal wasn't confirmed
+    kept_dirs: Vec<PathBuf>,
+    /// Directories with a stale `.git` file that still hold other files,
+    /// e.g. worktrees of a repository that was moved; never deleted
+    repairable_dirs: Vec<PathBuf>,
+    /// State file entries for worktrees that no longer exist
+    forgotten: Vec<String>,
+    /// The cache directory, if it was (or would be) cleared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cleared_cache: Option<PathBuf>,
+}
+
+/// Prune stale worktrees of the current repository (if inside one), delete
+/// leftover directories of worktrees wt recorded that git has forgotten, drop
+/// state entries for worktrees that are gone and, with `caches`, clear the
+/// cache directory. With `dry_run`, only report what would be done.
+///
+/// Leftover directories are only deleted with `force` or after asking on a
+/// terminal, and only when nothing but the stale `.git` file is left in
+/// them; ones with other files are reported for `git worktree repair`.
+pub fn gc(dry_run: bool, force: bool, caches: bool, json: bool) -> Result<()> {
+    let mut report = GcReport {
+        dry_run,
+        ..GcReport::default()
+    };
+
+    if let Ok(repo_root) = git::repo_root(None) {
+        let worktrees = git::worktrees_porcelain(&repo_root)
+            .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+        report.pruned = worktrees
+            .into_iter()
+            .filter(|wt| wt.prunable.is_some())
+            .map(|wt| wt.path)
+            .collect();
+        if !dry_run && !report.pruned.is_empty() {
+            process::run("git", &["worktree", "prune"], Some(&repo_root))
+                .map_err(|e| WtError::git_error_with_source("failed to prune worktrees", e))?;
+        }
+    }
+
+    let state = state::load();
+    let (empty, repairable) = find_leftovers(state.worktrees.keys().map(PathBuf::from));
+    report.repairable_dirs = repairable;
+    let delete = !empty.is_empty()
+        && !dry_run
+        && (force || (!json && io::stdin().is_terminal() && confirm(&empty)?));
+    if dry_run || delete {
+        remove_leftovers(&empty, dry_run)?;
+        report.removed_dirs = empty;
+    } else {
+        report.kept_dirs = empty;
+    }
+    report.forgotten = state
+        .worktrees
+        .keys()
+        .filter(|path| {
+            let path = Path::new(path);
+            !path.exists() || report.removed_dirs.iter().any(|removed| removed == path)
+        })
+        .cloned()
+        .collect();
+    if !dry_run && !report.forgotten.is_empty() {
+        state::update(|s| {
+            for path in &report.forgotten {
+                s.forget(Path::new(path));
+            }
+        })
+        .map_err(|e| WtError::io_error_with_source("failed to update state", e))?;
+    }
+
+    let cache_dir = config::cache_dir();
+    if caches && cache_dir.exists() {
+        if !dry_run {
+            fs::remove_dir_all(&cache_dir).map_err(|e| {
+                WtError::io_error_with_source(
+                    format!("failed to clear cache {}", cache_dir.display()),
+                    e.into(),
+                )
+            })?;
+        }
+        report.cleared_cache = Some(cache_dir);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+/// Whether `path` is the directory of a former linked worktree: it has a
+/// `.git` file, but git has dropped the worktree it points to.
+fn is_leftover_checkout(path: &Path) -> bool {
+    path.join(".git").is_file() && !git::has_valid_git_link(path)
+}
+
+/// Split the leftover checkouts among `paths` into the ones holding nothing
+/// but their `.git` file, and the ones with other files. The latter are most
+/// likely worktrees whose repository was moved, which `git worktree repair`
+/// reconnects, so they're never deleted.
+fn find_leftovers(paths: impl Iterator<Item = PathBuf>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    paths
+        .filter(|path| is_leftover_checkout(path))
+        .partition(|path| {
+            fs::read_dir(path).is_ok_and(|mut entries| {
+                entries.all(|entry| entry.is_ok_and(|entry| entry.file_name() == ".git"))
+            })
+        })
+}
+
+/// Delete the leftover directories `dirs` from [`find_leftovers`], unless
+/// `dry_run`.
+fn remove_leftovers(dirs: &[PathBuf], dry_run: bool) -> Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+    for path in dirs {
+        fs::remove_dir_all(path).map_err(|e| {
+            WtError::io_error_with_source(format!("failed to remove {}", path.display()), e.into())
+        })?;
+    }
+    Ok(())
+}
+
+fn confirm(dirs: &[PathBuf]) -> Result<bool> {
+    eprintln!("Leftover directories of worktrees git no longer knows about:");
+    for path in dirs {
+        eprintln!("  - {}", path.display());
+    }
+    eprint!(
+        "Delete {} director{}? (y/N): ",
+        dirs.len(),
+        if dirs.len() == 1 { "y" } else { "ies" }
+    );
+    io::stderr().flush()?;
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    Ok(matches!(response.trim(), "y" | "Y"))
+}
+
+fn print_report(report: &GcReport) {
+    let (prune, remove, forget, clear) = if report.dry_run {
+        ("Would prune", "Would remove", "Would forget", "Would clear")
+    } else {
+        ("Pruned", "Removed", "Forgot", "Cleared")
+    };
+    for path in &report.pruned {
+        println!("{} stale worktree {}", prune, path.display());
+    }
+    for path in &report.removed_dirs {
+        println!("{} leftover directory {}", remove, path.display());
+    }
+    for path in &report.kept_dirs {
+        println!(
+            "Kept leftover directory {} (delete it with --force)",
+            path.display()
+        );
+    }
+    for path in &report.repairable_dirs {
+        println!(
+            "Kept {}: it has files besides a stale .git; if its repository moved, run `git worktree repair {}` there",
+            path.display(),
+            path.display()
+        );
+    }
+    for path in &report.forgotten {
+        println!("{} state entry {}", forget, path);
+    }
+    if let Some(cache) = &report.cleared_cache {
+        println!("{} cache {}", clear, cache.display());
+    }
+    if report.pruned.is_empty()
+        && report.removed_dirs.is_empty()
+        && report.kept_dirs.is_empty()
+        && report.repairable_dirs.is_empty()
+        && report.forgotten.is_empty()
+        && report.cleared_cache.is_none()
+    {
+        eprintln!("Nothing to clean up.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::{git, init_repo};
+
+    #[test]
+    fn keeps_worktrees_of_a_moved_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        init_repo(&root.join("repo"));
+        git(
+            &root.join("repo"),
+            &["worktree", "add", "-q", "../repo-feature", "-b", "feature"],
+        );
+        let worktree = root.join("repo-feature");
+        fs::write(worktree.join("work.txt"), "uncommitted").unwrap();
+        fs::rename(root.join("repo"), root.join("moved")).unwrap();
+
+        let (empty, repairable) = find_leftovers([worktree.clone()].into_iter());
+        assert!(empty.is_empty());
+        assert_eq!(repairable, vec![worktree.clone()]);
+        remove_leftovers(&empty, false).unwrap();
+        assert!(worktree.join("work.txt").is_file());
+    }
+
+    #[test]
+    fn only_a_stale_git_file_makes_a_directory_deletable() {
+        let dir = tempfile::tempdir().unwrap();
+        let leftover = dir.path().join("leftover");
+        fs::create_dir(&leftover).unwrap();
+        fs::write(
+            leftover.join(".git"),
+            "gitdir: /nonexistent/.git/worktrees/x\n",
+        )
+        .unwrap();
+
+        let (empty, repairable) = find_leftovers([leftover.clone()].into_iter());
+        assert_eq!(empty, vec![leftover]);
+        assert!(repairable.is_empty());
+    }
+}
@@ -1,11 +1,100 @@
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 use crate::error::WtError;
 use crate::process;
 use crate::worktree::{self, Worktree};
 
+#[cfg(feature = "gix")]
+mod gitoxide;
+
+/// Answer a read-only query with gitoxide when built with the `gix` feature,
+/// returning from the calling function; on errors fall through to git.
+macro_rules! try_gitoxide {
+    ($query:expr) => {
+        #[cfg(feature = "gix")]
+        match $query {
+            Ok(answer) => return Ok(answer),
+            Err(e) => tracing::debug!(error = %format!("{:#}", e), "gitoxide failed, using git"),
+        }
+    };
+}
+
+/// Oldest git with `git worktree remove` and `git worktree move`.
+pub const MIN_VERSION: (u32, u32) = (2, 17);
+
+/// What the installed git supports beyond [`MIN_VERSION`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `worktree list --porcelain` marks `locked` and `prunable` worktrees
+    /// (2.31)
+    pub worktree_annotations: bool,
+    /// `worktree list --porcelain -z`, for paths with newlines (2.36)
+    pub worktree_list_nul: bool,
+}
+
+impl Capabilities {
+    pub fn for_version(version: (u32, u32)) -> Self {
+        Capabilities {
+            worktree_annotations: version >= (2, 31),
+            worktree_list_nul: version >= (2, 36),
+        }
+    }
+}
+
+/// Major and minor version of the installed git, from `git --version`,
+/// asked once per run. `None` if git can't be run or says something
+/// unexpected.
+pub fn version() -> Option<(u32, u32)> {
+    static VERSION: OnceLock<Option<(u32, u32)>> = OnceLock::new();
+    *VERSION.get_or_init(|| {
+        process::run_stdout("git", &["--version"], None)
+            .ok()
+            .and_then(|out| parse_version(&out))
+    })
+}
+
+/// What the installed git supports; everything if its version is unknown.
+pub fn capabilities() -> Capabilities {
+    Capabilities::for_version(version().unwrap_or((u32::MAX, 0)))
+}
+
+/// Fail unless git is on PATH and at least [`MIN_VERSION`]. A version that
+/// can't be parsed (a vendor build, say) is given the benefit of the doubt.
+pub fn check_version() -> Result<()> {
+    let output = process::run_stdout("git", &["--version"], None)
+        .map_err(|e| WtError::not_found_with_source("git not found on PATH", e))?;
+    match parse_version(&output) {
+        Some(found) if found < MIN_VERSION => Err(WtError::git_error(format!(
+            "{} is too old; wt needs git {}.{} or newer",
+            output.trim(),
+            MIN_VERSION.0,
+            MIN_VERSION.1
+        ))
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Major and minor version from `git --version` output, e.g. "git version
+/// 2.39.5 (Apple Git-154)".
+pub fn parse_version(output: &str) -> Option<(u32, u32)> {
+    let version = output
+        .trim()
+        .strip_prefix("git version ")?
+        .split_whitespace()
+        .next()?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
 pub fn repo_root(cwd: Option<&Path>) -> Result<PathBuf> {
     let out = process::run_stdout("git", &["rev-parse", "--show-toplevel"], cwd)
         .map_err(|_| anyhow::Error::new(WtError::not_found("not in a git repository")))?;
@@ -13,14 +102,34 @@ pub fn repo_root(cwd: Option<&Path>) -> Result<PathBuf> {
 }
 
 pub fn worktrees_porcelain(repo_root: &Path) -> Result<Vec<Worktree>> {
-    let out = process::run_stdout("git", &["worktree", "list", "--porcelain"], Some(repo_root))
-        .map_err(|e| {
-            anyhow::Error::new(WtError::git_error_with_source(
-                "failed to list worktrees",
-                e,
-            ))
-        })?;
-    worktree::parse_porcelain(&out)
+    try_gitoxide!(gitoxide::worktrees(repo_root));
+    let capabilities = capabilities();
+    let args: &[&str] = if capabilities.worktree_list_nul {
+        &["worktree", "list", "--porcelain", "-z"]
+    } else {
+        &["worktree", "list", "--porcelain"]
+    };
+    let out = process::run_stdout("git", args, Some(repo_root)).map_err(|e| {
+        anyhow::Error::new(WtError::git_error_with_source(
+            "failed to list worktrees",
+            e,
+        ))
+    })?;
+    let mut worktrees = if capabilities.worktree_list_nul {
+        worktree::parse_porcelain_z(&out)?
+    } else {
+        worktree::parse_porcelain(&out)?
+    };
+    // Older git doesn't say which worktrees are prunable; the common case is
+    // a directory deleted without `git worktree remove`
+    if !capabilities.worktree_annotations {
+        for wt in worktrees.iter_mut().skip(1) {
+            if wt.prunable.is_none() && !wt.path.exists() {
+                wt.prunable = Some("gitdir file points to non-existent location".to_string());
+            }
+        }
+    }
+    Ok(worktrees)
 }
 
 /// Detect the main branch for a repository.
@@ -32,6 +141,12 @@ pub fn worktrees_porcelain(repo_root: &Path) -> Result<Vec<Worktree>> {
 ///
 /// Returns the branch name (e.g., "main") without the refs/heads/ prefix.
 pub fn main_branch(repo_root: &Path) -> Option<String> {
+    #[cfg(feature = "gix")]
+    match gitoxide::main_branch(repo_root) {
+        Ok(branch) => return branch,
+        Err(e) => tracing::debug!(error = %format!("{:#}", e), "gitoxide failed, using git"),
+    }
+
     // Try to get the remote default branch
     if let Ok(output) = process::run_stdout(
         "git",
@@ -71,7 +186,675 @@ pub fn is_main_branch(repo_root: &Path, branch_ref: &str) -> bool {
     };
 
     // Handle both "refs/heads/main" and "main" formats
-    let branch_name = branch_ref.strip_prefix("refs/heads/").unwrap_or(branch_ref);
+    let branch_name = short_branch(branch_ref);
 
     branch_name == main
 }
+
+/// Local branch names (`feature-x`), sorted.
+pub fn local_branches(repo_root: &Path) -> Result<Vec<String>> {
+    branches(repo_root, "refs/heads/")
+}
+
+/// Remote-tracking branch names (`origin/feature-x`) without the remotes'
+/// `HEAD`, sorted.
+pub fn remote_branches(repo_root: &Path) -> Result<Vec<String>> {
+    let mut branches = branches(repo_root, "refs/remotes/")?;
+    branches.retain(|b| !b.ends_with("/HEAD"));
+    Ok(branches)
+}
+
+/// The repository's remotes (`origin`, `upstream`).
+pub fn remotes(repo_root: &Path) -> Result<Vec<String>> {
+    let out = process::run_stdout("git", &["remote"], Some(repo_root))
+        .map_err(|e| WtError::git_error_with_source("failed to list remotes", e))?;
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// Names of the refs under `prefix`, without it.
+fn branches(repo_root: &Path, prefix: &str) -> Result<Vec<String>> {
+    try_gitoxide!(gitoxide::branches(repo_root, prefix));
+    let out = process::run_stdout(
+        "git",
+        &["for-each-ref", "--format=%(refname)", prefix],
+        Some(repo_root),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to list branches", e))?;
+    Ok(out
+        .lines()
+        .filter_map(|line| line.strip_prefix(prefix))
+        .map(str::to_string)
+        .collect())
+}
+
+/// The last commit on a local or remote-tracking branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchTip {
+    /// Full ref name (`refs/heads/feature-x`, `refs/remotes/origin/feature-x`)
+    pub refname: String,
+    /// Unix timestamp (seconds) of the commit
+    pub time: u64,
+    pub author: String,
+    pub subject: String,
+}
+
+impl BranchTip {
+    /// The name [`local_branches`] and [`remote_branches`] use: `feature-x`
+    /// or `origin/feature-x`.
+    pub fn name(&self) -> &str {
+        self.refname
+            .strip_prefix("refs/heads/")
+            .or_else(|| self.refname.strip_prefix("refs/remotes/"))
+            .unwrap_or(&self.refname)
+    }
+}
+
+/// The last commit of every local and remote-tracking branch (without the
+/// remotes' `HEAD`), most recent first, from a single `git for-each-ref`.
+pub fn branch_tips(repo_root: &Path) -> Result<Vec<BranchTip>> {
+    let out = process::run_stdout(
+        "git",
+        &[
+            "for-each-ref",
+            "--sort=-committerdate",
+            "--format=%(refname)%09%(committerdate:unix)%09%(authorname)%09%(contents:subject)",
+            "refs/heads/",
+            "refs/remotes/",
+        ],
+        Some(repo_root),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to list branches", e))?;
+    Ok(parse_branch_tips(&out))
+}
+
+/// Parse `git for-each-ref` output in the format [`branch_tips`] asks for.
+fn parse_branch_tips(output: &str) -> Vec<BranchTip> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            Some(BranchTip {
+                refname: fields.next()?.to_string(),
+                time: fields.next()?.parse().unwrap_or(0),
+                author: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .filter(|tip| !(tip.refname.starts_with("refs/remotes/") && tip.refname.ends_with("/HEAD")))
+        .collect()
+}
+
+/// Upstream tracking information for a worktree's checked-out branch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Upstream {
+    /// Short upstream name (e.g. "origin/main")
+    pub name: String,
+    /// Commits on HEAD not on the upstream
+    pub ahead: usize,
+    /// Commits on the upstream not on HEAD
+    pub behind: usize,
+}
+
+/// Snapshot of a worktree's state from `git status --porcelain=v2 --branch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorktreeStatus {
+    /// Commit HEAD points to (`None` on an unborn branch)
+    pub head: Option<String>,
+    /// Checked-out branch name (`None` on detached HEAD)
+    pub branch: Option<String>,
+    pub upstream: Option<Upstream>,
+    /// Changed paths in `git status --short` format (e.g. " M src/main.rs")
+    pub changes: Vec<String>,
+}
+
+impl WorktreeStatus {
+    pub fn is_dirty(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Branch summary in `git status -sb` format (e.g. "## main...origin/main [ahead 1]").
+    pub fn branch_line(&self) -> String {
+        let mut line = format!(
+            "## {}",
+            self.branch.as_deref().unwrap_or("HEAD (no branch)")
+        );
+        if let Some(up) = &self.upstream {
+            line.push_str("...");
+            line.push_str(&up.name);
+            match (up.ahead, up.behind) {
+                (0, 0) => {}
+                (a, 0) => line.push_str(&format!(" [ahead {a}]")),
+                (0, b) => line.push_str(&format!(" [behind {b}]")),
+                (a, b) => line.push_str(&format!(" [ahead {a}, behind {b}]")),
+            }
+        }
+        line
+    }
+}
+
+/// Read branch, upstream divergence, and changed files for a worktree in one git call.
+pub fn worktree_status(worktree_path: &Path) -> Result<WorktreeStatus> {
+    try_gitoxide!(gitoxide::worktree_status(worktree_path));
+    let out = process::run_stdout(
+        "git",
+        &["status", "--porcelain=v2", "--branch"],
+        Some(worktree_path),
+    )?;
+    Ok(parse_status_v2(&out))
+}
+
+/// Parse `git status --porcelain=v2 --branch` output.
+///
+/// Header lines start with `#`; entries are `1` (changed), `2` (renamed/copied),
+/// `u` (unmerged), `?` (untracked) and `!` (ignored, skipped).
+fn parse_status_v2(output: &str) -> WorktreeStatus {
+    let mut status = WorktreeStatus::default();
+    let mut upstream_name = None;
+    let mut ahead_behind = (0, 0);
+
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => status.head = Some(value.to_string()),
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => upstream_name = Some(value.to_string()),
+                "branch.ab" => {
+                    let mut parts = value.split_whitespace();
+                    let ahead = parts
+                        .next()
+                        .and_then(|a| a.trim_start_matches('+').parse().ok());
+                    let behind = parts
+                        .next()
+                        .and_then(|b| b.trim_start_matches('-').parse().ok());
+                    ahead_behind = (ahead.unwrap_or(0), behind.unwrap_or(0));
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let short = |xy: &str| xy.replace('.', " ");
+        let entry = match line.split_once(' ') {
+            Some(("1", rest)) => {
+                let fields: Vec<_> = rest.splitn(8, ' ').collect();
+                fields
+                    .get(7)
+                    .map(|path| format!("{} {}", short(fields[0]), path))
+            }
+            Some(("2", rest)) => {
+                let fields: Vec<_> = rest.splitn(9, ' ').collect();
+                fields.get(8).map(|paths| {
+                    let (path, orig) = paths.split_once('\t').unwrap_or((paths, ""));
+                    format!("{} {} -> {}", short(fields[0]), orig, path)
+                })
+            }
+            Some(("u", rest)) => {
+                let fields: Vec<_> = rest.splitn(10, ' ').collect();
+                fields
+                    .get(9)
+                    .map(|path| format!("{} {}", short(fields[0]), path))
+            }
+            Some(("?", path)) => Some(format!("?? {}", path)),
+            _ => None,
+        };
+        if let Some(entry) = entry {
+            status.changes.push(entry);
+        }
+    }
+
+    status.upstream = upstream_name.map(|name| Upstream {
+        name,
+        ahead: ahead_behind.0,
+        behind: ahead_behind.1,
+    });
+    status
+}
+
+/// Git directories for a worktree, from a single `git rev-parse` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoDirs {
+    /// Top-level directory of the worktree
+    pub toplevel: PathBuf,
+    /// The worktree's own git dir (`.git` or `.git/worktrees/<name>`)
+    pub git_dir: PathBuf,
+    /// Git dir shared by all worktrees of the repository
+    pub common_dir: PathBuf,
+}
+
+impl RepoDirs {
+    /// Time of the last `git fetch` in this repository.
+    ///
+    /// Uses the modification time of `FETCH_HEAD` in the common git directory,
+    /// which is shared by all worktrees. Returns `None` if the repo was never fetched.
+    pub fn last_fetch_time(&self) -> Option<SystemTime> {
+        fs::metadata(self.common_dir.join("FETCH_HEAD"))
+            .and_then(|m| m.modified())
+            .ok()
+    }
+}
+
+/// Resolve the top-level, git, and common git directories for `path`.
+pub fn repo_dirs(path: &Path) -> Result<RepoDirs> {
+    let out = process::run_stdout(
+        "git",
+        &[
+            "rev-parse",
+            "--show-toplevel",
+            "--git-dir",
+            "--git-common-dir",
+        ],
+        Some(path),
+    )
+    .map_err(|_| anyhow::Error::new(WtError::not_found("not in a git repository")))?;
+
+    let mut lines = out.lines();
+    let mut next = || -> Result<PathBuf> {
+        let line = lines
+            .next()
+            .ok_or_else(|| WtError::git_error("unexpected `git rev-parse` output"))?;
+        // Older git versions print the git dirs relative to the working directory.
+        Ok(path.join(line.trim()))
+    };
+    Ok(RepoDirs {
+        toplevel: next()?,
+        git_dir: next()?,
+        common_dir: next()?,
+    })
+}
+
+/// A stash entry, as shown by `git stash list`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stash {
+    /// Stash reference (e.g. "stash@{0}")
+    pub reference: String,
+    /// Stash subject (e.g. "WIP on main: abc1234 fix parser")
+    pub message: String,
+}
+
+impl Stash {
+    /// Whether this stash was created on `branch`.
+    ///
+    /// Stashes are shared by all worktrees of a repository, so the branch is taken
+    /// from the subject git records (`WIP on <branch>:` / `On <branch>:`).
+    pub fn is_on_branch(&self, branch: &str) -> bool {
+        let subject = self
+            .message
+            .strip_prefix("WIP on ")
+            .or_else(|| self.message.strip_prefix("On "));
+        subject
+            .and_then(|s| s.strip_prefix(branch))
+            .is_some_and(|rest| rest.starts_with(':'))
+    }
+}
+
+/// List all stash entries of the repository containing `repo_path`.
+pub fn stashes(repo_path: &Path) -> Vec<Stash> {
+    process::run_stdout(
+        "git",
+        &["stash", "list", "--format=%gd%x09%gs"],
+        Some(repo_path),
+    )
+    .map(|out| parse_stash_list(&out))
+    .unwrap_or_default()
+}
+
+/// Commits on `rev` (e.g. `HEAD` of the worktree at `repo`) that no
+/// remote-tracking branch or `main` contains; 0 if git can't tell.
+pub fn unpushed_commits(repo: &Path, rev: &str, main: Option<&str>) -> usize {
+    let mut args = vec!["rev-list", "--count", rev, "--not", "--remotes"];
+    args.extend(main);
+    process::run_stdout("git", &args, Some(repo))
+        .ok()
+        .and_then(|out| out.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Committer time of HEAD in the worktree at `worktree_path`, or `None` on an
+/// unborn branch.
+pub fn last_commit_time(worktree_path: &Path) -> Option<SystemTime> {
+    let out =
+        process::run_stdout("git", &["log", "-1", "--format=%ct"], Some(worktree_path)).ok()?;
+    let secs = out.trim().parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// The commit HEAD points to, as reported by `git log -1`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitSummary {
+    /// Abbreviated hash
+    pub hash: String,
+    pub subject: String,
+    /// Unix timestamp (seconds) of the commit
+    pub time: u64,
+}
+
+/// HEAD's abbreviated hash, subject and committer time in the worktree at
+/// `worktree_path`, or `None` on an unborn branch.
+pub fn last_commit(worktree_path: &Path) -> Option<CommitSummary> {
+    let out = process::run_stdout(
+        "git",
+        &["log", "-1", "--format=%h%x09%ct%x09%s"],
+        Some(worktree_path),
+    )
+    .ok()?;
+    parse_commit_summary(&out)
+}
+
+/// Parse `git log -1 --format=%h%x09%ct%x09%s` output.
+fn parse_commit_summary(output: &str) -> Option<CommitSummary> {
+    let mut fields = output.trim_end_matches('\n').splitn(3, '\t');
+    Some(CommitSummary {
+        hash: fields.next().filter(|h| !h.is_empty())?.to_string(),
+        time: fields.next()?.parse().ok()?,
+        subject: fields.next().unwrap_or_default().to_string(),
+    })
+}
+
+/// Parse `git stash list --format=%gd%x09%gs` output.
+fn parse_stash_list(output: &str) -> Vec<Stash> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(reference, subject)| Stash {
+            reference: reference.to_string(),
+            message: subject.to_string(),
+        })
+        .collect()
+}
+
+/// State of a submodule relative to the commit recorded in the superproject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmoduleState {
+    /// Checked out at the recorded commit
+    Ok,
+    /// Not initialized (`git submodule update --init` needed)
+    Uninitialized,
+    /// Checked out at a different commit than recorded
+    Modified,
+    /// Has merge conflicts
+    Conflict,
+}
+
+/// A submodule entry from `git submodule status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submodule {
+    pub path: String,
+    pub commit: String,
+    pub state: SubmoduleState,
+}
+
+/// List submodules of the worktree at `worktree_path`.
+///
+/// Returns an empty list without spawning git when there is no `.gitmodules`.
+pub fn submodules(worktree_path: &Path) -> Vec<Submodule> {
+    if !worktree_path.join(".gitmodules").exists() {
+        return Vec::new();
+    }
+    process::run_stdout("git", &["submodule", "status"], Some(worktree_path))
+        .map(|out| parse_submodule_status(&out))
+        .unwrap_or_default()
+}
+
+/// Parse `git submodule status` output.
+///
+/// Each line is `<marker><sha> <path>[ (<describe>)]`, where the marker is a
+/// space (ok), `-` (uninitialized), `+` (modified) or `U` (conflict).
+fn parse_submodule_status(output: &str) -> Vec<Submodule> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let state = match chars.next()? {
+                ' ' => SubmoduleState::Ok,
+                '-' => SubmoduleState::Uninitialized,
+                '+' => SubmoduleState::Modified,
+                'U' => SubmoduleState::Conflict,
+                _ => return None,
+            };
+            let (commit, rest) = chars.as_str().split_once(' ')?;
+            // Strip the optional " (<describe>)" suffix.
+            let path = match rest.rfind(" (") {
+                Some(idx) if rest.ends_with(')') => &rest[..idx],
+                _ => rest,
+            };
+            Some(Submodule {
+                path: path.to_string(),
+                commit: commit.to_string(),
+                state,
+            })
+        })
+        .collect()
+}
+
+/// A branch ref without its `refs/heads/` prefix: `refs/heads/feature/x` is
+/// `feature/x`. Other refs are returned as they are.
+pub fn short_branch(refname: &str) -> &str {
+    refname.strip_prefix("refs/heads/").unwrap_or(refname)
+}
+
+/// Whether the linked worktree at `path` has a `.git` file pointing to an
+/// existing git directory.
+pub fn has_valid_git_link(path: &Path) -> bool {
+    fs::read_to_string(path.join(".git"))
+        .ok()
+        .and_then(|content| parse_gitdir(&content))
+        .is_some_and(|gitdir| path.join(gitdir).is_dir())
+}
+
+/// The target of a `.git` file (`gitdir: <path>`).
+pub fn parse_gitdir(content: &str) -> Option<PathBuf> {
+    content
+        .lines()
+        .next()?
+        .strip_prefix("gitdir: ")
+        .map(|p| PathBuf::from(p.trim()))
+}
+
+/// Helpers for tests that need a real repository.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Run git in `dir` with a fixed identity, failing the test if it fails.
+    pub fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=wt", "-c", "user.email=wt@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    /// Create a repository at `dir` on branch `main` with one empty commit.
+    pub fn init_repo(dir: &Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        git(dir, &["init", "-q", "-b", "main"]);
+        git(dir, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Capabilities, CommitSummary, MIN_VERSION, Stash, Submodule, SubmoduleState, Upstream,
+        parse_branch_tips, parse_commit_summary, parse_gitdir, parse_stash_list, parse_status_v2,
+        parse_submodule_status, parse_version,
+    };
+
+    #[test]
+    fn parses_git_versions() {
+        assert_eq!(parse_version("git version 2.39.5\n"), Some((2, 39)));
+        assert_eq!(
+            parse_version("git version 2.39.5 (Apple Git-154)"),
+            Some((2, 39))
+        );
+        assert_eq!(parse_version("git version 2.45.windows.1"), Some((2, 45)));
+        assert_eq!(parse_version("hub version 2.14"), None);
+        assert!(parse_version("git version 2.9.0").unwrap() < MIN_VERSION);
+    }
+
+    #[test]
+    fn gates_features_on_the_git_version() {
+        let old = Capabilities::for_version(MIN_VERSION);
+        assert!(!old.worktree_annotations && !old.worktree_list_nul);
+        let mid = Capabilities::for_version((2, 34));
+        assert!(mid.worktree_annotations && !mid.worktree_list_nul);
+        assert!(Capabilities::for_version((3, 0)).worktree_list_nul);
+    }
+
+    #[test]
+    fn parses_status_v2_branch_headers() {
+        let output = "# branch.oid 1234abcd\n\
+                      # branch.head feature\n\
+                      # branch.upstream origin/feature\n\
+                      # branch.ab +2 -5\n";
+        let got = parse_status_v2(output);
+        assert_eq!(got.head.as_deref(), Some("1234abcd"));
+        assert_eq!(got.branch.as_deref(), Some("feature"));
+        assert_eq!(
+            got.upstream,
+            Some(Upstream {
+                name: "origin/feature".to_string(),
+                ahead: 2,
+                behind: 5,
+            })
+        );
+        assert!(!got.is_dirty());
+        assert_eq!(
+            got.branch_line(),
+            "## feature...origin/feature [ahead 2, behind 5]"
+        );
+    }
+
+    #[test]
+    fn parses_status_v2_detached_without_upstream() {
+        let output = "# branch.oid 1234abcd\n# branch.head (detached)\n";
+        let got = parse_status_v2(output);
+        assert_eq!(got.branch, None);
+        assert_eq!(got.upstream, None);
+        assert_eq!(got.branch_line(), "## HEAD (no branch)");
+    }
+
+    #[test]
+    fn parses_status_v2_entries_as_short_format() {
+        let output = "# branch.oid (initial)\n\
+                      # branch.head main\n\
+                      1 .M N... 100644 100644 100644 aaa bbb src/main.rs\n\
+                      1 A. N... 000000 100644 100644 000 ccc new file.rs\n\
+                      2 R. N... 100644 100644 100644 ddd eee R100 src/new.rs\tsrc/old.rs\n\
+                      u UU N... 100644 100644 100644 100644 f1 f2 f3 conflict.rs\n\
+                      ? untracked.txt\n\
+                      ! ignored.log\n";
+        let got = parse_status_v2(output);
+        assert_eq!(got.head, None);
+        assert_eq!(
+            got.changes,
+            vec![
+                " M src/main.rs",
+                "A  new file.rs",
+                "R  src/old.rs -> src/new.rs",
+                "UU conflict.rs",
+                "?? untracked.txt",
+            ]
+        );
+        assert!(got.is_dirty());
+    }
+    #[test]
+    fn parses_stash_list() {
+        let output = "stash@{0}\tWIP on feature: abc1234 wip\nstash@{1}\tOn main: experiment\n";
+        let got = parse_stash_list(output);
+        assert_eq!(
+            got,
+            vec![
+                Stash {
+                    reference: "stash@{0}".to_string(),
+                    message: "WIP on feature: abc1234 wip".to_string(),
+                },
+                Stash {
+                    reference: "stash@{1}".to_string(),
+                    message: "On main: experiment".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_stash_branch_exactly() {
+        let stash = |message: &str| Stash {
+            reference: "stash@{0}".to_string(),
+            message: message.to_string(),
+        };
+        assert!(stash("WIP on feature: abc1234 wip").is_on_branch("feature"));
+        assert!(stash("On feature: saved").is_on_branch("feature"));
+        assert!(!stash("On feature-2: other").is_on_branch("feature"));
+        assert!(!stash("On main: experiment").is_on_branch("feature"));
+    }
+
+    #[test]
+    fn parses_branch_tips() {
+        let output = "refs/heads/feature\t1700000000\tAda Lovelace\tAdd\tthing\n\
+                      refs/remotes/origin/HEAD\t1690000000\tBob\tInit\n\
+                      refs/remotes/origin/fix\t1690000000\tBob\t\n";
+        let tips = parse_branch_tips(output);
+        assert_eq!(tips.len(), 2);
+        assert_eq!(tips[0].name(), "feature");
+        assert_eq!(tips[0].author, "Ada Lovelace");
+        assert_eq!(tips[0].subject, "Add\tthing");
+        assert_eq!(tips[1].name(), "origin/fix");
+        assert_eq!(tips[1].time, 1690000000);
+        assert_eq!(tips[1].subject, "");
+    }
+
+    #[test]
+    fn parses_commit_summary() {
+        assert_eq!(
+            parse_commit_summary("abc1234\t1700000000\tFix: tabs\tin subject\n"),
+            Some(CommitSummary {
+                hash: "abc1234".to_string(),
+                subject: "Fix: tabs\tin subject".to_string(),
+                time: 1700000000,
+            })
+        );
+        assert_eq!(parse_commit_summary(""), None);
+    }
+
+    #[test]
+    fn parses_submodule_markers() {
+        let output = " 1111111 libs/ok (v1.0)\n\
+                      -2222222 libs/new\n\
+                      +3333333 libs/moved (heads/main)\n\
+                      U4444444 libs/conflict\n";
+        let got = parse_submodule_status(output);
+        let states: Vec<_> = got.iter().map(|s| (s.path.as_str(), s.state)).collect();
+        assert_eq!(
+            states,
+            vec![
+                ("libs/ok", SubmoduleState::Ok),
+                ("libs/new", SubmoduleState::Uninitialized),
+                ("libs/moved", SubmoduleState::Modified),
+                ("libs/conflict", SubmoduleState::Conflict),
+            ]
+        );
+        assert_eq!(
+            got[1],
+            Submodule {
+                path: "libs/new".to_string(),
+                commit: "2222222".to_string(),
+                state: SubmoduleState::Uninitialized,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_gitdir_links() {
+        assert_eq!(
+            parse_gitdir("gitdir: /src/app/.git/worktrees/feature\n"),
+            Some(std::path::PathBuf::from("/src/app/.git/worktrees/feature"))
+        );
+        assert_eq!(parse_gitdir("ref: refs/heads/main\n"), None);
+    }
+}
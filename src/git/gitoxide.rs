@@ -0,0 +1,370 @@
+//! The read-only queries of [`crate::git`] answered in-process with gitoxide,
+//! for builds with the `gix` feature. Listing worktrees, branches and status
+//! of every repository otherwise spawns several git processes each, which
+//! dominates `wt list --all`, previews and `wt agent context` on large setups.
+//!
+//! Each function mirrors the output of the git command it replaces. Errors
+//! (including repository layouts these don't handle, like relative worktree
+//! links) make the caller fall back to git, so they only cost the speedup.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use gix::bstr::ByteSlice;
+use gix::status::index_worktree;
+use gix::status::plumbing::index_as_worktree::{Change, Conflict, EntryStatus};
+
+use super::{Upstream, WorktreeStatus};
+use crate::worktree::Worktree;
+
+/// `git worktree list --porcelain`: the main worktree, then the linked ones
+/// sorted by path.
+pub fn worktrees(repo_root: &Path) -> Result<Vec<Worktree>> {
+    let repo = gix::open(repo_root)?;
+    let main = repo.main_repo()?;
+
+    // Like git, the main worktree's path is the common dir without `/.git`
+    let common_dir = gix::path::realpath(main.common_dir())?;
+    let mut worktrees = vec![if main.is_bare() {
+        Worktree {
+            path: common_dir,
+            head: None,
+            branch: None,
+            locked: false,
+            prunable: None,
+            bare: true,
+        }
+    } else {
+        let (head, branch) = head(&main)?;
+        Worktree {
+            path: match common_dir.file_name() {
+                Some(name) if name == ".git" => common_dir.parent().unwrap_or(&common_dir).into(),
+                _ => common_dir.clone(),
+            },
+            head,
+            branch,
+            locked: false,
+            prunable: None,
+            bare: false,
+        }
+    }];
+
+    let mut linked = Vec::new();
+    for proxy in main.worktrees()? {
+        let path = proxy.base()?;
+        if !path.is_absolute() {
+            bail!("relative worktree path {}", path.display());
+        }
+        let locked = proxy.is_locked();
+        let prunable = (!locked && !path.join(".git").exists())
+            .then(|| "gitdir file points to non-existent location".to_string());
+        let (head, branch) = head(&proxy.into_repo_with_possibly_inaccessible_worktree()?)?;
+        linked.push(Worktree {
+            path,
+            head,
+            branch,
+            locked,
+            prunable,
+            bare: false,
+        });
+    }
+    linked.sort_by(|a, b| a.path.cmp(&b.path));
+    worktrees.extend(linked);
+    Ok(worktrees)
+}
+
+/// HEAD's commit and branch ref as `git worktree list --porcelain` reports
+/// them: the null id on an unborn branch, no branch when detached.
+fn head(repo: &gix::Repository) -> Result<(Option<String>, Option<String>)> {
+    let head = repo.head()?;
+    let id = match head.id() {
+        Some(id) => id.to_string(),
+        None => repo.object_hash().null().to_string(),
+    };
+    let branch = head.referent_name().map(|name| name.as_bstr().to_string());
+    Ok((Some(id), branch))
+}
+
+/// The main branch, by the rules of [`super::main_branch`].
+pub fn main_branch(repo_root: &Path) -> Result<Option<String>> {
+    let repo = gix::open(repo_root)?;
+    if let Some(origin_head) = repo.try_find_reference("refs/remotes/origin/HEAD")?
+        && let Some(target) = origin_head.target().try_name()
+        && let Some(branch) = target
+            .as_bstr()
+            .to_str()?
+            .strip_prefix("refs/remotes/origin/")
+    {
+        return Ok(Some(branch.to_string()));
+    }
+    for candidate in ["main", "master"] {
+        if repo
+            .try_find_reference(format!("refs/heads/{}", candidate).as_str())?
+            .is_some()
+        {
+            return Ok(Some(candidate.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Names of the refs under `prefix` (e.g. `refs/heads/`) without it, sorted.
+pub fn branches(repo_root: &Path, prefix: &str) -> Result<Vec<String>> {
+    let repo = gix::open(repo_root)?;
+    let references = repo.references()?;
+    let mut names = Vec::new();
+    for reference in references.prefixed(prefix)? {
+        let reference = reference.map_err(|e| anyhow::anyhow!(e))?;
+        if let Some(name) = reference.name().as_bstr().to_str()?.strip_prefix(prefix) {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// `git status --porcelain=v2 --branch`, with changes in `git status --short`
+/// format: tracked paths sorted, then untracked ones.
+pub fn worktree_status(worktree_path: &Path) -> Result<WorktreeStatus> {
+    let repo = gix::open(worktree_path)?;
+    let head = repo.head()?;
+    let head_id = head.id().map(|id| id.detach());
+    let mut status = WorktreeStatus {
+        head: head_id.map(|id| id.to_string()),
+        branch: head.referent_name().map(|name| name.shorten().to_string()),
+        upstream: None,
+        changes: Vec::new(),
+    };
+    if let Some(name) = head.referent_name() {
+        status.upstream = upstream(&repo, name, head_id)?;
+    }
+
+    // Path -> (index status, worktree status, rename source)
+    let mut tracked: BTreeMap<String, (char, char, Option<String>)> = BTreeMap::new();
+    let mut conflicts = BTreeMap::new();
+    let mut untracked = Vec::new();
+    let items = repo
+        .status(gix::progress::Discard)?
+        .untracked_files(gix::status::UntrackedFiles::Collapsed)
+        .index_worktree_rewrites(None)
+        .into_iter(Vec::new())?;
+    for item in items {
+        match item? {
+            gix::status::Item::IndexWorktree(index_worktree::Item::Modification {
+                rela_path,
+                status,
+                ..
+            }) => {
+                if let EntryStatus::Conflict { summary, .. } = status {
+                    conflicts.insert(rela_path.to_string(), conflict_code(summary));
+                    continue;
+                }
+                let entry = tracked
+                    .entry(rela_path.to_string())
+                    .or_insert((' ', ' ', None));
+                match status {
+                    EntryStatus::Conflict { .. } | EntryStatus::NeedsUpdate(_) => {}
+                    EntryStatus::Change(Change::Removed) => entry.1 = 'D',
+                    EntryStatus::Change(Change::Type { .. }) => entry.1 = 'T',
+                    EntryStatus::Change(
+                        Change::Modification { .. } | Change::SubmoduleModification(_),
+                    ) => entry.1 = 'M',
+                    EntryStatus::IntentToAdd => entry.1 = 'A',
+                }
+            }
+            gix::status::Item::IndexWorktree(index_worktree::Item::DirectoryContents {
+                entry,
+                ..
+            }) => {
+                if entry.status == gix::dir::entry::Status::Untracked {
+                    let mut path = entry.rela_path.to_string();
+                    if entry.disk_kind == Some(gix::dir::entry::Kind::Directory) {
+                        path.push('/');
+                    }
+                    untracked.push(path);
+                }
+            }
+            gix::status::Item::IndexWorktree(index_worktree::Item::Rewrite { .. }) => {
+                bail!("unexpected worktree rename");
+            }
+            gix::status::Item::TreeIndex(change) => {
+                use gix::diff::index::ChangeRef;
+                let (path, x, source) = match change {
+                    ChangeRef::Addition { location, .. } => (location, 'A', None),
+                    ChangeRef::Deletion { location, .. } => (location, 'D', None),
+                    ChangeRef::Modification { location, .. } => (location, 'M', None),
+                    ChangeRef::Rewrite {
+                        source_location,
+                        location,
+                        copy,
+                        ..
+                    } => (
+                        location,
+                        if copy { 'C' } else { 'R' },
+                        Some(source_location.to_string()),
+                    ),
+                };
+                let entry = tracked.entry(path.to_string()).or_insert((' ', ' ', None));
+                entry.0 = x;
+                entry.2 = source;
+            }
+        }
+    }
+
+    for (path, code) in conflicts {
+        tracked.insert(path, (code.0, code.1, None));
+    }
+    for (path, (x, y, source)) in tracked {
+        if (x, y) == (' ', ' ') {
+            continue;
+        }
+        status.changes.push(match source {
+            Some(source) => format!("{}{} {} -> {}", x, y, source, path),
+            None => format!("{}{} {}", x, y, path),
+        });
+    }
+    untracked.sort();
+    status
+        .changes
+        .extend(untracked.into_iter().map(|path| format!("?? {}", path)));
+    Ok(status)
+}
+
+/// The `git status --short` codes of an unmerged path.
+fn conflict_code(conflict: Conflict) -> (char, char) {
+    match conflict {
+        Conflict::BothDeleted => ('D', 'D'),
+        Conflict::AddedByUs => ('A', 'U'),
+        Conflict::DeletedByThem => ('U', 'D'),
+        Conflict::AddedByThem => ('U', 'A'),
+        Conflict::DeletedByUs => ('D', 'U'),
+        Conflict::BothAdded => ('A', 'A'),
+        Conflict::BothModified => ('U', 'U'),
+    }
+}
+
+/// The upstream of `branch` and how far `head` diverged from it; without a
+/// remote-tracking ref (upstream gone) ahead and behind are 0, as git omits them.
+fn upstream(
+    repo: &gix::Repository,
+    branch: &gix::refs::FullNameRef,
+    head: Option<gix::ObjectId>,
+) -> Result<Option<Upstream>> {
+    let Some(tracking) = repo
+        .branch_remote_tracking_ref_name(branch, gix::remote::Direction::Fetch)
+        .transpose()?
+    else {
+        return Ok(None);
+    };
+    let mut upstream = Upstream {
+        name: tracking.shorten().to_string(),
+        ahead: 0,
+        behind: 0,
+    };
+    if let Some(head) = head
+        && let Some(mut reference) = repo.try_find_reference(tracking.as_ref())?
+    {
+        let theirs = reference.peel_to_id()?.detach();
+        upstream.ahead = count_commits(repo, head, theirs)?;
+        upstream.behind = count_commits(repo, theirs, head)?;
+    }
+    Ok(Some(upstream))
+}
+
+/// Commits reachable from `tip` but not from `hidden` (`git rev-list --count hidden..tip`).
+fn count_commits(
+    repo: &gix::Repository,
+    tip: gix::ObjectId,
+    hidden: gix::ObjectId,
+) -> Result<usize> {
+    let mut count = 0;
+    for info in repo.rev_walk([tip]).with_hidden([hidden]).all()? {
+        info?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::git::test_support::git;
+
+    #[test]
+    fn matches_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root: PathBuf = dir.path().canonicalize().unwrap().join("repo");
+        std::fs::create_dir(&root).unwrap();
+        git(&root, &["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("a"), "a").unwrap();
+        std::fs::write(root.join("b"), "b").unwrap();
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-qm", "init"]);
+        git(
+            &root,
+            &["worktree", "add", "-q", "../feature", "-b", "feature"],
+        );
+        git(&root, &["worktree", "add", "-q", "--detach", "../detached"]);
+        git(&root, &["worktree", "lock", "../detached"]);
+        std::fs::write(root.join("a"), "changed").unwrap();
+        std::fs::write(root.join("c"), "new").unwrap();
+        git(&root, &["add", "c"]);
+        std::fs::remove_file(root.join("b")).unwrap();
+        std::fs::create_dir(root.join("untracked")).unwrap();
+        std::fs::write(root.join("untracked/file"), "").unwrap();
+
+        assert_eq!(
+            worktrees(&root).unwrap(),
+            crate::worktree::parse_porcelain(
+                &crate::process::run_stdout(
+                    "git",
+                    &["worktree", "list", "--porcelain"],
+                    Some(&root)
+                )
+                .unwrap()
+            )
+            .unwrap()
+        );
+        let status = worktree_status(&root).unwrap();
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.changes, [" M a", " D b", "A  c", "?? untracked/"]);
+        assert_eq!(status, git_status(&root));
+        assert_eq!(main_branch(&root).unwrap().as_deref(), Some("main"));
+        assert_eq!(branches(&root, "refs/heads/").unwrap(), ["feature", "main"]);
+
+        // A clone one commit ahead of its upstream, which is one behind
+        let clone = root.with_file_name("clone");
+        git(&root, &["clone", "-q", "--no-local", ".", "../clone"]);
+        git(&clone, &["commit", "-q", "--allow-empty", "-m", "ahead"]);
+        git(&root, &["commit", "-q", "-m", "behind"]);
+        git(&clone, &["fetch", "-q"]);
+        let status = worktree_status(&clone).unwrap();
+        assert_eq!(
+            status.upstream,
+            Some(Upstream {
+                name: "origin/main".to_string(),
+                ahead: 1,
+                behind: 1,
+            })
+        );
+        assert_eq!(status, git_status(&clone));
+        assert_eq!(
+            branches(&clone, "refs/remotes/").unwrap(),
+            ["origin/HEAD", "origin/feature", "origin/main"]
+        );
+    }
+
+    fn git_status(dir: &Path) -> WorktreeStatus {
+        super::super::parse_status_v2(
+            &crate::process::run_stdout(
+                "git",
+                &["status", "--porcelain=v2", "--branch"],
+                Some(dir),
+            )
+            .unwrap(),
+        )
+    }
+}
@@ -0,0 +1,235 @@
+//! GitHub pull requests and check status via the GitHub CLI (`gh`).
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::forge::{self, ChecksState, PrState, PullRequest, ReviewState};
+
+/// Fields requested from `gh pr list`.
+const PR_FIELDS: &str =
+    "number,title,state,isDraft,reviewDecision,statusCheckRollup,url,headRefName";
+
+/// A pull request as printed by `gh pr list --json`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhPullRequest {
+    number: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    is_draft: bool,
+    #[serde(default)]
+    review_decision: Option<String>,
+    #[serde(default)]
+    status_check_rollup: Option<Vec<GhCheck>>,
+    url: String,
+    head_ref_name: String,
+}
+
+/// A check run (`status`/`conclusion`) or commit status (`state`).
+#[derive(Deserialize)]
+struct GhCheck {
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    conclusion: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+impl From<GhPullRequest> for PullRequest {
+    fn from(pr: GhPullRequest) -> Self {
+        let state = match pr.state.as_str() {
+            "MERGED" => PrState::Merged,
+            "CLOSED" => PrState::Closed,
+            _ if pr.is_draft => PrState::Draft,
+            _ => PrState::Open,
+        };
+        let review = match pr.review_decision.as_deref() {
+            Some("APPROVED") => Some(ReviewState::Approved),
+            Some("CHANGES_REQUESTED") => Some(ReviewState::ChangesRequested),
+            Some("REVIEW_REQUIRED") => Some(ReviewState::ReviewRequired),
+            _ => None,
+        };
+        PullRequest {
+            number: pr.number,
+            title: pr.title,
+            state,
+            review,
+            checks: checks_state(pr.status_check_rollup.as_deref().unwrap_or_default()),
+            url: pr.url,
+        }
+    }
+}
+
+/// Combine checks: any failure fails, then anything unfinished is
+/// pending; skipped and neutral checks count as passing.
+fn checks_state(checks: &[GhCheck]) -> Option<ChecksState> {
+    if checks.is_empty() {
+        return None;
+    }
+    let mut pending = false;
+    for check in checks {
+        let outcome = check
+            .conclusion
+            .as_deref()
+            .filter(|c| !c.is_empty())
+            .or(check.state.as_deref());
+        match outcome {
+            Some(
+                "FAILURE" | "ERROR" | "CANCELLED" | "TIMED_OUT" | "ACTION_REQUIRED"
+                | "STARTUP_FAILURE",
+            ) => return Some(ChecksState::Failing),
+            Some("SUCCESS" | "NEUTRAL" | "SKIPPED" | "STALE") => {}
+            _ if check.status.as_deref() == Some("COMPLETED") => {}
+            _ => pending = true,
+        }
+    }
+    Some(if pending {
+        ChecksState::Pending
+    } else {
+        ChecksState::Passing
+    })
+}
+
+/// Recent pull requests of the repository at `path` in any state, newest
+/// first, with their head branch names. One `gh pr list` call.
+pub fn pull_requests(path: &Path) -> Result<Vec<(String, PullRequest)>> {
+    let prs = gh_pr_list(path, &["--limit", "200"])?;
+    Ok(prs
+        .into_iter()
+        .map(|pr| (pr.head_ref_name.clone(), pr.into()))
+        .collect())
+}
+
+/// The most recent pull request opened from `branch`, if any.
+pub fn pull_request(path: &Path, branch: &str) -> Result<Option<PullRequest>> {
+    let prs = gh_pr_list(path, &["--head", branch, "--limit", "1"])?;
+    Ok(prs.into_iter().next().map(Into::into))
+}
+
+/// GitHub's check runs for a commit (REST API).
+#[derive(Deserialize)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    status: String,
+    #[serde(default)]
+    conclusion: Option<String>,
+}
+
+/// GitHub's combined commit status (REST API).
+#[derive(Deserialize)]
+struct CombinedStatus {
+    state: String,
+    total_count: u64,
+}
+
+/// The combined state of commit `sha`'s check runs and commit statuses,
+/// from two `gh api` calls.
+pub fn commit_checks(path: &Path, sha: &str) -> Result<Option<ChecksState>> {
+    let runs: CheckRuns = forge::query_cli(
+        "gh",
+        "GitHub CLI",
+        &[
+            "api",
+            &format!(
+                "repos/{{owner}}/{{repo}}/commits/{}/check-runs?per_page=100",
+                sha
+            ),
+        ],
+        path,
+    )?;
+    let status: CombinedStatus = forge::query_cli(
+        "gh",
+        "GitHub CLI",
+        &[
+            "api",
+            &format!("repos/{{owner}}/{{repo}}/commits/{}/status", sha),
+        ],
+        path,
+    )?;
+
+    // The REST API spells states in lowercase; the rollup logic expects the
+    // GraphQL spelling used by `gh pr list`.
+    let mut checks: Vec<GhCheck> = runs
+        .check_runs
+        .into_iter()
+        .map(|run| GhCheck {
+            status: Some(run.status.to_ascii_uppercase()),
+            conclusion: run.conclusion.map(|c| c.to_ascii_uppercase()),
+            state: None,
+        })
+        .collect();
+    if status.total_count > 0 {
+        checks.push(GhCheck {
+            status: None,
+            conclusion: None,
+            state: Some(status.state.to_ascii_uppercase()),
+        });
+    }
+    Ok(checks_state(&checks))
+}
+
+fn gh_pr_list(path: &Path, extra: &[&str]) -> Result<Vec<GhPullRequest>> {
+    let mut args = vec!["pr", "list", "--state", "all", "--json", PR_FIELDS];
+    args.extend_from_slice(extra);
+    forge::query_cli("gh", "GitHub CLI", &args, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_requests_from_gh_output() {
+        let out = r#"[
+            {"number": 12, "title": "Add login", "state": "OPEN", "isDraft": true,
+             "reviewDecision": "", "url": "https://github.com/org/app/pull/12",
+             "headRefName": "login",
+             "statusCheckRollup": [
+                {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS"},
+                {"__typename": "CheckRun", "status": "IN_PROGRESS", "conclusion": ""}
+             ]},
+            {"number": 9, "title": "Fix typo", "state": "MERGED", "isDraft": false,
+             "reviewDecision": "APPROVED", "url": "https://github.com/org/app/pull/9",
+             "headRefName": "typo",
+             "statusCheckRollup": [{"__typename": "StatusContext", "state": "ERROR"}]}
+        ]"#;
+        let prs: Vec<PullRequest> = serde_json::from_str::<Vec<GhPullRequest>>(out)
+            .unwrap()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(prs[0].state, PrState::Draft);
+        assert_eq!(prs[0].review, None);
+        assert_eq!(prs[0].checks, Some(ChecksState::Pending));
+        assert_eq!(prs[0].summary(), "#12 draft ●");
+        assert_eq!(prs[1].state, PrState::Merged);
+        assert_eq!(prs[1].checks, Some(ChecksState::Failing));
+        assert_eq!(prs[1].summary(), "#9 merged ✗ approved");
+    }
+
+    #[test]
+    fn checks_without_failures_pass() {
+        let check = |conclusion: &str| GhCheck {
+            status: Some("COMPLETED".to_string()),
+            conclusion: Some(conclusion.to_string()),
+            state: None,
+        };
+        assert_eq!(checks_state(&[]), None);
+        assert_eq!(
+            checks_state(&[check("SUCCESS"), check("SKIPPED")]),
+            Some(ChecksState::Passing)
+        );
+        assert_eq!(
+            checks_state(&[check("SUCCESS"), check("TIMED_OUT")]),
+            Some(ChecksState::Failing)
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! GitLab merge requests and pipelines via the GitLab CLI (`glab`).
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::forge::{self, ChecksState, PrState, PullRequest, ReviewState};
+
+/// A merge request as printed by `glab mr list/view --output json` (GitLab's
+/// API representation).
+#[derive(Deserialize)]
+struct GlMergeRequest {
+    iid: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    work_in_progress: bool,
+    web_url: String,
+    source_branch: String,
+    #[serde(default)]
+    detailed_merge_status: Option<String>,
+    /// Only included when viewing a single merge request
+    #[serde(default)]
+    head_pipeline: Option<GlPipeline>,
+}
+
+#[derive(Deserialize)]
+struct GlPipeline {
+    status: String,
+}
+
+impl From<GlMergeRequest> for PullRequest {
+    fn from(mr: GlMergeRequest) -> Self {
+        let state = match mr.state.as_str() {
+            "merged" => PrState::Merged,
+            "closed" | "locked" => PrState::Closed,
+            _ if mr.draft || mr.work_in_progress => PrState::Draft,
+            _ => PrState::Open,
+        };
+        let review = match mr.detailed_merge_status.as_deref() {
+            Some("not_approved") => Some(ReviewState::ReviewRequired),
+            Some("requested_changes") => Some(ReviewState::ChangesRequested),
+            _ => None,
+        };
+        PullRequest {
+            number: mr.iid,
+            title: mr.title,
+            state,
+            review,
+            checks: mr.head_pipeline.map(|p| pipeline_state(&p.status)),
+            url: mr.web_url,
+        }
+    }
+}
+
+/// Map a pipeline status; skipped and manual pipelines count as passing.
+fn pipeline_state(status: &str) -> ChecksState {
+    match status {
+        "success" | "skipped" | "manual" => ChecksState::Passing,
+        "failed" | "canceled" => ChecksState::Failing,
+        _ => ChecksState::Pending,
+    }
+}
+
+/// Recent merge requests of the repository at `path` in any state, newest
+/// first, with their source branch names. One `glab mr list` call, which
+/// doesn't report pipelines.
+pub fn merge_requests(path: &Path) -> Result<Vec<(String, PullRequest)>> {
+    let mrs = glab_mr_list(path, &["--per-page", "100"])?;
+    Ok(mrs
+        .into_iter()
+        .map(|mr| (mr.source_branch.clone(), mr.into()))
+        .collect())
+}
+
+/// The most recent merge request opened from `branch`, if any, with its
+/// pipeline status.
+pub fn merge_request(path: &Path, branch: &str) -> Result<Option<PullRequest>> {
+    let Some(mr) = glab_mr_list(path, &["--source-branch", branch, "--per-page", "1"])?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    // The list doesn't include the head pipeline; viewing the merge request
+    // does. Fall back to the listed one without checks.
+    let iid = mr.iid.to_string();
+    let mr = forge::query_cli::<GlMergeRequest>(
+        "glab",
+        "GitLab CLI",
+        &["mr", "view", &iid, "--output", "json"],
+        path,
+    )
+    .unwrap_or(mr);
+    Ok(Some(mr.into()))
+}
+
+/// A commit as returned by GitLab's API.
+#[derive(Deserialize)]
+struct GlCommit {
+    #[serde(default)]
+    last_pipeline: Option<GlPipeline>,
+}
+
+/// The status of the latest pipeline for commit `sha`, from one `glab api`
+/// call.
+pub fn commit_pipeline(path: &Path, sha: &str) -> Result<Option<ChecksState>> {
+    let commit: GlCommit = forge::query_cli(
+        "glab",
+        "GitLab CLI",
+        &["api", &format!("projects/:id/repository/commits/{}", sha)],
+        path,
+    )?;
+    Ok(commit.last_pipeline.map(|p| pipeline_state(&p.status)))
+}
+
+fn glab_mr_list(path: &Path, extra: &[&str]) -> Result<Vec<GlMergeRequest>> {
+    let mut args = vec!["mr", "list", "--all", "--output", "json"];
+    args.extend_from_slice(extra);
+    forge::query_cli("glab", "GitLab CLI", &args, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_requests_from_glab_output() {
+        let out = r#"[
+            {"iid": 4, "title": "Draft: Add login", "state": "opened", "draft": true,
+             "web_url": "https://gitlab.com/g/app/-/merge_requests/4",
+             "source_branch": "login", "detailed_merge_status": "draft_status"},
+            {"iid": 3, "title": "Fix typo", "state": "opened", "draft": false,
+             "web_url": "https://gitlab.com/g/app/-/merge_requests/3",
+             "source_branch": "typo", "detailed_merge_status": "not_approved",
+             "head_pipeline": {"id": 9, "status": "failed"}}
+        ]"#;
+        let mrs: Vec<PullRequest> = serde_json::from_str::<Vec<GlMergeRequest>>(out)
+            .unwrap()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(mrs[0].summary(), "#4 draft");
+        assert_eq!(mrs[1].state, PrState::Open);
+        assert_eq!(mrs[1].review, Some(ReviewState::ReviewRequired));
+        assert_eq!(mrs[1].checks, Some(ChecksState::Failing));
+        assert_eq!(pipeline_state("running"), ChecksState::Pending);
+    }
+}
@@ -0,0 +1,257 @@
+//! `wt agent guard`: pre-flight checks an agent runs before a destructive
+//! operation, reporting what would be lost or would make it fail.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::worktree::{self, Worktree};
+use crate::{git, process};
+
+#[derive(Serialize)]
+struct GuardReport {
+    /// The operation as given, e.g. "remove feature-x"
+    operation: String,
+    /// `true` when nothing blocks the operation
+    pass: bool,
+    /// Worktrees the operation would affect
+    targets: Vec<String>,
+    blockers: Vec<Finding>,
+    /// Things worth knowing that don't block the operation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<Finding>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Finding {
+    check: Check,
+    path: String,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Check {
+    /// The bare repository entry, which can't be removed
+    Bare,
+    /// The main branch's worktree
+    ProtectedBranch,
+    Locked,
+    /// Uncommitted or untracked changes
+    Dirty,
+    /// Commits on neither a remote-tracking branch nor the main branch
+    Unpushed,
+    /// Stashes made on the worktree's branch
+    Stashes,
+}
+
+impl Check {
+    fn as_str(self) -> &'static str {
+        match self {
+            Check::Bare => "bare",
+            Check::ProtectedBranch => "protected_branch",
+            Check::Locked => "locked",
+            Check::Dirty => "dirty",
+            Check::Unpushed => "unpushed",
+            Check::Stashes => "stashes",
+        }
+    }
+}
+
+/// Check whether `operation` (`remove <target>` or `prune`) is safe to run
+/// in the current repository. Exits with status 1 when something blocks it.
+pub fn guard(operation: &[String], json: bool) -> Result<(), WtError> {
+    let repo_root = git::repo_root(None)?;
+    let worktrees = git::worktrees_porcelain(&repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+
+    let (targets, blockers, warnings) = match operation {
+        [op, target] if op == "remove" => {
+            let wt = worktree::find_worktree(&worktrees, target)
+                .map_err(|e| WtError::not_found(format!("{:#}", e)))?;
+            (vec![wt], check_remove(&repo_root, wt), Vec::new())
+        }
+        [op] if op == "prune" => {
+            let stale: Vec<_> = worktrees
+                .iter()
+                .filter(|wt| wt.prunable.is_some())
+                .collect();
+            let warnings = check_prune(&repo_root, &stale);
+            (stale, Vec::new(), warnings)
+        }
+        _ => {
+            return Err(WtError::user_error(format!(
+                "unsupported operation '{}'; expected `remove <target>` or `prune`",
+                operation.join(" ")
+            )));
+        }
+    };
+
+    let report = GuardReport {
+        operation: operation.join(" "),
+        pass: blockers.is_empty(),
+        targets: targets
+            .iter()
+            .map(|wt| wt.path.display().to_string())
+            .collect(),
+        blockers,
+        warnings,
+    };
+    if json {
+        let json_str = serde_json::to_string(&report)
+            .map_err(|e| WtError::io_error_with_source("failed to serialize JSON", e.into()))?;
+        println!("{}", json_str);
+    } else {
+        print_report(&report);
+    }
+
+    if !report.pass {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// What stands in the way of removing `wt`, or would be lost with it.
+fn check_remove(repo_root: &Path, wt: &Worktree) -> Vec<Finding> {
+    let path = wt.path.display().to_string();
+    let finding = |check, message: String| Finding {
+        check,
+        path: path.clone(),
+        message,
+    };
+    let mut blockers = Vec::new();
+
+    if wt.bare {
+        blockers.push(finding(
+            Check::Bare,
+            "the bare repository entry can't be removed".to_string(),
+        ));
+        return blockers;
+    }
+    let main = git::main_branch(repo_root);
+    let branch = wt.branch_name();
+    if let Some(branch) = branch
+        && main.as_deref() == Some(branch)
+    {
+        blockers.push(finding(
+            Check::ProtectedBranch,
+            format!("'{}' is the main branch's worktree", branch),
+        ));
+    }
+    if wt.locked {
+        blockers.push(finding(
+            Check::Locked,
+            "worktree is locked; unlock it with `git worktree unlock` first".to_string(),
+        ));
+    }
+    if !wt.path.is_dir() {
+        return blockers;
+    }
+
+    match git::worktree_status(&wt.path) {
+        Ok(status) if status.is_dirty() => blockers.push(finding(
+            Check::Dirty,
+            format!(
+                "{} uncommitted change{} would be lost",
+                status.changes.len(),
+                plural(status.changes.len())
+            ),
+        )),
+        Ok(_) => {}
+        Err(e) => blockers.push(finding(
+            Check::Dirty,
+            format!(
+                "could not read status: {}",
+                process::error_summary(&format!("{:#}", e))
+            ),
+        )),
+    }
+    let unpushed = git::unpushed_commits(&wt.path, "HEAD", main.as_deref());
+    if unpushed > 0 {
+        blockers.push(finding(
+            Check::Unpushed,
+            format!(
+                "{} commit{} not on any remote or the main branch",
+                unpushed,
+                plural(unpushed)
+            ),
+        ));
+    }
+    if let Some(branch) = branch {
+        let stashes = git::stashes(repo_root)
+            .iter()
+            .filter(|s| s.is_on_branch(branch))
+            .count();
+        if stashes > 0 {
+            blockers.push(finding(
+                Check::Stashes,
+                format!(
+                    "{} stash{} made on '{}'",
+                    stashes,
+                    if stashes == 1 { "" } else { "es" },
+                    branch
+                ),
+            ));
+        }
+    }
+    blockers
+}
+
+/// Notes on the stale worktrees `git worktree prune` would forget. Pruning
+/// only drops git's records of worktrees whose directories are gone; their
+/// branches stay, so nothing blocks it.
+fn check_prune(repo_root: &Path, stale: &[&Worktree]) -> Vec<Finding> {
+    let main = git::main_branch(repo_root);
+    let mut warnings = Vec::new();
+    for wt in stale {
+        let path = wt.path.display().to_string();
+        if wt.locked {
+            warnings.push(Finding {
+                check: Check::Locked,
+                path: path.clone(),
+                message: "locked, so prune keeps it".to_string(),
+            });
+        }
+        let Some(branch) = wt.branch.as_deref() else {
+            continue;
+        };
+        let unpushed = git::unpushed_commits(repo_root, branch, main.as_deref());
+        if unpushed > 0 {
+            warnings.push(Finding {
+                check: Check::Unpushed,
+                path,
+                message: format!(
+                    "branch '{}' has {} unpushed commit{} (kept after pruning)",
+                    git::short_branch(branch),
+                    unpushed,
+                    plural(unpushed)
+                ),
+            });
+        }
+    }
+    warnings
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
+
+fn print_report(report: &GuardReport) {
+    let verdict = if report.pass { "ok" } else { "blocked" };
+    println!("{}: {}", report.operation, verdict);
+    for blocker in &report.blockers {
+        println!("  ✗ {}: {}", blocker.check.as_str(), blocker.message);
+    }
+    for warning in &report.warnings {
+        println!(
+            "  ! {} ({}): {}",
+            warning.check.as_str(),
+            warning.path,
+            warning.message
+        );
+    }
+    if report.targets.is_empty() {
+        println!("  nothing to do");
+    }
+}
@@ -0,0 +1,184 @@
+//! Lifecycle hooks: user commands from the config's `hooks` section, run
+//! around worktree operations.
+
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::Result;
+
+use crate::config::{self, HookFailurePolicy, HooksConfig};
+use crate::error::WtError;
+use crate::{git, process};
+
+/// A point in a worktree's life where a hook can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    PostAdd,
+    PreRemove,
+    PostRemove,
+    PostSwitch,
+}
+
+impl Event {
+    /// The event's key in the `hooks` config section.
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::PostAdd => "post_add",
+            Event::PreRemove => "pre_remove",
+            Event::PostRemove => "post_remove",
+            Event::PostSwitch => "post_switch",
+        }
+    }
+
+    fn command(self, hooks: &HooksConfig) -> Option<&str> {
+        let command = match self {
+            Event::PostAdd => &hooks.post_add,
+            Event::PreRemove => &hooks.pre_remove,
+            Event::PostRemove => &hooks.post_remove,
+            Event::PostSwitch => &hooks.post_switch,
+        };
+        command.as_deref().filter(|c| !c.trim().is_empty())
+    }
+}
+
+/// Run the hook configured for `event` on the worktree at `path` (checked out
+/// on `branch`), if there is one. `repo_root` is any worktree of the same
+/// repository. Returns an error only if the hook fails and `on_failure` is
+/// `abort`; otherwise failures are reported as warnings.
+pub fn run(event: Event, path: &Path, branch: Option<&str>, repo_root: &Path) -> Result<()> {
+    let hooks = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?
+        .hooks;
+    let Some(command) = event.command(&hooks) else {
+        return Ok(());
+    };
+
+    match execute(event, command, path, branch, repo_root) {
+        Ok(()) => Ok(()),
+        Err(e) if hooks.on_failure == HookFailurePolicy::Abort => Err(e),
+        Err(e) => {
+            eprintln!("Warning: {:#}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Run the `post_switch` hook for the worktree at `path`, looking up its
+/// branch only when a hook is configured.
+pub fn post_switch(path: &Path) -> Result<()> {
+    let hooks = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?
+        .hooks;
+    if Event::PostSwitch.command(&hooks).is_none() {
+        return Ok(());
+    }
+    let branch = process::run_stdout(
+        "git",
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+        Some(path),
+    )
+    .ok()
+    .map(|b| b.trim().to_string());
+    run(Event::PostSwitch, path, branch.as_deref(), path)
+}
+
+/// Run `command` with [`process::shell`] in `path`, or in the main worktree
+/// if `path` is gone. Its stdout goes to stderr so it can't corrupt a v1
+/// `cd|PATH` line or JSON output.
+fn execute(
+    event: Event,
+    command: &str,
+    path: &Path,
+    branch: Option<&str>,
+    repo_root: &Path,
+) -> Result<()> {
+    // Hooks see the main worktree (the bare repository in a `wt clone`
+    // layout) as the repository root, whichever worktree `wt` ran in
+    let main_root = git::worktrees_porcelain(repo_root)
+        .ok()
+        .and_then(|worktrees| worktrees.into_iter().next())
+        .map(|wt| wt.path)
+        .unwrap_or_else(|| repo_root.to_path_buf());
+    let dir = if path.is_dir() { path } else { &main_root };
+
+    eprintln!("Running {} hook: {}", event.name(), command);
+    let status = process::shell(command)
+        .current_dir(dir)
+        .env("WT_HOOK", event.name())
+        .env("WT_PATH", path)
+        .env("WT_BRANCH", branch.unwrap_or(""))
+        .env("WT_REPO_ROOT", &main_root)
+        .stdin(Stdio::inherit())
+        .stdout(io::stderr())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| {
+            WtError::io_error_with_source(format!("failed to run {} hook", event.name()), e.into())
+        })?;
+
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => Err(WtError::user_error(format!(
+            "{} hook exited with status {}: {}",
+            event.name(),
+            code,
+            command
+        ))
+        .into()),
+        None => Err(WtError::user_error(format!(
+            "{} hook was terminated by a signal: {}",
+            event.name(),
+            command
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_hooks_are_not_run() {
+        let hooks = HooksConfig {
+            post_add: Some("npm install".to_string()),
+            pre_remove: Some("  ".to_string()),
+            ..HooksConfig::default()
+        };
+        assert_eq!(Event::PostAdd.command(&hooks), Some("npm install"));
+        assert_eq!(Event::PreRemove.command(&hooks), None);
+        assert_eq!(Event::PostSwitch.command(&hooks), None);
+    }
+
+    #[test]
+    fn hooks_get_worktree_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("env");
+        let command = format!(
+            "printf '%s|%s|%s|%s' \"$WT_HOOK\" \"$WT_PATH\" \"$WT_BRANCH\" \"$(pwd -P)\" > '{}'",
+            out.display()
+        );
+        execute(
+            Event::PostAdd,
+            &command,
+            dir.path(),
+            Some("feature"),
+            dir.path(),
+        )
+        .unwrap();
+        let env = std::fs::read_to_string(&out).unwrap();
+        let cwd = dir.path().canonicalize().unwrap();
+        assert_eq!(
+            env,
+            format!(
+                "post_add|{}|feature|{}",
+                dir.path().display(),
+                cwd.display()
+            )
+        );
+
+        let err = execute(Event::PreRemove, "exit 3", dir.path(), None, dir.path()).unwrap_err();
+        assert!(format!("{:#}", err).contains("pre_remove hook exited with status 3"));
+    }
+}
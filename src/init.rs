@@ -7,26 +7,137 @@
 use std::env;
 use std::fs::{self, OpenOptions};
 use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
+use clap::CommandFactory;
 
-use crate::cli::Shell;
+use crate::cli::{Cli, Shell};
+use crate::config;
+use crate::error::WtError;
 
 /// The marker comment we add to identify our integration line
 const MARKER: &str = "# wt shell integration";
 
-/// Returns the shell integration code for the given shell.
-pub fn shell_init(shell: Shell) -> String {
+/// The default name of the shell function, the same as the binary's.
+const DEFAULT_CMD: &str = "wt";
+
+/// Returns the shell integration code for the given shell: the wrapper
+/// function named `cmd` (it always runs the `wt` binary), then its
+/// [`completions`].
+pub fn shell_init(shell: Shell, cmd: &str) -> String {
+    let mut code = match shell {
+        Shell::Zsh => ZSH_INIT.replace("\nwt() {", &format!("\n{}() {{", cmd)),
+        Shell::Bash => BASH_INIT.replace("\nwt() {", &format!("\n{}() {{", cmd)),
+        Shell::Fish => FISH_INIT.replace("\nfunction wt\n", &format!("\nfunction {}\n", cmd)),
+    };
+    code.push('\n');
+    code.push_str(&completions(shell, cmd));
+    code
+}
+
+/// Returns the completions for the command `cmd`: generated from the CLI
+/// definition, with worktrees, branches and the like from `wt __complete`.
+/// Works both evaluated by `wt init` and installed as a completion file (see
+/// [`completion_file_name`]).
+pub fn completions(shell: Shell, cmd: &str) -> String {
+    let generator = match shell {
+        Shell::Zsh => clap_complete::Shell::Zsh,
+        Shell::Bash => clap_complete::Shell::Bash,
+        Shell::Fish => clap_complete::Shell::Fish,
+    };
+    let mut code = String::new();
+    if let Shell::Zsh = shell {
+        code.push_str(&format!("#compdef {}\n", cmd));
+    }
+    code.push_str("# Completions, generated from wt's command-line definition\n");
+    if let Shell::Fish = shell {
+        code.push_str(&format!("complete -c {} -e\n", cmd));
+    }
+    let mut generated = Vec::new();
+    clap_complete::generate(generator, &mut Cli::command(), cmd, &mut generated);
+    let mut generated = String::from_utf8_lossy(&generated).into_owned();
+    if let Shell::Zsh = shell {
+        // The dynamic layer takes over registering (or, autoloaded, running)
+        // the completion function
+        if let Some(start) = generated.find(&format!("\nif [ \"$funcstack[1]\" = \"_{}\" ]", cmd)) {
+            generated.truncate(start + 1);
+        }
+        generated = generated.replacen(&format!("#compdef {}\n", cmd), "", 1);
+    }
+    code.push_str(&generated);
+
+    // Worktrees, branches and the like come from `wt __complete`, falling
+    // back to the generated completions for everything else
+    let dynamic = match shell {
+        Shell::Zsh => ZSH_DYNAMIC_COMPLETIONS,
+        Shell::Bash => BASH_DYNAMIC_COMPLETIONS,
+        Shell::Fish => FISH_DYNAMIC_COMPLETIONS,
+    };
+    code.push_str(&dynamic.replace("{cmd}", cmd));
+    code
+}
+
+/// The file name completion managers expect for `cmd`'s completions: `_wt`
+/// in zsh's `$fpath`, `wt` in bash-completion's directory and `wt.fish` in
+/// fish's.
+pub fn completion_file_name(shell: Shell, cmd: &str) -> String {
     match shell {
-        Shell::Zsh => ZSH_INIT.to_string(),
-        Shell::Bash => BASH_INIT.to_string(),
-        Shell::Fish => FISH_INIT.to_string(),
+        Shell::Zsh => format!("_{}", cmd),
+        Shell::Bash => cmd.to_string(),
+        Shell::Fish => format!("{}.fish", cmd),
     }
 }
 
+/// `wt completions`: print the completions for `shell`, or write them to
+/// their conventional file name in `dir`.
+pub fn write_completions(shell: Shell, cmd: &str, dir: Option<&Path>) -> Result<()> {
+    let code = completions(shell, cmd);
+    let Some(dir) = dir else {
+        print!("{}", code);
+        return Ok(());
+    };
+    fs::create_dir_all(dir).map_err(|e| {
+        WtError::io_error_with_source(format!("failed to create {}", dir.display()), e.into())
+    })?;
+    let path = dir.join(completion_file_name(shell, cmd));
+    fs::write(&path, code).map_err(|e| {
+        WtError::io_error_with_source(format!("failed to write {}", path.display()), e.into())
+    })?;
+    eprintln!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// The shell function name to use: `cmd` from `wt init --cmd`, else
+/// `shell.cmd` from the config. Must be usable as a function name in every
+/// shell.
+pub fn command_name(cmd: Option<&str>) -> Result<String> {
+    let cmd = match cmd {
+        Some(cmd) => cmd.to_string(),
+        None => {
+            config::load()
+                .map_err(|e| WtError::config_error_with_source("failed to load config", e))?
+                .shell
+                .cmd
+        }
+    };
+    let valid = cmd.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && cmd
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(WtError::user_error(format!(
+            "invalid shell function name '{}': use letters, digits, '_' and '-', starting with a letter",
+            cmd
+        ))
+        .into());
+    }
+    Ok(cmd)
+}
+
 /// Run interactive shell setup - detect shell, find config, ask user, add integration.
-pub fn run_interactive_setup() -> Result<()> {
+/// The wrapper function is named `cmd`.
+pub fn run_interactive_setup(cmd: &str) -> Result<()> {
     // Detect shell
     let shell = detect_shell()?;
     eprintln!("Detected shell: {}", shell_name(shell));
@@ -49,7 +160,7 @@ pub fn run_interactive_setup() -> Result<()> {
     }
 
     // Show what we'll add
-    let integration_line = integration_line_for_shell(shell);
+    let integration_line = integration_line_for_shell(shell, cmd);
     eprintln!("\nwt needs to add shell integration for cd/edit actions to work.");
     eprintln!(
         "The following line will be added to {}:\n",
@@ -65,7 +176,7 @@ pub fn run_interactive_setup() -> Result<()> {
     }
 
     // Append to config file
-    append_to_config(&config_path, shell)?;
+    append_to_config(&config_path, shell, cmd)?;
 
     eprintln!("\n✓ Added shell integration to {}", config_path.display());
     eprintln!(
@@ -76,9 +187,23 @@ pub fn run_interactive_setup() -> Result<()> {
     Ok(())
 }
 
-/// Detect the user's shell from $SHELL environment variable.
+/// Detect the user's shell from $SHELL. Without it, Git Bash and MSYS2 on
+/// Windows are recognized by $MSYSTEM.
 fn detect_shell() -> Result<Shell> {
-    let shell_path = env::var("SHELL").context("$SHELL environment variable not set")?;
+    shell_from(
+        env::var("SHELL").ok().as_deref(),
+        env::var_os("MSYSTEM").is_some(),
+    )
+}
+
+fn shell_from(shell_path: Option<&str>, msystem: bool) -> Result<Shell> {
+    let shell_path = match shell_path.filter(|s| !s.trim().is_empty()) {
+        Some(shell_path) => shell_path,
+        None if msystem => "bash",
+        None => bail!(
+            "Could not detect your shell: $SHELL is not set\nSupported shells: zsh, bash, fish (on Windows: Git Bash or MSYS2; PowerShell and cmd have no shell integration)\n\nFor manual setup, run: wt init <shell>"
+        ),
+    };
 
     if shell_path.contains("zsh") {
         Ok(Shell::Zsh)
@@ -96,8 +221,10 @@ fn detect_shell() -> Result<Shell> {
 
 /// Get the config file path for the given shell.
 fn shell_config_path(shell: Shell) -> Result<PathBuf> {
-    let home = env::var("HOME").context("$HOME environment variable not set")?;
-    let home = PathBuf::from(home);
+    let home = directories::BaseDirs::new()
+        .context("failed to determine home directory")?
+        .home_dir()
+        .to_path_buf();
 
     let path = match shell {
         Shell::Zsh => {
@@ -139,6 +266,14 @@ fn shell_config_path(shell: Shell) -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Find the config file of the user's shell (from $SHELL) and whether it
+/// sets up wt shell integration.
+pub fn integration_status() -> Result<(PathBuf, bool)> {
+    let config_path = shell_config_path(detect_shell()?)?;
+    let configured = is_already_configured(&config_path)?;
+    Ok((config_path, configured))
+}
+
 /// Check if the config file already has wt integration.
 ///
 /// This checks for the actual functional code (wt() function or eval line),
@@ -166,16 +301,20 @@ fn is_already_configured(config_path: &PathBuf) -> Result<bool> {
 }
 
 /// Get the integration line for a shell (what we show the user).
-fn integration_line_for_shell(shell: Shell) -> &'static str {
+fn integration_line_for_shell(shell: Shell, cmd: &str) -> String {
+    let args = if cmd == DEFAULT_CMD {
+        shell_name(shell).to_string()
+    } else {
+        format!("{} --cmd {}", shell_name(shell), cmd)
+    };
     match shell {
-        Shell::Zsh => "eval \"$(wt init zsh)\"",
-        Shell::Bash => "eval \"$(wt init bash)\"",
-        Shell::Fish => "wt init fish | source",
+        Shell::Zsh | Shell::Bash => format!("eval \"$(wt init {})\"", args),
+        Shell::Fish => format!("wt init {} | source", args),
     }
 }
 
 /// Append the integration to the config file.
-fn append_to_config(config_path: &PathBuf, shell: Shell) -> Result<()> {
+fn append_to_config(config_path: &PathBuf, shell: Shell, cmd: &str) -> Result<()> {
     // Ensure parent directory exists (for fish)
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)
@@ -202,7 +341,7 @@ fn append_to_config(config_path: &PathBuf, shell: Shell) -> Result<()> {
 
     // Write the integration line with marker
     writeln!(file, "\n{}", MARKER)?;
-    writeln!(file, "{}", integration_line_for_shell(shell))?;
+    writeln!(file, "{}", integration_line_for_shell(shell, cmd))?;
 
     Ok(())
 }
@@ -281,99 +420,45 @@ __wt_edit() {
 }
 
 wt() {
-    if [[ $# -eq 0 ]] || [[ "$1" == "interactive" ]]; then
-        local output
-        output=$(command wt "$@" 2>&1)
-        local exit_code=$?
-        
-        if [[ $exit_code -ne 0 ]]; then
-            echo "$output" >&2
-            return $exit_code
-        fi
-        
-        case "$output" in
-            cd\|*)
-                __wt_cd "${output#cd|}"
-                ;;
-            edit\|*)
-                __wt_edit "${output#edit|}"
-                ;;
-            *)
-                [[ -n "$output" ]] && echo "$output"
-                ;;
-        esac
-    else
-        command wt "$@"
+    # wt can't change this shell's directory, so the picker, `wt switch`,
+    # `wt add --cd`, `wt last`, `wt main` and `wt shift` hand back an action (cd, edit or run) through a file:
+    # NUL-terminated protocol version, action and argument
+    local action_file
+    action_file=$(mktemp "${TMPDIR:-/tmp}/wt-action.XXXXXX") || { command wt "$@"; return; }
+
+    WT_SHELL_PROTOCOL=2 WT_ACTION_FILE="$action_file" command wt "$@"
+    local exit_code=$?
+
+    local version= action= argument=
+    {
+        IFS= read -r -d '' version
+        IFS= read -r -d '' action
+        IFS= read -r -d '' argument
+    } < "$action_file"
+    command rm -f -- "$action_file"
+
+    if [[ $exit_code -ne 0 ]]; then
+        return $exit_code
+    fi
+    [[ -z "$version" ]] && return 0
+    if [[ "$version" != 2 ]]; then
+        echo "wt: unsupported shell action protocol: $version (re-run wt init)" >&2
+        return 1
     fi
-}
-
-# Completions
-_wt() {
-    local -a commands
-    commands=(
-        'init:Set up shell integration'
-        'interactive:Interactive picker (fzf)'
-        'list:List worktrees'
-        'add:Add a new worktree'
-        'remove:Remove a worktree'
-        'prune:Prune stale worktrees'
-        'preview:Print preview information'
-        'config:Configuration management'
-        'help:Print help'
-    )
-
-    local -a config_commands
-    config_commands=(
-        'init:Create an initial config file'
-        'show:Show effective config'
-        'set-editor:Set default editor'
-        'set-discovery-paths:Set auto-discovery search roots'
-    )
-
-    local -a shells
-    shells=('bash' 'zsh' 'fish')
-
-    _arguments -C \
-        '1: :->command' \
-        '*:: :->args'
 
-    case $state in
-        command)
-            _describe -t commands 'wt command' commands
+    case "$action" in
+        cd)
+            __wt_cd "$argument"
+            ;;
+        edit)
+            __wt_edit "$argument"
             ;;
-        args)
-            case $words[1] in
-                init)
-                    _describe -t shells 'shell' shells
-                    ;;
-                config)
-                    _describe -t config_commands 'config command' config_commands
-                    ;;
-                add)
-                    local -a branches
-                    branches=($(git branch --format='%(refname:short)' 2>/dev/null))
-                    _describe -t branches 'branch' branches
-                    ;;
-                remove)
-                    local -a worktrees
-                    worktrees=($(git worktree list --porcelain 2>/dev/null | grep '^branch' | sed 's/branch refs\/heads\///'))
-                    _describe -t worktrees 'worktree' worktrees
-                    ;;
-                list)
-                    _arguments \
-                        '--json[JSON output]' \
-                        '--all[List across all discovered repositories]'
-                    ;;
-                interactive)
-                    _arguments \
-                        '--all[Pick from all discovered repositories]'
-                    ;;
-            esac
+        run)
+            eval "$argument"
             ;;
     esac
 }
 
-compdef _wt wt
 "#;
 
 /// Bash shell integration
@@ -421,77 +506,45 @@ __wt_edit() {
 }
 
 wt() {
-    if [[ $# -eq 0 ]] || [[ "$1" == "interactive" ]]; then
-        local output
-        output=$(command wt "$@" 2>&1)
-        local exit_code=$?
-        
-        if [[ $exit_code -ne 0 ]]; then
-            echo "$output" >&2
-            return $exit_code
-        fi
-        
-        case "$output" in
-            cd\|*)
-                __wt_cd "${output#cd|}"
-                ;;
-            edit\|*)
-                __wt_edit "${output#edit|}"
-                ;;
-            *)
-                [[ -n "$output" ]] && echo "$output"
-                ;;
-        esac
-    else
-        command wt "$@"
+    # wt can't change this shell's directory, so the picker, `wt switch`,
+    # `wt add --cd`, `wt last`, `wt main` and `wt shift` hand back an action (cd, edit or run) through a file:
+    # NUL-terminated protocol version, action and argument
+    local action_file
+    action_file=$(mktemp "${TMPDIR:-/tmp}/wt-action.XXXXXX") || { command wt "$@"; return; }
+
+    WT_SHELL_PROTOCOL=2 WT_ACTION_FILE="$action_file" command wt "$@"
+    local exit_code=$?
+
+    local version= action= argument=
+    {
+        IFS= read -r -d '' version
+        IFS= read -r -d '' action
+        IFS= read -r -d '' argument
+    } < "$action_file"
+    command rm -f -- "$action_file"
+
+    if [[ $exit_code -ne 0 ]]; then
+        return $exit_code
+    fi
+    [[ -z "$version" ]] && return 0
+    if [[ "$version" != 2 ]]; then
+        echo "wt: unsupported shell action protocol: $version (re-run wt init)" >&2
+        return 1
     fi
-}
 
-# Completions
-_wt_completions() {
-    local cur prev commands config_commands shells
-    COMPREPLY=()
-    cur="${COMP_WORDS[COMP_CWORD]}"
-    prev="${COMP_WORDS[COMP_CWORD-1]}"
-    
-    commands="init interactive list add remove prune preview config help"
-    config_commands="init show set-editor set-discovery-paths"
-    shells="bash zsh fish"
-
-    case "${COMP_CWORD}" in
-        1)
-            COMPREPLY=( $(compgen -W "${commands}" -- "${cur}") )
+    case "$action" in
+        cd)
+            __wt_cd "$argument"
+            ;;
+        edit)
+            __wt_edit "$argument"
             ;;
-        2)
-            case "${prev}" in
-                init)
-                    COMPREPLY=( $(compgen -W "${shells}" -- "${cur}") )
-                    ;;
-                config)
-                    COMPREPLY=( $(compgen -W "${config_commands}" -- "${cur}") )
-                    ;;
-                add)
-                    local branches
-                    branches=$(git branch --format='%(refname:short)' 2>/dev/null)
-                    COMPREPLY=( $(compgen -W "${branches}" -- "${cur}") )
-                    ;;
-                remove)
-                    local worktrees
-                    worktrees=$(git worktree list --porcelain 2>/dev/null | grep '^branch' | sed 's/branch refs\/heads\///')
-                    COMPREPLY=( $(compgen -W "${worktrees}" -- "${cur}") )
-                    ;;
-                list)
-                    COMPREPLY=( $(compgen -W "--json --all" -- "${cur}") )
-                    ;;
-                interactive)
-                    COMPREPLY=( $(compgen -W "--all" -- "${cur}") )
-                    ;;
-            esac
+        run)
+            eval "$argument"
             ;;
     esac
 }
 
-complete -F _wt_completions wt
 "#;
 
 /// Fish shell integration
@@ -535,59 +588,105 @@ function __wt_edit
 end
 
 function wt
-    if test (count $argv) -eq 0; or test "$argv[1]" = "interactive"
-        set -l output (command wt $argv 2>&1)
-        set -l exit_code $status
-        
-        if test $exit_code -ne 0
-            echo "$output" >&2
-            return $exit_code
-        end
-        
-        switch "$output"
-            case 'cd|*'
-                set -l path (string replace 'cd|' '' "$output")
-                __wt_cd "$path"
-            case 'edit|*'
-                set -l path (string replace 'edit|' '' "$output")
-                __wt_edit "$path"
-            case '*'
-                if test -n "$output"
-                    echo "$output"
-                end
-        end
-    else
+    # wt can't change this shell's directory, so the picker, `wt switch`,
+    # `wt add --cd`, `wt last`, `wt main` and `wt shift` hand back an action (cd, edit or run) through a file:
+    # NUL-terminated protocol version, action and argument
+    set -l tmpdir /tmp
+    set -q TMPDIR; and set tmpdir $TMPDIR
+    set -l action_file (mktemp "$tmpdir/wt-action.XXXXXX")
+    or begin
         command wt $argv
+        return
     end
-end
 
-# Completions
-complete -c wt -e
-complete -c wt -n "__fish_use_subcommand" -a "init" -d "Set up shell integration"
-complete -c wt -n "__fish_use_subcommand" -a "interactive" -d "Interactive picker (fzf)"
-complete -c wt -n "__fish_use_subcommand" -a "list" -d "List worktrees"
-complete -c wt -n "__fish_use_subcommand" -a "add" -d "Add a new worktree"
-complete -c wt -n "__fish_use_subcommand" -a "remove" -d "Remove a worktree"
-complete -c wt -n "__fish_use_subcommand" -a "prune" -d "Prune stale worktrees"
-complete -c wt -n "__fish_use_subcommand" -a "preview" -d "Print preview information"
-complete -c wt -n "__fish_use_subcommand" -a "config" -d "Configuration management"
-complete -c wt -n "__fish_use_subcommand" -a "help" -d "Print help"
+    WT_SHELL_PROTOCOL=2 WT_ACTION_FILE=$action_file command wt $argv
+    set -l exit_code $status
 
-complete -c wt -n "__fish_seen_subcommand_from init" -a "bash zsh fish" -d "Shell"
+    set -l fields (string split0 < $action_file)
+    command rm -f -- $action_file
 
-complete -c wt -n "__fish_seen_subcommand_from config" -a "init" -d "Create initial config file"
-complete -c wt -n "__fish_seen_subcommand_from config" -a "show" -d "Show effective config"
-complete -c wt -n "__fish_seen_subcommand_from config" -a "set-editor" -d "Set default editor"
-complete -c wt -n "__fish_seen_subcommand_from config" -a "set-discovery-paths" -d "Set discovery search roots"
+    if test $exit_code -ne 0
+        return $exit_code
+    end
+    test (count $fields) -eq 0; and return 0
+    if test "$fields[1]" != 2; or test (count $fields) -lt 3
+        echo "wt: unsupported shell action protocol: $fields[1] (re-run wt init)" >&2
+        return 1
+    end
 
-complete -c wt -n "__fish_seen_subcommand_from list" -l json -d "JSON output"
-complete -c wt -n "__fish_seen_subcommand_from list" -l all -d "List across all repos"
+    switch $fields[2]
+        case cd
+            __wt_cd $fields[3]
+        case edit
+            __wt_edit $fields[3]
+        case run
+            eval $fields[3]
+    end
+end
+
+"#;
+
+// The dynamic completion layers below use `{cmd}` for the shell function name.
+// `wt __complete` prints `value<TAB>description` lines, or nothing when the
+// generated completions should handle the word.
+
+const ZSH_DYNAMIC_COMPLETIONS: &str = r#"
+_{cmd}_dynamic() {
+    local -a candidates described
+    candidates=("${(@f)$(command wt __complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    candidates=(${candidates:#})
+    if (( ! ${#candidates} )); then
+        _{cmd} "$@"
+        return
+    fi
+    local candidate value
+    for candidate in "${candidates[@]}"; do
+        value="${candidate%%$'\t'*}"
+        if [[ "$candidate" == *$'\t'* ]]; then
+            described+=("${value//:/\\:}:${candidate#*$'\t'}")
+        else
+            described+=("${value//:/\\:}")
+        fi
+    done
+    _describe -t values 'value' described
+}
 
-complete -c wt -n "__fish_seen_subcommand_from interactive" -l all -d "Pick from all repos"
+compdef _{cmd}_dynamic {cmd}
+if [ "$funcstack[1]" = "_{cmd}" ]; then
+    # Autoloaded from a `_{cmd}` file in $fpath: complete the word at hand too
+    _{cmd}_dynamic "$@"
+fi
+"#;
 
-complete -c wt -n "__fish_seen_subcommand_from add" -a "(git branch --format='%(refname:short)' 2>/dev/null)"
+const BASH_DYNAMIC_COMPLETIONS: &str = r#"
+_{cmd}_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" candidates candidate
+    candidates="$(command wt __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null)"
+    if [[ -z "$candidates" ]]; then
+        _{cmd} "$@"
+        return
+    fi
+    # `--open=NAME` is split at `=`, so only NAME is being completed
+    [[ "$cur" == "=" ]] && cur=""
+    COMPREPLY=()
+    while IFS= read -r candidate; do
+        candidate="${candidate%%$'\t'*}"
+        candidate="${candidate#--*=}"
+        [[ "$candidate" == "$cur"* ]] && COMPREPLY+=("$candidate")
+    done <<< "$candidates"
+}
+complete -F _{cmd}_dynamic -o bashdefault -o default {cmd}
+"#;
 
-complete -c wt -n "__fish_seen_subcommand_from remove" -a "(git worktree list --porcelain 2>/dev/null | string match 'branch *' | string replace 'branch refs/heads/' '')"
+const FISH_DYNAMIC_COMPLETIONS: &str = r#"
+function __{cmd}_dynamic
+    set -l words (commandline -opc) (commandline -ct)
+    set -e words[1]
+    set -l candidates (command wt __complete -- $words 2>/dev/null)
+    test -n "$candidates"; or return 1
+    string join \n -- $candidates
+end
+complete -c {cmd} -f -n '__{cmd}_dynamic >/dev/null' -a '(__{cmd}_dynamic)'
 "#;
 
 #[cfg(test)]
@@ -596,7 +695,7 @@ mod tests {
 
     #[test]
     fn test_zsh_init_contains_wt_function() {
-        let output = shell_init(Shell::Zsh);
+        let output = shell_init(Shell::Zsh, "wt");
         assert!(output.contains("wt()"));
         assert!(output.contains("__wt_cd"));
         assert!(output.contains("__wt_edit"));
@@ -604,7 +703,7 @@ mod tests {
 
     #[test]
     fn test_bash_init_contains_wt_function() {
-        let output = shell_init(Shell::Bash);
+        let output = shell_init(Shell::Bash, "wt");
         assert!(output.contains("wt()"));
         assert!(output.contains("__wt_cd"));
         assert!(output.contains("__wt_edit"));
@@ -612,28 +711,131 @@ mod tests {
 
     #[test]
     fn test_fish_init_contains_wt_function() {
-        let output = shell_init(Shell::Fish);
+        let output = shell_init(Shell::Fish, "wt");
         assert!(output.contains("function wt"));
         assert!(output.contains("function __wt_cd"));
         assert!(output.contains("function __wt_edit"));
     }
 
+    #[test]
+    fn test_completions_follow_cli_definition() {
+        // Nested subcommands and their flags come from the clap definition
+        assert!(shell_init(Shell::Zsh, "wt").contains("'--max-tokens=["));
+        assert!(shell_init(Shell::Bash, "wt").contains("wt__subcmd__agent,context)"));
+        assert!(shell_init(Shell::Fish, "wt").contains("-l max-tokens"));
+    }
+
+    #[test]
+    fn test_standalone_completions() {
+        // Installable as `_wt` in $fpath: no wrapper function, `#compdef` first,
+        // and only the dynamic layer registers itself
+        let zsh = completions(Shell::Zsh, "wt");
+        assert!(zsh.starts_with("#compdef wt\n"));
+        assert!(!zsh.contains("\nwt() {") && !zsh.contains("WT_ACTION_FILE"));
+        assert!(!zsh.contains("compdef _wt wt"));
+        assert_eq!(zsh.matches("#compdef").count(), 1);
+
+        let fish = completions(Shell::Fish, "wt");
+        assert!(!fish.contains("function wt\n"));
+        assert!(fish.contains("complete -c wt -e\n"));
+
+        assert_eq!(completion_file_name(Shell::Zsh, "w"), "_w");
+        assert_eq!(completion_file_name(Shell::Bash, "w"), "w");
+        assert_eq!(completion_file_name(Shell::Fish, "w"), "w.fish");
+    }
+
+    #[test]
+    fn test_dynamic_completions_wrap_generated_ones() {
+        let zsh = shell_init(Shell::Zsh, "w");
+        assert!(zsh.contains("command wt __complete -- "));
+        assert!(zsh.contains("        _w \"$@\"\n"));
+        assert!(zsh.contains("\ncompdef _w_dynamic w\n"));
+
+        let bash = shell_init(Shell::Bash, "w");
+        assert!(bash.contains("command wt __complete -- "));
+        assert!(bash.ends_with("complete -F _w_dynamic -o bashdefault -o default w\n"));
+
+        let fish = shell_init(Shell::Fish, "w");
+        assert!(fish.contains("command wt __complete -- $words"));
+        assert!(
+            fish.ends_with("complete -c w -f -n '__w_dynamic >/dev/null' -a '(__w_dynamic)'\n")
+        );
+    }
+
+    #[test]
+    fn test_wrappers_leave_stderr_on_terminal() {
+        for shell in [Shell::Zsh, Shell::Bash, Shell::Fish] {
+            assert!(!shell_init(shell, "wt").contains("2>&1"));
+        }
+    }
+
+    #[test]
+    fn test_wrappers_negotiate_action_protocol() {
+        let protocol = format!("WT_SHELL_PROTOCOL={}", crate::action::PROTOCOL_VERSION);
+        for shell in [Shell::Zsh, Shell::Bash, Shell::Fish] {
+            let output = shell_init(shell, "wt");
+            assert!(output.contains(&protocol));
+            assert!(output.contains("WT_ACTION_FILE="));
+        }
+    }
+
+    #[test]
+    fn test_custom_command_name() {
+        let zsh = shell_init(Shell::Zsh, "w");
+        assert!(zsh.contains("\nw() {") && !zsh.contains("\nwt() {"));
+        assert!(zsh.contains("compdef _w_dynamic w\n"));
+        assert!(zsh.contains("command wt \"$@\""));
+
+        let bash = shell_init(Shell::Bash, "w");
+        assert!(bash.contains("\nw() {"));
+        assert!(bash.contains("complete -F _w "));
+
+        let fish = shell_init(Shell::Fish, "w");
+        assert!(fish.contains("\nfunction w\n") && !fish.contains("function wt\n"));
+        assert!(!fish.contains("complete -c wt "));
+        assert!(fish.contains("command wt $argv"));
+
+        assert_eq!(
+            integration_line_for_shell(Shell::Fish, "w"),
+            "wt init fish --cmd w | source"
+        );
+        assert!(command_name(Some("wk")).is_ok());
+        assert!(command_name(Some("-w")).is_err());
+        assert!(command_name(Some("w t")).is_err());
+    }
+
     #[test]
     fn test_integration_line_for_shell() {
         assert_eq!(
-            integration_line_for_shell(Shell::Zsh),
+            integration_line_for_shell(Shell::Zsh, "wt"),
             "eval \"$(wt init zsh)\""
         );
         assert_eq!(
-            integration_line_for_shell(Shell::Bash),
+            integration_line_for_shell(Shell::Bash, "wt"),
             "eval \"$(wt init bash)\""
         );
         assert_eq!(
-            integration_line_for_shell(Shell::Fish),
+            integration_line_for_shell(Shell::Fish, "wt"),
             "wt init fish | source"
         );
     }
 
+    #[test]
+    fn test_shell_from() {
+        assert!(matches!(
+            shell_from(Some("/bin/zsh"), false),
+            Ok(Shell::Zsh)
+        ));
+        assert!(matches!(
+            shell_from(Some("/usr/local/bin/fish"), true),
+            Ok(Shell::Fish)
+        ));
+        // Git Bash and MSYS2 without $SHELL
+        assert!(matches!(shell_from(None, true), Ok(Shell::Bash)));
+        assert!(shell_from(None, false).is_err());
+        assert!(shell_from(Some("/bin/tcsh"), false).is_err());
+    }
+
     #[test]
     fn test_shell_name() {
         assert_eq!(shell_name(Shell::Zsh), "zsh");
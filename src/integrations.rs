@@ -0,0 +1,67 @@
+//! Optional integrations with other tools, enabled in the config's
+//! `integrations` section. They are best effort: failures are warnings.
+
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::{config, process};
+
+/// Run `direnv allow` for the worktree at `path` if the direnv integration is
+/// on and the worktree has an `.envrc`, so its environment loads on the first
+/// `cd`. Returns whether the `.envrc` was allowed.
+pub fn direnv_allow(path: &Path) -> bool {
+    let enabled = config::load()
+        .map(|c| c.integrations.direnv)
+        .unwrap_or_default();
+    if !enabled || !path.join(".envrc").is_file() {
+        return false;
+    }
+    let Some(path_str) = path.to_str() else {
+        return false;
+    };
+    match process::run("direnv", &["allow", path_str], Some(path)) {
+        Ok(_) => true,
+        Err(e) => {
+            if is_not_installed(&e) {
+                eprintln!("Warning: integrations.direnv is on but direnv isn't installed");
+            } else {
+                eprintln!(
+                    "Warning: direnv allow failed: {}",
+                    process::error_summary(&format!("{:#}", e))
+                );
+            }
+            false
+        }
+    }
+}
+
+/// Tell zoxide about the worktree at `path` if the zoxide integration is on,
+/// so `z <name>` finds it right away. Does nothing if zoxide isn't installed.
+pub fn zoxide_add(path: &Path) {
+    let enabled = config::load()
+        .map(|c| c.integrations.zoxide)
+        .unwrap_or_default();
+    if !enabled {
+        return;
+    }
+    let Some(path_str) = path.to_str() else {
+        return;
+    };
+    if let Err(e) = process::run("zoxide", &["add", path_str], None)
+        && !is_not_installed(&e)
+    {
+        eprintln!(
+            "Warning: zoxide add failed: {}",
+            process::error_summary(&format!("{:#}", e))
+        );
+    }
+}
+
+/// Whether `error` (from [`process::run`]) means the program isn't on PATH.
+pub fn is_not_installed(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == ErrorKind::NotFound)
+    })
+}
@@ -1,136 +1,686 @@
-use std::io::Write;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::thread;
 
 use anyhow::Result;
 
+use crate::action::{self, ShellAction};
+use crate::color::{self, Colors};
 use crate::error::WtError;
-use crate::{config, git};
+use crate::keymap::{self, Action, Keymap};
+use crate::state::{self, State};
+use crate::worktree::Worktree;
+use crate::{config, git, hooks, integrations, process};
 
 /// Run the interactive worktree picker.
-/// Outputs action in format "cd|PATH" or "edit|PATH" for shell wrapper to parse.
+/// Hands the chosen action (cd, edit or run) to the shell wrapper (see
+/// [`crate::action`]). Keys are bound to actions by the `keybindings` config section.
 ///
 /// # Arguments
 ///
 /// * `all` - If true, show worktrees from all discovered repositories
-pub fn run_interactive(all: bool) -> Result<()> {
+/// * `by_repo` - If true, pick a discovered repository first, then a worktree in it
+/// * `repo` - Glob limiting `all` and `by_repo` to matching repositories
+/// * `no_color` - If true, disable colors in candidates and previews
+/// * `query` - Initial fzf query; a single match is accepted without showing the picker
+/// * `refresh` - If true, rediscover repositories instead of using the cached list
+/// * `recent` - If true, pick from recently visited worktrees across all repositories
+///
+/// Without a terminal (scripts, agents) the picker can't be used, so this prints
+/// `wt list` output instead.
+pub fn run_interactive(
+    all: bool,
+    by_repo: bool,
+    repo: Option<&str>,
+    no_color: bool,
+    query: Option<&str>,
+    refresh: bool,
+    recent: bool,
+) -> Result<()> {
+    if repo.is_some() && !all && !by_repo {
+        return Err(WtError::user_error("--repo needs --all or --by-repo").into());
+    }
+    if !is_interactive_terminal() {
+        eprintln!("Not running in a terminal; listing worktrees instead of opening the picker.");
+        if recent {
+            return crate::recent::list_recent(crate::recent::DEFAULT_LIMIT, false);
+        }
+        return crate::list::list_worktrees(false, all || by_repo, repo, false, false);
+    }
+
     // Load config for fzf settings
     let config = config::load()
         .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
 
-    if all {
-        run_interactive_all(&config)
+    if recent {
+        run_interactive_recent(&config, no_color, query)
+    } else if by_repo {
+        let repos = crate::discovery::discover_configured_repos(&config, refresh, repo)?;
+        run_interactive_by_repo(&config, &repos, no_color, query)
+    } else if all {
+        let repos = crate::discovery::discover_configured_repos(&config, refresh, repo)?;
+        run_interactive_all(&config, &repos, no_color, query)
     } else {
-        run_interactive_single(&config)
+        run_interactive_single(&config, no_color, query)
     }
 }
 
+/// Whether a user can drive fzf: it reads keys from the terminal and draws on it.
+/// Older shell wrappers capture stdout, so only stdin and stderr are checked.
+fn is_interactive_terminal() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
 /// Run interactive picker for a single repository (current directory).
-fn run_interactive_single(config: &crate::config::Config) -> Result<()> {
-    // Get repository root and worktrees
+fn run_interactive_single(
+    config: &crate::config::Config,
+    no_color: bool,
+    query: Option<&str>,
+) -> Result<()> {
     let repo_root = git::repo_root(None)?;
-    let worktrees = git::worktrees_porcelain(&repo_root)?;
-
-    if worktrees.is_empty() {
-        return Err(WtError::not_found("no worktrees found in repository").into());
+    if pick_in_repo(config, &repo_root, no_color, query, false)? {
+        Ok(())
+    } else {
+        Err(WtError::cancelled().into())
     }
+}
+
+/// Run the worktree picker for the repository at `repo_root`.
+/// Returns `true` once an action finished, or `false` if the user cancelled.
+/// With `nested`, Backspace on an empty query also cancels (to go back a level).
+fn pick_in_repo(
+    config: &crate::config::Config,
+    repo_root: &Path,
+    no_color: bool,
+    mut query: Option<&str>,
+    nested: bool,
+) -> Result<bool> {
+    let colors = Colors::new(color::enabled(no_color));
+    let keymap = Keymap::from_config(&config.keybindings, &config.actions);
+
+    // Reopen the picker with a fresh list after actions like remove and lock
+    loop {
+        let mut worktrees = git::worktrees_porcelain(repo_root)?;
+
+        if worktrees.is_empty() {
+            return Err(WtError::not_found("no worktrees found in repository").into());
+        }
+
+        let state = state::load();
+        if config.interactive.frecency {
+            state.sort_by_frecency(&mut worktrees, |wt| wt.path.as_path());
+        }
 
-    // Prepare candidates for fzf
-    // Format: "<branch>  <path>" with aligned columns
-    let candidates = prepare_candidates(&worktrees);
+        // Prepare candidates for fzf
+        // Format: "<branch>  <path>  <markers> <note>" with aligned columns
+        let paths: Vec<&Path> = worktrees.iter().map(|wt| wt.path.as_path()).collect();
+        let markers = with_annotations(collect_markers(&paths, colors), &paths, &state);
+        let candidates = prepare_candidates(&worktrees, &markers, colors);
 
-    // Run fzf with --expect to capture which key was pressed
-    let selection = run_fzf_with_expect(&candidates, &config.fzf, false)?;
+        // Run fzf with --expect to capture which key was pressed
+        // Only the first run uses the query, so a reopened picker isn't auto-accepted
+        let selection = run_fzf_with_expect(
+            &candidates,
+            &config.fzf,
+            &keymap,
+            false,
+            nested,
+            no_color,
+            query.take(),
+        )?;
 
-    // Handle the selection
-    match selection {
-        Some((key, line)) => {
+        // Handle the selection
+        let Some((key, line)) = selection else {
+            // User cancelled; the caller decides whether that ends the run
+            return Ok(false);
+        };
+        let action = keymap.action_for(&key);
+        let done = if action == Action::New {
+            create_from_picker(repo_root)?
+        } else {
             // Extract path from the selected line (second column)
             let path = extract_path(&line)?;
+            run_action(&action, repo_root, &path)?
+        };
+        if done {
+            return Ok(true);
+        }
+    }
+}
 
-            // Output action based on which key was pressed
-            if key == "ctrl-e" {
-                println!("edit|{}", path);
-            } else {
-                // Enter key or empty means cd action
-                println!("cd|{}", path);
+/// Run a picker action on the worktree at `path`.
+/// Hands the shell wrapper its action (see [`crate::action`])
+/// and returns `true`, or returns `false` if the picker should reopen.
+/// Opening the worktree counts as a visit in the state file.
+fn run_action(action: &Action, repo_root: &Path, path: &str) -> Result<bool> {
+    if matches!(
+        action,
+        Action::Cd | Action::Edit(_) | Action::Multiplexer | Action::Command(_)
+    ) {
+        state::update_or_warn(|s| s.record_visit(Path::new(path), state::now()));
+        integrations::zoxide_add(Path::new(path));
+    }
+    match action {
+        Action::Cd => {
+            hooks::post_switch(Path::new(path))?;
+            action::emit(ShellAction::Cd(PathBuf::from(path)))?
+        }
+        Action::Edit(name) => action::emit(crate::editor::picker_action(
+            Path::new(path),
+            name.as_deref(),
+        )?)?,
+        Action::Command(template) => {
+            action::emit(ShellAction::Run(keymap::render_command(template, path)))?
+        }
+        Action::New => return create_from_picker(repo_root),
+        Action::Multiplexer => open_in_multiplexer(Path::new(path))?,
+        Action::Remove => {
+            // Failures are reported without leaving the picker
+            match crate::remove::remove_worktree_in(repo_root, path, false, false, false) {
+                Err(e)
+                    if e.downcast_ref::<WtError>()
+                        .is_some_and(WtError::is_cancelled) =>
+                {
+                    eprintln!("{}", e)
+                }
+                Err(e) => eprintln!("Error: {:#}", e),
+                Ok(()) => {}
+            }
+            return Ok(false);
+        }
+        Action::Lock => {
+            if let Err(e) = toggle_lock(repo_root, path) {
+                eprintln!("Error: {:#}", e);
             }
-            Ok(())
+            return Ok(false);
         }
-        None => {
-            // User cancelled - exit cleanly without output
-            Ok(())
+        Action::Exec(command) => {
+            if let Err(e) = run_in_worktree(command, path) {
+                eprintln!("Error: {:#}", e);
+            }
+            return Ok(false);
+        }
+        Action::Open(name) => {
+            if let Err(e) = crate::launcher::launch(Path::new(path), name) {
+                eprintln!("Error: {:#}", e);
+            }
+            return Ok(false);
         }
     }
+    Ok(true)
 }
 
-/// Run interactive picker across all discovered repositories.
-fn run_interactive_all(config: &crate::config::Config) -> Result<()> {
-    // Check that discovery paths are configured
-    if config.auto_discovery.paths.is_empty() {
-        return Err(WtError::user_error(
-            "No auto-discovery paths configured. Run: wt config set-discovery-paths <paths...>",
+/// Open the worktree at `path` in the multiplexer chosen by the
+/// `multiplexer` config setting.
+fn open_in_multiplexer(path: &Path) -> Result<()> {
+    let config = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
+    match config.multiplexer.as_str() {
+        "tmux" => crate::tmux::open(path),
+        "zellij" => crate::zellij::open(path),
+        "none" => Err(WtError::user_error(
+            "no terminal multiplexer configured; set `multiplexer` to tmux or zellij",
         )
-        .into());
+        .into()),
+        other => Err(WtError::config_error(format!(
+            "unknown multiplexer '{}' (expected tmux, zellij or none)",
+            other
+        ))
+        .into()),
     }
+}
 
-    // Discover all repos
-    let repos = crate::discovery::discover_repos(&config.auto_discovery.paths)?;
-    if repos.is_empty() {
-        return Err(
-            WtError::not_found("No git repositories found in configured discovery paths.").into(),
-        );
-    }
+/// Create a worktree from the picker and print the `cd` action for it.
+/// Returns `false` if the user cancelled branch selection.
+fn create_from_picker(repo_root: &Path) -> Result<bool> {
+    let Some(picked) = crate::add::pick_branch(repo_root, None)? else {
+        return Ok(false);
+    };
+    let path = crate::add::add_worktree_in(
+        repo_root,
+        &picked.name,
+        None,
+        picked.remote.as_deref(),
+        false,
+        false,
+        false,
+    )?;
+    hooks::post_switch(&path)?;
+    action::emit(ShellAction::Cd(path))?;
+    Ok(true)
+}
+
+/// Run a custom action's command with `path` as working directory and report
+/// its exit status. Output goes to stderr so the shell wrapper doesn't capture it.
+fn run_in_worktree(command: &str, path: &str) -> Result<()> {
+    let command = command.replace("{path}", &process::shell_quote(path));
+    eprintln!("$ {}", command);
+    let status = process::shell(&command)
+        .current_dir(path)
+        .stdin(Stdio::inherit())
+        .stdout(io::stderr())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| WtError::io_error_with_source("failed to run command", e.into()))?;
 
-    // Collect worktrees from all repos
-    let candidates = prepare_all_candidates(&repos)?;
+    match status.code() {
+        Some(0) => eprintln!("✓ '{}' succeeded", command),
+        Some(code) => eprintln!("✗ '{}' exited with status {}", command, code),
+        None => eprintln!("✗ '{}' was terminated by a signal", command),
+    }
+    Ok(())
+}
 
+/// [`find_worktree`](crate::worktree::find_worktree), but when `target`
+/// matches several worktrees, or none while some come close, ask which one
+/// was meant if stdin and stderr are a terminal. Otherwise (and when the
+/// question is skipped) the error lists the candidates.
+pub fn find_or_pick<'a>(worktrees: &'a [Worktree], target: &str) -> Result<&'a Worktree> {
+    let error = match crate::worktree::find_worktree(worktrees, target) {
+        Ok(wt) => return Ok(wt),
+        Err(error) => error,
+    };
+    let candidates: Vec<&Worktree> = match error.downcast_ref::<WtError>() {
+        Some(wt_error) if io::stdin().is_terminal() && io::stderr().is_terminal() => wt_error
+            .candidates()
+            .iter()
+            .filter_map(|path| {
+                worktrees
+                    .iter()
+                    .find(|wt| wt.path.display().to_string() == *path)
+            })
+            .collect(),
+        _ => return Err(error),
+    };
     if candidates.is_empty() {
-        return Err(WtError::not_found("No worktrees found in any discovered repository").into());
+        return Err(error);
     }
 
-    // Run fzf with --expect to capture which key was pressed
-    let selection = run_fzf_with_expect(&candidates, &config.fzf, true)?;
+    eprintln!("{}", error);
+    for (i, wt) in candidates.iter().enumerate() {
+        eprintln!(
+            "  {}) {}  {}",
+            i + 1,
+            crate::worktree::display_name(wt),
+            wt.path.display()
+        );
+    }
+    eprint!("Pick one [1-{}, Enter to cancel]: ", candidates.len());
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    match answer.trim().parse::<usize>() {
+        Ok(n) if (1..=candidates.len()).contains(&n) => Ok(candidates[n - 1]),
+        _ => Err(error),
+    }
+}
 
-    // Handle the selection
-    match selection {
-        Some((key, line)) => {
+/// Lock the worktree at `path`, or unlock it if it's already locked.
+fn toggle_lock(repo_root: &Path, path: &str) -> Result<()> {
+    let worktrees = git::worktrees_porcelain(repo_root)?;
+    let worktree = crate::worktree::find_worktree(&worktrees, path)?;
+    let (verb, done) = if worktree.locked {
+        ("unlock", "Unlocked")
+    } else {
+        ("lock", "Locked")
+    };
+    process::run("git", &["worktree", verb, path], Some(repo_root))
+        .map_err(|e| WtError::git_error_with_source(format!("failed to {} worktree", verb), e))?;
+    eprintln!("{} {}", done, path);
+    Ok(())
+}
+
+/// Run interactive picker across all discovered repositories.
+fn run_interactive_all(
+    config: &crate::config::Config,
+    repos: &[PathBuf],
+    no_color: bool,
+    query: Option<&str>,
+) -> Result<()> {
+    pick_across_repos(config, no_color, query, || {
+        let state = config.interactive.frecency.then(state::load);
+        let worktrees = collect_all_worktrees(repos, state.as_ref());
+        if worktrees.is_empty() {
+            return Err(
+                WtError::not_found("No worktrees found in any discovered repository").into(),
+            );
+        }
+        Ok(worktrees)
+    })
+}
+
+/// Run the picker over the most recently visited worktrees, most recent first.
+fn run_interactive_recent(
+    config: &crate::config::Config,
+    no_color: bool,
+    query: Option<&str>,
+) -> Result<()> {
+    pick_across_repos(config, no_color, query, || {
+        let worktrees: Vec<_> = crate::recent::recent_worktrees(crate::recent::DEFAULT_LIMIT)
+            .into_iter()
+            .map(|recent| (recent.repo, recent.worktree))
+            .collect();
+        if worktrees.is_empty() {
+            return Err(WtError::not_found(
+                "No recently visited worktrees; open one with `wt` first",
+            )
+            .into());
+        }
+        Ok(worktrees)
+    })
+}
+
+/// Run the 3-column (repo, branch, path) picker over the worktrees `gather`
+/// returns, gathering them again each time the picker reopens.
+fn pick_across_repos(
+    config: &crate::config::Config,
+    no_color: bool,
+    mut query: Option<&str>,
+    gather: impl Fn() -> Result<Vec<(String, crate::worktree::Worktree)>>,
+) -> Result<()> {
+    let colors = Colors::new(color::enabled(no_color));
+    let keymap = Keymap::from_config(&config.keybindings, &config.actions);
+
+    // Reopen the picker with a fresh list after actions like remove and lock
+    loop {
+        let candidates = prepare_all_candidates(&gather()?, &state::load(), colors);
+
+        // Run fzf with --expect to capture which key was pressed
+        // Only the first run uses the query, so a reopened picker isn't auto-accepted
+        let selection = run_fzf_with_expect(
+            &candidates,
+            &config.fzf,
+            &keymap,
+            true,
+            false,
+            no_color,
+            query.take(),
+        )?;
+
+        // Handle the selection
+        let Some((key, line)) = selection else {
+            return Err(WtError::cancelled().into());
+        };
+        let action = keymap.action_for(&key);
+        let done = if action == Action::New {
+            // Create the worktree in the highlighted row's repository, or the
+            // current one if nothing matched
+            let repo_root = match extract_path_from_all(&line) {
+                Ok(path) => git::repo_root(Some(Path::new(&path)))?,
+                Err(_) => git::repo_root(None)?,
+            };
+            create_from_picker(&repo_root)?
+        } else {
             // Extract path from the selected line (third column for --all mode)
             let path = extract_path_from_all(&line)?;
+            // Any worktree of a repository can drive `git worktree` commands
+            match git::repo_root(Some(Path::new(&path))) {
+                Ok(repo_root) => run_action(&action, &repo_root, &path)?,
+                Err(e) => {
+                    eprintln!("Error: {:#}", e);
+                    false
+                }
+            }
+        };
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Run the two-stage picker: choose a repository, then a worktree within it.
+/// Cancelling the worktree picker returns to the repository list.
+fn run_interactive_by_repo(
+    config: &crate::config::Config,
+    repos: &[PathBuf],
+    no_color: bool,
+    mut query: Option<&str>,
+) -> Result<()> {
+    let colors = Colors::new(color::enabled(no_color));
+
+    loop {
+        let candidates = prepare_repo_candidates(repos, colors);
+
+        // Only the first run uses the query, so returning here isn't auto-accepted
+        let Some(line) = run_fzf_repo_picker(&candidates, &config.fzf, query.take())? else {
+            return Err(WtError::cancelled().into());
+        };
+        let repo_root = extract_repo_path(&line)?;
+
+        if pick_in_repo(config, Path::new(&repo_root), no_color, None, true)? {
+            return Ok(());
+        }
+    }
+}
+
+/// Prepare repository candidates: "<repo>  <N worktrees>  <path>" with aligned columns.
+fn prepare_repo_candidates(repos: &[PathBuf], colors: Colors) -> Vec<String> {
+    let rows: Vec<(String, String, String)> = repos
+        .iter()
+        .map(|repo_root| {
+            let name = repo_root
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("(unknown)")
+                .to_string();
+            let count = match git::worktrees_porcelain(repo_root) {
+                Ok(worktrees) if worktrees.len() == 1 => "1 worktree".to_string(),
+                Ok(worktrees) => format!("{} worktrees", worktrees.len()),
+                Err(_) => "? worktrees".to_string(),
+            };
+            (name, count, repo_root.display().to_string())
+        })
+        .collect();
+
+    let max_name_len = rows
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    let max_count_len = rows
+        .iter()
+        .map(|(_, count, _)| count.len())
+        .max()
+        .unwrap_or(0);
+
+    rows.iter()
+        .map(|(name, count, path)| {
+            let name = format!("{:width$}", name, width = max_name_len);
+            format!(
+                "{}  {:count_width$}  {}",
+                colors.green(&name),
+                count,
+                path,
+                count_width = max_count_len
+            )
+        })
+        .collect()
+}
+
+/// Extract the repository path from a repository candidate line (last column).
+fn extract_repo_path(line: &str) -> Result<String> {
+    let parts: Vec<&str> = line
+        .split("  ")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if parts.len() >= 3 {
+        Ok(parts[parts.len() - 1].to_string())
+    } else {
+        Err(WtError::user_error(format!("failed to extract path from fzf output: {}", line)).into())
+    }
+}
+
+/// Run fzf to let the user pick a repository, previewing its worktrees.
+fn run_fzf_repo_picker(
+    candidates: &[String],
+    fzf_config: &config::FzfConfig,
+    query: Option<&str>,
+) -> Result<Option<String>> {
+    let mut args = vec![
+        "--height".to_string(),
+        fzf_config.height.clone(),
+        "--layout".to_string(),
+        fzf_config.layout.clone(),
+        "--preview-window".to_string(),
+        fzf_config.preview_window.clone(),
+        "--preview".to_string(),
+        "git -C {-1} worktree list".to_string(),
+        "--ansi".to_string(),
+        "--prompt".to_string(),
+        "Repository> ".to_string(),
+        "--header".to_string(),
+        "Enter: pick worktree | Esc: cancel".to_string(),
+    ];
+    if let Some(query) = query {
+        args.push("--query".to_string());
+        args.push(query.to_string());
+        args.push("--select-1".to_string()); // Auto-accept a single match
+    }
+
+    let mut child = Command::new("fzf")
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| {
+            WtError::user_error_with_source("failed to spawn fzf (is it installed?)", e)
+        })?;
+
+    {
+        let stdin = child
+            .stdin
+            .as_mut()
+            .ok_or_else(|| WtError::io_error("failed to open fzf stdin"))?;
+
+        for candidate in candidates {
+            writeln!(stdin, "{}", candidate).map_err(|e| {
+                WtError::io_error_with_source("failed to write to fzf stdin", e.into())
+            })?;
+        }
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| WtError::io_error_with_source("failed to wait for fzf", e.into()))?;
 
-            // Output action based on which key was pressed
-            if key == "ctrl-e" {
-                println!("edit|{}", path);
+    match output.status.code() {
+        Some(0) => {
+            let selection = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if selection.is_empty() {
+                Ok(None)
             } else {
-                // Enter key or empty means cd action
-                println!("cd|{}", path);
+                Ok(Some(selection))
             }
-            Ok(())
         }
-        None => {
-            // User cancelled - exit cleanly without output
-            Ok(())
+        Some(1) | Some(130) => Ok(None), // No match or cancelled
+        Some(code) => Err(WtError::user_error(format!("fzf exited with code: {}", code)).into()),
+        None => Err(WtError::user_error("fzf terminated by signal").into()),
+    }
+}
+
+/// Compute status markers for each worktree, running `git status` concurrently.
+/// Worktrees whose status can't be read (bare, missing directory) get no markers.
+fn collect_markers(paths: &[&Path], colors: Colors) -> Vec<String> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .iter()
+            .map(|path| {
+                scope.spawn(move || {
+                    git::worktree_status(path)
+                        .map(|status| format_markers(&status, colors))
+                        .unwrap_or_default()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Format compact status markers: `*` for uncommitted changes and `↑N↓M` for
+/// commits ahead of/behind the upstream (e.g. "*↑2↓1").
+fn format_markers(status: &git::WorktreeStatus, colors: Colors) -> String {
+    let mut markers = String::new();
+    if status.is_dirty() {
+        markers.push_str(&colors.red("*"));
+    }
+    if let Some(upstream) = &status.upstream {
+        let mut divergence = String::new();
+        if upstream.ahead > 0 {
+            divergence.push_str(&format!("↑{}", upstream.ahead));
+        }
+        if upstream.behind > 0 {
+            divergence.push_str(&format!("↓{}", upstream.behind));
         }
+        markers.push_str(&colors.yellow(&divergence));
+    }
+    markers
+}
+
+/// Append each worktree's note and labels to its markers, e.g. "*↑2 wip #perf".
+fn with_annotations(markers: Vec<String>, paths: &[&Path], state: &State) -> Vec<String> {
+    markers
+        .into_iter()
+        .zip(paths)
+        .map(|(markers, path)| {
+            let annotation = state.get(path).map(|s| s.annotation()).unwrap_or_default();
+            match (markers.is_empty(), annotation.is_empty()) {
+                (_, true) => markers,
+                (true, false) => annotation,
+                (false, false) => format!("{} {}", markers, annotation),
+            }
+        })
+        .collect()
+}
+
+/// Append markers as a trailing column after the path, padding paths so the
+/// markers line up. Markers go last so fzf's `{2}`/`{3}` path fields stay put.
+fn with_markers(line: String, path: &str, path_width: usize, markers: &str) -> String {
+    if markers.is_empty() {
+        line
+    } else {
+        let padding = path_width.saturating_sub(path.chars().count());
+        format!("{}{}  {}", line, " ".repeat(padding), markers)
     }
 }
 
 /// Prepare candidate lines for fzf display.
-/// Format: "<branch>  <path>" with aligned columns.
-fn prepare_candidates(worktrees: &[crate::worktree::Worktree]) -> Vec<String> {
+/// Format: "<branch>  <path>  <markers>" with aligned columns; the branch is colored green.
+fn prepare_candidates(
+    worktrees: &[crate::worktree::Worktree],
+    markers: &[String],
+    colors: Colors,
+) -> Vec<String> {
     // First pass: find the maximum branch name length for alignment
     let max_branch_len = worktrees
         .iter()
         .map(|wt| format_branch_name(wt).len())
         .max()
         .unwrap_or(0);
+    let max_path_len = worktrees
+        .iter()
+        .map(|wt| wt.path.display().to_string().chars().count())
+        .max()
+        .unwrap_or(0);
 
     // Second pass: format each worktree with aligned columns
     worktrees
         .iter()
-        .map(|wt| {
+        .zip(markers)
+        .map(|(wt, markers)| {
             let branch = format_branch_name(wt);
-            let path = wt.path.display();
+            let path = wt.path.display().to_string();
+            // Pad before coloring so escape codes don't count towards the width.
             // Use two spaces as separator between columns
-            format!("{:width$}  {}", branch, path, width = max_branch_len)
+            let branch = format!("{:width$}", branch, width = max_branch_len);
+            let line = format!("{}  {}", colors.green(&branch), path);
+            with_markers(line, &path, max_path_len, markers)
         })
         .collect()
 }
@@ -174,8 +724,12 @@ fn extract_path(line: &str) -> Result<String> {
     }
 }
 
-/// Prepare candidates for cross-repo display (3 columns: repo, branch, path).
-fn prepare_all_candidates(repos: &[std::path::PathBuf]) -> Result<Vec<String>> {
+/// Collect the worktrees of all `repos`, paired with their repository name.
+/// With `state`, worktrees are ordered by frecency across all repositories.
+fn collect_all_worktrees(
+    repos: &[PathBuf],
+    state: Option<&State>,
+) -> Vec<(String, crate::worktree::Worktree)> {
     let mut all_worktrees: Vec<(String, crate::worktree::Worktree)> = Vec::new();
 
     // Collect all worktrees from all repos
@@ -198,6 +752,19 @@ fn prepare_all_candidates(repos: &[std::path::PathBuf]) -> Result<Vec<String>> {
         }
     }
 
+    if let Some(state) = state {
+        state.sort_by_frecency(&mut all_worktrees, |(_, wt)| wt.path.as_path());
+    }
+    all_worktrees
+}
+
+/// Prepare candidates for cross-repo display (3 columns: repo, branch, path).
+/// Notes and labels from `state` follow the markers.
+fn prepare_all_candidates(
+    all_worktrees: &[(String, crate::worktree::Worktree)],
+    state: &State,
+    colors: Colors,
+) -> Vec<String> {
     // Find max widths for alignment
     let max_repo_len = all_worktrees
         .iter()
@@ -211,24 +778,39 @@ fn prepare_all_candidates(repos: &[std::path::PathBuf]) -> Result<Vec<String>> {
         .max()
         .unwrap_or(0);
 
-    // Format each worktree with aligned columns: <repo>  <branch>  <path>
-    let candidates: Vec<String> = all_worktrees
+    let max_path_len = all_worktrees
         .iter()
-        .map(|(repo, wt)| {
-            let branch = format_branch_name(wt);
-            let path = wt.path.display();
-            format!(
-                "{:repo_width$}  {:branch_width$}  {}",
+        .map(|(_, wt)| wt.path.display().to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let paths: Vec<&Path> = all_worktrees
+        .iter()
+        .map(|(_, wt)| wt.path.as_path())
+        .collect();
+    let markers = with_annotations(collect_markers(&paths, colors), &paths, state);
+
+    // Format each worktree with aligned columns: <repo>  <branch>  <path>  <markers> <note>
+    all_worktrees
+        .iter()
+        .zip(&markers)
+        .map(|((repo, wt), markers)| {
+            let branch = format!(
+                "{:branch_width$}",
+                format_branch_name(wt),
+                branch_width = max_branch_len
+            );
+            let path = wt.path.display().to_string();
+            let line = format!(
+                "{:repo_width$}  {}  {}",
                 repo,
-                branch,
+                colors.green(&branch),
                 path,
                 repo_width = max_repo_len,
-                branch_width = max_branch_len
-            )
+            );
+            with_markers(line, &path, max_path_len, markers)
         })
-        .collect();
-
-    Ok(candidates)
+        .collect()
 }
 
 /// Extract path from 3-column format (repo, branch, path).
@@ -263,18 +845,29 @@ fn extract_path_from_all(line: &str) -> Result<String> {
 ///
 /// * `candidates` - List of formatted candidate strings
 /// * `fzf_config` - Fzf configuration
+/// * `keymap` - Bound keys, passed to `--expect` and listed in the header
 /// * `all_mode` - If true, use 3-column format (repo, branch, path); otherwise 2-column (branch, path)
+/// * `nested` - If true, Backspace on an empty query cancels to go back a level
+/// * `no_color` - If true, ask the preview command for plain output
+/// * `query` - Initial query; with exactly one match fzf selects it immediately
 fn run_fzf_with_expect(
     candidates: &[String],
     fzf_config: &config::FzfConfig,
+    keymap: &Keymap,
     all_mode: bool,
+    nested: bool,
+    no_color: bool,
+    query: Option<&str>,
 ) -> Result<Option<(String, String)>> {
     // Preview column depends on mode: {2} for single repo, {3} for all repos
     let preview_column = if all_mode { "{3}" } else { "{2}" };
-    let preview_cmd = format!("wt preview --path {}", preview_column);
+    let mut preview_cmd = format!("wt preview --path {}", preview_column);
+    if no_color {
+        preview_cmd.push_str(" --no-color");
+    }
 
     // Build fzf command arguments
-    let args = vec![
+    let mut args = vec![
         "--height".to_string(),
         fzf_config.height.clone(),
         "--layout".to_string(),
@@ -283,13 +876,32 @@ fn run_fzf_with_expect(
         fzf_config.preview_window.clone(),
         "--preview".to_string(),
         preview_cmd,
+        "--ansi".to_string(), // Candidates carry color codes; fzf strips them from output
         "--prompt".to_string(),
         "Worktree> ".to_string(),
         "--header".to_string(),
-        "Enter: cd | Ctrl-E: edit".to_string(),
-        "--expect".to_string(),
-        "ctrl-e".to_string(), // Capture ctrl-e presses
+        if nested {
+            format!("{} | Esc: back", keymap.header())
+        } else {
+            keymap.header()
+        },
     ];
+    if nested {
+        args.push("--bind".to_string());
+        args.push("bspace:backward-delete-char/eof".to_string());
+    }
+    // Capture action key presses; with no bindings fzf prints just the selection
+    let expect_keys = keymap.expect_keys();
+    let expecting = !expect_keys.is_empty();
+    if expecting {
+        args.push("--expect".to_string());
+        args.push(expect_keys);
+    }
+    if let Some(query) = query {
+        args.push("--query".to_string());
+        args.push(query.to_string());
+        args.push("--select-1".to_string()); // Auto-accept a single match
+    }
 
     // Spawn fzf process
     let mut child = Command::new("fzf")
@@ -330,24 +942,23 @@ fn run_fzf_with_expect(
         Some(0) => {
             // User made a selection
             let stdout = String::from_utf8_lossy(&output.stdout);
-            let lines: Vec<&str> = stdout.lines().collect();
+            let mut lines: Vec<&str> = stdout.lines().collect();
+            if !expecting {
+                lines.insert(0, "");
+            }
 
             // When using --expect, fzf outputs:
-            // Line 1: The key pressed (empty for Enter, "ctrl-e" for Ctrl-E)
+            // Line 1: The key pressed (empty for Enter, "ctrl-e" etc. otherwise)
             // Line 2: The selected item
             match lines.len() {
                 0 => Ok(None), // No selection
-                1 => {
-                    // Only one line means empty key (Enter) and no selection on second line
-                    // This shouldn't happen with valid selection, treat as no selection
-                    Ok(None)
-                }
                 _ => {
                     // Normal case: key on first line, selection on second
                     let key = lines[0].to_string();
-                    let selection = lines[1].to_string();
+                    let selection = lines.get(1).copied().unwrap_or_default().to_string();
 
-                    if selection.is_empty() {
+                    // Creating a worktree doesn't need a highlighted row
+                    if selection.is_empty() && keymap.action_for(&key) != Action::New {
                         Ok(None)
                     } else {
                         Ok(Some((key, selection)))
@@ -436,7 +1047,8 @@ mod tests {
             },
         ];
 
-        let candidates = prepare_candidates(&worktrees);
+        let markers = vec![String::new(); 2];
+        let candidates = prepare_candidates(&worktrees, &markers, Colors::new(false));
         assert_eq!(candidates.len(), 2);
 
         // Check that shorter branch name is padded to match longer one
@@ -444,6 +1056,105 @@ mod tests {
         assert!(candidates[1].starts_with("feature-branch"));
     }
 
+    #[test]
+    fn test_prepare_candidates_colors_padded_branch() {
+        let worktrees = vec![
+            Worktree {
+                path: PathBuf::from("/tmp/repo1"),
+                head: Some("abc".to_string()),
+                branch: Some("refs/heads/main".to_string()),
+                locked: false,
+                prunable: None,
+                bare: false,
+            },
+            Worktree {
+                path: PathBuf::from("/tmp/repo2"),
+                head: Some("def".to_string()),
+                branch: Some("refs/heads/feature".to_string()),
+                locked: false,
+                prunable: None,
+                bare: false,
+            },
+        ];
+
+        let markers = vec![String::new(); 2];
+        let candidates = prepare_candidates(&worktrees, &markers, Colors::new(true));
+        assert_eq!(candidates[0], "\x1b[32mmain   \x1b[0m  /tmp/repo1");
+        assert_eq!(candidates[1], "\x1b[32mfeature\x1b[0m  /tmp/repo2");
+    }
+
+    #[test]
+    fn test_prepare_candidates_aligns_markers_after_path() {
+        let worktrees = vec![
+            Worktree {
+                path: PathBuf::from("/tmp/repo"),
+                head: Some("abc".to_string()),
+                branch: Some("refs/heads/main".to_string()),
+                locked: false,
+                prunable: None,
+                bare: false,
+            },
+            Worktree {
+                path: PathBuf::from("/tmp/repo-feature"),
+                head: Some("def".to_string()),
+                branch: Some("refs/heads/feature".to_string()),
+                locked: false,
+                prunable: None,
+                bare: false,
+            },
+        ];
+
+        let markers = vec!["*↑2".to_string(), String::new()];
+        let candidates = prepare_candidates(&worktrees, &markers, Colors::new(false));
+        assert_eq!(candidates[0], "main     /tmp/repo          *↑2");
+        assert_eq!(candidates[1], "feature  /tmp/repo-feature");
+        assert_eq!(extract_path(&candidates[0]).unwrap(), "/tmp/repo");
+    }
+
+    #[test]
+    fn test_format_markers() {
+        let mut status = git::WorktreeStatus {
+            head: None,
+            branch: Some("main".to_string()),
+            upstream: None,
+            changes: Vec::new(),
+        };
+        assert_eq!(format_markers(&status, Colors::new(false)), "");
+
+        status.changes.push(" M src/main.rs".to_string());
+        status.upstream = Some(git::Upstream {
+            name: "origin/main".to_string(),
+            ahead: 2,
+            behind: 1,
+        });
+        assert_eq!(format_markers(&status, Colors::new(false)), "*↑2↓1");
+
+        status.changes.clear();
+        status.upstream = Some(git::Upstream {
+            name: "origin/main".to_string(),
+            ahead: 0,
+            behind: 0,
+        });
+        assert_eq!(format_markers(&status, Colors::new(false)), "");
+    }
+
+    #[test]
+    fn test_with_annotations_appends_note_after_markers() {
+        let mut state = State::default();
+        state.set_note(Path::new("/repo/a"), "wip".to_string());
+        state.set_labels(Path::new("/repo/b"), vec!["keep".to_string()]);
+        let paths = [
+            Path::new("/repo/a"),
+            Path::new("/repo/b"),
+            Path::new("/repo/c"),
+        ];
+        let markers = vec!["*".to_string(), String::new(), "↑1".to_string()];
+        assert_eq!(
+            with_annotations(markers, &paths, &state),
+            vec!["* wip", "#keep", "↑1"]
+        );
+    }
+
     #[test]
     fn test_extract_path_success() {
         let line = "main  /tmp/repo/main";
@@ -492,6 +1203,28 @@ mod tests {
         assert_eq!(path, "/Users/svenlochner/dev/time-budgeted-todo-m");
     }
 
+    #[test]
+    fn test_prepare_repo_candidates_alignment() {
+        // Paths that aren't repositories report an unknown worktree count
+        let repos = vec![
+            PathBuf::from("/nonexistent/api"),
+            PathBuf::from("/nonexistent/frontend"),
+        ];
+        let candidates = prepare_repo_candidates(&repos, Colors::new(false));
+        assert_eq!(candidates[0], "api       ? worktrees  /nonexistent/api");
+        assert_eq!(
+            candidates[1],
+            "frontend  ? worktrees  /nonexistent/frontend"
+        );
+    }
+
+    #[test]
+    fn test_extract_repo_path() {
+        let line = "api       2 worktrees  /Users/user/dev/api";
+        assert_eq!(extract_repo_path(line).unwrap(), "/Users/user/dev/api");
+        assert!(extract_repo_path("api  /Users/user/dev/api").is_err());
+    }
+
     #[test]
     fn test_extract_path_from_all_success() {
         let line = "worktree-manager  main       /Users/user/dev/worktree-manager";
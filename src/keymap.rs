@@ -0,0 +1,220 @@
+//! Picker keybindings: which action each fzf key triggers.
+
+use std::collections::BTreeMap;
+
+/// An action the interactive picker can run on the highlighted worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Change into the worktree
+    Cd,
+    /// Change into the worktree and open an editor: the named one from
+    /// `editors`, or the default `editor` (`$EDITOR` if unset)
+    Edit(Option<String>),
+    /// Remove the worktree (after confirmation) and reopen the picker
+    Remove,
+    /// Toggle the worktree's lock and reopen the picker
+    Lock,
+    /// Create a new worktree and change into it
+    New,
+    /// Open the worktree in the configured terminal multiplexer
+    Multiplexer,
+    /// Run a shell command template, with `{path}` replaced by the worktree path
+    Command(String),
+    /// Run a command in the worktree, report its exit status and reopen the picker
+    Exec(String),
+    /// Run the named launcher from `launchers` in the worktree and reopen the picker
+    Open(String),
+}
+
+impl Action {
+    /// Parse an action from config; anything that isn't a built-in action name
+    /// is a shell command template.
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "cd" => Action::Cd,
+            "edit" => Action::Edit(None),
+            "remove" => Action::Remove,
+            "lock" => Action::Lock,
+            "new" => Action::New,
+            // "tmux" is the name from before zellij support
+            "multiplexer" | "tmux" => Action::Multiplexer,
+            value => {
+                if let Some(name) = value.strip_prefix("edit:") {
+                    Action::Edit(Some(name.trim().to_string()))
+                } else if let Some(name) = value.strip_prefix("open:") {
+                    Action::Open(name.trim().to_string())
+                } else {
+                    Action::Command(value.to_string())
+                }
+            }
+        }
+    }
+
+    /// Short label for the picker header.
+    fn label(&self) -> &str {
+        match self {
+            Action::Cd => "cd",
+            Action::Edit(None) => "edit",
+            Action::Edit(Some(name)) => name,
+            Action::Remove => "delete",
+            Action::Lock => "lock/unlock",
+            Action::New => "new",
+            Action::Multiplexer => "multiplexer",
+            Action::Command(command) | Action::Exec(command) => command,
+            Action::Open(name) => name,
+        }
+    }
+}
+
+/// Render a command template for the shell wrapper, quoting the path.
+pub fn render_command(template: &str, path: &str) -> String {
+    template.replace("{path}", &shell_quote(path))
+}
+
+/// Single-quote a value for POSIX shells and fish.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Keys bound in the picker, in header order. Enter always means `cd`.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(String, Action)>,
+}
+
+impl Keymap {
+    /// Build the keymap from the `keybindings` config section (key -> action)
+    /// and the `actions` section (key -> command run in the worktree).
+    /// Bindings with an empty action are skipped with a warning; `actions`
+    /// entries win over `keybindings` for the same key.
+    pub fn from_config(
+        keybindings: &BTreeMap<String, String>,
+        actions: &BTreeMap<String, String>,
+    ) -> Self {
+        let mut bindings: Vec<(String, Action)> = keybindings
+            .iter()
+            .filter(|(key, _)| !actions.contains_key(*key))
+            .filter_map(|(key, value)| {
+                if value.trim().is_empty() {
+                    eprintln!("Warning: empty action for key '{}' in config", key);
+                    None
+                } else {
+                    Some((key.clone(), Action::parse(value)))
+                }
+            })
+            .collect();
+        for (key, command) in actions {
+            if command.trim().is_empty() {
+                eprintln!("Warning: empty command for key '{}' in config", key);
+            } else {
+                bindings.push((key.clone(), Action::Exec(command.trim().to_string())));
+            }
+        }
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self { bindings }
+    }
+
+    /// Keys for fzf's `--expect` option.
+    pub fn expect_keys(&self) -> String {
+        self.bindings
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Header line listing the bindings, e.g. "Enter: cd | ctrl-e: edit".
+    pub fn header(&self) -> String {
+        std::iter::once("Enter: cd".to_string())
+            .chain(
+                self.bindings
+                    .iter()
+                    .map(|(key, action)| format!("{}: {}", key, action.label())),
+            )
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// Action for a key reported by fzf; Enter (empty key) and unknown keys mean `cd`.
+    pub fn action_for(&self, key: &str) -> Action {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound == key)
+            .map(|(_, action)| action.clone())
+            .unwrap_or(Action::Cd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn keymap(pairs: &[(&str, &str)]) -> Keymap {
+        Keymap::from_config(&map(pairs), &BTreeMap::new())
+    }
+
+    #[test]
+    fn parses_builtin_actions_and_commands() {
+        assert_eq!(Action::parse("edit"), Action::Edit(None));
+        assert_eq!(
+            Action::parse("edit:code"),
+            Action::Edit(Some("code".to_string()))
+        );
+        assert_eq!(
+            Action::parse("open:lazygit"),
+            Action::Open("lazygit".to_string())
+        );
+        assert_eq!(Action::parse(" lock "), Action::Lock);
+        assert_eq!(
+            Action::parse("tmux new-window -c {path}"),
+            Action::Command("tmux new-window -c {path}".to_string())
+        );
+    }
+
+    #[test]
+    fn keymap_builds_expect_keys_and_header() {
+        let keymap = keymap(&[("ctrl-e", "edit"), ("ctrl-d", "remove"), ("ctrl-x", "")]);
+        assert_eq!(keymap.expect_keys(), "ctrl-d,ctrl-e");
+        assert_eq!(keymap.header(), "Enter: cd | ctrl-d: delete | ctrl-e: edit");
+    }
+
+    #[test]
+    fn unbound_keys_fall_back_to_cd() {
+        let keymap = keymap(&[("ctrl-e", "edit")]);
+        assert_eq!(keymap.action_for("ctrl-e"), Action::Edit(None));
+        assert_eq!(keymap.action_for(""), Action::Cd);
+        assert_eq!(keymap.action_for("ctrl-z"), Action::Cd);
+    }
+
+    #[test]
+    fn actions_override_keybindings() {
+        let keymap = Keymap::from_config(
+            &map(&[("ctrl-e", "edit"), ("ctrl-o", "open {path}")]),
+            &map(&[("ctrl-b", "cargo build"), ("ctrl-o", "gh pr view --web")]),
+        );
+        assert_eq!(keymap.expect_keys(), "ctrl-b,ctrl-e,ctrl-o");
+        assert_eq!(
+            keymap.action_for("ctrl-o"),
+            Action::Exec("gh pr view --web".to_string())
+        );
+        assert_eq!(
+            keymap.action_for("ctrl-b"),
+            Action::Exec("cargo build".to_string())
+        );
+    }
+
+    #[test]
+    fn render_command_quotes_path() {
+        assert_eq!(
+            render_command("code {path}", "/tmp/it's here"),
+            r"code '/tmp/it'\''s here'"
+        );
+    }
+}
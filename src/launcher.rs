@@ -0,0 +1,84 @@
+//! `wt open`: named "open with" launchers from the `launchers` config.
+
+use std::io;
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::Result;
+
+use crate::error::WtError;
+use crate::process::shell_quote;
+use crate::{config, editor, forge, preview, process};
+
+/// Run the launcher `name` for the worktree matching `target` (branch name
+/// or path).
+pub fn open_target(target: &str, name: &str) -> Result<()> {
+    let path = preview::resolve_target(target)?;
+    launch(&path, name)
+}
+
+/// Run the launcher `name` with the worktree at `path` as working directory
+/// and wait for it; terminal programs like lazygit take over the terminal
+/// until they exit. Output goes to stderr so the shell wrapper doesn't
+/// capture it.
+pub fn launch(path: &Path, name: &str) -> Result<()> {
+    let launchers = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?
+        .launchers;
+    let template = launchers.get(name).ok_or_else(|| {
+        let known = launchers.keys().cloned().collect::<Vec<_>>();
+        WtError::config_error(if known.is_empty() {
+            format!("unknown launcher '{}'; no `launchers` are configured", name)
+        } else {
+            format!(
+                "unknown launcher '{}' (configured: {})",
+                name,
+                known.join(", ")
+            )
+        })
+    })?;
+    let command = render(template, path)?;
+
+    let status = process::shell(&command)
+        .current_dir(path)
+        .stdin(Stdio::inherit())
+        .stdout(io::stderr())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| {
+            WtError::io_error_with_source(format!("failed to run launcher '{}'", name), e.into())
+        })?;
+    if !status.success() {
+        return Err(WtError::user_error(format!("launcher '{}' failed: {}", name, command)).into());
+    }
+    Ok(())
+}
+
+/// Fill in `{path}` and `{branch}` as [`editor::render`] does, and `{url}`
+/// (the repository's web URL), all shell-quoted. Tokens are only resolved
+/// when the template uses them.
+fn render(template: &str, path: &Path) -> Result<String> {
+    let mut command = editor::render(template, path, shell_quote);
+    if command.contains("{url}") {
+        let url = forge::repo_web_url(path).ok_or_else(|| {
+            WtError::user_error(
+                "no web URL for this repository (its origin remote isn't a hosted URL)",
+            )
+        })?;
+        command = command.replace("{url}", &shell_quote(&url));
+    }
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_fills_path() {
+        assert_eq!(
+            render("lazygit -p {path}", Path::new("/src/app")).unwrap(),
+            "lazygit -p '/src/app'"
+        );
+    }
+}
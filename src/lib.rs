@@ -0,0 +1,137 @@
+//! Git worktree management as a library: what the `wt` binary is built on.
+//!
+//! The documented modules are the API. [`git`] wraps the git commands wt
+//! runs and [`worktree`] parses their output; [`discovery`] finds
+//! repositories under the configured [`config`] paths; [`list`], [`add`] and
+//! [`remove`] are the high-level operations, returning typed results rather
+//! than printing them. Errors are [`WtError`]s inside [`anyhow::Error`].
+//!
+//! ```no_run
+//! use worktree_manager::{add, git, list};
+//!
+//! let repo_root = git::repo_root(None)?;
+//! let added = add::create_worktree(&repo_root, "feature-x", None, None, false)?;
+//! println!("created {}", added.path.display());
+//! for worktree in list::worktrees(&repo_root)? {
+//!     println!("{:?} at {}", worktree.branch, worktree.path.display());
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The other modules implement the CLI and are not part of the API.
+
+#[doc(hidden)]
+pub mod action;
+pub mod add;
+#[doc(hidden)]
+pub mod agent;
+#[doc(hidden)]
+pub mod browse;
+#[doc(hidden)]
+pub mod ci;
+#[doc(hidden)]
+pub mod clean;
+#[doc(hidden)]
+pub mod cli;
+#[doc(hidden)]
+pub mod clone;
+#[doc(hidden)]
+pub mod color;
+#[doc(hidden)]
+pub mod complete;
+pub mod config;
+#[doc(hidden)]
+pub mod cp;
+#[doc(hidden)]
+pub mod current;
+#[doc(hidden)]
+pub mod diff;
+pub mod discovery;
+#[doc(hidden)]
+pub mod doctor;
+#[doc(hidden)]
+pub mod editor;
+pub mod error;
+#[doc(hidden)]
+pub mod exec;
+#[doc(hidden)]
+pub mod fetch;
+#[doc(hidden)]
+pub mod forge;
+#[doc(hidden)]
+pub mod gc;
+pub mod git;
+#[doc(hidden)]
+pub mod github;
+#[doc(hidden)]
+pub mod gitlab;
+#[doc(hidden)]
+pub mod guard;
+#[doc(hidden)]
+pub mod hooks;
+#[doc(hidden)]
+pub mod init;
+#[doc(hidden)]
+pub mod integrations;
+#[doc(hidden)]
+pub mod interactive;
+#[doc(hidden)]
+pub mod keymap;
+#[doc(hidden)]
+pub mod launcher;
+pub mod list;
+#[doc(hidden)]
+pub mod logging;
+#[doc(hidden)]
+pub mod main_worktree;
+#[doc(hidden)]
+pub mod man;
+#[doc(hidden)]
+pub mod migrate;
+#[doc(hidden)]
+pub mod note;
+#[doc(hidden)]
+pub mod onboard;
+#[doc(hidden)]
+pub mod overlap;
+#[doc(hidden)]
+pub mod preview;
+#[doc(hidden)]
+pub mod process;
+#[doc(hidden)]
+pub mod progress;
+#[doc(hidden)]
+pub mod prune;
+#[doc(hidden)]
+pub mod recent;
+pub mod remove;
+#[doc(hidden)]
+pub mod repo;
+#[doc(hidden)]
+pub mod schema;
+#[doc(hidden)]
+pub mod self_update;
+#[doc(hidden)]
+pub mod shift;
+#[doc(hidden)]
+pub mod state;
+#[doc(hidden)]
+pub mod status;
+#[doc(hidden)]
+pub mod switch;
+#[doc(hidden)]
+pub mod sync;
+#[doc(hidden)]
+pub mod tmux;
+#[doc(hidden)]
+pub mod version;
+#[doc(hidden)]
+pub mod vscode;
+#[doc(hidden)]
+pub mod watch;
+pub mod worktree;
+#[doc(hidden)]
+pub mod zellij;
+
+pub use error::WtError;
+pub use worktree::Worktree;
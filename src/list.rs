@@ -1,37 +1,111 @@
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
 use crate::error::WtError;
-use crate::{config, discovery, git};
+use crate::forge::{self, ChecksState, PullRequest};
+use crate::worktree::Worktree;
+use crate::{ci, config, discovery, git, process, state};
 
-pub fn list_worktrees(json: bool, all: bool) -> Result<()> {
+/// How many forge queries `wt list --pr/--ci` runs at once.
+const FORGE_JOBS: usize = 8;
+
+/// Pull requests by head branch for one repository (empty without `--pr`).
+type PullRequests = HashMap<String, PullRequest>;
+
+/// The worktrees of the repository at `repo_root`, main worktree first.
+pub fn worktrees(repo_root: &Path) -> Result<Vec<Worktree>> {
+    git::worktrees_porcelain(repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to parse worktrees", e).into())
+}
+
+/// The worktrees of every repository under the configured auto-discovery
+/// paths and of every registered one (those matching the `repo` glob, if
+/// given), each with its repository's root. Repositories whose worktrees
+/// can't be listed are skipped with a warning.
+pub fn all_worktrees(
+    config: &config::Config,
+    repo: Option<&str>,
+) -> Result<Vec<(PathBuf, Worktree)>> {
+    if config.auto_discovery.paths.is_empty() && config.auto_discovery.repos.is_empty() {
+        return Err(WtError::user_error(
+            "No auto-discovery paths configured. Run: wt config <paths...> or wt repo add",
+        )
+        .into());
+    }
+
+    let mut all = Vec::new();
+    let mut repos = discovery::discover(&config.auto_discovery)?;
+    if let Some(pattern) = repo {
+        repos = discovery::filter_repos(repos, pattern)?;
+    }
+    for repo_root in repos {
+        match git::worktrees_porcelain(&repo_root) {
+            Ok(worktrees) => all.extend(worktrees.into_iter().map(|wt| (repo_root.clone(), wt))),
+            Err(e) => {
+                eprintln!(
+                    "Warning: failed to list worktrees for {}: {}",
+                    repo_name(&repo_root),
+                    e
+                );
+            }
+        }
+    }
+    Ok(all)
+}
+
+pub fn list_worktrees(json: bool, all: bool, repo: Option<&str>, pr: bool, ci: bool) -> Result<()> {
     if all {
-        list_all_worktrees(json)
+        list_all_worktrees(json, repo, pr, ci)
     } else {
-        list_single_repo_worktrees(json)
+        list_single_repo_worktrees(json, pr, ci)
     }
 }
 
-fn list_single_repo_worktrees(json: bool) -> Result<()> {
+fn list_single_repo_worktrees(json: bool, pr: bool, ci: bool) -> Result<()> {
     let repo_root = git::repo_root(None)?;
-    let worktrees = git::worktrees_porcelain(&repo_root)
-        .map_err(|e| WtError::git_error_with_source("failed to parse worktrees", e))?;
+    let worktrees = worktrees(&repo_root)?;
+
+    let state = state::load();
+    let prs = if pr {
+        pull_requests_or_warn(&repo_root)
+    } else {
+        PullRequests::new()
+    };
+    let checks = if ci {
+        let targets: Vec<_> = worktrees
+            .iter()
+            .map(|wt| (repo_root.as_path(), wt))
+            .collect();
+        ci_statuses(&targets)
+    } else {
+        Vec::new()
+    };
 
     if json {
         // Minimal JSON array of objects; we can refine schema later.
         let value = serde_json::to_value(
             worktrees
                 .iter()
-                .map(|wt| {
-                    serde_json::json!({
+                .enumerate()
+                .map(|(i, wt)| {
+                    let mut value = serde_json::json!({
                         "path": wt.path,
                         "head": wt.head,
                         "branch": wt.branch,
                         "locked": wt.locked,
                         "prunable": wt.prunable,
                         "bare": wt.bare,
-                    })
+                        "state": state.get(&wt.path),
+                    });
+                    if pr {
+                        value["pull_request"] = serde_json::json!(pull_request(&prs, wt));
+                    }
+                    if ci {
+                        value["ci"] = serde_json::json!(checks[i]);
+                    }
+                    value
                 })
                 .collect::<Vec<_>>(),
         )?;
@@ -39,77 +113,91 @@ fn list_single_repo_worktrees(json: bool) -> Result<()> {
         return Ok(());
     }
 
-    let rendered: Vec<(String, String, String)> = worktrees
+    let mut rendered: Vec<(String, String, String)> = worktrees
         .iter()
         .map(|wt| {
             (
                 pretty_ref(wt.branch.as_deref()),
                 display_path(&repo_root, &wt.path),
-                flags(wt),
+                suffix(wt, &state),
             )
         })
         .collect();
+    if pr {
+        let summaries = worktrees.iter().map(|wt| pr_summary(&prs, wt)).collect();
+        append_column(rendered.iter_mut().map(|(branch, _, _)| branch), summaries);
+    }
+    if ci {
+        let markers = checks.iter().map(|c| ci_marker(*c)).collect();
+        append_column(rendered.iter_mut().map(|(branch, _, _)| branch), markers);
+    }
 
     let max_branch = rendered
         .iter()
-        .map(|(branch, _, _)| branch.len())
+        .map(|(branch, _, _)| branch.chars().count())
         .max()
         .unwrap_or(0);
 
-    for (branch, path, flags) in rendered {
-        if flags.is_empty() {
+    for (branch, path, suffix) in rendered {
+        if suffix.is_empty() {
             println!("{branch:<width$}  {path}", width = max_branch);
         } else {
-            println!("{branch:<width$}  {path}  [{flags}]", width = max_branch);
+            println!("{branch:<width$}  {path}  {suffix}", width = max_branch);
         }
     }
 
     Ok(())
 }
 
-fn list_all_worktrees(json: bool) -> Result<()> {
+fn list_all_worktrees(json: bool, repo: Option<&str>, pr: bool, ci: bool) -> Result<()> {
     let config = config::load()?;
-    if config.auto_discovery.paths.is_empty() {
-        return Err(WtError::user_error(
-            "No auto-discovery paths configured. Run: wt config set-discovery-paths <paths...>",
-        )
-        .into());
-    }
-
-    let repos = discovery::discover_repos(&config.auto_discovery.paths)?;
-    if repos.is_empty() {
+    let found = all_worktrees(&config, repo)?;
+    if found.is_empty() {
         eprintln!("No git repositories found in configured discovery paths.");
         return Ok(());
     }
 
-    // Collect all worktrees from all repos
-    let mut all_worktrees: Vec<(String, crate::worktree::Worktree)> = Vec::new();
+    // Pull requests are looked up once per repository, concurrently
+    let repo_prs: HashMap<PathBuf, PullRequests> = if pr {
+        let repos: Vec<PathBuf> = found
+            .iter()
+            .map(|(root, _)| root.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let maps = process::parallel_map(&repos, FORGE_JOBS, |repo| pull_requests_or_warn(repo));
+        repos.into_iter().zip(maps).collect()
+    } else {
+        HashMap::new()
+    };
+    let no_prs = PullRequests::new();
 
-    for repo_root in repos {
-        let repo_name = repo_root
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("(unknown)")
-            .to_string();
+    let all_worktrees: Vec<(String, &Path, &PullRequests, &Worktree)> = found
+        .iter()
+        .map(|(root, wt)| {
+            let prs = repo_prs.get(root).unwrap_or(&no_prs);
+            (repo_name(root), root.as_path(), prs, wt)
+        })
+        .collect();
 
-        match git::worktrees_porcelain(&repo_root) {
-            Ok(worktrees) => {
-                for wt in worktrees {
-                    all_worktrees.push((repo_name.clone(), wt));
-                }
-            }
-            Err(e) => {
-                eprintln!("Warning: failed to list worktrees for {}: {}", repo_name, e);
-            }
-        }
-    }
+    let state = state::load();
+    let checks = if ci {
+        let targets: Vec<_> = all_worktrees
+            .iter()
+            .map(|(_, root, _, wt)| (*root, *wt))
+            .collect();
+        ci_statuses(&targets)
+    } else {
+        Vec::new()
+    };
 
     if json {
         let value = serde_json::to_value(
             all_worktrees
                 .iter()
-                .map(|(repo, wt)| {
-                    serde_json::json!({
+                .enumerate()
+                .map(|(i, (repo, _, prs, wt))| {
+                    let mut value = serde_json::json!({
                         "repo": repo,
                         "path": wt.path,
                         "head": wt.head,
@@ -117,7 +205,15 @@ fn list_all_worktrees(json: bool) -> Result<()> {
                         "locked": wt.locked,
                         "prunable": wt.prunable,
                         "bare": wt.bare,
-                    })
+                        "state": state.get(&wt.path),
+                    });
+                    if pr {
+                        value["pull_request"] = serde_json::json!(pull_request(prs, wt));
+                    }
+                    if ci {
+                        value["ci"] = serde_json::json!(checks[i]);
+                    }
+                    value
                 })
                 .collect::<Vec<_>>(),
         )?;
@@ -126,17 +222,31 @@ fn list_all_worktrees(json: bool) -> Result<()> {
     }
 
     // Render in table format with repo name
-    let rendered: Vec<(String, String, String, String)> = all_worktrees
+    let mut rendered: Vec<(String, String, String, String)> = all_worktrees
         .iter()
-        .map(|(repo, wt)| {
+        .map(|(repo, _, _, wt)| {
             (
                 repo.clone(),
                 pretty_ref(wt.branch.as_deref()),
                 wt.path.to_string_lossy().to_string(),
-                flags(wt),
+                suffix(wt, &state),
             )
         })
         .collect();
+    if pr {
+        let summaries = all_worktrees
+            .iter()
+            .map(|(_, _, prs, wt)| pr_summary(prs, wt))
+            .collect();
+        append_column(
+            rendered.iter_mut().map(|(_, branch, _, _)| branch),
+            summaries,
+        );
+    }
+    if ci {
+        let markers = checks.iter().map(|c| ci_marker(*c)).collect();
+        append_column(rendered.iter_mut().map(|(_, branch, _, _)| branch), markers);
+    }
 
     let max_repo = rendered
         .iter()
@@ -146,12 +256,12 @@ fn list_all_worktrees(json: bool) -> Result<()> {
 
     let max_branch = rendered
         .iter()
-        .map(|(_, branch, _, _)| branch.len())
+        .map(|(_, branch, _, _)| branch.chars().count())
         .max()
         .unwrap_or(0);
 
-    for (repo, branch, path, flags) in rendered {
-        if flags.is_empty() {
+    for (repo, branch, path, suffix) in rendered {
+        if suffix.is_empty() {
             println!(
                 "{repo:<repo_width$}  {branch:<branch_width$}  {path}",
                 repo_width = max_repo,
@@ -159,7 +269,7 @@ fn list_all_worktrees(json: bool) -> Result<()> {
             );
         } else {
             println!(
-                "{repo:<repo_width$}  {branch:<branch_width$}  {path}  [{flags}]",
+                "{repo:<repo_width$}  {branch:<branch_width$}  {path}  {suffix}",
                 repo_width = max_repo,
                 branch_width = max_branch
             );
@@ -169,6 +279,73 @@ fn list_all_worktrees(json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Pull requests for the repository at `repo_root`; failures (gh missing or
+/// not logged in) are reported once and leave the PR column empty.
+fn pull_requests_or_warn(repo_root: &Path) -> PullRequests {
+    forge::pull_requests(repo_root).unwrap_or_else(|e| {
+        eprintln!("Warning: {}: {:#}", repo_root.display(), e);
+        PullRequests::new()
+    })
+}
+
+fn pull_request<'a>(prs: &'a PullRequests, wt: &Worktree) -> Option<&'a PullRequest> {
+    let branch = wt.branch.as_deref()?;
+    prs.get(git::short_branch(branch))
+}
+
+/// The `--pr` column: the worktree's pull request summary, or "-".
+fn pr_summary(prs: &PullRequests, wt: &Worktree) -> String {
+    pull_request(prs, wt)
+        .map(PullRequest::summary)
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// CI status of each worktree's HEAD (`None` for bare entries), looked up
+/// concurrently. Failures are reported once per repository and message.
+fn ci_statuses(targets: &[(&Path, &Worktree)]) -> Vec<Option<ChecksState>> {
+    let results = process::parallel_map(targets, FORGE_JOBS, |(repo_root, wt)| match &wt.head {
+        Some(sha) if !wt.bare => ci::status(repo_root, sha),
+        _ => Ok(None),
+    });
+    let mut warnings = BTreeSet::new();
+    let checks = results
+        .into_iter()
+        .zip(targets)
+        .map(|(result, (repo_root, _))| {
+            result.unwrap_or_else(|e| {
+                warnings.insert(format!("{}: {:#}", repo_root.display(), e));
+                None
+            })
+        })
+        .collect();
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    checks
+}
+
+/// The `--ci` column: ✓ passing, ✗ failing, ● pending, or "-".
+fn ci_marker(checks: Option<ChecksState>) -> String {
+    checks.map_or("-", ChecksState::marker).to_string()
+}
+
+/// Pad `cells` to a common width and append `extra` as another column.
+fn append_column<'a>(cells: impl Iterator<Item = &'a mut String>, extra: Vec<String>) {
+    let mut cells: Vec<_> = cells.collect();
+    let width = cells.iter().map(|c| c.chars().count()).max().unwrap_or(0);
+    for (cell, extra) in cells.iter_mut().zip(extra) {
+        **cell = format!("{:<width$}  {}", cell, extra, width = width);
+    }
+}
+
+fn repo_name(repo_root: &Path) -> String {
+    repo_root
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("(unknown)")
+        .to_string()
+}
+
 fn pretty_ref(r: Option<&str>) -> String {
     r.map(|r| {
         r.strip_prefix("refs/heads/")
@@ -189,7 +366,21 @@ fn display_path(repo_root: &Path, path: &Path) -> String {
         .unwrap_or_else(|_| path.to_string_lossy().to_string())
 }
 
-fn flags(wt: &crate::worktree::Worktree) -> String {
+/// Text after the path: flags in brackets, then the worktree's note and labels.
+fn suffix(wt: &Worktree, state: &state::State) -> String {
+    let flags = flags(wt);
+    let annotation = state
+        .get(&wt.path)
+        .map(|s| s.annotation())
+        .unwrap_or_default();
+    match (flags.is_empty(), annotation.is_empty()) {
+        (true, _) => annotation,
+        (false, true) => format!("[{flags}]"),
+        (false, false) => format!("[{flags}]  {annotation}"),
+    }
+}
+
+fn flags(wt: &Worktree) -> String {
     let mut parts = Vec::new();
     if wt.locked {
         parts.push("locked".to_string());
@@ -0,0 +1,42 @@
+//! Diagnostic logging on stderr, off unless asked for with `-v`/`--verbose`
+//! or the `WT_LOG` environment variable.
+
+use std::io::IsTerminal;
+
+use tracing_subscriber::EnvFilter;
+
+use crate::cli::LogFormat;
+
+/// Environment variable holding a log filter, e.g. `debug` or `worktree_manager::process=trace`.
+const LOG_ENV: &str = "WT_LOG";
+
+/// Install the stderr logger. `verbosity` is the number of `-v` flags: one
+/// logs every spawned command (debug), two adds trace output. Without `-v`,
+/// `WT_LOG` decides what is logged. With [`LogFormat::Json`] each event is a
+/// JSON object on its own line, with its fields at the top level.
+pub fn init(verbosity: u8, format: LogFormat) {
+    let filter = match verbosity {
+        0 => match std::env::var(LOG_ENV) {
+            Ok(spec) if !spec.trim().is_empty() => EnvFilter::try_new(&spec).unwrap_or_else(|e| {
+                eprintln!("warning: ignoring invalid {}='{}': {}", LOG_ENV, spec, e);
+                EnvFilter::new("off")
+            }),
+            _ => return,
+        },
+        1 => EnvFilter::new("worktree_manager=debug"),
+        _ => EnvFilter::new("worktree_manager=trace"),
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => builder.with_ansi(std::io::stderr().is_terminal()).init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(false)
+            .init(),
+    }
+}
@@ -1,45 +1,77 @@
-mod add;
-mod agent;
-mod cli;
-mod config;
-mod discovery;
-mod error;
-mod git;
-mod init;
-mod interactive;
-mod list;
-mod preview;
-mod process;
-mod prune;
-mod remove;
-mod worktree;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
 
-use crate::cli::{Cli, Command};
-use crate::error::WtError;
+use worktree_manager::cli::{self, Cli, Command, ErrorStream};
+use worktree_manager::error::WtError;
+use worktree_manager::{complete, logging};
 
 fn main() {
+    // `wt __complete -- <words>` serves the shell completions. It isn't part
+    // of the CLI definition, so the completions generated from it don't offer it.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [complete, dashes, words @ ..] = args.as_slice()
+        && complete == complete::COMMAND
+        && dashes == "--"
+    {
+        logging::init(0, cli::LogFormat::Text);
+        complete::complete(words);
+        return;
+    }
+
     let cli = Cli::parse();
+    logging::init(cli.verbose, cli.log_format);
 
     // Check if --json flag is present in any command for error handling
     let has_json_flag = cli.has_json_flag();
 
-    if let Err(err) = run() {
-        handle_error(err, has_json_flag);
+    if let Err(err) = change_directory(&cli.directory).and_then(|()| run()) {
+        let stream = has_json_flag.then(|| error_stream(cli.json_errors));
+        handle_error(err, stream);
+    }
+}
+
+/// Where JSON errors go: `--json-errors`, else the config's `json_errors`.
+/// A config that fails to load is the error being reported, so it counts as
+/// the default.
+fn error_stream(flag: Option<ErrorStream>) -> ErrorStream {
+    flag.unwrap_or_else(|| {
+        match worktree_manager::config::load().map(|config| config.json_errors) {
+            Ok(stream) if stream == "stderr" => ErrorStream::Stderr,
+            _ => ErrorStream::Stdout,
+        }
+    })
+}
+
+/// Apply the `-C` options in order, each relative to the one before, so every
+/// command works as if started there.
+fn change_directory(dirs: &[PathBuf]) -> Result<()> {
+    for dir in dirs {
+        std::env::set_current_dir(dir).map_err(|e| {
+            WtError::user_error_with_source(format!("cannot change to '{}'", dir.display()), e)
+        })?;
     }
+    Ok(())
 }
 
-/// Handle errors with proper exit codes and optional JSON output
-fn handle_error(err: anyhow::Error, json: bool) {
+/// Handle errors with proper exit codes, as JSON on `json` if given
+fn handle_error(err: anyhow::Error, json: Option<ErrorStream>) {
+    let print_json = |value: serde_json::Value| {
+        let line = serde_json::to_string(&value).unwrap();
+        match json {
+            Some(ErrorStream::Stderr) => eprintln!("{}", line),
+            _ => println!("{}", line),
+        }
+    };
+
     // Try to downcast to WtError for structured error handling
     if let Some(wt_err) = err.downcast_ref::<WtError>() {
         let exit_code = wt_err.exit_code();
 
-        if json {
+        if json.is_some() {
             // Output JSON error format
-            println!("{}", serde_json::to_string(&wt_err.to_json()).unwrap());
+            print_json(wt_err.to_json());
         } else {
             // Output human-readable error
             wt_err.print_human();
@@ -48,13 +80,12 @@ fn handle_error(err: anyhow::Error, json: bool) {
         std::process::exit(exit_code);
     } else {
         // Fallback for non-WtError errors (shouldn't happen, but handle gracefully)
-        if json {
-            let json_err = serde_json::json!({
+        if json.is_some() {
+            print_json(serde_json::json!({
                 "error": true,
                 "code": "unknown",
                 "message": format!("{:#}", err)
-            });
-            println!("{}", serde_json::to_string(&json_err).unwrap());
+            }));
         } else {
             eprintln!("error: {:#}", err);
         }
@@ -65,55 +96,292 @@ fn handle_error(err: anyhow::Error, json: bool) {
 fn run() -> Result<()> {
     let cli = Cli::parse();
 
-    match cli.command.unwrap_or(Command::Interactive { all: false }) {
-        Command::Init { shell } => match shell {
-            Some(s) => {
-                // Explicit shell - output code to stdout (for manual setup)
-                print!("{}", crate::init::shell_init(s));
-                Ok(())
-            }
-            None => {
-                // No shell specified - run interactive setup
-                crate::init::run_interactive_setup()
+    if cli.version {
+        return worktree_manager::version::print_version(cli.json);
+    }
+
+    // The picker query only stands on its own: `wt feat list` is a mistake,
+    // not a `wt list`
+    if let (Some(query), Some(_)) = (&cli.query, &cli.command) {
+        return Err(WtError::user_error(format!(
+            "unexpected argument '{}' before the subcommand",
+            query
+        ))
+        .into());
+    }
+
+    let command = cli.command.unwrap_or(Command::Interactive {
+        all: false,
+        by_repo: false,
+        repo: None,
+        no_color: false,
+        query: cli.query,
+        json: false,
+        refresh: false,
+        recent: false,
+    });
+    if needs_git(&command) {
+        worktree_manager::git::check_version()?;
+    }
+
+    match command {
+        Command::Init { shell, cmd } => {
+            let cmd = worktree_manager::init::command_name(cmd.as_deref())?;
+            match shell {
+                Some(s) => {
+                    // Explicit shell - output code to stdout (for manual setup)
+                    print!("{}", worktree_manager::init::shell_init(s, &cmd));
+                    Ok(())
+                }
+                None => {
+                    // No shell specified - run interactive setup
+                    worktree_manager::init::run_interactive_setup(&cmd)
+                }
             }
-        },
-        Command::Interactive { all } => crate::interactive::run_interactive(all),
-        Command::List { json, all } => crate::list::list_worktrees(json, all),
+        }
+        Command::Completions { shell, cmd, dir } => {
+            let cmd = worktree_manager::init::command_name(cmd.as_deref())?;
+            worktree_manager::init::write_completions(shell, &cmd, dir.as_deref())
+        }
+        Command::Interactive { json: true, .. } => Err(WtError::user_error(
+            "the interactive picker has no JSON output; use `wt list --json`",
+        )
+        .into()),
+        Command::Interactive {
+            all,
+            by_repo,
+            repo,
+            no_color,
+            query,
+            refresh,
+            recent,
+            ..
+        } => worktree_manager::interactive::run_interactive(
+            all,
+            by_repo,
+            repo.as_deref(),
+            no_color,
+            query.as_deref(),
+            refresh,
+            recent,
+        ),
+        Command::List {
+            json,
+            all,
+            repo,
+            pr,
+            ci,
+        } => worktree_manager::list::list_worktrees(json, all, repo.as_deref(), pr, ci),
         Command::Add {
             branch,
             path,
+            force_path,
             track,
+            remote,
             beads,
+            cd,
+            open,
             json,
             quiet,
-        } => match branch {
-            Some(b) => {
-                crate::add::add_worktree(&b, path.as_deref(), track.as_deref(), beads, json, quiet)
+        } => {
+            let target = match branch {
+                Some(b) => {
+                    let track = match track {
+                        Some(track) => Some(track),
+                        None => worktree_manager::add::tracking_remote(
+                            &worktree_manager::git::repo_root(None)?,
+                            &b,
+                            remote.as_deref(),
+                        )?,
+                    };
+                    worktree_manager::add::add_worktree(
+                        &b,
+                        path.as_deref(),
+                        track.as_deref(),
+                        beads,
+                        force_path,
+                        json,
+                        quiet,
+                    )?
+                }
+                None => worktree_manager::add::interactive_add(
+                    path.as_deref(),
+                    track.as_deref(),
+                    remote.as_deref(),
+                    beads,
+                    force_path,
+                    json,
+                    quiet,
+                )?,
+            };
+            if let Some(name) = open {
+                worktree_manager::editor::open(
+                    &target,
+                    Some(name.as_str()).filter(|n| !n.is_empty()),
+                )?;
             }
-            None => {
-                crate::add::interactive_add(path.as_deref(), track.as_deref(), beads, json, quiet)
+            if cd {
+                worktree_manager::switch::cd(target.canonicalize().unwrap_or(target))?;
             }
-        },
+            Ok(())
+        }
+        Command::Clone {
+            url,
+            dir,
+            json,
+            quiet,
+        } => worktree_manager::clone::clone(&url, dir.as_deref(), json, quiet),
+        Command::Migrate { force, json } => worktree_manager::migrate::migrate(force, json),
         Command::Remove {
             target,
+            current,
             force,
             json,
             quiet,
         } => match target {
-            Some(t) => crate::remove::remove_worktree(&t, force, json, quiet),
-            None => crate::remove::interactive_remove(force, json, quiet),
+            Some(t) => worktree_manager::remove::remove_worktree(&t, force, json, quiet),
+            None if current => worktree_manager::remove::remove_worktree(".", force, json, quiet),
+            None => worktree_manager::remove::interactive_remove(force, json, quiet),
         },
-        Command::Prune { json, quiet } => {
-            crate::prune::prune_worktrees(json, quiet).map_err(|e| anyhow::anyhow!(e))
+        Command::Prune { force, json, quiet } => {
+            worktree_manager::prune::prune_worktrees(force, json, quiet)
+                .map_err(|e| anyhow::anyhow!(e))
+        }
+        Command::Current { json } => worktree_manager::current::show_current(json),
+        Command::Recent { limit, json } => worktree_manager::recent::list_recent(limit, json),
+        Command::Shift { branch, path } => worktree_manager::shift::shift(&branch, path.as_deref()),
+        Command::Note { command } => {
+            use worktree_manager::cli::NoteCommand;
+            match command {
+                NoteCommand::Set {
+                    text,
+                    labels,
+                    worktree,
+                } => {
+                    worktree_manager::note::set_note(worktree.as_deref(), text.as_deref(), &labels)
+                }
+                NoteCommand::Get { worktree, json } => {
+                    worktree_manager::note::get_note(worktree.as_deref(), json)
+                }
+                NoteCommand::Clear { worktree } => {
+                    worktree_manager::note::clear_note(worktree.as_deref())
+                }
+            }
         }
-        Command::Preview { path, json } => {
-            crate::preview::print_preview(std::path::Path::new(&path), json)
+        Command::Switch { target, path_only } => {
+            worktree_manager::switch::switch(&target, path_only)
+        }
+        Command::Main { path_only } => worktree_manager::main_worktree::cd_main(path_only),
+        Command::Last => worktree_manager::recent::cd_last(),
+        Command::Exec { target, command } => worktree_manager::exec::exec(&target, &command),
+        Command::Diff { a, b, stat } => worktree_manager::diff::diff(&a, &b, stat),
+        Command::Cp {
+            target,
+            paths,
+            from,
+            dry_run,
+            force,
+        } => worktree_manager::cp::copy(&target, &paths, from, dry_run, force),
+        Command::Each {
+            all,
+            repo,
+            parallel,
+            json,
+            command,
+        } => worktree_manager::exec::each(&command, all, repo.as_deref(), parallel, json),
+        Command::Fetch {
+            all,
+            repo,
+            jobs,
+            json,
+        } => worktree_manager::fetch::fetch(all, repo.as_deref(), jobs, json),
+        Command::Sync {
+            all,
+            repo,
+            jobs,
+            json,
+        } => worktree_manager::sync::sync(all, repo.as_deref(), jobs, json),
+        Command::Status {
+            all,
+            repo,
+            jobs,
+            json,
+        } => worktree_manager::status::show_status(all, repo.as_deref(), jobs, json),
+        Command::Doctor { json } => worktree_manager::doctor::doctor(json),
+        Command::Clean {
+            dry_run,
+            force,
+            delete_branches,
+            no_fetch,
+            json,
+        } => worktree_manager::clean::clean(dry_run, force, delete_branches, no_fetch, json),
+        Command::Gc {
+            dry_run,
+            force,
+            caches,
+            json,
+        } => worktree_manager::gc::gc(dry_run, force, caches, json),
+        Command::Tmux { target } => worktree_manager::tmux::open_target(&target),
+        Command::Zellij { target } => worktree_manager::zellij::open_target(&target),
+        Command::Code { target } => worktree_manager::vscode::open_target(&target),
+        Command::Open { target, with } => worktree_manager::launcher::open_target(&target, &with),
+        Command::Browse {
+            target,
+            compare,
+            pr,
+            print,
+        } => {
+            use worktree_manager::browse::View;
+            let view = if compare {
+                View::Compare
+            } else if pr {
+                View::PullRequest
+            } else {
+                View::Branch
+            };
+            worktree_manager::browse::browse(&target, view, print)
+        }
+        Command::Repo { command } => {
+            use worktree_manager::cli::RepoCommand;
+            match command {
+                RepoCommand::Add { paths } => worktree_manager::repo::add_repos(&paths),
+                RepoCommand::Remove { paths } => worktree_manager::repo::remove_repos(&paths),
+                RepoCommand::List { json } => worktree_manager::repo::list_repos(json),
+            }
+        }
+        Command::Workspace { command } => {
+            use worktree_manager::cli::WorkspaceCommand;
+            match command {
+                WorkspaceCommand::Generate { output, json } => {
+                    worktree_manager::vscode::generate(output.as_deref(), json)
+                }
+            }
+        }
+        Command::Preview {
+            target,
+            path,
+            json,
+            no_color,
+        } => {
+            let path = match (path, target) {
+                (Some(p), _) => std::path::PathBuf::from(p),
+                (None, Some(t)) => worktree_manager::preview::resolve_target(&t)?,
+                (None, None) => unreachable!("clap requires a target or --path"),
+            };
+            worktree_manager::preview::print_preview(&path, json, no_color)
         }
 
+        Command::Schema { command } => worktree_manager::schema::show_schema(&command),
+        Command::Man { command, dir } => worktree_manager::man::man(&command, dir.as_deref()),
+        Command::SelfUpdate { check } => worktree_manager::self_update::self_update(check),
         Command::Config { paths } => {
-            let mut config = crate::config::load()?;
+            let mut config = worktree_manager::config::load()?;
             config.auto_discovery.paths = paths.clone();
-            crate::config::save(&config)?;
+            config
+                .auto_discovery
+                .validate()
+                .map_err(|e| WtError::user_error(e.to_string()))?;
+            worktree_manager::config::save(&config)?;
             eprintln!("Auto-discovery paths configured:");
             for path in &paths {
                 eprintln!("  {}", path);
@@ -124,18 +392,55 @@ fn run() -> Result<()> {
             Ok(())
         }
         Command::Agent { command } => {
-            use crate::cli::AgentCommand;
+            use worktree_manager::cli::AgentCommand;
             match command {
-                AgentCommand::Context { json } => {
-                    crate::agent::show_context(json).map_err(|e| anyhow::anyhow!(e))
+                AgentCommand::Context {
+                    json,
+                    no_pr,
+                    brief,
+                    max_tokens,
+                } => {
+                    let limits = worktree_manager::agent::ContextLimits {
+                        brief,
+                        max_tokens: max_tokens.map(|n| n as usize),
+                    };
+                    worktree_manager::agent::show_context(json, !no_pr, limits)
+                        .map_err(|e| anyhow::anyhow!(e))
                 }
                 AgentCommand::Status { json } => {
-                    crate::agent::show_status(json).map_err(|e| anyhow::anyhow!(e))
+                    worktree_manager::agent::show_status(json).map_err(|e| anyhow::anyhow!(e))
+                }
+                AgentCommand::Diff { json, base } => {
+                    worktree_manager::overlap::show_diff(json, base.as_deref())
+                        .map_err(|e| anyhow::anyhow!(e))
                 }
-                AgentCommand::Onboard => {
-                    crate::agent::show_onboard().map_err(|e| anyhow::anyhow!(e))
+                AgentCommand::Guard { operation, json } => {
+                    worktree_manager::guard::guard(&operation, json).map_err(|e| anyhow::anyhow!(e))
+                }
+                AgentCommand::Watch { interval, poll } => worktree_manager::watch::watch(
+                    std::time::Duration::from_secs(interval),
+                    std::time::Duration::from_secs(poll),
+                )
+                .map_err(|e| anyhow::anyhow!(e)),
+                AgentCommand::Onboard { json } => {
+                    worktree_manager::agent::show_onboard(json).map_err(|e| anyhow::anyhow!(e))
                 }
             }
         }
     }
 }
+
+/// Whether `command` runs git, so git should be checked first. `wt doctor`
+/// reports an old git itself.
+fn needs_git(command: &Command) -> bool {
+    !matches!(
+        command,
+        Command::Init { .. }
+            | Command::Completions { .. }
+            | Command::Doctor { .. }
+            | Command::Schema { .. }
+            | Command::Man { .. }
+            | Command::SelfUpdate { .. }
+            | Command::Config { .. }
+    )
+}
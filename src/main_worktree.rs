@@ -0,0 +1,53 @@
+//! `wt main`: jump back to the primary checkout.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::action::{self, ShellAction};
+use crate::error::WtError;
+use crate::{git, hooks, state};
+
+/// Change the shell into the worktree that has the main branch checked out, or
+/// just the path with `path_only`.
+pub fn cd_main(path_only: bool) -> Result<()> {
+    let path = main_worktree_path()?;
+    if path_only {
+        println!("{}", path.display());
+    } else {
+        state::update_or_warn(|s| s.record_visit(&path, state::now()));
+        hooks::post_switch(&path)?;
+        action::emit(ShellAction::Cd(path))?;
+    }
+    Ok(())
+}
+
+/// The worktree with the main branch (see `git::main_branch`) checked out.
+/// Falls back to the main worktree when the main branch isn't checked out.
+pub fn main_worktree_path() -> Result<PathBuf> {
+    let repo_root = git::repo_root(None)?;
+    let worktrees = git::worktrees_porcelain(&repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+
+    if let Some(main) = git::main_branch(&repo_root) {
+        let main_ref = format!("refs/heads/{}", main);
+        if let Some(wt) = worktrees
+            .iter()
+            .find(|wt| wt.branch.as_deref() == Some(main_ref.as_str()))
+        {
+            return Ok(wt.path.clone());
+        }
+        eprintln!(
+            "Branch '{}' isn't checked out in any worktree; using the main worktree",
+            main
+        );
+    }
+
+    // `git worktree list` always lists the main worktree first
+    worktrees
+        .into_iter()
+        .next()
+        .filter(|wt| !wt.bare)
+        .map(|wt| wt.path)
+        .ok_or_else(|| WtError::not_found("no main worktree found").into())
+}
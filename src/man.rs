@@ -0,0 +1,91 @@
+//! `wt man`: man pages generated from the CLI definition, one for `wt` and
+//! one per subcommand (`wt-agent-context.1`), like git's.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_mangen::Man;
+
+use crate::cli::Cli;
+use crate::error::WtError;
+
+/// Print the page for `command` (e.g. `["agent", "context"]`, or `wt` itself
+/// when empty), or write every page to `dir`.
+pub fn man(command: &[String], dir: Option<&Path>) -> Result<()> {
+    let mut root = Cli::command().disable_help_subcommand(true);
+    root.build();
+
+    if let Some(dir) = dir {
+        fs::create_dir_all(dir).map_err(|e| {
+            WtError::io_error_with_source(format!("failed to create {}", dir.display()), e.into())
+        })?;
+        let written = write_pages(&root, dir)?;
+        eprintln!("Wrote {} man pages to {}", written.len(), dir.display());
+        return Ok(());
+    }
+
+    let mut page = &root;
+    for name in command {
+        page = page
+            .get_subcommands()
+            .find(|s| s.get_name() == name && !s.is_hide_set())
+            .ok_or_else(|| {
+                WtError::not_found(format!("no man page for '{}'", command.join(" ")))
+            })?;
+    }
+    let mut out = Vec::new();
+    man_page(page).render(&mut out)?;
+    std::io::stdout().write_all(&out)?;
+    Ok(())
+}
+
+/// Write the pages for `command` and its subcommands to `dir`, returning
+/// their paths.
+fn write_pages(command: &clap::Command, dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    let page = man_page(command);
+    let path = dir.join(page.get_filename());
+    let mut out = Vec::new();
+    page.render(&mut out)?;
+    fs::write(&path, out).map_err(|e| {
+        WtError::io_error_with_source(format!("failed to write {}", path.display()), e.into())
+    })?;
+    written.push(path);
+    for sub in command.get_subcommands().filter(|s| !s.is_hide_set()) {
+        written.extend(write_pages(sub, dir)?);
+    }
+    Ok(written)
+}
+
+/// The page for a (built) command, with wt's version as its source rather
+/// than the subcommand's.
+fn man_page(command: &clap::Command) -> Man {
+    Man::new(command.clone())
+        .source(format!("wt {}", env!("CARGO_PKG_VERSION")))
+        .manual("wt Manual")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_page_per_subcommand() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut root = Cli::command().disable_help_subcommand(true);
+        root.build();
+        let written = write_pages(&root, dir.path()).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+
+        assert_eq!(names[0], "wt.1");
+        assert!(names.contains(&"wt-add.1".to_string()));
+        assert!(names.contains(&"wt-agent-context.1".to_string()));
+        assert!(!names.iter().any(|n| n.contains("help")));
+    }
+}
@@ -0,0 +1,368 @@
+//! `wt migrate`: convert a normal clone into the bare repository plus
+//! worktrees layout that `wt clone` creates.
+
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::{git, process};
+
+/// Result of migrating (for JSON output)
+#[derive(Serialize)]
+struct MigrateResult {
+    success: bool,
+    bare: String,
+    worktree: String,
+    /// Branch checked out in the worktree (`None` on detached HEAD)
+    branch: Option<String>,
+    /// Linked worktrees whose links were repaired
+    repaired: Vec<String>,
+}
+
+/// Move `<repo>/.git` to `<repo>.git`, make it bare and register `<repo>` as
+/// its worktree, keeping the checked-out branch, the index, uncommitted and
+/// untracked files, local branches, stashes and linked worktrees.
+pub fn migrate(force: bool, json: bool) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| WtError::io_error_with_source("failed to get current directory", e.into()))?;
+    migrate_in(&cwd, force, json)
+}
+
+/// [`migrate`] the repository containing `dir`.
+fn migrate_in(dir: &Path, force: bool, json: bool) -> Result<()> {
+    let dirs = git::repo_dirs(dir)?;
+    let worktree_path = dirs.toplevel.canonicalize().unwrap_or(dirs.toplevel);
+    let git_dir = worktree_path.join(".git");
+    let bare_path = {
+        let mut name = worktree_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".git");
+        worktree_path.with_file_name(name)
+    };
+
+    check_migratable(&worktree_path, &git_dir, &bare_path)?;
+
+    let head = process::run_stdout(
+        "git",
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+        Some(&worktree_path),
+    )
+    .ok()
+    .map(|b| b.trim().to_string());
+    let commit = process::run_stdout(
+        "git",
+        &["rev-parse", "--verify", "--quiet", "HEAD"],
+        Some(&worktree_path),
+    )
+    .map(|c| c.trim().to_string())
+    .map_err(|_| WtError::user_error("the repository has no commits yet; nothing to migrate"))?;
+    let linked: Vec<PathBuf> = git::worktrees_porcelain(&worktree_path)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?
+        .into_iter()
+        .skip(1)
+        .filter(|wt| wt.path.is_dir())
+        .map(|wt| wt.path)
+        .collect();
+
+    if !force {
+        // Never ask for an answer to a plan the user can't see (--json), or
+        // where nobody can answer
+        if json || !io::stdin().is_terminal() {
+            return Err(
+                WtError::user_error("not migrating without a terminal to confirm on")
+                    .with_hint("rerun with --force")
+                    .into(),
+            );
+        }
+        eprintln!("This will:");
+        eprintln!(
+            "  move {} to {} and make it a bare repository",
+            git_dir.display(),
+            bare_path.display()
+        );
+        eprintln!(
+            "  register {} as its worktree (on {}), keeping all changes",
+            worktree_path.display(),
+            head.as_deref().unwrap_or("detached HEAD")
+        );
+        if !linked.is_empty() {
+            eprintln!("  repair {} linked worktrees", linked.len());
+        }
+        eprint!("Continue? (y/N): ");
+        io::stderr().flush()?;
+        let mut response = String::new();
+        io::stdin().read_line(&mut response)?;
+        if !matches!(response.trim(), "y" | "Y") {
+            return Err(WtError::cancelled().into());
+        }
+    }
+
+    fs::rename(&git_dir, &bare_path).map_err(|e| {
+        WtError::io_error_with_source(
+            format!(
+                "failed to move {} to {}",
+                git_dir.display(),
+                bare_path.display()
+            ),
+            e.into(),
+        )
+    })?;
+
+    if let Err(e) = convert(
+        &bare_path,
+        &worktree_path,
+        head.as_deref(),
+        &commit,
+        &linked,
+    ) {
+        eprintln!(
+            "Migration failed after moving the git directory. To undo:\n  mv {} {}\n  git -C {} config core.bare false",
+            bare_path.display(),
+            git_dir.display(),
+            worktree_path.display()
+        );
+        return Err(e);
+    }
+
+    if json {
+        let result = MigrateResult {
+            success: true,
+            bare: bare_path.to_string_lossy().to_string(),
+            worktree: worktree_path.to_string_lossy().to_string(),
+            branch: head,
+            repaired: linked
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+        };
+        println!("{}", serde_json::to_string(&result)?);
+    } else {
+        eprintln!(
+            "Migrated; {} is now a worktree of {}",
+            worktree_path.display(),
+            bare_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Refuse layouts and states the migration can't carry over safely.
+fn check_migratable(worktree_path: &Path, git_dir: &Path, bare_path: &Path) -> Result<()> {
+    if !git_dir.is_dir() {
+        return Err(WtError::user_error(
+            "not the main worktree of a normal clone (no .git directory); run from the main worktree",
+        )
+        .into());
+    }
+    if bare_path.exists() {
+        return Err(
+            WtError::user_error(format!("path already exists: {}", bare_path.display())).into(),
+        );
+    }
+    if worktree_path.join(".gitmodules").exists() {
+        return Err(WtError::user_error(
+            "repositories with submodules can't be migrated (their git directories live inside .git)",
+        )
+        .into());
+    }
+    let in_progress = [
+        "MERGE_HEAD",
+        "CHERRY_PICK_HEAD",
+        "REVERT_HEAD",
+        "rebase-merge",
+        "rebase-apply",
+        "BISECT_LOG",
+    ];
+    if let Some(op) = in_progress.iter().find(|op| git_dir.join(op).exists()) {
+        return Err(WtError::user_error(format!(
+            "an operation is in progress ({}); finish or abort it first",
+            op
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Turn the moved git directory into a bare repository and attach the old
+/// working tree to it as a worktree.
+fn convert(
+    bare_path: &Path,
+    worktree_path: &Path,
+    head: Option<&str>,
+    commit: &str,
+    linked: &[PathBuf],
+) -> Result<()> {
+    let git = |args: &[&str]| {
+        process::run("git", args, Some(bare_path))
+            .map_err(|e| WtError::git_error_with_source("failed to convert the repository", e))
+    };
+    git(&["config", "core.bare", "true"])?;
+
+    // `git worktree add` names the worktree entry after the directory, so
+    // create it under a scratch directory with the final name, then move its
+    // `.git` link into the real working tree
+    let name = worktree_path.file_name().unwrap_or_default();
+    let scratch = bare_path.join("wt-migrate");
+    let scratch_worktree = scratch.join(name);
+    let scratch_str = scratch_worktree
+        .to_str()
+        .ok_or_else(|| WtError::io_error("invalid path encoding"))?;
+    match head {
+        Some(branch) => git(&[
+            "worktree",
+            "add",
+            "--quiet",
+            "--no-checkout",
+            scratch_str,
+            branch,
+        ])?,
+        None => git(&[
+            "worktree",
+            "add",
+            "--quiet",
+            "--no-checkout",
+            "--detach",
+            scratch_str,
+            commit,
+        ])?,
+    };
+
+    let io_err = |msg: &str, e: io::Error| WtError::io_error_with_source(msg.to_string(), e.into());
+    fs::rename(scratch_worktree.join(".git"), worktree_path.join(".git"))
+        .map_err(|e| io_err("failed to link the worktree", e))?;
+    fs::remove_dir_all(&scratch).map_err(|e| io_err("failed to remove scratch directory", e))?;
+
+    // Keep staged changes: the old index becomes the worktree's index. The
+    // entry may be suffixed if a linked worktree already uses the name.
+    let admin = fs::read_to_string(worktree_path.join(".git"))
+        .ok()
+        .and_then(|link| git::parse_gitdir(&link))
+        .ok_or_else(|| WtError::git_error("failed to read the new worktree link"))?;
+    fs::rename(bare_path.join("index"), admin.join("index"))
+        .map_err(|e| io_err("failed to move the index", e))?;
+
+    let mut repair = vec!["worktree", "repair"];
+    let worktree_str = worktree_path.to_string_lossy().to_string();
+    let linked: Vec<String> = linked
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    repair.push(&worktree_str);
+    repair.extend(linked.iter().map(String::as_str));
+    git(&repair)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::{git, init_repo};
+
+    fn stdout(dir: &Path, args: &[&str]) -> String {
+        process::run_stdout("git", args, Some(dir)).unwrap()
+    }
+
+    #[test]
+    fn keeps_staged_unstaged_and_untracked_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        init_repo(&app);
+        fs::write(app.join("tracked"), "one\n").unwrap();
+        git(&app, &["add", "tracked"]);
+        git(&app, &["commit", "-q", "-m", "tracked"]);
+        fs::write(app.join("tracked"), "two\n").unwrap();
+        fs::write(app.join("staged"), "").unwrap();
+        git(&app, &["add", "staged"]);
+        fs::write(app.join("untracked"), "").unwrap();
+
+        migrate_in(&app, true, true).unwrap();
+
+        let app = app.canonicalize().unwrap();
+        let bare = app.with_file_name("app.git");
+        assert_eq!(stdout(&bare, &["config", "core.bare"]).trim(), "true");
+        assert!(app.join(".git").is_file());
+        assert_eq!(stdout(&app, &["branch", "--show-current"]).trim(), "main");
+        assert_eq!(
+            stdout(&app, &["status", "--porcelain"]),
+            "A  staged\n M tracked\n?? untracked\n"
+        );
+    }
+
+    #[test]
+    fn repairs_linked_worktrees() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        init_repo(&app);
+        git(
+            &app,
+            &["worktree", "add", "-q", "-b", "feature", "../app-feature"],
+        );
+
+        migrate_in(&app, true, true).unwrap();
+
+        let feature = tmp.path().join("app-feature").canonicalize().unwrap();
+        assert_eq!(
+            stdout(&feature, &["branch", "--show-current"]).trim(),
+            "feature"
+        );
+        let bare = feature.with_file_name("app.git");
+        let worktrees = git::worktrees_porcelain(&bare).unwrap();
+        let paths: Vec<_> = worktrees.iter().map(|wt| wt.path.clone()).collect();
+        assert_eq!(paths, [bare.clone(), bare.with_file_name("app"), feature]);
+    }
+
+    #[test]
+    fn refuses_layouts_it_cant_carry_over() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        init_repo(&app);
+        let git_dir = app.join(".git");
+        let bare = tmp.path().join("app.git");
+        let refusal = |worktree: &Path, git_dir: &Path| {
+            check_migratable(worktree, git_dir, &bare)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(check_migratable(&app, &git_dir, &bare).is_ok());
+
+        git(
+            &app,
+            &["worktree", "add", "-q", "-b", "feature", "../app-feature"],
+        );
+        let feature = tmp.path().join("app-feature");
+        assert!(refusal(&feature, &feature.join(".git")).contains("not the main worktree"));
+
+        fs::create_dir(&bare).unwrap();
+        assert!(refusal(&app, &git_dir).contains("path already exists"));
+        fs::remove_dir(&bare).unwrap();
+
+        fs::write(app.join(".gitmodules"), "").unwrap();
+        assert!(refusal(&app, &git_dir).contains("submodules"));
+        fs::remove_file(app.join(".gitmodules")).unwrap();
+
+        fs::write(git_dir.join("MERGE_HEAD"), "").unwrap();
+        assert!(refusal(&app, &git_dir).contains("in progress (MERGE_HEAD)"));
+    }
+
+    #[test]
+    fn refuses_a_repository_without_commits() {
+        let tmp = tempfile::tempdir().unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        let err = migrate_in(tmp.path(), true, true).unwrap_err();
+        assert!(err.to_string().contains("no commits yet"));
+        assert!(tmp.path().join(".git").is_dir());
+    }
+
+    #[test]
+    fn refuses_to_ask_for_confirmation_with_json() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        init_repo(&app);
+        let err = migrate_in(&app, false, true).unwrap_err();
+        assert!(err.to_string().contains("without a terminal"));
+        assert!(app.join(".git").is_dir());
+    }
+}
@@ -0,0 +1,72 @@
+//! Notes and labels on worktrees (`wt note`), kept in the state file.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::error::WtError;
+use crate::{git, preview, state};
+
+/// Resolve `target` (branch name or path) to a worktree's top-level path, or
+/// the current worktree without a target. Paths are normalized so they match
+/// the keys the picker and `wt add` use.
+fn resolve(target: Option<&str>) -> Result<PathBuf> {
+    match target {
+        Some(target) => git::repo_root(Some(&preview::resolve_target(target)?)),
+        None => git::repo_root(None),
+    }
+}
+
+/// Set the note and/or labels of a worktree. Labels replace existing ones.
+pub fn set_note(target: Option<&str>, text: Option<&str>, labels: &[String]) -> Result<()> {
+    let path = resolve(target)?;
+    let text = text.map(str::trim).filter(|t| !t.is_empty());
+    if text.is_none() && labels.is_empty() {
+        return Err(WtError::user_error("nothing to set; pass a note and/or --label").into());
+    }
+
+    state::update(|s| {
+        if let Some(text) = text {
+            s.set_note(&path, text.to_string());
+        }
+        if !labels.is_empty() {
+            s.set_labels(&path, labels.to_vec());
+        }
+    })
+    .map_err(|e| WtError::io_error_with_source("failed to save note", e))?;
+
+    eprintln!("Updated note for {}", path.display());
+    Ok(())
+}
+
+/// Print the note and labels of a worktree.
+pub fn get_note(target: Option<&str>, json: bool) -> Result<()> {
+    let path = resolve(target)?;
+    let state = state::load();
+    let entry = state.get(&path);
+
+    if json {
+        let value = serde_json::json!({
+            "path": path,
+            "note": entry.and_then(|e| e.note.as_ref()),
+            "labels": entry.map(|e| e.labels.as_slice()).unwrap_or_default(),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    match entry.map(|e| e.annotation()).filter(|a| !a.is_empty()) {
+        Some(annotation) => println!("{}", annotation),
+        None => eprintln!("No note for {}", path.display()),
+    }
+    Ok(())
+}
+
+/// Remove the note and labels of a worktree.
+pub fn clear_note(target: Option<&str>) -> Result<()> {
+    let path = resolve(target)?;
+    state::update(|s| s.clear_note(&path))
+        .map_err(|e| WtError::io_error_with_source("failed to save note", e))?;
+    eprintln!("Cleared note for {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,239 @@
+//! `wt agent onboard --json`: machine-readable metadata for the commands in
+//! the onboarding reference, so agent frameworks can generate tool
+//! definitions. Arguments and flags are read from the CLI definition; output
+//! examples and usage examples are kept here.
+
+use clap::{Arg, CommandFactory};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cli::Cli;
+use crate::error::WtError;
+
+/// A command in the onboarding reference, with an example of its `--json`
+/// output (where it has one) and of its use.
+struct Entry {
+    path: &'static [&'static str],
+    output: Option<&'static str>,
+    examples: &'static [&'static str],
+}
+
+const ENTRIES: &[Entry] = &[
+    Entry {
+        path: &["list"],
+        output: Some(
+            r#"[{"path": "/path", "head": "abc1234...", "branch": "refs/heads/main", "locked": false, "prunable": null, "bare": false}]"#,
+        ),
+        examples: &["wt list --json", "wt list --all --json"],
+    },
+    Entry {
+        path: &["add"],
+        output: Some(r#"{"success": true, "branch": "feature-x", "path": "/path/to/feature-x"}"#),
+        examples: &["wt add feature-x --json --quiet"],
+    },
+    Entry {
+        path: &["remove"],
+        output: Some(
+            r#"{"success": true, "removed": true, "branch": "feature-x", "path": "/path/to/feature-x"}"#,
+        ),
+        examples: &["wt remove feature-x --force --quiet --json"],
+    },
+    Entry {
+        path: &["prune"],
+        output: Some(
+            r#"{"success": true, "pruned": [{"path": "/path", "reason": "gitdir file points to non-existent location"}], "stale_locks": [], "orphaned": [{"path": "/path-gone", "removed": false, "files": 12, "bytes": 48213}]}"#,
+        ),
+        examples: &["wt prune --quiet --json"],
+    },
+    Entry {
+        path: &["agent", "context"],
+        output: Some(
+            r#"{"current_worktree": {"path": "/path", "branch": "main", "head": "abc1234...", "dirty": false, "stashes": 0}, "other_worktrees": [], "repository": {"root": "/path", "total_worktrees": 1}, "beads": {"available": false, "canonical_dir": null}}"#,
+        ),
+        examples: &[
+            "wt agent context --json",
+            "wt agent context --json --max-tokens 500",
+        ],
+    },
+    Entry {
+        path: &["agent", "status"],
+        output: Some(
+            r#"{"current": {"path": "/path", "branch": "main", "dirty": true}, "count": 3}"#,
+        ),
+        examples: &["wt agent status --json"],
+    },
+    Entry {
+        path: &["agent", "diff"],
+        output: Some(
+            r#"{"base": "main", "worktrees": [{"path": "/a", "branch": "a", "insertions": 3, "deletions": 1, "files": [{"path": "src/lib.rs", "insertions": 3, "deletions": 1}]}], "overlaps": [{"path": "src/lib.rs", "worktrees": ["/a", "/b"]}]}"#,
+        ),
+        examples: &["wt agent diff --json"],
+    },
+    Entry {
+        path: &["agent", "guard"],
+        output: Some(
+            r#"{"operation": "remove feature-x", "pass": false, "targets": ["/path/to/feature-x"], "blockers": [{"check": "dirty", "path": "/path/to/feature-x", "message": "2 uncommitted changes would be lost"}]}"#,
+        ),
+        examples: &[
+            "wt agent guard remove feature-x --json",
+            "wt agent guard prune --json",
+        ],
+    },
+    Entry {
+        path: &["agent", "watch"],
+        output: Some(
+            r#"{"time": 1700000000, "event": "dirty_changed", "path": "/path", "dirty": true}"#,
+        ),
+        examples: &["wt agent watch --interval 2"],
+    },
+];
+
+#[derive(Serialize)]
+struct Onboarding {
+    name: &'static str,
+    version: &'static str,
+    commands: Vec<CommandSpec>,
+}
+
+#[derive(Serialize)]
+struct CommandSpec {
+    /// Full invocation, e.g. "wt agent context"
+    name: String,
+    description: String,
+    /// Positional arguments
+    args: Vec<ArgSpec>,
+    flags: Vec<FlagSpec>,
+    /// Example of the `--json` output (one line per event for streams)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_schema: Option<Value>,
+    examples: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct ArgSpec {
+    name: String,
+    description: String,
+    required: bool,
+}
+
+#[derive(Serialize)]
+struct FlagSpec {
+    /// Long form, e.g. "--json"
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short: Option<String>,
+    description: String,
+    /// Placeholder for the flag's value; `None` for switches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    possible_values: Vec<String>,
+}
+
+/// Print the command metadata as pretty JSON.
+pub fn print_json() -> Result<(), WtError> {
+    let onboarding = Onboarding {
+        name: "wt",
+        version: env!("CARGO_PKG_VERSION"),
+        commands: command_specs(),
+    };
+    let json_str = serde_json::to_string_pretty(&onboarding)
+        .map_err(|e| WtError::io_error_with_source("failed to serialize JSON", e.into()))?;
+    println!("{}", json_str);
+    Ok(())
+}
+
+fn command_specs() -> Vec<CommandSpec> {
+    let cli = Cli::command();
+    ENTRIES
+        .iter()
+        .filter_map(|entry| {
+            let command = entry
+                .path
+                .iter()
+                .try_fold(&cli, |command, name| command.find_subcommand(name))?;
+            let visible = || command.get_arguments().filter(|arg| !arg.is_hide_set());
+            Some(CommandSpec {
+                name: format!("wt {}", entry.path.join(" ")),
+                description: command
+                    .get_about()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                args: visible()
+                    .filter(|arg| arg.is_positional())
+                    .map(|arg| ArgSpec {
+                        name: arg.get_id().to_string(),
+                        description: help(arg),
+                        required: arg.is_required_set(),
+                    })
+                    .collect(),
+                flags: visible()
+                    .filter_map(|arg| Some((arg, arg.get_long()?)))
+                    .map(|(arg, long)| flag_spec(arg, long))
+                    .collect(),
+                output_schema: entry.output.and_then(|o| serde_json::from_str(o).ok()),
+                examples: entry.examples.to_vec(),
+            })
+        })
+        .collect()
+}
+
+fn flag_spec(arg: &Arg, long: &str) -> FlagSpec {
+    let takes_value = arg.get_action().takes_values();
+    FlagSpec {
+        name: format!("--{}", long),
+        short: arg.get_short().map(|c| format!("-{}", c)),
+        description: help(arg),
+        value: takes_value.then(|| {
+            arg.get_value_names()
+                .and_then(|names| names.first())
+                .map(ToString::to_string)
+                .unwrap_or_else(|| arg.get_id().to_string().to_uppercase())
+        }),
+        default: arg
+            .get_default_values()
+            .first()
+            .filter(|_| takes_value)
+            .map(|v| v.to_string_lossy().into_owned()),
+        possible_values: arg
+            .get_possible_values()
+            .iter()
+            .filter(|_| takes_value)
+            .filter(|v| !v.is_hide_set())
+            .map(|v| v.get_name().to_string())
+            .collect(),
+    }
+}
+
+fn help(arg: &Arg) -> String {
+    arg.get_help().map(ToString::to_string).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_entry_matches_a_command() {
+        let specs = command_specs();
+        assert_eq!(specs.len(), ENTRIES.len());
+        for (spec, entry) in specs.iter().zip(ENTRIES) {
+            assert!(
+                entry.output.is_none() || spec.output_schema.is_some(),
+                "{}: output example isn't valid JSON",
+                spec.name
+            );
+        }
+
+        let context = specs.iter().find(|s| s.name == "wt agent context").unwrap();
+        assert!(context.flags.iter().any(|f| f.name == "--json"));
+        let max_tokens = context
+            .flags
+            .iter()
+            .find(|f| f.name == "--max-tokens")
+            .unwrap();
+        assert_eq!(max_tokens.value.as_deref(), Some("N"));
+    }
+}
@@ -0,0 +1,306 @@
+//! `wt agent diff`: the files each worktree changed relative to the main
+//! branch, and the files changed in more than one worktree, so agents
+//! working in parallel can spot overlapping edits before they turn into
+//! merge conflicts.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::worktree::Worktree;
+use crate::{git, process};
+
+/// How many worktrees are diffed at once.
+const DIFF_JOBS: usize = 8;
+
+#[derive(Serialize)]
+struct DiffReport {
+    /// Branch the worktrees are compared against
+    base: String,
+    worktrees: Vec<WorktreeChanges>,
+    /// Files changed in more than one worktree
+    overlaps: Vec<Overlap>,
+}
+
+#[derive(Serialize)]
+struct WorktreeChanges {
+    path: String,
+    branch: Option<String>,
+    insertions: usize,
+    deletions: usize,
+    files: Vec<FileChange>,
+    /// Why the worktree couldn't be diffed (e.g. no commits yet)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct FileChange {
+    path: String,
+    /// `None` for binary and untracked files
+    insertions: Option<usize>,
+    deletions: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Overlap {
+    path: String,
+    /// Paths of the worktrees that changed the file
+    worktrees: Vec<String>,
+}
+
+/// Report what each worktree changed since it forked from `base` (default:
+/// the main branch), committed or not, and which files overlap.
+pub fn show_diff(json: bool, base: Option<&str>) -> Result<(), WtError> {
+    let repo_root = git::repo_root(None)?;
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => git::main_branch(&repo_root)
+            .ok_or_else(|| WtError::not_found("could not determine the main branch; use --base"))?,
+    };
+    let commit = format!("{}^{{commit}}", base);
+    if process::run(
+        "git",
+        &["rev-parse", "--verify", "--quiet", &commit],
+        Some(&repo_root),
+    )
+    .is_err()
+    {
+        return Err(WtError::not_found(format!(
+            "unknown base branch '{}'",
+            base
+        )));
+    }
+    let worktrees = git::worktrees_porcelain(&repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+    let worktrees: Vec<_> = worktrees
+        .into_iter()
+        .filter(|wt| !wt.bare && wt.path.is_dir())
+        .collect();
+
+    let changes = process::parallel_map(&worktrees, DIFF_JOBS, |wt| worktree_changes(wt, &base));
+    let report = DiffReport {
+        overlaps: overlaps(&changes),
+        base,
+        worktrees: changes,
+    };
+
+    if json {
+        let json_str = serde_json::to_string(&report)
+            .map_err(|e| WtError::io_error_with_source("failed to serialize JSON", e.into()))?;
+        println!("{}", json_str);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn worktree_changes(wt: &Worktree, base: &str) -> WorktreeChanges {
+    let (files, error) = match changed_files(&wt.path, base) {
+        Ok(files) => (files, None),
+        Err(e) => (
+            Vec::new(),
+            Some(process::error_summary(&format!("{:#}", e))),
+        ),
+    };
+    WorktreeChanges {
+        path: wt.path.display().to_string(),
+        branch: wt.branch_name().map(str::to_string),
+        insertions: files.iter().filter_map(|f| f.insertions).sum(),
+        deletions: files.iter().filter_map(|f| f.deletions).sum(),
+        files,
+        error,
+    }
+}
+
+/// Files changed in the worktree at `path` since its merge base with `base`:
+/// committed, staged and unstaged changes, then untracked files.
+fn changed_files(path: &Path, base: &str) -> anyhow::Result<Vec<FileChange>> {
+    let merge_base = process::run_stdout("git", &["merge-base", base, "HEAD"], Some(path))?;
+    let numstat = process::run_stdout(
+        "git",
+        &["diff", "--numstat", "--no-renames", "-z", merge_base.trim()],
+        Some(path),
+    )?;
+    let untracked = process::run_stdout(
+        "git",
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+        Some(path),
+    )?;
+
+    let mut files = parse_numstat(&numstat);
+    files.extend(
+        untracked
+            .split('\0')
+            .filter(|p| !p.is_empty())
+            .map(|p| FileChange {
+                path: p.to_string(),
+                insertions: None,
+                deletions: None,
+            }),
+    );
+    Ok(files)
+}
+
+/// Parse `git diff --numstat --no-renames -z` output: one
+/// `<added>\t<deleted>\t<path>` record per file, with `-` counts for binary
+/// files.
+fn parse_numstat(output: &str) -> Vec<FileChange> {
+    output
+        .split('\0')
+        .filter_map(|record| {
+            let mut fields = record.splitn(3, '\t');
+            let insertions = fields.next()?;
+            let deletions = fields.next()?;
+            let path = fields.next().filter(|p| !p.is_empty())?;
+            Some(FileChange {
+                path: path.to_string(),
+                insertions: insertions.parse().ok(),
+                deletions: deletions.parse().ok(),
+            })
+        })
+        .collect()
+}
+
+/// Files changed in more than one worktree, sorted by path.
+fn overlaps(changes: &[WorktreeChanges]) -> Vec<Overlap> {
+    let mut by_file: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for wt in changes {
+        for file in &wt.files {
+            by_file.entry(&file.path).or_default().push(wt.path.clone());
+        }
+    }
+    by_file
+        .into_iter()
+        .filter(|(_, worktrees)| worktrees.len() > 1)
+        .map(|(path, worktrees)| Overlap {
+            path: path.to_string(),
+            worktrees,
+        })
+        .collect()
+}
+
+fn print_report(report: &DiffReport) {
+    println!("Changes since {}:", report.base);
+    for wt in &report.worktrees {
+        let label = wt.branch.as_deref().unwrap_or("<detached>");
+        if let Some(error) = &wt.error {
+            println!("  {} @ {}: {}", label, wt.path, error);
+            continue;
+        }
+        let count = wt.files.len();
+        println!(
+            "  {} @ {}: {} file{}, +{} -{}",
+            label,
+            wt.path,
+            count,
+            if count == 1 { "" } else { "s" },
+            wt.insertions,
+            wt.deletions
+        );
+        for file in &wt.files {
+            match (file.insertions, file.deletions) {
+                (Some(ins), Some(del)) => println!("      {} +{} -{}", file.path, ins, del),
+                _ => println!("      {}", file.path),
+            }
+        }
+    }
+
+    if report.overlaps.is_empty() {
+        println!();
+        println!("No files changed in more than one worktree.");
+        return;
+    }
+    let labels: BTreeMap<&str, &str> = report
+        .worktrees
+        .iter()
+        .map(|wt| (wt.path.as_str(), wt.branch.as_deref().unwrap_or(&wt.path)))
+        .collect();
+    println!();
+    println!("Changed in more than one worktree:");
+    for overlap in &report.overlaps {
+        let names: Vec<&str> = overlap
+            .worktrees
+            .iter()
+            .map(|path| labels.get(path.as_str()).copied().unwrap_or(path))
+            .collect();
+        println!("  {}: {}", overlap.path, names.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(path: &str, files: &[&str]) -> WorktreeChanges {
+        WorktreeChanges {
+            path: path.to_string(),
+            branch: None,
+            insertions: 0,
+            deletions: 0,
+            files: files
+                .iter()
+                .map(|f| FileChange {
+                    path: f.to_string(),
+                    insertions: Some(1),
+                    deletions: Some(0),
+                })
+                .collect(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn parses_numstat() {
+        let output = "3\t1\tsrc/main.rs\0-\t-\tlogo.png\0\
+                      1\t0\tdir/with\ttab.txt\0";
+        assert_eq!(
+            parse_numstat(output),
+            vec![
+                FileChange {
+                    path: "src/main.rs".to_string(),
+                    insertions: Some(3),
+                    deletions: Some(1),
+                },
+                FileChange {
+                    path: "logo.png".to_string(),
+                    insertions: None,
+                    deletions: None,
+                },
+                FileChange {
+                    path: "dir/with\ttab.txt".to_string(),
+                    insertions: Some(1),
+                    deletions: Some(0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_files_changed_in_several_worktrees() {
+        let report = [
+            changes("/repo-a", &["src/lib.rs", "README.md"]),
+            changes("/repo-b", &["src/lib.rs"]),
+            changes("/repo-c", &["README.md", "src/lib.rs", "docs.md"]),
+        ];
+        assert_eq!(
+            overlaps(&report),
+            vec![
+                Overlap {
+                    path: "README.md".to_string(),
+                    worktrees: vec!["/repo-a".to_string(), "/repo-c".to_string()],
+                },
+                Overlap {
+                    path: "src/lib.rs".to_string(),
+                    worktrees: vec![
+                        "/repo-a".to_string(),
+                        "/repo-b".to_string(),
+                        "/repo-c".to_string(),
+                    ],
+                },
+            ]
+        );
+    }
+}
@@ -1,119 +1,665 @@
-use std::path::Path;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::git;
-use crate::process;
+use crate::color::{self, Colors};
+use crate::error::WtError;
+use crate::forge::{ChecksState, PullRequest};
+use crate::worktree::find_worktree;
+use crate::{ci, config, forge, git, interactive, process, state};
 
-#[derive(Serialize)]
+/// How long a cached preview stays valid. Edits to tracked files don't touch any
+/// git metadata, so the cache key alone can't detect a worktree turning dirty.
+const CACHE_TTL: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize)]
 struct PreviewOutput {
     repo: String,
     branch: String,
     path: String,
     status: StatusInfo,
+    upstream: Option<git::Upstream>,
+    /// Unix timestamp (seconds) of the last fetch, if the repo was ever fetched
+    last_fetch: Option<u64>,
     recent_commits: Vec<String>,
     changed_files: Vec<String>,
+    /// Stash entries created on this worktree's branch
+    stashes: Vec<git::Stash>,
+    diffstat: Diffstat,
+    /// Submodules and whether they need `git submodule update`
+    submodules: Vec<git::Submodule>,
+    /// The branch's pull request; only looked up when the `pull_request`
+    /// section is enabled
+    #[serde(default)]
+    pull_request: Option<PullRequest>,
+    /// Why the pull request couldn't be looked up (e.g. gh isn't installed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pull_request_error: Option<String>,
+    /// CI status of HEAD; only looked up when the `ci` section is enabled
+    #[serde(default)]
+    ci: Option<ChecksState>,
+    /// Why the CI status couldn't be looked up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ci_error: Option<String>,
+    /// What wt recorded about this worktree (creation, visits, labels);
+    /// filled in after the cache lookup since visits don't change git state
+    #[serde(default)]
+    state: Option<state::WorktreeState>,
+}
+
+/// `git diff --stat` output for uncommitted changes, one line per entry.
+#[derive(Serialize, Deserialize)]
+struct Diffstat {
+    /// Staged changes (index vs HEAD)
+    staged: Vec<String>,
+    /// Unstaged changes (working tree vs index)
+    unstaged: Vec<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 struct StatusInfo {
     branch_line: String,
     dirty: bool,
 }
 
-pub fn print_preview(path: &Path, json: bool) -> Result<()> {
+/// A configurable section of the human-readable preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Status,
+    Commits,
+    ChangedFiles,
+    Diffstat,
+    Stashes,
+    Submodules,
+    PullRequest,
+    Ci,
+}
+
+impl Section {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "status" => Some(Section::Status),
+            "commits" => Some(Section::Commits),
+            "changed_files" => Some(Section::ChangedFiles),
+            "diffstat" => Some(Section::Diffstat),
+            "stashes" => Some(Section::Stashes),
+            "submodules" => Some(Section::Submodules),
+            "pull_request" => Some(Section::PullRequest),
+            "ci" => Some(Section::Ci),
+            _ => None,
+        }
+    }
+}
+
+/// On-disk cache entry for a single worktree's preview.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<P> {
+    key: String,
+    /// Unix timestamp (seconds) when the entry was written
+    created: u64,
+    preview: P,
+}
+
+/// Resolve a preview target (branch name or path) to a worktree path.
+///
+/// Targets are matched against the current repository's worktrees the same way
+/// as `wt remove`, including the question when it's unclear which worktree
+/// was meant. An existing directory that doesn't match is previewed as-is,
+/// so paths into other repositories keep working.
+pub fn resolve_target(target: &str) -> Result<PathBuf> {
+    let target_path = Path::new(target);
+
+    if let Ok(repo_root) = git::repo_root(None) {
+        let worktrees = git::worktrees_porcelain(&repo_root)?;
+        if !target_path.is_dir() {
+            return Ok(interactive::find_or_pick(&worktrees, target)?.path.clone());
+        }
+        if let Ok(wt) = find_worktree(&worktrees, target) {
+            return Ok(wt.path.clone());
+        }
+    }
+
+    if target_path.is_dir() {
+        Ok(target_path.to_path_buf())
+    } else {
+        Err(
+            WtError::not_found(format!("no worktree found matching '{}'", target))
+                .with_target(target)
+                .into(),
+        )
+    }
+}
+
+pub fn print_preview(path: &Path, json: bool, no_color: bool) -> Result<()> {
     let abs_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let dirs = git::repo_dirs(&abs_path).context("not inside a git repository")?;
+
+    // The preview runs once per highlighted fzf line; a broken config file
+    // shouldn't blank the pane, so fall back to defaults.
+    let preview_config = config::load().map(|c| c.preview).unwrap_or_default();
+    let limits = &preview_config.limits;
+    let sections = parse_sections(&preview_config.sections);
+    let forge_sections = ForgeSections {
+        pull_request: sections.contains(&Section::PullRequest),
+        ci: sections.contains(&Section::Ci),
+    };
+
+    // Reuse a recent preview if nothing git-visible changed since it was rendered.
+    let key = cache_key(&dirs).map(|k| format!("{}|{:?}|{:?}", k, limits, forge_sections));
+    let mut preview = match key.as_deref().and_then(|k| load_cached(&abs_path, k)) {
+        Some(preview) => preview,
+        None => {
+            let preview = collect_preview(&abs_path, &dirs, limits, forge_sections);
+            if let Some(key) = key {
+                store_cached(&abs_path, key, &preview);
+            }
+            preview
+        }
+    };
+    let state = state::load();
+    preview.state = state.get(path).or_else(|| state.get(&abs_path)).cloned();
 
+    if json {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+    } else {
+        let colors = Colors::new(color::stdout_enabled(no_color));
+        print_human(&preview, &sections, limits, colors);
+    }
+
+    Ok(())
+}
+
+/// Which opt-in sections that query the forge are enabled.
+#[derive(Debug, Clone, Copy)]
+struct ForgeSections {
+    pull_request: bool,
+    ci: bool,
+}
+
+/// Gather preview data with one `git status`, one `git log`, one `git stash list`,
+/// two `git diff --stat`, (with submodules) one `git submodule status` and the
+/// enabled forge lookups, run concurrently.
+fn collect_preview(
+    abs_path: &Path,
+    dirs: &git::RepoDirs,
+    limits: &config::PreviewLimits,
+    forge_sections: ForgeSections,
+) -> PreviewOutput {
     // Repo name derived from repo root directory name.
-    let repo_root = git::repo_root(Some(&abs_path)).context("not inside a git repository")?;
-    let repo_name = repo_root
+    let repo_name = dirs
+        .toplevel
         .file_name()
         .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_else(|| repo_root.to_string_lossy().to_string());
+        .unwrap_or_else(|| dirs.toplevel.to_string_lossy().to_string());
 
-    // Best-effort: branch from worktree porcelain.
-    let worktrees = git::worktrees_porcelain(&repo_root).unwrap_or_default();
-    let branch = worktrees
-        .iter()
-        .find(|wt| wt.path == abs_path)
-        .and_then(|wt| wt.branch.as_deref())
-        .map(pretty_ref)
-        .unwrap_or_else(|| "(unknown)".to_string());
+    let (status, commits, stashes, staged, unstaged, submodules, (pull_request, checks)) =
+        thread::scope(|scope| {
+            let status = scope.spawn(|| git::worktree_status(abs_path));
+            let commits = scope.spawn(|| {
+                let count = limits.commits.to_string();
+                process::run_stdout(
+                    "git",
+                    &["log", "-n", &count, "--oneline", "--decorate"],
+                    Some(abs_path),
+                )
+            });
+            let stashes = scope.spawn(|| git::stashes(abs_path));
+            let staged = scope.spawn(|| diffstat(abs_path, true, limits.diffstat));
+            let unstaged = scope.spawn(|| diffstat(abs_path, false, limits.diffstat));
+            let submodules = scope.spawn(|| git::submodules(abs_path));
+            let forge = scope.spawn(|| {
+                let pull_request = forge_sections
+                    .pull_request
+                    .then(|| branch_pull_request(abs_path));
+                let checks = forge_sections.ci.then(|| head_checks(abs_path));
+                (pull_request, checks)
+            });
+            (
+                status.join().expect("status thread panicked"),
+                commits.join().expect("log thread panicked"),
+                stashes.join().expect("stash thread panicked"),
+                staged.join().expect("diffstat thread panicked"),
+                unstaged.join().expect("diffstat thread panicked"),
+                submodules.join().expect("submodule thread panicked"),
+                forge.join().expect("forge thread panicked"),
+            )
+        });
 
-    // Status summary.
-    let status = process::run_stdout(
-        "git",
-        &["-C", &abs_path.to_string_lossy(), "status", "-sb"],
-        None,
-    )
-    .unwrap_or_else(|_| "(failed to read status)".to_string());
+    let (branch, status_info, upstream, changed_files) = match status {
+        Ok(status) => (
+            status
+                .branch
+                .clone()
+                .unwrap_or_else(|| "(detached)".to_string()),
+            StatusInfo {
+                branch_line: status.branch_line(),
+                dirty: status.is_dirty(),
+            },
+            status.upstream,
+            status.changes,
+        ),
+        Err(_) => (
+            "(unknown)".to_string(),
+            StatusInfo {
+                branch_line: "(failed to read status)".to_string(),
+                dirty: false,
+            },
+            None,
+            Vec::new(),
+        ),
+    };
 
-    // Recent commits.
-    let commits = process::run_stdout(
-        "git",
-        &[
-            "-C",
-            &abs_path.to_string_lossy(),
-            "log",
-            "-n",
-            "5",
-            "--oneline",
-            "--decorate",
-        ],
-        None,
-    )
-    .unwrap_or_else(|_| "(failed to read log)".to_string());
+    let recent_commits = match commits {
+        Ok(out) => out.lines().map(|s| s.to_string()).collect(),
+        Err(_) => vec!["(failed to read log)".to_string()],
+    };
+
+    // Stashes are repo-wide; keep only the ones made on this branch.
+    let stashes = stashes
+        .into_iter()
+        .filter(|s| s.is_on_branch(&branch))
+        .collect();
 
-    // Changed files summary.
-    let changed = process::run_stdout(
+    let (pull_request, pull_request_error) = match pull_request {
+        Some(Ok(pr)) => (pr, None),
+        Some(Err(e)) => (None, Some(format!("{:#}", e))),
+        None => (None, None),
+    };
+    let (ci, ci_error) = match checks {
+        Some(Ok(checks)) => (checks, None),
+        Some(Err(e)) => (None, Some(format!("{:#}", e))),
+        None => (None, None),
+    };
+
+    PreviewOutput {
+        repo: repo_name,
+        branch,
+        path: abs_path.to_string_lossy().to_string(),
+        status: status_info,
+        upstream,
+        last_fetch: dirs.last_fetch_time().map(unix_secs),
+        recent_commits,
+        changed_files,
+        stashes,
+        diffstat: Diffstat { staged, unstaged },
+        submodules,
+        pull_request,
+        pull_request_error,
+        ci,
+        ci_error,
+        state: None,
+    }
+}
+
+/// The pull request for the branch checked out at `abs_path` (none on
+/// detached HEAD).
+fn branch_pull_request(abs_path: &Path) -> Result<Option<PullRequest>> {
+    let Ok(branch) = process::run_stdout(
         "git",
-        &[
-            "-C",
-            &abs_path.to_string_lossy(),
-            "status",
-            "--porcelain=v1",
-        ],
-        None,
-    )
-    .unwrap_or_else(|_| "".to_string());
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+        Some(abs_path),
+    ) else {
+        return Ok(None);
+    };
+    forge::pull_request(abs_path, branch.trim())
+}
 
-    if json {
-        let status_trimmed = status.trim();
-        let branch_line = status_trimmed.lines().next().unwrap_or("").to_string();
-        let dirty = !changed.trim().is_empty();
-
-        let output = PreviewOutput {
-            repo: repo_name,
-            branch: branch.clone(),
-            path: abs_path.to_string_lossy().to_string(),
-            status: StatusInfo { branch_line, dirty },
-            recent_commits: commits.trim().lines().map(|s| s.to_string()).collect(),
-            changed_files: changed.trim().lines().map(|s| s.to_string()).collect(),
-        };
-        println!("{}", serde_json::to_string_pretty(&output)?);
-    } else {
-        println!("Repo:   {repo_name}");
-        println!("Branch: {branch}");
-        println!("Path:   {}", abs_path.to_string_lossy());
-        println!();
+/// The CI status of the commit checked out at `abs_path`.
+fn head_checks(abs_path: &Path) -> Result<Option<ChecksState>> {
+    let head = process::run_stdout("git", &["rev-parse", "HEAD"], Some(abs_path))?;
+    ci::status(abs_path, head.trim())
+}
+
+/// Run `git diff --stat` for staged (`--cached`) or unstaged changes.
+///
+/// `--stat-count` caps the per-file lines while keeping git's summary line.
+fn diffstat(abs_path: &Path, staged: bool, max_files: usize) -> Vec<String> {
+    let stat_count = format!("--stat-count={}", max_files);
+    let mut args = vec!["diff", "--stat", &stat_count, "--color=never"];
+    if staged {
+        args.push("--cached");
+    }
+    process::run_stdout("git", &args, Some(abs_path))
+        .map(|out| out.lines().map(|s| s.to_string()).collect())
+        .unwrap_or_default()
+}
 
-        print_section("Status", status.trim_end());
-        print_section("Recent commits", commits.trim_end());
+/// Resolve configured section names, warning about (and skipping) unknown ones.
+fn parse_sections(names: &[String]) -> Vec<Section> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let section = Section::from_name(name);
+            if section.is_none() {
+                eprintln!("Warning: unknown preview section '{}' in config", name);
+            }
+            section
+        })
+        .collect()
+}
 
-        if !changed.trim().is_empty() {
-            print_section("Changed files", changed.trim_end());
+fn print_human(
+    preview: &PreviewOutput,
+    sections: &[Section],
+    limits: &config::PreviewLimits,
+    colors: Colors,
+) {
+    // Dirty files in red, commit hashes in yellow.
+    let changed_files: Vec<_> = preview
+        .changed_files
+        .iter()
+        .map(|f| colors.red(f))
+        .collect();
+    let recent_commits: Vec<_> = preview
+        .recent_commits
+        .iter()
+        .map(|line| match line.split_once(' ') {
+            Some((hash, rest)) => format!("{} {}", colors.yellow(hash), rest),
+            None => line.clone(),
+        })
+        .collect();
+
+    println!("Repo:   {}", preview.repo);
+    println!("Branch: {}", colors.green(&preview.branch));
+    println!("Path:   {}", preview.path);
+    match &preview.upstream {
+        Some(up) => println!(
+            "Upstream: {} ({})",
+            up.name,
+            format_divergence(up.ahead, up.behind)
+        ),
+        None => println!("Upstream: (none)"),
+    }
+    if let Some(fetched) = preview.last_fetch {
+        println!(
+            "Fetched: {}",
+            format_age(UNIX_EPOCH + Duration::from_secs(fetched))
+        );
+    }
+    if let Some(state) = &preview.state {
+        let mut parts = Vec::new();
+        if let Some(created) = state.created_at {
+            parts.push(format!(
+                "created {}",
+                format_age(UNIX_EPOCH + Duration::from_secs(created))
+            ));
+        }
+        if let Some(visited) = state.last_visited {
+            parts.push(format!(
+                "visited {} ({} visits)",
+                format_age(UNIX_EPOCH + Duration::from_secs(visited)),
+                state.visit_count
+            ));
+        }
+        if !parts.is_empty() {
+            println!("History: {}", parts.join(", "));
+        }
+        if let Some(note) = &state.note {
+            println!("Note: {}", note);
+        }
+        if !state.labels.is_empty() {
+            println!("Labels: {}", state.labels.join(", "));
         }
     }
+    println!();
 
-    Ok(())
+    for section in sections {
+        match section {
+            Section::Status => {
+                let mut status = preview.status.branch_line.clone();
+                if !changed_files.is_empty() {
+                    status.push('\n');
+                    status.push_str(&truncated(&changed_files, limits.changed_files));
+                }
+                print_section("Status", &status);
+            }
+            Section::Commits => {
+                print_section("Recent commits", &recent_commits.join("\n"));
+            }
+            Section::ChangedFiles => {
+                if !changed_files.is_empty() {
+                    let body = truncated(&changed_files, limits.changed_files);
+                    print_section("Changed files", &body);
+                }
+            }
+            Section::Diffstat => {
+                let Diffstat { staged, unstaged } = &preview.diffstat;
+                if !staged.is_empty() || !unstaged.is_empty() {
+                    let mut lines = Vec::new();
+                    for (label, stat) in [("Staged", staged), ("Unstaged", unstaged)] {
+                        if !stat.is_empty() {
+                            lines.push(format!("{label}:"));
+                            lines.extend(stat.iter().map(|l| color_diffstat_line(l, colors)));
+                        }
+                    }
+                    print_section("Diffstat", &lines.join("\n"));
+                }
+            }
+            Section::Submodules => {
+                if !preview.submodules.is_empty() {
+                    let lines: Vec<_> = preview
+                        .submodules
+                        .iter()
+                        .map(|sub| format_submodule(sub, colors))
+                        .collect();
+                    let pending = preview
+                        .submodules
+                        .iter()
+                        .filter(|sub| sub.state != git::SubmoduleState::Ok)
+                        .count();
+                    let title = if pending == 0 {
+                        format!("Submodules ({})", lines.len())
+                    } else {
+                        format!("Submodules ({}, {} need update)", lines.len(), pending)
+                    };
+                    print_section(&title, &lines.join("\n"));
+                }
+            }
+            Section::Stashes => {
+                if !preview.stashes.is_empty() {
+                    let lines: Vec<_> = preview
+                        .stashes
+                        .iter()
+                        .map(|s| format!("{}: {}", s.reference, s.message))
+                        .collect();
+                    let title = format!("Stashes ({})", preview.stashes.len());
+                    print_section(&title, &truncated(&lines, limits.stashes));
+                }
+            }
+            Section::PullRequest => {
+                let body = match (&preview.pull_request, &preview.pull_request_error) {
+                    (Some(pr), _) => format_pull_request(pr, colors),
+                    (None, Some(error)) => format!("(unavailable: {})", error),
+                    (None, None) => String::new(),
+                };
+                print_section("Pull request", &body);
+            }
+            Section::Ci => {
+                let body = match (preview.ci, &preview.ci_error) {
+                    (Some(checks), _) => color_checks(
+                        checks,
+                        &format!("{} {}", checks.marker(), checks.as_str()),
+                        colors,
+                    ),
+                    (None, Some(error)) => format!("(unavailable: {})", error),
+                    (None, None) => String::new(),
+                };
+                print_section("CI", &body);
+            }
+        }
+    }
+}
+
+/// Pull request lines: number, title and state, then review and checks, then
+/// its URL.
+fn format_pull_request(pr: &PullRequest, colors: Colors) -> String {
+    let state = match pr.state {
+        forge::PrState::Open => colors.green(pr.state.as_str()),
+        forge::PrState::Closed => colors.red(pr.state.as_str()),
+        forge::PrState::Draft | forge::PrState::Merged => pr.state.as_str().to_string(),
+    };
+    let mut lines = vec![format!("#{} {} ({})", pr.number, pr.title, state)];
+    let mut details = Vec::new();
+    if let Some(review) = pr.review {
+        details.push(review.as_str().to_string());
+    }
+    if let Some(checks) = pr.checks {
+        let text = format!("{} checks {}", checks.marker(), checks.as_str());
+        details.push(color_checks(checks, &text, colors));
+    }
+    if !details.is_empty() {
+        lines.push(details.join(", "));
+    }
+    lines.push(pr.url.clone());
+    lines.join("\n")
+}
+
+/// Color `text` green, red or yellow for passing, failing or pending checks.
+fn color_checks(checks: ChecksState, text: &str, colors: Colors) -> String {
+    match checks {
+        ChecksState::Passing => colors.green(text),
+        ChecksState::Failing => colors.red(text),
+        ChecksState::Pending => colors.yellow(text),
+    }
+}
+
+/// Format a submodule line with git's status marker, e.g. "- libs/foo (uninitialized)".
+fn format_submodule(sub: &git::Submodule, colors: Colors) -> String {
+    match sub.state {
+        git::SubmoduleState::Ok => format!("  {}", sub.path),
+        git::SubmoduleState::Uninitialized => {
+            colors.red(&format!("- {} (uninitialized)", sub.path))
+        }
+        git::SubmoduleState::Modified => colors.red(&format!("+ {} (modified)", sub.path)),
+        git::SubmoduleState::Conflict => colors.red(&format!("U {} (conflict)", sub.path)),
+    }
+}
+
+/// Color the `+`/`-` graph of a `git diff --stat` file line green/red.
+fn color_diffstat_line(line: &str, colors: Colors) -> String {
+    let Some((file, graph)) = line.rsplit_once('|') else {
+        // Summary line ("N files changed, ...")
+        return line.to_string();
+    };
+    let plus_start = graph.find(['+', '-']).unwrap_or(graph.len());
+    let (count, bar) = graph.split_at(plus_start);
+    let pluses: String = bar.chars().filter(|c| *c == '+').collect();
+    let minuses: String = bar.chars().filter(|c| *c == '-').collect();
+    format!(
+        "{}|{}{}{}",
+        file,
+        count,
+        colors.green(&pluses),
+        colors.red(&minuses)
+    )
+}
+
+/// Join at most `limit` lines, noting how many were left out.
+fn truncated(lines: &[String], limit: usize) -> String {
+    let mut body = lines
+        .iter()
+        .take(limit)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if lines.len() > limit {
+        body.push_str(&format!("\n... and {} more", lines.len() - limit));
+    }
+    body
+}
+
+/// Build a cache key from HEAD and the mtimes of git files that change when
+/// commits, the index, fetches, or stashes change.
+///
+/// Returns `None` if HEAD can't be read, which disables caching.
+fn cache_key(dirs: &git::RepoDirs) -> Option<String> {
+    let head = fs::read_to_string(dirs.git_dir.join("HEAD")).ok()?;
+    let mtime = |path: PathBuf| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0)
+    };
+
+    Some(format!(
+        "{}|{}|{}|{}|{}",
+        head.trim(),
+        mtime(dirs.git_dir.join("index")),
+        mtime(dirs.git_dir.join("logs/HEAD")),
+        mtime(dirs.common_dir.join("FETCH_HEAD")),
+        mtime(dirs.common_dir.join("logs/refs/stash")),
+    ))
+}
+
+/// Cache file for a worktree: `~/.cache/worktree-manager/preview/<hash>.json`
+fn cache_file(worktree_path: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    worktree_path.hash(&mut hasher);
+    config::cache_dir()
+        .join("preview")
+        .join(format!("{:016x}.json", hasher.finish()))
+}
+
+fn load_cached(worktree_path: &Path, key: &str) -> Option<PreviewOutput> {
+    let content = fs::read_to_string(cache_file(worktree_path)).ok()?;
+    let entry: CacheEntry<PreviewOutput> = serde_json::from_str(&content).ok()?;
+    let age = unix_secs(SystemTime::now()).saturating_sub(entry.created);
+
+    (entry.key == key && age < CACHE_TTL.as_secs()).then_some(entry.preview)
+}
+
+/// Best-effort write; a failed cache write just means the next render recomputes.
+fn store_cached(worktree_path: &Path, key: String, preview: &PreviewOutput) {
+    let path = cache_file(worktree_path);
+    let entry = CacheEntry {
+        key,
+        created: unix_secs(SystemTime::now()),
+        preview,
+    };
+    if let Some(dir) = path.parent()
+        && fs::create_dir_all(dir).is_ok()
+        && let Ok(content) = serde_json::to_string(&entry)
+    {
+        let _ = fs::write(path, content);
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Describe ahead/behind counts, e.g. "up to date" or "2 ahead, 1 behind".
+fn format_divergence(ahead: usize, behind: usize) -> String {
+    match (ahead, behind) {
+        (0, 0) => "up to date".to_string(),
+        (a, 0) => format!("{a} ahead"),
+        (0, b) => format!("{b} behind, needs pull"),
+        (a, b) => format!("{a} ahead, {b} behind, diverged"),
+    }
 }
 
-fn pretty_ref(r: &str) -> String {
-    r.strip_prefix("refs/heads/")
-        .or_else(|| r.strip_prefix("refs/remotes/"))
-        .unwrap_or(r)
-        .to_string()
+/// Format how long ago a timestamp was, e.g. "5m ago" or "3d ago".
+pub fn format_age(time: SystemTime) -> String {
+    let elapsed = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    match elapsed {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", elapsed / 60),
+        3600..86400 => format!("{}h ago", elapsed / 3600),
+        _ => format!("{}d ago", elapsed / 86400),
+    }
 }
 
 fn print_section(title: &str, body: &str) {
@@ -128,3 +674,61 @@ fn print_section(title: &str, body: &str) {
     }
     println!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divergence_descriptions() {
+        assert_eq!(format_divergence(0, 0), "up to date");
+        assert_eq!(format_divergence(3, 0), "3 ahead");
+        assert_eq!(format_divergence(0, 2), "2 behind, needs pull");
+        assert_eq!(format_divergence(1, 4), "1 ahead, 4 behind, diverged");
+    }
+
+    #[test]
+    fn age_buckets() {
+        let now = SystemTime::now();
+        assert_eq!(format_age(now), "just now");
+        assert_eq!(format_age(now - Duration::from_secs(5 * 60)), "5m ago");
+        assert_eq!(format_age(now - Duration::from_secs(3 * 3600)), "3h ago");
+        assert_eq!(format_age(now - Duration::from_secs(2 * 86400)), "2d ago");
+    }
+
+    #[test]
+    fn parses_known_sections_in_order() {
+        let names: Vec<String> = ["commits", "status", "bogus", "stashes", "pull_request"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            parse_sections(&names),
+            vec![
+                Section::Commits,
+                Section::Status,
+                Section::Stashes,
+                Section::PullRequest
+            ]
+        );
+    }
+
+    #[test]
+    fn truncates_long_sections() {
+        let lines: Vec<String> = (1..=4).map(|i| format!("line {i}")).collect();
+        assert_eq!(truncated(&lines, 10), "line 1\nline 2\nline 3\nline 4");
+        assert_eq!(truncated(&lines, 2), "line 1\nline 2\n... and 2 more");
+    }
+
+    #[test]
+    fn colors_diffstat_graph() {
+        let line = " src/main.rs | 5 +++--";
+        assert_eq!(color_diffstat_line(line, Colors::new(false)), line);
+        assert_eq!(
+            color_diffstat_line(line, Colors::new(true)),
+            " src/main.rs | 5 \x1b[32m+++\x1b[0m\x1b[31m--\x1b[0m"
+        );
+        let summary = " 1 file changed, 3 insertions(+), 2 deletions(-)";
+        assert_eq!(color_diffstat_line(summary, Colors::new(true)), summary);
+    }
+}
@@ -1,8 +1,14 @@
-use std::path::Path;
-use std::process::{Command, ExitStatus};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 
+use crate::config::{self, Config, GitConfig};
 use crate::error::WtError;
 
 #[derive(Debug, Clone)]
@@ -15,15 +21,45 @@ pub struct CmdOutput {
 }
 
 pub fn run(program: &str, args: &[&str], cwd: Option<&Path>) -> Result<CmdOutput> {
+    let mut cmd = command(program, args, cwd);
+    checked(program, args, output(&mut cmd))
+}
+
+pub fn run_stdout(program: &str, args: &[&str], cwd: Option<&Path>) -> Result<String> {
+    Ok(run(program, args, cwd)?.stdout)
+}
+
+/// Like [`run`], but pass each line the command prints to `on_line` as it
+/// arrives (see [`stream`]).
+pub fn run_streaming(
+    program: &str,
+    args: &[&str],
+    cwd: Option<&Path>,
+    on_line: &mut dyn FnMut(&str),
+) -> Result<CmdOutput> {
+    let mut cmd = command(program, args, cwd);
+    checked(program, args, stream(&mut cmd, on_line))
+}
+
+fn command(program: &str, args: &[&str], cwd: Option<&Path>) -> Command {
     let mut cmd = Command::new(program);
     cmd.args(args);
 
     if let Some(cwd) = cwd {
         cmd.current_dir(cwd);
     }
+    cmd
+}
 
-    let output = cmd.output().map_err(|e| {
-        WtError::io_error_with_source(format!("failed to execute {}", program), e.into())
+/// Turn a finished command into a [`CmdOutput`], or an error if it couldn't
+/// run, timed out or failed.
+fn checked(program: &str, args: &[&str], output: io::Result<Output>) -> Result<CmdOutput> {
+    let output = output.map_err(|e| {
+        if e.kind() == io::ErrorKind::TimedOut {
+            WtError::timeout(e)
+        } else {
+            WtError::io_error_with_source(format!("failed to execute {}", program), e.into())
+        }
     })?;
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -45,6 +81,625 @@ pub fn run(program: &str, args: &[&str], cwd: Option<&Path>) -> Result<CmdOutput
     })
 }
 
-pub fn run_stdout(program: &str, args: &[&str], cwd: Option<&Path>) -> Result<String> {
-    Ok(run(program, args, cwd)?.stdout)
+/// Run `cmd` to completion, capturing its output, and log it (see [`log_finished`]).
+///
+/// Commands with a timeout in the config (see [`timeout_for`]) are killed
+/// once it passes, failing with [`io::ErrorKind::TimedOut`]. Stdin is null
+/// either way, as with [`Command::output`]. Git runs non-interactively (see
+/// [`git_environment`]).
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    spawn(cmd, None)
+}
+
+/// Like [`output`], but also pass each line the command prints, on stdout or
+/// stderr, to `on_line` as it arrives. Carriage returns end lines too, so
+/// progress meters like git's `Receiving objects:  45%` come through as they
+/// update.
+pub fn stream(cmd: &mut Command, on_line: &mut dyn FnMut(&str)) -> io::Result<Output> {
+    spawn(cmd, Some(on_line))
+}
+
+fn spawn(cmd: &mut Command, on_line: Option<&mut dyn FnMut(&str)>) -> io::Result<Output> {
+    let started = Instant::now();
+    if program_name(cmd) == "git" {
+        git_environment(cmd, &settings().git, |name| {
+            std::env::var_os(name).is_some()
+        });
+    }
+    let timeout = timeout_for(cmd);
+    let result = match (&timeout, on_line) {
+        (None, None) => cmd.output(),
+        (timeout, on_line) => capture(cmd, timeout.as_ref().map(|(_, t)| *t), on_line),
+    };
+    let result = result.map_err(|e| match &timeout {
+        Some((key, timeout)) if e.kind() == io::ErrorKind::TimedOut => io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "`{}` timed out after {}s; raise timeouts.commands.{} in the config",
+                command_line(cmd),
+                timeout.as_secs(),
+                key
+            ),
+        ),
+        _ => e,
+    });
+    log_finished(cmd, started, result.as_ref().map(|output| &output.status));
+    result
+}
+
+/// The config, loaded once for every command spawned (the defaults if it
+/// can't be read; commands that need it report that themselves).
+fn settings() -> &'static Config {
+    static SETTINGS: OnceLock<Config> = OnceLock::new();
+    SETTINGS.get_or_init(|| config::load().unwrap_or_default())
+}
+
+/// Keep git from waiting on a credential prompt nobody sees, unless
+/// `git.prompts` allows it: terminal prompts are off, Git Credential Manager
+/// won't open its dialogs, and without an askpass helper of the user's own,
+/// `true` answers any prompt with nothing so authentication fails at once.
+/// Messages are untranslated (`LC_ALL=C`) for parsing unless `git.localized`.
+fn git_environment(cmd: &mut Command, git: &GitConfig, is_set: impl Fn(&str) -> bool) {
+    if !git.prompts {
+        cmd.env("GIT_TERMINAL_PROMPT", "0");
+        cmd.env("GCM_INTERACTIVE", "never");
+        if cfg!(unix) && !is_set("GIT_ASKPASS") && !is_set("SSH_ASKPASS") {
+            cmd.env("GIT_ASKPASS", "true");
+        }
+    }
+    if !git.localized {
+        cmd.env("LC_ALL", "C");
+    }
+}
+
+/// The name of the program `cmd` runs, without its directory or `.exe`.
+fn program_name(cmd: &Command) -> String {
+    Path::new(cmd.get_program())
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The configured timeout for `cmd` and the key it's configured under: the
+/// git subcommand for git, else the program's name.
+fn timeout_for(cmd: &Command) -> Option<(String, Duration)> {
+    let key = timeout_key(cmd);
+    settings()
+        .timeouts
+        .for_command(&key)
+        .map(|timeout| (key, timeout))
+}
+
+fn timeout_key(cmd: &Command) -> String {
+    let program = program_name(cmd);
+    if program != "git" {
+        return program;
+    }
+    let mut args = cmd.get_args().map(|a| a.to_string_lossy());
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            // Global options that take their value as the next argument
+            "-C" | "-c" | "--git-dir" | "--work-tree" | "--namespace" => {
+                args.next();
+            }
+            a if a.starts_with('-') => {}
+            a => return a.to_string(),
+        }
+    }
+    program
+}
+
+/// Like [`Command::output`], but kill the child if it's still running after
+/// `timeout`, and pass its output to `on_line` line by line. The pipes are
+/// read on background threads so a chatty child can't block on a full pipe.
+fn capture(
+    cmd: &mut Command,
+    timeout: Option<Duration>,
+    mut on_line: Option<&mut dyn FnMut(&str)>,
+) -> io::Result<Output> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Each reader sends chunks as it reads them, then an empty one at the
+    // end. The pipes close when the child exits (or a grandchild holding
+    // them does), so wait for both before waiting for the child itself.
+    let (sender, receiver) = mpsc::channel::<(usize, Vec<u8>)>();
+    let pipes: [Option<Box<dyn Read + Send>>; 2] = [
+        child.stdout.take().map(|p| Box::new(p) as _),
+        child.stderr.take().map(|p| Box::new(p) as _),
+    ];
+    for (index, pipe) in pipes.into_iter().enumerate() {
+        let sender = sender.clone();
+        thread::spawn(move || {
+            if let Some(mut pipe) = pipe {
+                let mut chunk = [0; 8192];
+                while let Ok(n @ 1..) = pipe.read(&mut chunk) {
+                    if sender.send((index, chunk[..n].to_vec())).is_err() {
+                        return;
+                    }
+                }
+            }
+            let _ = sender.send((index, Vec::new()));
+        });
+    }
+    drop(sender);
+
+    let mut buffers = [Vec::new(), Vec::new()];
+    let mut lines = [LineBuffer::default(), LineBuffer::default()];
+    let mut open = 2;
+    while open > 0 {
+        let received = match deadline {
+            Some(deadline) => {
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => receiver.recv().map_err(mpsc::RecvTimeoutError::from),
+        };
+        let (index, chunk) = match received {
+            Ok(received) => received,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                return Err(kill(&mut child, timeout.unwrap_or_default()));
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        if let Some(on_line) = on_line.as_deref_mut() {
+            lines[index].push(&chunk, on_line);
+        }
+        if chunk.is_empty() {
+            open -= 1;
+        }
+        buffers[index].extend_from_slice(&chunk);
+    }
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            let [stdout, stderr] = buffers;
+            return Ok(Output {
+                status,
+                stdout,
+                stderr,
+            });
+        }
+        match deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                return Err(kill(&mut child, timeout.unwrap_or_default()));
+            }
+            Some(_) => thread::sleep(Duration::from_millis(5)),
+            None => {
+                let status = child.wait()?;
+                let [stdout, stderr] = buffers;
+                return Ok(Output {
+                    status,
+                    stdout,
+                    stderr,
+                });
+            }
+        }
+    }
+}
+
+/// Splits a stream into lines at `\n` and `\r`, skipping empty ones.
+#[derive(Default)]
+struct LineBuffer(Vec<u8>);
+
+impl LineBuffer {
+    /// Add `chunk` and pass each line it completes to `on_line`; an empty
+    /// chunk marks the end of the stream and flushes the rest.
+    fn push(&mut self, chunk: &[u8], on_line: &mut dyn FnMut(&str)) {
+        let mut emit = |line: &[u8]| {
+            let line = String::from_utf8_lossy(line);
+            if !line.trim().is_empty() {
+                on_line(line.trim_end());
+            }
+        };
+        if chunk.is_empty() {
+            emit(&self.0);
+            self.0.clear();
+            return;
+        }
+        for &byte in chunk {
+            if byte == b'\n' || byte == b'\r' {
+                emit(&self.0);
+                self.0.clear();
+            } else {
+                self.0.push(byte);
+            }
+        }
+    }
+}
+
+fn kill(child: &mut std::process::Child, timeout: Duration) -> io::Error {
+    let _ = child.kill();
+    let _ = child.wait();
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("timed out after {}s", timeout.as_secs()),
+    )
+}
+
+/// Run `cmd` to completion with inherited stdio, and log it (see [`log_finished`]).
+pub fn status(cmd: &mut Command) -> io::Result<ExitStatus> {
+    let started = Instant::now();
+    let result = cmd.status();
+    log_finished(cmd, started, result.as_ref());
+    result
+}
+
+/// Log a finished command with its arguments, working directory, duration and
+/// exit code at debug level, or why it couldn't be started.
+fn log_finished(cmd: &Command, started: Instant, result: Result<&ExitStatus, &io::Error>) {
+    if !tracing::enabled!(tracing::Level::DEBUG) {
+        return;
+    }
+    let command = command_line(cmd);
+    let cwd = cmd
+        .get_current_dir()
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    let cwd = cwd.display();
+    let duration_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(status) => match status.code() {
+            Some(exit_code) => {
+                tracing::debug!(%command, %cwd, duration_ms, exit_code, "command finished");
+            }
+            None => {
+                tracing::debug!(%command, %cwd, duration_ms, %status, "command killed");
+            }
+        },
+        Err(error) if error.kind() == io::ErrorKind::TimedOut => {
+            tracing::debug!(%command, %cwd, duration_ms, "command timed out");
+        }
+        Err(error) => {
+            tracing::debug!(%command, %cwd, duration_ms, %error, "command failed to start");
+        }
+    }
+}
+
+/// Kinds of shell that run command templates from the config (hooks,
+/// launchers, editors, tmux layouts, the picker's actions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// sh, bash, zsh and friends: `-c`, single quotes
+    Posix,
+    /// Windows' cmd.exe: `/C`, double quotes
+    Cmd,
+    /// PowerShell: `-Command`, single quotes
+    PowerShell,
+}
+
+/// The shell command templates run in, and how to quote values for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandShell {
+    pub program: String,
+    pub kind: ShellKind,
+}
+
+impl CommandShell {
+    /// The shell for this system: `sh` on Unix. On Windows, the shell named
+    /// by `$SHELL` when set (Git Bash and MSYS2 set it), else `%ComSpec%`
+    /// (cmd.exe).
+    pub fn detect() -> Self {
+        Self::for_system(
+            cfg!(windows),
+            std::env::var("SHELL").ok().as_deref(),
+            std::env::var("ComSpec").ok().as_deref(),
+        )
+    }
+
+    fn for_system(windows: bool, shell: Option<&str>, comspec: Option<&str>) -> Self {
+        if !windows {
+            return CommandShell {
+                program: "sh".to_string(),
+                kind: ShellKind::Posix,
+            };
+        }
+        if let Some(shell) = shell.filter(|s| !s.trim().is_empty()) {
+            // MSYS paths like /usr/bin/bash mean nothing to CreateProcess, so
+            // look the shell up on PATH by name
+            let name = shell
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or(shell)
+                .trim_end_matches(".exe")
+                .to_string();
+            let kind = match name.to_ascii_lowercase().as_str() {
+                "pwsh" | "powershell" => ShellKind::PowerShell,
+                "cmd" => ShellKind::Cmd,
+                _ => ShellKind::Posix,
+            };
+            return CommandShell {
+                program: name,
+                kind,
+            };
+        }
+        CommandShell {
+            program: comspec
+                .filter(|c| !c.trim().is_empty())
+                .unwrap_or("cmd.exe")
+                .to_string(),
+            kind: ShellKind::Cmd,
+        }
+    }
+
+    /// A command running `script` in this shell.
+    pub fn command(&self, script: &str) -> Command {
+        let mut cmd = Command::new(&self.program);
+        match self.kind {
+            ShellKind::Posix => {
+                cmd.args(["-c", script]);
+            }
+            ShellKind::PowerShell => {
+                cmd.args(["-NoProfile", "-Command", script]);
+            }
+            ShellKind::Cmd => {
+                cmd.arg("/C");
+                // cmd.exe doesn't parse its command line like other programs,
+                // so pass the script as is rather than escaped as one argument
+                #[cfg(windows)]
+                {
+                    use std::os::windows::process::CommandExt;
+                    cmd.raw_arg(script);
+                }
+                #[cfg(not(windows))]
+                cmd.arg(script);
+            }
+        }
+        cmd
+    }
+
+    /// Quote `value` as a single word for this shell.
+    pub fn quote(&self, value: &str) -> String {
+        match self.kind {
+            ShellKind::Posix => format!("'{}'", value.replace('\'', r"'\''")),
+            ShellKind::PowerShell => format!("'{}'", value.replace('\'', "''")),
+            ShellKind::Cmd => format!("\"{}\"", value.replace('"', "\"\"")),
+        }
+    }
+}
+
+/// The program to spawn for `name`. On Windows, a bare name is looked up on
+/// PATH with each extension in `%PATHEXT%`, so scripts like `npm.cmd` run as
+/// they would from cmd (`Command` alone only finds `.exe` files).
+pub fn program(name: &str) -> PathBuf {
+    if !cfg!(windows) || name.contains(['/', '\\']) || Path::new(name).extension().is_some() {
+        return PathBuf::from(name);
+    }
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into());
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            extensions
+                .split(';')
+                .filter(|ext| !ext.is_empty())
+                .map(move |ext| dir.join(format!("{}{}", name, ext)))
+        })
+        .find(|candidate| candidate.is_file())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// A command running `script` in the [`CommandShell`] for this system.
+pub fn shell(script: &str) -> Command {
+    CommandShell::detect().command(script)
+}
+
+/// Quote `value` for the [`CommandShell`] for this system, for filling in
+/// command templates run with [`shell`].
+pub fn shell_quote(value: &str) -> String {
+    CommandShell::detect().quote(value)
+}
+
+/// `cmd`'s program and arguments as one line, quoting arguments that are
+/// empty or contain whitespace or quotes.
+fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|word| {
+            let word = word.to_string_lossy();
+            if word.is_empty()
+                || word.contains(|c: char| c.is_whitespace() || c == '\'' || c == '"')
+            {
+                format!("'{}'", word.replace('\'', "'\\''"))
+            } else {
+                word.into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The most useful line of a failed command's error (see [`run`]): the first `fatal:` or `error:` line,
+/// or else the last non-empty one.
+pub fn error_summary(message: &str) -> String {
+    let mut lines = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    lines
+        .clone()
+        .find(|line| line.starts_with("fatal:") || line.starts_with("error:"))
+        .or_else(|| lines.next_back())
+        .unwrap_or(message)
+        .to_string()
+}
+
+/// Apply `f` to every item on up to `jobs` threads, returning results in the
+/// order of `items`.
+pub fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    jobs: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..items.len()).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = f(item);
+                    if let Ok(mut results) = results.lock() {
+                        results[index] = Some(result);
+                    }
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_map_keeps_order() {
+        let items: Vec<u64> = (0..20).collect();
+        let doubled = parallel_map(&items, 4, |n| n * 2);
+        assert_eq!(doubled, (0..20).map(|n| n * 2).collect::<Vec<_>>());
+        assert!(parallel_map(&[] as &[u64], 4, |n| *n).is_empty());
+    }
+
+    #[test]
+    fn command_line_quotes_awkward_arguments() {
+        let mut cmd = Command::new("git");
+        cmd.args(["commit", "-m", "it's done", ""]);
+        assert_eq!(command_line(&cmd), r"git commit -m 'it'\''s done' ''");
+    }
+
+    #[test]
+    fn picks_the_shell_for_the_system() {
+        let unix = CommandShell::for_system(false, Some("/bin/zsh"), None);
+        assert_eq!((unix.program.as_str(), unix.kind), ("sh", ShellKind::Posix));
+
+        let cmd = CommandShell::for_system(true, None, Some(r"C:\Windows\system32\cmd.exe"));
+        assert_eq!(cmd.kind, ShellKind::Cmd);
+        assert_eq!(cmd.program, r"C:\Windows\system32\cmd.exe");
+        assert_eq!(
+            CommandShell::for_system(true, None, None).program,
+            "cmd.exe"
+        );
+
+        let git_bash = CommandShell::for_system(true, Some("/usr/bin/bash"), None);
+        assert_eq!(
+            (git_bash.program.as_str(), git_bash.kind),
+            ("bash", ShellKind::Posix)
+        );
+        let pwsh =
+            CommandShell::for_system(true, Some(r"C:\Program Files\PowerShell\7\pwsh.exe"), None);
+        assert_eq!(
+            (pwsh.program.as_str(), pwsh.kind),
+            ("pwsh", ShellKind::PowerShell)
+        );
+    }
+
+    #[test]
+    fn quotes_for_each_shell() {
+        let shell = |kind| CommandShell {
+            program: String::new(),
+            kind,
+        };
+        assert_eq!(shell(ShellKind::Posix).quote("it's"), r"'it'\''s'");
+        assert_eq!(shell(ShellKind::PowerShell).quote("it's"), "'it''s'");
+        assert_eq!(
+            shell(ShellKind::Cmd).quote(r"C:\my repo"),
+            r#""C:\my repo""#
+        );
+    }
+
+    #[test]
+    fn times_out_by_git_subcommand_or_program() {
+        let mut cmd = Command::new("git");
+        cmd.args(["-C", "/repo", "-c", "a=b", "--no-pager", "fetch", "origin"]);
+        assert_eq!(timeout_key(&cmd), "fetch");
+        assert_eq!(timeout_key(Command::new("/usr/bin/gh").arg("pr")), "gh");
+    }
+
+    #[test]
+    fn git_runs_without_prompts_in_the_c_locale() {
+        let env = |git: &GitConfig, askpass: bool| {
+            let mut cmd = Command::new("git");
+            git_environment(&mut cmd, git, |name| askpass && name == "SSH_ASKPASS");
+            cmd.get_envs()
+                .map(|(k, v)| {
+                    let v = v.map(|v| v.to_string_lossy().into_owned());
+                    (k.to_string_lossy().into_owned(), v.unwrap_or_default())
+                })
+                .collect::<std::collections::BTreeMap<_, _>>()
+        };
+
+        let defaults = env(&GitConfig::default(), false);
+        assert_eq!(defaults["GIT_TERMINAL_PROMPT"], "0");
+        assert_eq!(defaults["LC_ALL"], "C");
+        assert_eq!(
+            defaults.get("GIT_ASKPASS").map(String::as_str),
+            cfg!(unix).then_some("true")
+        );
+        assert!(!env(&GitConfig::default(), true).contains_key("GIT_ASKPASS"));
+
+        let opted_out = GitConfig {
+            prompts: true,
+            localized: true,
+        };
+        assert!(env(&opted_out, false).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_commands_that_run_too_long() {
+        let started = Instant::now();
+        let error = capture(
+            Command::new("sleep").arg("10"),
+            Some(Duration::from_millis(200)),
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let output = capture(
+            Command::new("echo").arg("hi"),
+            Some(Duration::from_secs(10)),
+            None,
+        )
+        .unwrap();
+        assert_eq!(output.stdout, b"hi\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn streams_lines_from_both_pipes() {
+        let mut lines = Vec::new();
+        let output = stream(
+            Command::new("sh").args(["-c", "echo one; echo two >&2; printf '10%%\\r50%%'"]),
+            &mut |line| lines.push(line.to_string()),
+        )
+        .unwrap();
+        lines.sort();
+        assert_eq!(lines, ["10%", "50%", "one", "two"]);
+        assert_eq!(output.stderr, b"two\n");
+    }
+
+    #[test]
+    fn error_summary_prefers_fatal_line() {
+        assert_eq!(
+            error_summary("command failed\nstderr:\nfatal: no remote\nPlease make sure\n"),
+            "fatal: no remote"
+        );
+        assert_eq!(error_summary("timed out\n\n"), "timed out");
+    }
 }
@@ -0,0 +1,113 @@
+//! Progress on stderr for operations over many repositories or worktrees: a
+//! bar when stderr is a terminal, otherwise a `[n/total]` line per item so
+//! logs and agents still see how far along it is.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+pub struct Progress {
+    /// Drawn on a terminal; `None` when printing lines or disabled
+    bar: Option<ProgressBar>,
+    enabled: bool,
+    total: usize,
+    done: AtomicUsize,
+}
+
+impl Progress {
+    /// Progress over `total` items, labelled e.g. "Fetching". Nothing is
+    /// shown unless `enabled` (off for `--json`).
+    pub fn new(label: &str, total: usize, enabled: bool) -> Self {
+        let bar = (enabled && draws()).then(|| {
+            let bar = ProgressBar::new(total as u64).with_style(
+                ProgressStyle::with_template("{prefix} [{bar:24}] {pos}/{len} {wide_msg}")
+                    .expect("valid template")
+                    .progress_chars("=> "),
+            );
+            bar.set_prefix(label.to_string());
+            bar.enable_steady_tick(Duration::from_millis(120));
+            bar
+        });
+        Progress {
+            bar,
+            enabled,
+            total,
+            done: AtomicUsize::new(0),
+        }
+    }
+
+    /// A spinner for one long operation, e.g. a clone. Only shown on a
+    /// terminal.
+    pub fn spinner(label: &str, enabled: bool) -> Self {
+        let bar = (enabled && draws()).then(|| {
+            let bar = ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {prefix} {wide_msg}")
+                    .expect("valid template"),
+            );
+            bar.set_prefix(label.to_string());
+            bar.enable_steady_tick(Duration::from_millis(120));
+            bar
+        });
+        Progress {
+            bar,
+            enabled: false,
+            total: 0,
+            done: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether a bar is drawn, so live output (see [`Progress::message`]) is
+    /// worth collecting.
+    pub fn is_live(&self) -> bool {
+        self.bar.is_some()
+    }
+
+    /// Show what's happening now, e.g. a line streamed from git.
+    pub fn message(&self, message: &str) {
+        if let Some(bar) = &self.bar {
+            bar.set_message(message.to_string());
+        }
+    }
+
+    /// Count an item as done and print `line` about it: above the bar, or
+    /// as `[n/total] line` without one.
+    pub fn finish_item(&self, line: &str) {
+        let n = self.done.fetch_add(1, Ordering::SeqCst) + 1;
+        match &self.bar {
+            Some(bar) => {
+                bar.println(line);
+                bar.inc(1);
+            }
+            None if self.enabled => eprintln!("[{}/{}] {}", n, self.total, line),
+            None => {}
+        }
+    }
+
+    /// Count an item as done without printing anything about it.
+    pub fn tick(&self) {
+        self.done.fetch_add(1, Ordering::SeqCst);
+        if let Some(bar) = &self.bar {
+            bar.inc(1);
+        }
+    }
+
+    /// Remove the bar, leaving the lines printed above it.
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Bars need a terminal, and would garble `-v` logs on the same stderr.
+fn draws() -> bool {
+    std::io::stderr().is_terminal() && !tracing::enabled!(tracing::Level::DEBUG)
+}
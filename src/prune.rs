@@ -1,14 +1,22 @@
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
 use serde::Serialize;
+use walkdir::WalkDir;
 
 use crate::error::WtError;
 use crate::git;
 use crate::process;
+use crate::worktree::Worktree;
 
 /// Result of pruning worktrees (for JSON output)
 #[derive(Serialize)]
 struct PruneResult {
     success: bool,
     pruned: Vec<PrunedWorktree>,
+    stale_locks: Vec<StaleLock>,
+    orphaned: Vec<OrphanedDir>,
 }
 
 /// A single pruned worktree entry
@@ -18,13 +26,93 @@ struct PrunedWorktree {
     reason: String,
 }
 
+/// A locked worktree whose directory is gone, which `git worktree prune`
+/// keeps forever
+#[derive(Serialize)]
+struct StaleLock {
+    path: String,
+    /// Whether the lock was removed (so the worktree got pruned)
+    cleared: bool,
+}
+
+/// A worktree directory git no longer knows about
+#[derive(Serialize)]
+struct OrphanedDir {
+    path: String,
+    removed: bool,
+    /// What deleting it throws away: files besides `.git`, and their size
+    files: usize,
+    bytes: u64,
+}
+
+/// Files in a directory, and their total size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Usage {
+    files: usize,
+    bytes: u64,
+}
+
+impl Usage {
+    /// The files under `dir` other than its `.git` file.
+    fn of(dir: &Path) -> Self {
+        WalkDir::new(dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() > 1 || entry.file_name() != ".git")
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .fold(Usage::default(), |usage, entry| Usage {
+                files: usage.files + 1,
+                bytes: usage.bytes + entry.metadata().map_or(0, |m| m.len()),
+            })
+    }
+
+    fn add(self, other: Usage) -> Usage {
+        Usage {
+            files: self.files + other.files,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+
+    /// e.g. "12 files, 3.4 MB"
+    fn describe(self) -> String {
+        let size = match self.bytes {
+            b if b < 1024 => format!("{} B", b),
+            b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+            b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        };
+        format!(
+            "{} file{}, {}",
+            self.files,
+            if self.files == 1 { "" } else { "s" },
+            size
+        )
+    }
+}
+
 /// Prune stale worktrees.
 /// First lists any prunable worktrees, then runs git worktree prune.
+/// Also finds locked worktrees whose directory is gone and directories left
+/// behind by worktrees git has forgotten, and after asking (or with `force`)
+/// unlocks and deletes them.
 /// - json: output result as JSON
 /// - quiet: suppress non-essential output
-pub fn prune_worktrees(json: bool, quiet: bool) -> Result<(), WtError> {
-    let repo_root = git::repo_root(None)?;
-    let worktrees = git::worktrees_porcelain(&repo_root)
+///
+/// Without force, locks and directories are left alone unless the user
+/// agrees at a prompt, which is never shown with json or quiet, or without a
+/// terminal.
+pub fn prune_worktrees(force: bool, json: bool, quiet: bool) -> Result<(), WtError> {
+    prune_worktrees_in(&git::repo_root(None)?, force, json, quiet)
+}
+
+/// [`prune_worktrees`] for the repository at `repo_root`.
+fn prune_worktrees_in(
+    repo_root: &Path,
+    force: bool,
+    json: bool,
+    quiet: bool,
+) -> Result<(), WtError> {
+    let worktrees = git::worktrees_porcelain(repo_root)
         .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
 
     // Filter for stale (prunable) worktrees
@@ -32,38 +120,61 @@ pub fn prune_worktrees(json: bool, quiet: bool) -> Result<(), WtError> {
         .iter()
         .filter(|wt| wt.prunable.is_some())
         .collect();
+    // Git never prunes a locked worktree, even once its directory is gone
+    let stale_locks: Vec<_> = worktrees
+        .iter()
+        .filter(|wt| wt.locked && !wt.bare && !wt.path.exists())
+        .collect();
+    let orphaned: Vec<(PathBuf, Usage)> = find_orphaned_dirs(repo_root, &worktrees)?
+        .into_iter()
+        .map(|path| {
+            let usage = Usage::of(&path);
+            (path, usage)
+        })
+        .collect();
+
+    let mut result = PruneResult {
+        success: true,
+        pruned: vec![],
+        stale_locks: vec![],
+        orphaned: vec![],
+    };
 
-    // Handle case with no stale worktrees
-    if stale_worktrees.is_empty() {
+    // Handle case with nothing to clean up
+    if stale_worktrees.is_empty() && stale_locks.is_empty() && orphaned.is_empty() {
         if json {
-            let result = PruneResult {
-                success: true,
-                pruned: vec![],
-            };
-            println!(
-                "{}",
-                serde_json::to_string(&result).map_err(|e| WtError::io_error_with_source(
-                    "failed to serialize JSON",
-                    e.into()
-                ))?
-            );
+            print_json(&result)?;
         } else if !quiet {
             eprintln!("No stale worktrees found.");
         }
         return Ok(());
     }
 
-    // Print stale worktrees if not quiet and not json
+    // Print what was found if not quiet and not json
     if !quiet && !json {
-        eprintln!("Stale worktrees to prune:");
-        for wt in &stale_worktrees {
-            let reason = wt.prunable.as_ref().unwrap();
-            eprintln!("  - {} ({})", wt.path.display(), reason);
+        if !stale_worktrees.is_empty() {
+            eprintln!("Stale worktrees to prune:");
+            for wt in &stale_worktrees {
+                let reason = wt.prunable.as_ref().unwrap();
+                eprintln!("  - {} ({})", wt.path.display(), reason);
+            }
+        }
+        if !stale_locks.is_empty() {
+            eprintln!("Locked worktrees whose directory is gone:");
+            for wt in &stale_locks {
+                eprintln!("  - {}", wt.path.display());
+            }
+        }
+        if !orphaned.is_empty() {
+            eprintln!("Directories of worktrees git no longer knows about:");
+            for (path, usage) in &orphaned {
+                eprintln!("  - {} ({})", path.display(), usage.describe());
+            }
         }
     }
 
     // Collect info for JSON output before pruning
-    let pruned_info: Vec<PrunedWorktree> = stale_worktrees
+    result.pruned = stale_worktrees
         .iter()
         .map(|wt| PrunedWorktree {
             path: wt.path.display().to_string(),
@@ -71,23 +182,244 @@ pub fn prune_worktrees(json: bool, quiet: bool) -> Result<(), WtError> {
         })
         .collect();
 
+    // Unlocking and deleting directories can't be undone, so ask first, and
+    // only when the user can see the list and answer
+    let leftovers = !stale_locks.is_empty() || !orphaned.is_empty();
+    let can_ask = !json && !quiet && io::stdin().is_terminal();
+    let total = orphaned
+        .iter()
+        .fold(Usage::default(), |total, (_, usage)| total.add(*usage));
+    let clean_up =
+        leftovers && (force || (can_ask && confirm(stale_locks.len(), orphaned.len(), total)?));
+    if leftovers && !force && !can_ask && !json && !quiet {
+        eprintln!(
+            "Not unlocking or deleting anything without a terminal to confirm on; rerun with --force."
+        );
+    }
+
+    for wt in &stale_locks {
+        let path = wt.path.to_string_lossy();
+        let cleared = clean_up && {
+            process::run("git", &["worktree", "unlock", &path], Some(repo_root))
+                .map_err(|e| WtError::git_error_with_source("failed to unlock worktree", e))?;
+            true
+        };
+        if cleared {
+            result.pruned.push(PrunedWorktree {
+                path: path.to_string(),
+                reason: "locked, but its directory is gone".to_string(),
+            });
+        }
+        result.stale_locks.push(StaleLock {
+            path: path.to_string(),
+            cleared,
+        });
+    }
+    for (path, usage) in orphaned {
+        let removed = clean_up && {
+            fs::remove_dir_all(&path).map_err(|e| {
+                WtError::io_error_with_source(
+                    format!("failed to remove {}", path.display()),
+                    e.into(),
+                )
+            })?;
+            true
+        };
+        result.orphaned.push(OrphanedDir {
+            path: path.display().to_string(),
+            removed,
+            files: usage.files,
+            bytes: usage.bytes,
+        });
+    }
+
     // Run git worktree prune
-    process::run("git", &["worktree", "prune"], Some(&repo_root))
-        .map_err(|e| WtError::git_error_with_source("failed to prune worktrees", e))?;
+    if !result.pruned.is_empty() {
+        process::run("git", &["worktree", "prune"], Some(repo_root))
+            .map_err(|e| WtError::git_error_with_source("failed to prune worktrees", e))?;
+    }
 
     if json {
-        let result = PruneResult {
-            success: true,
-            pruned: pruned_info,
-        };
-        println!(
-            "{}",
-            serde_json::to_string(&result)
-                .map_err(|e| WtError::io_error_with_source("failed to serialize JSON", e.into()))?
-        );
+        print_json(&result)?;
     } else if !quiet {
-        eprintln!("Pruned stale worktrees.");
+        if !result.pruned.is_empty() {
+            eprintln!("Pruned stale worktrees.");
+        }
+        let removed = result.orphaned.iter().filter(|dir| dir.removed).count();
+        if removed > 0 {
+            eprintln!("Removed {}.", directories(removed));
+        }
     }
 
     Ok(())
 }
+
+fn print_json(result: &PruneResult) -> Result<(), WtError> {
+    println!(
+        "{}",
+        serde_json::to_string(result)
+            .map_err(|e| WtError::io_error_with_source("failed to serialize JSON", e.into()))?
+    );
+    Ok(())
+}
+
+fn directories(count: usize) -> String {
+    format!("{} director{}", count, if count == 1 { "y" } else { "ies" })
+}
+
+fn confirm(locks: usize, dirs: usize, usage: Usage) -> Result<bool, WtError> {
+    let mut actions = vec![];
+    if locks > 0 {
+        actions.push(format!(
+            "unlock {} worktree{}",
+            locks,
+            if locks == 1 { "" } else { "s" }
+        ));
+    }
+    if dirs > 0 {
+        actions.push(format!(
+            "delete {} ({})",
+            directories(dirs),
+            usage.describe()
+        ));
+    }
+    let mut prompt = actions.join(" and ");
+    prompt[..1].make_ascii_uppercase();
+    eprint!("{}? (y/N): ", prompt);
+
+    let read = || -> io::Result<String> {
+        io::stderr().flush()?;
+        let mut response = String::new();
+        io::stdin().read_line(&mut response)?;
+        Ok(response)
+    };
+    let response =
+        read().map_err(|e| WtError::io_error_with_source("failed to read answer", e.into()))?;
+    Ok(matches!(response.trim(), "y" | "Y"))
+}
+
+/// Directories next to the main worktree named like the ones `wt add`
+/// creates (`<repo>-<branch>`, see [`crate::add::calculate_default_path`]),
+/// whose `.git` file points into this repository for a worktree git has
+/// dropped. Directories that aren't worktrees of this repository are never
+/// included.
+fn find_orphaned_dirs(repo_root: &Path, worktrees: &[Worktree]) -> Result<Vec<PathBuf>, WtError> {
+    let main = worktrees
+        .first()
+        .filter(|wt| !wt.bare)
+        .map_or(repo_root, |wt| wt.path.as_path());
+    let (Some(parent), Some(name)) = (main.parent(), main.file_name()) else {
+        return Ok(vec![]);
+    };
+    let prefix = format!("{}-", name.to_string_lossy());
+    let common_dir = git::repo_dirs(repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to find the git directory", e))?
+        .common_dir;
+    let admin_dirs = [
+        common_dir.join("worktrees"),
+        common_dir
+            .canonicalize()
+            .unwrap_or_else(|_| common_dir.clone())
+            .join("worktrees"),
+    ];
+
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Ok(vec![]);
+    };
+    let mut orphaned: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && !worktrees.iter().any(|wt| &wt.path == path))
+        .filter(|path| {
+            fs::read_to_string(path.join(".git"))
+                .ok()
+                .and_then(|content| git::parse_gitdir(&content))
+                .map(|gitdir| path.join(gitdir))
+                .is_some_and(|gitdir| {
+                    admin_dirs.iter().any(|admin| gitdir.starts_with(admin)) && !gitdir.is_dir()
+                })
+        })
+        .collect();
+    orphaned.sort();
+    Ok(orphaned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::{git, init_repo};
+
+    #[test]
+    fn finds_directories_of_forgotten_worktrees_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap().join("repo");
+        init_repo(&root);
+        git(
+            &root,
+            &["worktree", "add", "-q", "../repo-kept", "-b", "kept"],
+        );
+        git(
+            &root,
+            &["worktree", "add", "-q", "../repo-gone", "-b", "gone"],
+        );
+        git(&root, &["worktree", "add", "-q", "../other", "-b", "other"]);
+        for name in ["repo-gone", "other"] {
+            fs::remove_dir_all(root.join(".git/worktrees").join(name)).unwrap();
+        }
+        // Named like a worktree, but not one
+        fs::create_dir(dir.path().join("repo-docs")).unwrap();
+
+        let worktrees = git::worktrees_porcelain(&root).unwrap();
+        let orphaned = find_orphaned_dirs(&root, &worktrees).unwrap();
+        assert_eq!(orphaned, vec![root.with_file_name("repo-gone")]);
+    }
+
+    #[test]
+    fn leaves_locks_and_directories_alone_without_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap().join("repo");
+        init_repo(&root);
+        git(
+            &root,
+            &["worktree", "add", "-q", "../repo-locked", "-b", "locked"],
+        );
+        git(&root, &["worktree", "lock", "../repo-locked"]);
+        let locked = root.with_file_name("repo-locked");
+        fs::remove_dir_all(&locked).unwrap();
+        git(
+            &root,
+            &["worktree", "add", "-q", "../repo-gone", "-b", "gone"],
+        );
+        let gone = root.with_file_name("repo-gone");
+        fs::remove_dir_all(root.join(".git/worktrees/repo-gone")).unwrap();
+        fs::write(gone.join("work.txt"), "uncommitted").unwrap();
+
+        // --quiet, and --json (which never asks either)
+        for (json, quiet) in [(false, true), (true, false)] {
+            prune_worktrees_in(&root, false, json, quiet).unwrap();
+            let worktrees = git::worktrees_porcelain(&root).unwrap();
+            assert!(worktrees.iter().any(|wt| wt.path == locked && wt.locked));
+            assert!(gone.join("work.txt").is_file());
+        }
+    }
+
+    #[test]
+    fn usage_counts_files_besides_the_git_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".git"), "gitdir: /gone\n").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.path().join("README"), "hi").unwrap();
+
+        let usage = Usage::of(dir.path());
+        assert_eq!(
+            usage,
+            Usage {
+                files: 2,
+                bytes: 14
+            }
+        );
+        assert_eq!(usage.describe(), "2 files, 14 B");
+    }
+}
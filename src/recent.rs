@@ -0,0 +1,159 @@
+//! Recently visited worktrees, across all repositories.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::action::{self, ShellAction};
+use crate::error::WtError;
+use crate::worktree::Worktree;
+use crate::{git, hooks, preview, state, worktree};
+
+/// Number of worktrees `wt recent` and `wt interactive --recent` show.
+pub const DEFAULT_LIMIT: usize = 10;
+
+/// A worktree visited through wt, with its repository name.
+#[derive(Debug, Clone)]
+pub struct RecentWorktree {
+    /// Directory name of the repository's main worktree
+    pub repo: String,
+    pub worktree: Worktree,
+    /// Unix timestamp (seconds) of the latest visit
+    pub last_visited: u64,
+    pub visit_count: u32,
+}
+
+/// Up to `limit` recently visited worktrees, most recent first. Worktrees
+/// that no longer exist are skipped.
+pub fn recent_worktrees(limit: usize) -> Vec<RecentWorktree> {
+    state::load()
+        .recently_visited(usize::MAX)
+        .into_iter()
+        .filter_map(|(path, s)| {
+            let (repo, worktree) = lookup(Path::new(path))?;
+            Some(RecentWorktree {
+                repo,
+                worktree,
+                last_visited: s.last_visited?,
+                visit_count: s.visit_count,
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Find the worktree at `path` and the name of its repository.
+fn lookup(path: &Path) -> Option<(String, Worktree)> {
+    if !path.is_dir() {
+        return None;
+    }
+    let repo = worktree::repo_worktrees(path).ok()?;
+    let worktree = repo.worktrees.into_iter().find(|wt| wt.path == path)?;
+    Some((repo.name, worktree))
+}
+
+/// Print the `limit` most recently visited worktrees as a table or JSON.
+pub fn list_recent(limit: usize, json: bool) -> Result<()> {
+    let recent = recent_worktrees(limit);
+
+    if json {
+        let value = serde_json::to_value(
+            recent
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "repo": r.repo,
+                        "path": r.worktree.path,
+                        "branch": r.worktree.branch,
+                        "last_visited": r.last_visited,
+                        "visit_count": r.visit_count,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )?;
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    if recent.is_empty() {
+        eprintln!("No recently visited worktrees. Worktrees opened with `wt` show up here.");
+        return Ok(());
+    }
+
+    let rendered: Vec<(String, String, String, String)> = recent
+        .iter()
+        .map(|r| {
+            (
+                preview::format_age(UNIX_EPOCH + Duration::from_secs(r.last_visited)),
+                r.repo.clone(),
+                r.worktree.branch_name().unwrap_or("(detached)").to_string(),
+                r.worktree.path.display().to_string(),
+            )
+        })
+        .collect();
+
+    let max_age = rendered.iter().map(|r| r.0.len()).max().unwrap_or(0);
+    let max_repo = rendered.iter().map(|r| r.1.len()).max().unwrap_or(0);
+    let max_branch = rendered.iter().map(|r| r.2.len()).max().unwrap_or(0);
+
+    for (age, repo, branch, path) in rendered {
+        println!(
+            "{age:<age_width$}  {repo:<repo_width$}  {branch:<branch_width$}  {path}",
+            age_width = max_age,
+            repo_width = max_repo,
+            branch_width = max_branch
+        );
+    }
+
+    Ok(())
+}
+
+/// Switch back to the previously visited worktree, like `cd -`: change
+/// the shell into the most recently visited worktree other than the current
+/// one and count it as a visit, so running `wt last` again switches back.
+pub fn cd_last() -> Result<()> {
+    let current = git::repo_root(None).ok();
+    let state = state::load();
+    let Some(path) = previous_worktree(&state, current.as_deref()) else {
+        return Err(WtError::not_found(
+            "no previous worktree; open worktrees with `wt` to build up history",
+        )
+        .into());
+    };
+
+    state::update_or_warn(|s| s.record_visit(&path, state::now()));
+    hooks::post_switch(&path)?;
+    action::emit(ShellAction::Cd(path))?;
+    Ok(())
+}
+
+/// The most recently visited existing worktree that isn't `current`.
+fn previous_worktree(state: &state::State, current: Option<&Path>) -> Option<PathBuf> {
+    state
+        .recently_visited(usize::MAX)
+        .into_iter()
+        .map(|(path, _)| PathBuf::from(path))
+        .find(|path| Some(path.as_path()) != current && path.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previous_worktree_skips_current_and_missing() {
+        let dir = std::env::temp_dir();
+        let other = dir.join("wt-recent-test-missing");
+        let mut state = state::State::default();
+        state.record_visit(&dir, 300);
+        state.record_visit(&other, 200);
+        state.record_visit(Path::new("/"), 100);
+
+        assert_eq!(previous_worktree(&state, None), Some(dir.clone()));
+        assert_eq!(
+            previous_worktree(&state, Some(&dir)),
+            Some(PathBuf::from("/"))
+        );
+    }
+}
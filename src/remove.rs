@@ -1,14 +1,21 @@
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use anyhow::Result;
 use serde::Serialize;
 
+use crate::action::{self, ShellAction};
+use crate::config;
 use crate::error::WtError;
 use crate::git;
+use crate::hooks;
+use crate::interactive;
+use crate::main_worktree;
 use crate::process;
-use crate::worktree::Worktree;
+use crate::state;
+use crate::vscode;
+use crate::worktree::{self, Worktree, find_worktree};
 
 /// Result of removing a worktree (for JSON output)
 #[derive(Serialize)]
@@ -21,6 +28,92 @@ struct RemoveResult {
     path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     reason: Option<String>,
+    /// The refusal's `reason` in JSON errors, e.g. "dirty"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    /// Commits that would be lost, when refused for them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unpushed: Option<usize>,
+}
+
+/// A worktree removed by [`delete_worktree`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedWorktree {
+    /// Branch the worktree had checked out (`None` when detached)
+    pub branch: Option<String>,
+    pub path: PathBuf,
+}
+
+/// Why a worktree wasn't removed: the short reason reported with --json, or
+/// `None` for failures that are errors either way.
+struct Refused {
+    reason: Option<String>,
+    error: anyhow::Error,
+    /// Unpushed commits, when those are why
+    unpushed: Option<usize>,
+}
+
+impl Refused {
+    fn new(reason: impl Into<String>, error: impl Into<anyhow::Error>) -> Self {
+        Refused {
+            reason: Some(reason.into()),
+            error: error.into(),
+            unpushed: None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for Refused {
+    fn from(error: anyhow::Error) -> Self {
+        Refused {
+            reason: None,
+            error,
+            unpushed: None,
+        }
+    }
+}
+
+/// What removing a worktree would throw away, shown before asking.
+struct SafetyReport {
+    dirty: usize,
+    unpushed: usize,
+    /// Stashes made on the worktree's branch
+    stashes: usize,
+    locked: bool,
+}
+
+impl SafetyReport {
+    fn of(repo_root: &Path, worktree: &Worktree) -> Self {
+        let branch = worktree.branch_name();
+        SafetyReport {
+            dirty: git::worktree_status(&worktree.path).map_or(0, |s| s.changes.len()),
+            unpushed: unpushed_commits(repo_root, worktree),
+            stashes: branch.map_or(0, |branch| {
+                git::stashes(repo_root)
+                    .iter()
+                    .filter(|s| s.is_on_branch(branch))
+                    .count()
+            }),
+            locked: worktree.locked,
+        }
+    }
+
+    /// One line, e.g. "2 uncommitted changes, 1 unpushed commit, no stashes
+    /// on the branch, not locked".
+    fn summary(&self) -> String {
+        let count = |n: usize, one: &str, many: &str| match n {
+            0 => format!("no {}", many),
+            1 => format!("1 {}", one),
+            n => format!("{} {}", n, many),
+        };
+        format!(
+            "{}, {}, {} on the branch, {}",
+            count(self.dirty, "uncommitted change", "uncommitted changes"),
+            count(self.unpushed, "unpushed commit", "unpushed commits"),
+            count(self.stashes, "stash", "stashes"),
+            if self.locked { "locked" } else { "not locked" }
+        )
+    }
 }
 
 /// Remove a worktree identified by branch name or path.
@@ -29,79 +122,96 @@ struct RemoveResult {
 /// - json: output result as JSON
 /// - quiet: suppress interactive prompts (without force, will not remove)
 pub fn remove_worktree(target: &str, force: bool, json: bool, quiet: bool) -> Result<()> {
-    // Get repo root and list worktrees
     let repo_root = git::repo_root(None)?;
-    let worktrees = git::worktrees_porcelain(&repo_root)?;
+    remove_worktree_in(&repo_root, target, force, json, quiet)
+}
 
-    // Find matching worktree
-    let matching_worktree = find_worktree(&worktrees, target)?;
+/// Remove a worktree of the repository at `repo_root`; see [`remove_worktree`].
+pub fn remove_worktree_in(
+    repo_root: &Path,
+    target: &str,
+    force: bool,
+    json: bool,
+    quiet: bool,
+) -> Result<()> {
+    let worktrees = git::worktrees_porcelain(repo_root)?;
+    let cwd = std::env::current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .ok();
+
+    // Find matching worktree, asking which one was meant when unclear. `.`
+    // is the worktree we're in, even from a subdirectory.
+    let matching_worktree = if target == "." {
+        cwd.as_deref()
+            .and_then(|cwd| worktree::containing(&worktrees, cwd))
+            .ok_or_else(|| WtError::not_found("not inside a worktree of this repository"))?
+    } else if json || quiet {
+        find_worktree(&worktrees, target)?
+    } else {
+        interactive::find_or_pick(&worktrees, target)?
+    };
+
+    // Removing the worktree we're in: run git from (and send the shell back
+    // to) the main worktree, since this directory is about to disappear
+    let inside = cwd
+        .as_deref()
+        .is_some_and(|cwd| cwd.starts_with(&matching_worktree.path));
+    let main_path = if inside {
+        Some(main_worktree::main_worktree_path()?)
+    } else {
+        None
+    };
+    let repo_root = match &main_path {
+        Some(main_path) if repo_root.starts_with(&matching_worktree.path) => main_path.as_path(),
+        _ => repo_root,
+    };
 
     let branch_display = matching_worktree
-        .branch
-        .as_ref()
-        .and_then(|b| b.strip_prefix("refs/heads/"))
+        .branch_name()
         .unwrap_or("<detached>")
         .to_string();
     let path_display = matching_worktree.path.display().to_string();
-
-    // Prevent removal of main/bare worktree
-    if matching_worktree.bare {
-        if json {
-            let result = RemoveResult {
-                success: false,
-                removed: false,
-                branch: Some(branch_display),
-                path: Some(path_display),
-                reason: Some("cannot remove the main worktree (bare repository location)".into()),
-            };
-            println!("{}", serde_json::to_string(&result)?);
-            return Ok(());
+    let print_result = |success: bool, removed: bool, reason: Option<&str>| -> Result<()> {
+        let result = RemoveResult {
+            success,
+            removed,
+            branch: Some(branch_display.clone()),
+            path: Some(path_display.clone()),
+            reason: reason.map(str::to_string),
+            code: None,
+            unpushed: None,
+        };
+        println!("{}", serde_json::to_string(&result)?);
+        Ok(())
+    };
+
+    let refused = |refused: Refused| -> Result<()> {
+        match refused.reason {
+            Some(reason) if json => {
+                let result = RemoveResult {
+                    success: false,
+                    removed: false,
+                    branch: Some(branch_display.clone()),
+                    path: Some(path_display.clone()),
+                    reason: Some(reason),
+                    code: refused
+                        .error
+                        .downcast_ref::<WtError>()
+                        .and_then(|e| e.details().reason.clone()),
+                    unpushed: refused.unpushed,
+                };
+                println!("{}", serde_json::to_string(&result)?);
+                Ok(())
+            }
+            _ => Err(refused.error),
         }
-        return Err(WtError::user_error(
-            "cannot remove the main worktree (bare repository location)",
-        )
-        .into());
-    }
+    };
 
-    // Prevent removal of the main branch worktree
-    if let Some(branch) = &matching_worktree.branch
-        && git::is_main_branch(&repo_root, branch)
-    {
-        if json {
-            let result = RemoveResult {
-                success: false,
-                removed: false,
-                branch: Some(branch_display),
-                path: Some(path_display),
-                reason: Some("cannot remove the main branch worktree".into()),
-            };
-            println!("{}", serde_json::to_string(&result)?);
-            return Ok(());
-        }
-        return Err(WtError::user_error(format!(
-            "cannot remove the main branch worktree (branch '{}')",
-            branch_display
-        ))
-        .into());
+    if let Err(refusal) = check_removable(repo_root, matching_worktree) {
+        return refused(refusal);
     }
-
-    // Check for locked worktrees
-    if matching_worktree.locked {
-        if json {
-            let result = RemoveResult {
-                success: false,
-                removed: false,
-                branch: Some(branch_display),
-                path: Some(path_display),
-                reason: Some("worktree is locked".into()),
-            };
-            println!("{}", serde_json::to_string(&result)?);
-            return Ok(());
-        }
-        return Err(WtError::user_error(format!(
-            "worktree '{}' is locked; use `git worktree unlock` first or `git worktree remove --force`",
-            matching_worktree.path.display()
-        )).into());
+    if !force && let Err(refusal) = check_pushed(repo_root, matching_worktree) {
+        return refused(refusal);
     }
 
     // Confirmation prompt (unless force or quiet)
@@ -109,18 +219,15 @@ pub fn remove_worktree(target: &str, force: bool, json: bool, quiet: bool) -> Re
         if quiet {
             // In quiet mode without force, don't remove (non-interactive)
             if json {
-                let result = RemoveResult {
-                    success: true,
-                    removed: false,
-                    branch: Some(branch_display),
-                    path: Some(path_display),
-                    reason: Some("skipped: --quiet without --force".into()),
-                };
-                println!("{}", serde_json::to_string(&result)?);
+                print_result(true, false, Some("skipped: --quiet without --force"))?;
             }
             return Ok(());
         }
 
+        eprintln!(
+            "  {}",
+            SafetyReport::of(repo_root, matching_worktree).summary()
+        );
         eprint!(
             "Remove worktree '{}' at {}? (y/N): ",
             branch_display, path_display
@@ -132,44 +239,171 @@ pub fn remove_worktree(target: &str, force: bool, json: bool, quiet: bool) -> Re
 
         let response = response.trim();
         if response != "y" && response != "Y" {
-            if json {
-                let result = RemoveResult {
-                    success: true,
-                    removed: false,
-                    branch: Some(branch_display),
-                    path: Some(path_display),
-                    reason: Some("cancelled by user".into()),
-                };
-                println!("{}", serde_json::to_string(&result)?);
-            } else {
-                eprintln!("Cancelled.");
-            }
-            return Ok(());
+            return Err(WtError::cancelled().into());
         }
     }
 
-    // Attempt to remove the worktree
-    let path_str = matching_worktree.path.to_string_lossy();
-    let result = process::run(
+    if let Some(main_path) = &main_path {
+        std::env::set_current_dir(main_path)?;
+    }
+    if let Err(refusal) = remove_checked(repo_root, matching_worktree, force) {
+        return refused(refusal);
+    }
+    if json {
+        print_result(true, true, None)?;
+    } else if !quiet {
+        eprintln!("Worktree removed.");
+    }
+    if let Some(main_path) = main_path
+        && !json
+    {
+        action::emit(ShellAction::Cd(main_path))?;
+    }
+    Ok(())
+}
+
+/// Remove the worktree `target` (a branch name or path) of the repository at
+/// `repo_root` without asking. Refuses the main worktree, the main branch's,
+/// locked worktrees and ones with uncommitted changes or unpushed commits.
+/// Runs the `pre_remove` and `post_remove` hooks, whose output goes to
+/// stderr, and forgets wt's state for the worktree.
+pub fn delete_worktree(repo_root: &Path, target: &str) -> Result<RemovedWorktree> {
+    let worktrees = git::worktrees_porcelain(repo_root)?;
+    let worktree = find_worktree(&worktrees, target)?;
+    check_removable(repo_root, worktree)
+        .and_then(|()| check_pushed(repo_root, worktree))
+        .and_then(|()| remove_checked(repo_root, worktree, false))
+        .map_err(|refused| refused.error)?;
+    Ok(RemovedWorktree {
+        branch: worktree.branch_name().map(str::to_string),
+        path: worktree.path.clone(),
+    })
+}
+
+/// Refuse to remove the main worktree, the main branch's and locked ones.
+fn check_removable(repo_root: &Path, worktree: &Worktree) -> Result<(), Refused> {
+    // Prevent removal of main/bare worktree
+    if worktree.bare {
+        return Err(Refused::new(
+            "cannot remove the main worktree (bare repository location)",
+            WtError::user_error("cannot remove the main worktree (bare repository location)")
+                .with_reason("main_worktree")
+                .with_path(&worktree.path),
+        ));
+    }
+
+    // Prevent removal of the main branch worktree
+    if let Some(branch) = &worktree.branch
+        && git::is_main_branch(repo_root, branch)
+    {
+        return Err(Refused::new(
+            "cannot remove the main branch worktree",
+            WtError::user_error(format!(
+                "cannot remove the main branch worktree (branch '{}')",
+                git::short_branch(branch)
+            ))
+            .with_reason("main_branch")
+            .with_path(&worktree.path)
+            .with_branch(branch),
+        ));
+    }
+
+    // Check for locked worktrees
+    if worktree.locked {
+        return Err(Refused::new(
+            "worktree is locked",
+            WtError::user_error(format!(
+                "worktree '{}' is locked; use `git worktree unlock` first or `git worktree remove --force`",
+                worktree.path.display()
+            ))
+            .with_reason("locked")
+            .with_path(&worktree.path)
+            .with_hint(format!(
+                "run `git worktree unlock {}`, then retry",
+                worktree.path.display()
+            )),
+        ));
+    }
+    Ok(())
+}
+
+/// Refuse to remove a worktree with commits that were never pushed (see
+/// [`unpushed_commits`]); `--force` removes it anyway.
+fn check_pushed(repo_root: &Path, worktree: &Worktree) -> Result<(), Refused> {
+    if !worktree.path.is_dir() {
+        return Ok(());
+    }
+    let unpushed = unpushed_commits(repo_root, worktree);
+    if unpushed == 0 {
+        return Ok(());
+    }
+    let commits = if unpushed == 1 { "commit" } else { "commits" };
+    let hint = match worktree.branch_name() {
+        Some(branch) => format!(
+            "push them (e.g. `git push -u origin {}`), or retry with --force to remove it anyway",
+            branch
+        ),
+        None => "retry with --force to remove it anyway".to_string(),
+    };
+    Err(Refused {
+        unpushed: Some(unpushed),
+        ..Refused::new(
+            format!("worktree has {} unpushed {}", unpushed, commits),
+            WtError::user_error(format!(
+                "worktree has {} unpushed {}; use --force to remove anyway",
+                unpushed, commits
+            ))
+            .with_reason("unpushed")
+            .with_path(&worktree.path)
+            .with_hint(hint),
+        )
+    })
+}
+
+/// Commits on the worktree's HEAD that aren't pushed: the ones ahead of the
+/// branch's upstream, or without an upstream (or on detached HEAD), the ones
+/// on no remote-tracking branch and not on the main branch.
+fn unpushed_commits(repo_root: &Path, worktree: &Worktree) -> usize {
+    match process::run_stdout(
         "git",
-        &["worktree", "remove", path_str.as_ref()],
-        Some(&repo_root),
-    );
+        &["rev-list", "--count", "@{upstream}..HEAD"],
+        Some(&worktree.path),
+    ) {
+        Ok(out) => out.trim().parse().unwrap_or(0),
+        Err(_) => git::unpushed_commits(
+            &worktree.path,
+            "HEAD",
+            git::main_branch(repo_root).as_deref(),
+        ),
+    }
+}
+
+/// Remove a worktree that passed [`check_removable`], with its hooks. With
+/// `force`, uncommitted changes are discarded.
+fn remove_checked(repo_root: &Path, worktree: &Worktree, force: bool) -> Result<(), Refused> {
+    let branch = worktree.branch_name();
+    if let Err(e) = hooks::run(hooks::Event::PreRemove, &worktree.path, branch, repo_root) {
+        return Err(Refused::new(
+            format!("{:#}", e),
+            WtError::user_error(format!("{:#}; worktree kept", e))
+                .with_reason("hook_failed")
+                .with_path(&worktree.path),
+        ));
+    }
+
+    // Attempt to remove the worktree
+    let path_str = worktree.path.to_string_lossy();
+    let mut args = vec!["worktree", "remove", path_str.as_ref()];
+    if force {
+        args.push("--force");
+    }
+    let result = process::run("git", &args, Some(repo_root));
 
     match result {
         Ok(_) => {
-            if json {
-                let result = RemoveResult {
-                    success: true,
-                    removed: true,
-                    branch: Some(branch_display),
-                    path: Some(path_display),
-                    reason: None,
-                };
-                println!("{}", serde_json::to_string(&result)?);
-            } else if !quiet {
-                eprintln!("Worktree removed.");
-            }
+            state::update_or_warn(|s| s.forget(&worktree.path));
+            vscode::refresh(repo_root);
+            hooks::run(hooks::Event::PostRemove, &worktree.path, branch, repo_root)?;
             Ok(())
         }
         Err(e) => {
@@ -177,27 +411,27 @@ pub fn remove_worktree(target: &str, force: bool, json: bool, quiet: bool) -> Re
             let error_msg = format!("{:#}", e);
             if error_msg.contains("uncommitted changes")
                 || error_msg.contains("modified files")
+                || error_msg.contains("modified or untracked files")
                 || error_msg.contains("changes would be lost")
             {
-                if json {
-                    let result = RemoveResult {
-                        success: false,
-                        removed: false,
-                        branch: Some(branch_display),
-                        path: Some(path_display),
-                        reason: Some("worktree has uncommitted changes".into()),
-                    };
-                    println!("{}", serde_json::to_string(&result)?);
-                    return Ok(());
-                }
-                return Err(WtError::user_error(format!(
-                    "worktree has uncommitted changes; use --force to remove anyway\nOriginal error: {}",
-                    error_msg
-                )).into());
+                return Err(Refused::new(
+                    "worktree has uncommitted changes",
+                    WtError::user_error(format!(
+                        "worktree has uncommitted changes; use --force to remove anyway\nOriginal error: {}",
+                        error_msg
+                    ))
+                    .with_reason("dirty")
+                    .with_path(&worktree.path)
+                    .with_hint("retry with --force to remove it anyway"),
+                ));
             }
 
             // Re-throw the original error as GitError
-            Err(WtError::git_error_with_source("failed to remove worktree", e).into())
+            Err(anyhow::Error::from(WtError::git_error_with_source(
+                "failed to remove worktree",
+                e,
+            ))
+            .into())
         }
     }
 }
@@ -236,7 +470,7 @@ pub fn interactive_remove(force: bool, json: bool, quiet: bool) -> Result<()> {
     let candidates = prepare_worktree_candidates(&removable);
 
     // Run fzf to select a worktree
-    let selected = run_fzf_worktree_picker(&candidates)?;
+    let selected = run_fzf_worktree_picker(&candidates, &config::load()?.fzf)?;
 
     match selected {
         Some(line) => {
@@ -244,10 +478,7 @@ pub fn interactive_remove(force: bool, json: bool, quiet: bool) -> Result<()> {
             let branch = line.split("  ").next().unwrap_or(&line).trim();
             remove_worktree(branch, force, json, quiet)
         }
-        None => {
-            // User cancelled
-            Ok(())
-        }
+        None => Err(WtError::cancelled().into()),
     }
 }
 
@@ -288,14 +519,23 @@ fn format_branch_name(wt: &Worktree) -> String {
     }
 }
 
-/// Run fzf to let user pick a worktree to remove.
-fn run_fzf_worktree_picker(candidates: &[String]) -> Result<Option<String>> {
+/// Run fzf to let user pick a worktree to remove, previewing the
+/// highlighted one like the main picker does, so its uncommitted changes and
+/// unpushed commits show before it's gone.
+fn run_fzf_worktree_picker(
+    candidates: &[String],
+    fzf_config: &config::FzfConfig,
+) -> Result<Option<String>> {
     let mut child = Command::new("fzf")
         .args([
             "--height=40%",
             "--layout=reverse",
             "--prompt=Remove> ",
             "--header=Select worktree to remove (Esc to cancel)",
+            "--preview-window",
+            &fzf_config.preview_window,
+            // The path is the second column
+            "--preview=wt preview --path {2}",
         ])
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
@@ -338,140 +578,56 @@ fn run_fzf_worktree_picker(candidates: &[String]) -> Result<Option<String>> {
     }
 }
 
-/// Find a worktree by target (path or branch name).
-/// Returns error if no match or multiple matches found.
-fn find_worktree<'a>(worktrees: &'a [Worktree], target: &str) -> Result<&'a Worktree> {
-    let target_path = Path::new(target);
-    let mut matches = Vec::new();
-
-    for wt in worktrees {
-        // Try exact path match
-        if wt.path == target_path {
-            matches.push(wt);
-            continue;
-        }
-
-        // Try branch name match
-        if let Some(branch) = &wt.branch {
-            let branch_name = branch
-                .strip_prefix("refs/heads/")
-                .or_else(|| branch.strip_prefix("refs/remotes/"))
-                .unwrap_or(branch);
-
-            if branch_name == target {
-                matches.push(wt);
-            }
-        }
-    }
-
-    match matches.len() {
-        0 => Err(WtError::not_found(format!("no worktree found matching '{}'", target)).into()),
-        1 => Ok(matches[0]),
-        _ => {
-            let paths: Vec<_> = matches
-                .iter()
-                .map(|wt| wt.path.display().to_string())
-                .collect();
-            Err(WtError::user_error(format!(
-                "target '{}' matches multiple worktrees:\n  {}",
-                target,
-                paths.join("\n  ")
-            ))
-            .into())
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
-
-    fn make_worktree(path: &str, branch: Option<&str>) -> Worktree {
-        Worktree {
-            path: PathBuf::from(path),
-            head: Some("abc123".to_string()),
-            branch: branch.map(|b| format!("refs/heads/{}", b)),
-            locked: false,
-            prunable: None,
-            bare: false,
-        }
-    }
+    use crate::git::test_support::{git, init_repo};
 
     #[test]
-    fn find_by_exact_path() {
-        let worktrees = vec![
-            make_worktree("/tmp/repo", Some("main")),
-            make_worktree("/tmp/repo-feature", Some("feature")),
-        ];
-
-        let found = find_worktree(&worktrees, "/tmp/repo-feature").unwrap();
-        assert_eq!(found.path, PathBuf::from("/tmp/repo-feature"));
-    }
-
-    #[test]
-    fn find_by_branch_name() {
-        let worktrees = vec![
-            make_worktree("/tmp/repo", Some("main")),
-            make_worktree("/tmp/repo-feature", Some("feature")),
-        ];
-
-        let found = find_worktree(&worktrees, "feature").unwrap();
-        assert_eq!(found.path, PathBuf::from("/tmp/repo-feature"));
-    }
-
-    #[test]
-    fn error_on_no_match() {
-        let worktrees = vec![make_worktree("/tmp/repo", Some("main"))];
-
-        let result = find_worktree(&worktrees, "nonexistent");
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("no worktree found")
+    fn counts_commits_ahead_of_the_upstream_or_on_no_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let origin = dir.path().join("origin");
+        init_repo(&origin);
+        git(&origin, &["branch", "pushed"]);
+        git(dir.path(), &["clone", "-q", "origin", "repo"]);
+        let root = dir.path().join("repo");
+        git(
+            &root,
+            &["worktree", "add", "-q", "../repo-pushed", "pushed"],
         );
-    }
-
-    #[test]
-    fn error_on_multiple_matches() {
-        // Create two worktrees with same branch name (edge case, but possible)
-        let worktrees = vec![
-            Worktree {
-                path: PathBuf::from("/tmp/repo1"),
-                head: Some("abc123".to_string()),
-                branch: Some("refs/heads/feature".to_string()),
-                locked: false,
-                prunable: None,
-                bare: false,
-            },
-            Worktree {
-                path: PathBuf::from("/tmp/repo2"),
-                head: Some("def456".to_string()),
-                branch: Some("refs/heads/feature".to_string()),
-                locked: false,
-                prunable: None,
-                bare: false,
-            },
-        ];
-
-        let result = find_worktree(&worktrees, "feature");
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("multiple worktrees")
+        git(
+            &root,
+            &["worktree", "add", "-q", "../repo-local", "-b", "local"],
         );
+        let worktrees = git::worktrees_porcelain(&root).unwrap();
+        let unpushed = |branch: &str| {
+            let wt = find_worktree(&worktrees, branch).unwrap();
+            unpushed_commits(&root, wt)
+        };
+        assert_eq!(unpushed("pushed"), 0);
+        assert_eq!(unpushed("local"), 0);
+
+        for name in ["repo-pushed", "repo-local"] {
+            git(
+                &dir.path().join(name),
+                &["commit", "-q", "--allow-empty", "-m", "new"],
+            );
+        }
+        assert_eq!(unpushed("pushed"), 1);
+        assert_eq!(unpushed("local"), 1);
     }
 
     #[test]
-    fn strips_refs_heads_prefix() {
-        let worktrees = vec![make_worktree("/tmp/repo", Some("main"))];
-
-        // Should find it by branch name without prefix
-        let found = find_worktree(&worktrees, "main").unwrap();
-        assert_eq!(found.path, PathBuf::from("/tmp/repo"));
+    fn safety_report_counts_what_would_be_lost() {
+        let report = SafetyReport {
+            dirty: 2,
+            unpushed: 1,
+            stashes: 0,
+            locked: false,
+        };
+        assert_eq!(
+            report.summary(),
+            "2 uncommitted changes, 1 unpushed commit, no stashes on the branch, not locked"
+        );
     }
 }
@@ -0,0 +1,134 @@
+//! `wt repo`: an explicit list of repositories for `--all` commands, kept in
+//! `auto_discovery.repos` and merged with the ones found by scanning the
+//! discovery paths.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::config;
+use crate::error::WtError;
+use crate::git;
+
+/// The main worktree of the repository containing `path`, which is what
+/// discovery reports for it too.
+fn repo_of(path: &str) -> Result<PathBuf> {
+    let dir = config::expand_home(path);
+    if !dir.is_dir() {
+        return Err(WtError::not_found(format!("not a directory: {}", dir.display())).into());
+    }
+    let root = git::repo_root(Some(&dir))?;
+    let main = git::worktrees_porcelain(&root)
+        .ok()
+        .and_then(|worktrees| worktrees.into_iter().next())
+        .filter(|wt| !wt.bare)
+        .map(|wt| wt.path);
+    Ok(main.unwrap_or(root))
+}
+
+/// Whether the registry entry `entry` names `repo`.
+fn names(entry: &str, repo: &Path) -> bool {
+    let path = config::expand_home(entry);
+    path == repo || path.canonicalize().is_ok_and(|p| p == repo)
+}
+
+/// Register the repositories containing `paths` (the current one without
+/// any). Already registered ones are left as they are.
+pub fn add_repos(paths: &[String]) -> Result<()> {
+    let current = [".".to_string()];
+    let paths = if paths.is_empty() {
+        &current[..]
+    } else {
+        paths
+    };
+    let repos = paths
+        .iter()
+        .map(|path| repo_of(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut config = config::load()?;
+    let registry = &mut config.auto_discovery.repos;
+    for repo in repos {
+        if registry.iter().any(|entry| names(entry, &repo)) {
+            eprintln!("Already registered: {}", repo.display());
+        } else {
+            registry.push(repo.display().to_string());
+            eprintln!("Registered {}", repo.display());
+        }
+    }
+    config::save(&config)
+}
+
+/// Unregister `paths`, matched as written in the registry or as the
+/// repository they're in. Fails without changing anything if one isn't
+/// registered.
+pub fn remove_repos(paths: &[String]) -> Result<()> {
+    let mut config = config::load()?;
+    let registry = &mut config.auto_discovery.repos;
+
+    for path in paths {
+        let repo = repo_of(path).ok();
+        let before = registry.len();
+        registry.retain(|entry| {
+            entry != path && !repo.as_deref().is_some_and(|repo| names(entry, repo))
+        });
+        if registry.len() == before {
+            return Err(
+                WtError::not_found(format!("'{}' is not a registered repository", path))
+                    .with_candidates(registry.clone())
+                    .into(),
+            );
+        }
+        eprintln!("Unregistered {}", path);
+    }
+    config::save(&config)
+}
+
+/// Print the registered repositories, flagging the ones that no longer
+/// exist.
+pub fn list_repos(json: bool) -> Result<()> {
+    let config = config::load()?;
+    let repos: Vec<(String, bool)> = config
+        .auto_discovery
+        .repos
+        .iter()
+        .map(|entry| (entry.clone(), config::expand_home(entry).is_dir()))
+        .collect();
+
+    if json {
+        let value: Vec<_> = repos
+            .iter()
+            .map(|(path, exists)| serde_json::json!({ "path": path, "exists": exists }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    if repos.is_empty() {
+        eprintln!("No repositories registered. Add one with: wt repo add [path]");
+    }
+    for (path, exists) in repos {
+        if exists {
+            println!("{}", path);
+        } else {
+            println!("{}  (missing)", path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_entries_name_a_repository_however_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().canonicalize().unwrap();
+        let unclean = format!("{}/./", repo.display());
+
+        assert!(names(&repo.display().to_string(), &repo));
+        assert!(names(&unclean, &repo));
+        assert!(!names("/nonexistent/repo", &repo));
+    }
+}
@@ -0,0 +1,156 @@
+//! `wt schema`: JSON Schemas (draft 2020-12) for the output of every `--json`
+//! command and for JSON errors. The schemas live in `src/schemas/`; types
+//! shared between outputs are kept once in `defs.json` and copied into the
+//! `$defs` of each schema that refers to them.
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+use crate::error::WtError;
+
+/// Each schema with the command it describes ("error" for JSON errors,
+/// "version" for `wt --version --json`).
+const SCHEMAS: &[(&str, &str)] = &[
+    ("list", include_str!("schemas/list.json")),
+    ("add", include_str!("schemas/add.json")),
+    ("clone", include_str!("schemas/clone.json")),
+    ("migrate", include_str!("schemas/migrate.json")),
+    ("remove", include_str!("schemas/remove.json")),
+    ("prune", include_str!("schemas/prune.json")),
+    ("preview", include_str!("schemas/preview.json")),
+    ("recent", include_str!("schemas/recent.json")),
+    ("current", include_str!("schemas/current.json")),
+    ("each", include_str!("schemas/each.json")),
+    ("fetch", include_str!("schemas/fetch.json")),
+    ("sync", include_str!("schemas/sync.json")),
+    ("status", include_str!("schemas/status.json")),
+    ("doctor", include_str!("schemas/doctor.json")),
+    ("clean", include_str!("schemas/clean.json")),
+    ("gc", include_str!("schemas/gc.json")),
+    ("note get", include_str!("schemas/note-get.json")),
+    ("repo list", include_str!("schemas/repo-list.json")),
+    (
+        "workspace generate",
+        include_str!("schemas/workspace-generate.json"),
+    ),
+    ("agent context", include_str!("schemas/agent-context.json")),
+    ("agent status", include_str!("schemas/agent-status.json")),
+    ("agent diff", include_str!("schemas/agent-diff.json")),
+    ("agent guard", include_str!("schemas/agent-guard.json")),
+    ("agent onboard", include_str!("schemas/agent-onboard.json")),
+    ("agent watch", include_str!("schemas/agent-watch.json")),
+    ("version", include_str!("schemas/version.json")),
+    ("error", include_str!("schemas/error.json")),
+    ("cancelled", include_str!("schemas/cancelled.json")),
+];
+
+const DEFS: &str = include_str!("schemas/defs.json");
+
+/// The commands that have a schema, e.g. `list` or `agent context`.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    SCHEMAS.iter().map(|(name, _)| *name)
+}
+
+/// Print the schema for `command` (e.g. `["agent", "context"]`), or all
+/// schemas keyed by command when it's empty.
+pub fn show_schema(command: &[String]) -> Result<()> {
+    let output = if command.is_empty() {
+        let all = SCHEMAS
+            .iter()
+            .map(|(name, source)| Ok((name.to_string(), resolve(source)?)))
+            .collect::<Result<Map<_, _>>>()?;
+        Value::Object(all)
+    } else {
+        let name = command.join(" ");
+        let (_, source) = SCHEMAS.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+            let names: Vec<_> = SCHEMAS.iter().map(|(n, _)| *n).collect();
+            WtError::not_found(format!(
+                "no schema for '{}'; available: {}",
+                name,
+                names.join(", ")
+            ))
+        })?;
+        resolve(source)?
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Parse a schema and copy the shared definitions it refers to (directly or
+/// through other definitions) into its `$defs`.
+fn resolve(source: &str) -> Result<Value> {
+    let mut schema: Value = serde_json::from_str(source)?;
+    let defs: Map<String, Value> = serde_json::from_str(DEFS)?;
+
+    let mut needed = Map::new();
+    let mut pending = refs(&schema);
+    while let Some(name) = pending.pop() {
+        if needed.contains_key(&name) {
+            continue;
+        }
+        let def = defs.get(&name).ok_or_else(|| {
+            WtError::config_error(format!("schema refers to unknown definition '{}'", name))
+        })?;
+        pending.extend(refs(def));
+        needed.insert(name, def.clone());
+    }
+    if !needed.is_empty()
+        && let Some(object) = schema.as_object_mut()
+    {
+        object.insert("$defs".to_string(), Value::Object(needed));
+    }
+    Ok(schema)
+}
+
+/// Names of the `#/$defs/...` definitions `value` refers to.
+fn refs(value: &Value) -> Vec<String> {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .flat_map(|(key, value)| match (key.as_str(), value) {
+                ("$ref", Value::String(target)) => target
+                    .strip_prefix("#/$defs/")
+                    .map(str::to_string)
+                    .into_iter()
+                    .collect(),
+                _ => refs(value),
+            })
+            .collect(),
+        Value::Array(items) => items.iter().flat_map(refs).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    #[test]
+    fn every_schema_resolves_and_names_a_command() {
+        let cli = Cli::command();
+        for (name, source) in SCHEMAS {
+            let schema = resolve(source).unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+            assert!(schema["title"].is_string(), "{}: no title", name);
+            if !["error", "cancelled", "version"].contains(name) {
+                let command = name
+                    .split(' ')
+                    .try_fold(&cli, |command, word| command.find_subcommand(word));
+                assert!(command.is_some(), "{}: no such command", name);
+            }
+        }
+    }
+
+    #[test]
+    fn copies_nested_definitions() {
+        let schema = resolve(include_str!("schemas/list.json")).unwrap();
+        let defs = schema["$defs"].as_object().unwrap();
+        // pull_request refers to checks_state, which list also uses directly
+        assert!(defs.contains_key("pull_request"));
+        assert!(defs.contains_key("checks_state"));
+        assert!(defs.contains_key("worktree_state"));
+        assert!(!defs.contains_key("upstream"));
+    }
+}
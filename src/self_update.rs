@@ -0,0 +1,297 @@
+//! `wt self-update`: replace the running binary with the latest GitHub
+//! release, for installs from the release archives. Downloads go through
+//! `curl` and archives are unpacked with `tar`, as on every platform the
+//! releases are built for.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Result;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::WtError;
+use crate::process;
+
+/// The repository whose releases are installed.
+const REPOSITORY: &str = "Radialarray/worktree-manager";
+
+/// The version of the running binary.
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A release as returned by the GitHub API.
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Result<&Asset> {
+        self.assets.iter().find(|a| a.name == name).ok_or_else(|| {
+            WtError::not_found(format!(
+                "release {} has no {} (see {})",
+                self.tag_name, name, self.html_url
+            ))
+            .into()
+        })
+    }
+}
+
+/// Update to the latest release if it's newer than this binary, or with
+/// `check` only report whether there is one.
+pub fn self_update(check: bool) -> Result<()> {
+    let release = latest_release()?;
+    let latest = release.tag_name.trim_start_matches('v');
+    if !is_newer(latest, CURRENT_VERSION) {
+        eprintln!("wt {} is up to date", CURRENT_VERSION);
+        return Ok(());
+    }
+    if check {
+        eprintln!(
+            "wt {} is available (installed: {}); run `wt self-update` to install it",
+            latest, CURRENT_VERSION
+        );
+        eprintln!("  {}", release.html_url);
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe()
+        .and_then(|p| p.canonicalize())
+        .map_err(|e| WtError::io_error_with_source("failed to locate the wt binary", e.into()))?;
+    if let Some(manager) = package_manager(&exe) {
+        return Err(WtError::user_error(format!(
+            "wt was installed with {}; update it there instead",
+            manager
+        ))
+        .into());
+    }
+
+    let archive_name = archive_name()?;
+    let archive = release.asset(&archive_name)?;
+    let checksum = release.asset(&format!("{}.sha256", archive_name))?;
+
+    let dir = std::env::temp_dir().join(format!("wt-self-update-{}", std::process::id()));
+    let result = install(&dir, archive, checksum);
+    let _ = fs::remove_dir_all(&dir);
+    result?;
+
+    eprintln!("✓ Updated wt {} → {}", CURRENT_VERSION, latest);
+    Ok(())
+}
+
+/// Download and verify `archive` in `dir`, then replace the running binary
+/// with the `wt` inside it.
+fn install(dir: &Path, archive: &Asset, checksum: &Asset) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|e| {
+        WtError::io_error_with_source(format!("failed to create {}", dir.display()), e.into())
+    })?;
+    let archive_path = dir.join(&archive.name);
+    eprintln!("Downloading {}", archive.browser_download_url);
+    download(&archive.browser_download_url, &archive_path)?;
+    let checksum_path = dir.join(&checksum.name);
+    download(&checksum.browser_download_url, &checksum_path)?;
+
+    let expected = fs::read_to_string(&checksum_path)
+        .ok()
+        .and_then(|content| parse_checksum(&content))
+        .ok_or_else(|| WtError::io_error(format!("unreadable checksum file {}", checksum.name)))?;
+    let bytes = fs::read(&archive_path).map_err(|e| {
+        WtError::io_error_with_source(format!("failed to read {}", archive.name), e.into())
+    })?;
+    let actual = sha256_hex(&bytes);
+    if actual != expected {
+        return Err(WtError::io_error(format!(
+            "checksum mismatch for {}: expected {}, got {}",
+            archive.name, expected, actual
+        ))
+        .into());
+    }
+
+    let unpacked = dir.join("unpacked");
+    fs::create_dir_all(&unpacked)?;
+    let output = process::output(
+        Command::new("tar")
+            .arg("-xf")
+            .arg(&archive_path)
+            .arg("-C")
+            .arg(&unpacked),
+    )
+    .map_err(|e| WtError::io_error_with_source("failed to run tar", e.into()))?;
+    if !output.status.success() {
+        return Err(WtError::io_error(format!(
+            "failed to unpack {}: {}",
+            archive.name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+
+    let binary = find_binary(&unpacked)
+        .ok_or_else(|| WtError::not_found(format!("no wt binary in {}", archive.name)))?;
+    self_replace::self_replace(&binary)
+        .map_err(|e| WtError::io_error_with_source("failed to replace the wt binary", e.into()))?;
+    Ok(())
+}
+
+/// The latest (non-prerelease) release, from the GitHub API. `GITHUB_TOKEN`
+/// is sent when set, to avoid the anonymous rate limit.
+fn latest_release() -> Result<Release> {
+    let url = format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        REPOSITORY
+    );
+    let mut cmd = Command::new("curl");
+    cmd.args(["-fsSL", "-H", "Accept: application/vnd.github+json"]);
+    if let Ok(token) = std::env::var("GITHUB_TOKEN")
+        && !token.trim().is_empty()
+    {
+        cmd.args(["-H", &format!("Authorization: Bearer {}", token.trim())]);
+    }
+    cmd.arg(&url);
+    let output = process::output(&mut cmd).map_err(|e| {
+        WtError::io_error_with_source("failed to run curl (is it installed?)", e.into())
+    })?;
+    if !output.status.success() {
+        return Err(WtError::io_error(format!(
+            "failed to check for releases: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| {
+        WtError::io_error_with_source("unexpected response from the GitHub API", e.into()).into()
+    })
+}
+
+fn download(url: &str, path: &Path) -> Result<()> {
+    let output = process::output(
+        Command::new("curl")
+            .args(["-fsSL", "-o"])
+            .arg(path)
+            .arg(url),
+    )
+    .map_err(|e| {
+        WtError::io_error_with_source("failed to run curl (is it installed?)", e.into())
+    })?;
+    if !output.status.success() {
+        return Err(WtError::io_error(format!(
+            "failed to download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// The release archive for this platform, e.g.
+/// `worktree-manager-x86_64-unknown-linux-gnu.tar.xz`.
+fn archive_name() -> Result<String> {
+    let target = match (std::env::consts::ARCH, std::env::consts::OS) {
+        ("aarch64", "macos") => "aarch64-apple-darwin",
+        ("aarch64", "linux") => "aarch64-unknown-linux-gnu",
+        ("x86_64", "linux") => "x86_64-unknown-linux-gnu",
+        ("x86_64", "windows") => "x86_64-pc-windows-msvc",
+        (arch, os) => {
+            return Err(WtError::not_found(format!(
+                "no release binaries for {}-{}; install from source with cargo install",
+                arch, os
+            ))
+            .into());
+        }
+    };
+    let extension = if cfg!(windows) { "zip" } else { "tar.xz" };
+    Ok(format!("worktree-manager-{}.{}", target, extension))
+}
+
+/// The package manager that owns the binary at `exe`, which should update it
+/// rather than `wt self-update`. (The release's shell installer uses
+/// `~/.cargo/bin` too, so cargo installs can't be told apart.)
+fn package_manager(exe: &Path) -> Option<&'static str> {
+    let path = exe.to_string_lossy();
+    if path.contains("/Cellar/") || path.contains("/homebrew/") || path.contains("/linuxbrew/") {
+        Some("Homebrew (brew upgrade worktree-manager)")
+    } else {
+        None
+    }
+}
+
+fn find_binary(dir: &Path) -> Option<PathBuf> {
+    let name = if cfg!(windows) { "wt.exe" } else { "wt" };
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .find(|entry| entry.file_type().is_file() && entry.file_name() == name)
+        .map(|entry| entry.into_path())
+}
+
+/// The hash in a `.sha256` file (`<hex>  <file name>` or just `<hex>`).
+fn parse_checksum(content: &str) -> Option<String> {
+    let hash = content.split_whitespace().next()?.to_ascii_lowercase();
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(hash)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Whether version `candidate` (`major.minor.patch`) is newer than `current`.
+/// Unparseable versions are never newer.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    fn parse(version: &str) -> Option<(u64, u64, u64)> {
+        let core = version.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        Some((parts.next()??, parts.next()??, parts.next()??))
+    }
+    match (parse(candidate), parse(current)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(is_newer("1.0.0", "0.9.9"));
+        assert!(!is_newer("0.1.6", "0.1.6"));
+        assert!(!is_newer("0.1.5", "0.1.6"));
+        assert!(!is_newer("nightly", "0.1.6"));
+    }
+
+    #[test]
+    fn verifies_checksums() {
+        let content =
+            "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855 *wt.tar.xz\n";
+        assert_eq!(parse_checksum(content), Some(sha256_hex(b"")));
+        assert_eq!(parse_checksum("not-a-hash wt.tar.xz"), None);
+    }
+
+    #[test]
+    fn leaves_package_manager_installs_alone() {
+        assert!(
+            package_manager(Path::new(
+                "/opt/homebrew/Cellar/worktree-manager/0.1.6/bin/wt"
+            ))
+            .is_some()
+        );
+        // Where the release's shell installer puts it
+        assert!(package_manager(Path::new("/home/me/.cargo/bin/wt")).is_none());
+        assert!(package_manager(Path::new("/home/me/.local/bin/wt")).is_none());
+    }
+}
@@ -0,0 +1,233 @@
+//! `wt shift`: move uncommitted changes into a new worktree on a new branch.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::action::{self, ShellAction};
+use crate::error::WtError;
+use crate::{add, git, hooks, process, state};
+
+/// Stash the current worktree's changes (untracked files included), create a
+/// worktree with a new branch `branch` at the same commit, restore the changes
+/// there and change the shell into it. The original worktree is left clean.
+pub fn shift(branch: &str, path: Option<&str>) -> Result<()> {
+    let current = git::repo_root(None)?;
+    let status = git::worktree_status(&current)
+        .map_err(|e| WtError::git_error_with_source("failed to read status", e))?;
+    if !status.is_dirty() {
+        return Err(WtError::user_error("no uncommitted changes to shift").into());
+    }
+    if process::run(
+        "git",
+        &[
+            "show-ref",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ],
+        Some(&current),
+    )
+    .is_ok()
+    {
+        return Err(WtError::user_error(format!(
+            "branch '{}' already exists; shift needs a new branch",
+            branch
+        ))
+        .into());
+    }
+
+    let message = format!("wt shift to {}", branch);
+    // The new branch starts at this worktree's HEAD, the commit the changes
+    // are based on
+    let (target, moved) = move_changes(&current, &message, || {
+        add::add_worktree_in(&current, branch, path, None, false, false, true)
+    })?;
+    if moved {
+        eprintln!(
+            "Moved {} changed file{} to {}",
+            status.changes.len(),
+            if status.changes.len() == 1 { "" } else { "s" },
+            target.display()
+        );
+    }
+
+    let target = target.canonicalize().unwrap_or(target);
+    state::update_or_warn(|s| s.record_visit(&target, state::now()));
+    hooks::post_switch(&target)?;
+    action::emit(ShellAction::Cd(target))?;
+    Ok(())
+}
+
+/// Stash the changes in `current` (untracked files included), create the new
+/// worktree with `add` and restore the changes there, or back in `current`
+/// if `add` fails. Returns the new worktree and whether the changes made it
+/// there.
+fn move_changes(
+    current: &Path,
+    message: &str,
+    add: impl FnOnce() -> Result<PathBuf>,
+) -> Result<(PathBuf, bool)> {
+    let before = stash_top(current);
+    process::run(
+        "git",
+        &["stash", "push", "--include-untracked", "--message", message],
+        Some(current),
+    )
+    .map_err(|e| WtError::git_error_with_source("failed to stash changes", e))?;
+    // `git stash push` succeeds without stashing anything when only changes
+    // it doesn't carry (e.g. in submodules) are left
+    let stash = match stash_top(current) {
+        Some(top) if Some(&top) != before.as_ref() => top,
+        _ => return Err(WtError::user_error("no changes to shift could be stashed").into()),
+    };
+
+    let target = match add() {
+        Ok(target) => target,
+        Err(e) => {
+            if let Err(restore) = pop_stash(current, &stash) {
+                eprintln!(
+                    "Warning: failed to restore your changes; they are in `git stash list` as '{}': {:#}",
+                    message, restore
+                );
+            }
+            return Err(e);
+        }
+    };
+
+    // The stash is shared by all worktrees, so the new one can pop it
+    if let Err(e) = pop_stash(&target, &stash) {
+        eprintln!(
+            "Warning: failed to apply your changes in {}; they are still stashed as '{}': {}",
+            target.display(),
+            message,
+            process::error_summary(&format!("{:#}", e))
+        );
+        return Ok((target, false));
+    }
+    Ok((target, true))
+}
+
+/// The commit of the newest stash entry, if there is one.
+fn stash_top(dir: &Path) -> Option<String> {
+    process::run_stdout(
+        "git",
+        &["rev-parse", "--quiet", "--verify", "refs/stash"],
+        Some(dir),
+    )
+    .ok()
+    .map(|commit| commit.trim().to_string())
+}
+
+/// Pop the stash entry for `commit` wherever it is in the list by now, as
+/// hooks or other worktrees may have stashed on top of it.
+fn pop_stash(dir: &Path, commit: &str) -> Result<()> {
+    let entries = process::run_stdout("git", &["stash", "list", "--format=%H"], Some(dir))?;
+    let index = entries
+        .lines()
+        .position(|entry| entry == commit)
+        .ok_or_else(|| WtError::git_error(format!("stash entry {} is gone", commit)))?;
+    process::run(
+        "git",
+        &["stash", "pop", "--index", &format!("stash@{{{}}}", index)],
+        Some(dir),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::{git, init_repo};
+    use std::fs;
+
+    fn status(dir: &Path) -> String {
+        process::run_stdout("git", &["status", "--porcelain"], Some(dir)).unwrap()
+    }
+
+    fn stash_subjects(dir: &Path) -> Vec<String> {
+        git::stashes(dir).into_iter().map(|s| s.message).collect()
+    }
+
+    /// A repository with a tracked file and an older, unrelated stash entry.
+    fn repo_with_stash(dir: &Path) {
+        init_repo(dir);
+        fs::write(dir.join("tracked"), "one\n").unwrap();
+        git(dir, &["add", "tracked"]);
+        git(dir, &["commit", "-q", "-m", "tracked"]);
+        fs::write(dir.join("tracked"), "older\n").unwrap();
+        git(dir, &["stash", "push", "-q", "-m", "older work"]);
+    }
+
+    #[test]
+    fn moves_dirty_changes_and_pops_only_its_own_stash() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        repo_with_stash(&app);
+        fs::write(app.join("tracked"), "two\n").unwrap();
+        fs::write(app.join("new"), "").unwrap();
+        let feature = tmp.path().join("feature");
+
+        let (target, moved) = move_changes(&app, "wt shift to feature", || {
+            git(
+                &app,
+                &["worktree", "add", "-q", "-b", "feature", "../feature"],
+            );
+            // Something else stashes while the worktree is being set up
+            fs::write(feature.join("tracked"), "hook\n").unwrap();
+            git(&feature, &["stash", "push", "-q", "-m", "hook work"]);
+            Ok(feature.clone())
+        })
+        .unwrap();
+
+        assert!(moved);
+        assert_eq!(target, feature);
+        assert_eq!(status(&app), "");
+        assert_eq!(status(&feature), " M tracked\n?? new\n");
+        assert_eq!(
+            fs::read_to_string(feature.join("tracked")).unwrap(),
+            "two\n"
+        );
+        let subjects = stash_subjects(&app);
+        assert_eq!(subjects.len(), 2);
+        assert!(subjects[0].ends_with("hook work"));
+        assert!(subjects[1].ends_with("older work"));
+    }
+
+    #[test]
+    fn a_clean_worktree_stashes_nothing_and_adds_nothing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        repo_with_stash(&app);
+
+        let err = move_changes(&app, "wt shift to feature", || {
+            panic!("no worktree should be added")
+        })
+        .unwrap_err();
+
+        assert!(err.to_string().contains("no changes to shift"));
+        assert_eq!(stash_subjects(&app).len(), 1);
+        assert_eq!(fs::read_to_string(app.join("tracked")).unwrap(), "one\n");
+    }
+
+    #[test]
+    fn a_failed_add_restores_the_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        repo_with_stash(&app);
+        fs::write(app.join("tracked"), "two\n").unwrap();
+        fs::write(app.join("staged"), "").unwrap();
+        git(&app, &["add", "staged"]);
+
+        let err = move_changes(&app, "wt shift to feature", || {
+            Err(WtError::user_error("add failed").into())
+        })
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "add failed");
+        assert_eq!(status(&app), "A  staged\n M tracked\n");
+        let subjects = stash_subjects(&app);
+        assert_eq!(subjects.len(), 1);
+        assert!(subjects[0].ends_with("older work"));
+    }
+}
@@ -0,0 +1,317 @@
+//! Persistent per-worktree metadata (creation and visit times, visit counts,
+//! labels), stored in `~/.local/share/worktree-manager/state.json`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// Metadata wt keeps about a single worktree.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorktreeState {
+    /// Unix timestamp (seconds) of when `wt add` created the worktree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// Unix timestamp (seconds) of the latest visit through the picker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_visited: Option<u64>,
+    pub visit_count: u32,
+    /// Free-text note set with `wt note set`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+}
+
+impl WorktreeState {
+    /// The note and labels on one line, e.g. "waiting on review #perf #keep".
+    /// Empty if the worktree has neither.
+    pub fn annotation(&self) -> String {
+        self.note
+            .iter()
+            .cloned()
+            .chain(self.labels.iter().map(|label| format!("#{}", label)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// All worktree metadata, keyed by worktree path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    #[serde(default)]
+    pub worktrees: BTreeMap<String, WorktreeState>,
+}
+
+impl State {
+    /// Metadata for the worktree at `path`, if wt has recorded any.
+    pub fn get(&self, path: &Path) -> Option<&WorktreeState> {
+        self.worktrees.get(path.to_string_lossy().as_ref())
+    }
+
+    fn entry(&mut self, path: &Path) -> &mut WorktreeState {
+        self.worktrees
+            .entry(path.to_string_lossy().to_string())
+            .or_default()
+    }
+
+    /// Count a visit to `path` at `now` (Unix seconds).
+    pub fn record_visit(&mut self, path: &Path, now: u64) {
+        let entry = self.entry(path);
+        entry.visit_count = entry.visit_count.saturating_add(1);
+        entry.last_visited = Some(now);
+    }
+
+    /// Note that `path` was created at `now` (Unix seconds).
+    pub fn record_created(&mut self, path: &Path, now: u64) {
+        self.entry(path).created_at = Some(now);
+    }
+
+    /// Set the note of `path`, keeping its labels.
+    pub fn set_note(&mut self, path: &Path, note: String) {
+        self.entry(path).note = Some(note);
+    }
+
+    /// Replace the labels of `path`, keeping its note.
+    pub fn set_labels(&mut self, path: &Path, labels: Vec<String>) {
+        self.entry(path).labels = labels;
+    }
+
+    /// Remove the note and labels of `path`.
+    pub fn clear_note(&mut self, path: &Path) {
+        if let Some(entry) = self.worktrees.get_mut(path.to_string_lossy().as_ref()) {
+            entry.note = None;
+            entry.labels.clear();
+        }
+    }
+
+    /// Drop everything recorded about `path`.
+    pub fn forget(&mut self, path: &Path) {
+        self.worktrees.remove(path.to_string_lossy().as_ref());
+    }
+
+    /// Paths of up to `limit` visited worktrees with their metadata, most
+    /// recently visited first.
+    pub fn recently_visited(&self, limit: usize) -> Vec<(&str, &WorktreeState)> {
+        let mut visited: Vec<(&str, &WorktreeState)> = self
+            .worktrees
+            .iter()
+            .filter(|(_, s)| s.last_visited.is_some())
+            .map(|(path, s)| (path.as_str(), s))
+            .collect();
+        visited.sort_by_key(|(_, s)| std::cmp::Reverse(s.last_visited));
+        visited.truncate(limit);
+        visited
+    }
+
+    /// Frecency score: visit count weighted by how recently the worktree was
+    /// visited. Unvisited worktrees score 0.
+    pub fn score(&self, path: &Path, now: u64) -> f64 {
+        let Some(last_visited) = self.get(path).and_then(|s| s.last_visited) else {
+            return 0.0;
+        };
+        let weight = match now.saturating_sub(last_visited) {
+            a if a < 60 * 60 => 4.0,
+            a if a < 24 * 60 * 60 => 2.0,
+            a if a < 7 * 24 * 60 * 60 => 0.5,
+            _ => 0.25,
+        };
+        f64::from(self.get(path).map_or(0, |s| s.visit_count)) * weight
+    }
+
+    /// Stable-sort `items` by descending frecency of the path `path_of` returns,
+    /// keeping the original order among equally scored items.
+    pub fn sort_by_frecency<T>(&self, items: &mut [T], path_of: impl Fn(&T) -> &Path) {
+        let now = now();
+        items.sort_by(|a, b| {
+            self.score(path_of(b), now)
+                .total_cmp(&self.score(path_of(a), now))
+        });
+    }
+}
+
+/// Returns the state file path: `~/.local/share/worktree-manager/state.json`
+pub fn state_path() -> PathBuf {
+    config::data_dir().join("state.json")
+}
+
+/// Load the state file; a missing or unreadable file is an empty state.
+pub fn load() -> State {
+    read(&state_path()).unwrap_or_default()
+}
+
+/// Read the state file at `file`: empty if it doesn't exist, an error if it
+/// can't be read or parsed.
+fn read(file: &Path) -> Result<State> {
+    match fs::read_to_string(file) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse state file: {}", file.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(State::default()),
+        Err(e) => Err(e).with_context(|| format!("failed to read state file: {}", file.display())),
+    }
+}
+
+/// Load the state file, apply `change` and write it back.
+pub fn update(change: impl FnOnce(&mut State)) -> Result<()> {
+    update_file(&state_path(), change)
+}
+
+/// [`update`] the state file at `file`. One that can't be parsed is left
+/// alone rather than replaced by an empty state, and the new state is written
+/// to a temporary file renamed over it, so an interrupted write or a
+/// concurrent `wt` never leaves a truncated file.
+fn update_file(file: &Path, change: impl FnOnce(&mut State)) -> Result<()> {
+    let mut state = read(file).context("leaving the state file unchanged")?;
+    change(&mut state);
+
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create data directory: {}", dir.display()))?;
+    }
+    let content = serde_json::to_string_pretty(&state).context("failed to serialize state")?;
+    let temp = file.with_extension(format!("json.{}.tmp", std::process::id()));
+    fs::write(&temp, content)
+        .and_then(|()| fs::rename(&temp, file))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })
+        .with_context(|| format!("failed to write state file: {}", file.display()))
+}
+
+/// Apply `change` to the state file, warning instead of failing: the state
+/// is bookkeeping and shouldn't break the command that triggered it.
+pub fn update_or_warn(change: impl FnOnce(&mut State)) {
+    if let Err(e) = update(change) {
+        eprintln!("Warning: failed to update state: {:#}", e);
+    }
+}
+
+/// Current time as a Unix timestamp in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn record_visit_counts_visits() {
+        let mut state = State::default();
+        state.record_visit(Path::new("/repo/a"), NOW - 10);
+        state.record_visit(Path::new("/repo/a"), NOW);
+        let entry = state.get(Path::new("/repo/a")).unwrap();
+        assert_eq!(entry.visit_count, 2);
+        assert_eq!(entry.last_visited, Some(NOW));
+    }
+
+    #[test]
+    fn forget_drops_entry() {
+        let mut state = State::default();
+        state.record_created(Path::new("/repo/a"), NOW);
+        state.forget(Path::new("/repo/a"));
+        assert!(state.get(Path::new("/repo/a")).is_none());
+    }
+
+    #[test]
+    fn annotation_joins_note_and_labels() {
+        let mut state = State::default();
+        let path = Path::new("/repo/a");
+        state.set_note(path, "waiting on review".to_string());
+        state.set_labels(path, vec!["perf".to_string(), "keep".to_string()]);
+        assert_eq!(
+            state.get(path).unwrap().annotation(),
+            "waiting on review #perf #keep"
+        );
+
+        state.clear_note(path);
+        assert_eq!(state.get(path).unwrap().annotation(), "");
+    }
+
+    #[test]
+    fn recently_visited_orders_by_last_visit() {
+        let mut state = State::default();
+        state.record_visit(Path::new("/repo/a"), NOW - 60);
+        state.record_visit(Path::new("/repo/b"), NOW);
+        state.record_visit(Path::new("/repo/c"), NOW - 120);
+        state.record_created(Path::new("/repo/d"), NOW);
+        let paths: Vec<&str> = state
+            .recently_visited(2)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(paths, vec!["/repo/b", "/repo/a"]);
+    }
+
+    #[test]
+    fn recent_visits_outweigh_old_frequent_ones() {
+        let mut state = State::default();
+        for _ in 0..10 {
+            state.record_visit(Path::new("/repo/old"), NOW - 30 * 24 * 60 * 60);
+        }
+        state.record_visit(Path::new("/repo/new"), NOW - 60);
+        assert_eq!(state.score(Path::new("/repo/old"), NOW), 2.5);
+        assert_eq!(state.score(Path::new("/repo/new"), NOW), 4.0);
+        assert_eq!(state.score(Path::new("/repo/none"), NOW), 0.0);
+    }
+
+    #[test]
+    fn sort_keeps_order_of_unvisited_items() {
+        let mut state = State::default();
+        state.record_visit(Path::new("/repo/c"), now());
+        let mut paths = vec![
+            PathBuf::from("/repo/a"),
+            PathBuf::from("/repo/b"),
+            PathBuf::from("/repo/c"),
+        ];
+        state.sort_by_frecency(&mut paths, |p| p.as_path());
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/repo/c"),
+                PathBuf::from("/repo/a"),
+                PathBuf::from("/repo/b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn updates_leave_an_unparsable_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state.json");
+        let path = Path::new("/repo-feature");
+
+        update_file(&file, |s| s.set_note(path, "keep".to_string())).unwrap();
+        assert_eq!(
+            read(&file).unwrap().get(path).unwrap().note.as_deref(),
+            Some("keep")
+        );
+
+        fs::write(&file, "{\"worktrees\": {").unwrap();
+        assert!(update_file(&file, |s| s.record_visit(path, 1)).is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "{\"worktrees\": {");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn serializes_without_empty_fields() {
+        let mut state = State::default();
+        state.record_created(Path::new("/repo/a"), NOW);
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(
+            json,
+            r#"{"worktrees":{"/repo/a":{"created_at":1700000000,"visit_count":0}}}"#
+        );
+    }
+}
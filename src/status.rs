@@ -0,0 +1,216 @@
+//! `wt status`: a read-only health table of every worktree.
+
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::worktree::Worktree;
+use crate::{config, discovery, git, preview, process, worktree};
+
+/// A worktree to inspect, with the stash count of its branch. Stashes are
+/// shared by all worktrees of a repository, so they're listed once per repo.
+struct StatusTarget {
+    repo: String,
+    worktree: Worktree,
+    stashes: usize,
+}
+
+/// Health of one worktree.
+#[derive(Debug, Serialize)]
+struct WorktreeHealth {
+    repo: String,
+    path: PathBuf,
+    branch: Option<String>,
+    /// Changed and untracked files
+    changes: usize,
+    /// Upstream branch, e.g. "origin/main"
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    /// Stashes created on this worktree's branch
+    stashes: usize,
+    /// Committer time of HEAD, in seconds since the Unix epoch
+    last_commit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Print the dirty state, upstream divergence, stash count and last commit
+/// age of every worktree in the current repository (or in all discovered
+/// repositories with `all`), gathering up to `jobs` worktrees at once.
+pub fn show_status(all: bool, repo: Option<&str>, jobs: usize, json: bool) -> Result<()> {
+    let targets = status_targets(all, repo)?;
+    if targets.is_empty() {
+        return Err(WtError::not_found("no worktrees found").into());
+    }
+
+    let rows = process::parallel_map(&targets, jobs, inspect);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+    } else {
+        print_table(&rows, all);
+    }
+    Ok(())
+}
+
+/// Worktrees of the current repository, or of all discovered repositories
+/// (those matching the `repo` glob, if given). Bare and missing worktrees are skipped.
+fn status_targets(all: bool, repo: Option<&str>) -> Result<Vec<StatusTarget>> {
+    let repos = if all {
+        let config = config::load()
+            .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
+        discovery::discover_configured_repos(&config, false, repo)?
+    } else {
+        vec![git::repo_root(None)?]
+    };
+
+    let mut targets = Vec::new();
+    for repo in worktree::repos_worktrees(&repos, !all)? {
+        let stashes = git::stashes(&repo.root);
+        targets.extend(repo.worktrees.into_iter().map(|worktree| StatusTarget {
+            repo: repo.name.clone(),
+            stashes: worktree.branch_name().map_or(0, |branch| {
+                stashes.iter().filter(|s| s.is_on_branch(branch)).count()
+            }),
+            worktree,
+        }));
+    }
+    Ok(targets)
+}
+
+/// Read the status and last commit time of one worktree.
+fn inspect(target: &StatusTarget) -> WorktreeHealth {
+    let wt = &target.worktree;
+    let mut health = WorktreeHealth {
+        repo: target.repo.clone(),
+        path: wt.path.clone(),
+        branch: wt.branch_name().map(str::to_string),
+        changes: 0,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        stashes: target.stashes,
+        last_commit: git::last_commit_time(&wt.path)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+        error: None,
+    };
+
+    match git::worktree_status(&wt.path) {
+        Ok(status) => {
+            health.changes = status.changes.len();
+            if let Some(upstream) = status.upstream {
+                health.upstream = Some(upstream.name);
+                health.ahead = upstream.ahead;
+                health.behind = upstream.behind;
+            }
+        }
+        Err(e) => health.error = Some(process::error_summary(&format!("{:#}", e))),
+    }
+    health
+}
+
+/// Print one aligned row per worktree. With `all`, rows start with the repo.
+fn print_table(rows: &[WorktreeHealth], all: bool) {
+    let cells: Vec<[String; 6]> = rows
+        .iter()
+        .map(|row| {
+            let branch = row.branch.as_deref().unwrap_or("(detached)");
+            let name = if all {
+                format!("{}  {}", row.repo, branch)
+            } else {
+                branch.to_string()
+            };
+            let changes = match (&row.error, row.changes) {
+                (Some(_), _) => "error".to_string(),
+                (None, 0) => "clean".to_string(),
+                (None, n) => format!("{} changed", n),
+            };
+            let stashes = match row.stashes {
+                0 => "-".to_string(),
+                1 => "1 stash".to_string(),
+                n => format!("{} stashes", n),
+            };
+            let age = row
+                .last_commit
+                .map(|secs| preview::format_age(UNIX_EPOCH + Duration::from_secs(secs)))
+                .unwrap_or_else(|| "no commits".to_string());
+            [
+                name,
+                changes,
+                divergence(row),
+                stashes,
+                age,
+                row.path.display().to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = [0; 5];
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for (row, cells) in rows.iter().zip(&cells) {
+        let mut line = String::new();
+        for (cell, width) in cells.iter().zip(widths) {
+            line.push_str(&format!("{cell:<width$}  "));
+        }
+        line.push_str(&cells[5]);
+        if let Some(error) = &row.error {
+            line.push_str(&format!("  ({})", error));
+        }
+        println!("{}", line);
+    }
+}
+
+/// Ahead/behind counts in compact form, e.g. "↑2 ↓1", "=" when in sync, or
+/// "-" without an upstream.
+fn divergence(row: &WorktreeHealth) -> String {
+    if row.upstream.is_none() {
+        return "-".to_string();
+    }
+    match (row.ahead, row.behind) {
+        (0, 0) => "=".to_string(),
+        (a, 0) => format!("↑{a}"),
+        (0, b) => format!("↓{b}"),
+        (a, b) => format!("↑{a} ↓{b}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divergence_is_compact() {
+        let mut row = WorktreeHealth {
+            repo: "app".to_string(),
+            path: PathBuf::from("/src/app"),
+            branch: Some("main".to_string()),
+            changes: 0,
+            upstream: None,
+            ahead: 0,
+            behind: 0,
+            stashes: 0,
+            last_commit: None,
+            error: None,
+        };
+        assert_eq!(divergence(&row), "-");
+
+        row.upstream = Some("origin/main".to_string());
+        assert_eq!(divergence(&row), "=");
+        row.ahead = 2;
+        assert_eq!(divergence(&row), "↑2");
+        row.behind = 1;
+        assert_eq!(divergence(&row), "↑2 ↓1");
+        row.ahead = 0;
+        assert_eq!(divergence(&row), "↓1");
+    }
+}
@@ -0,0 +1,31 @@
+//! `wt switch`: change into a worktree by branch name or path, the
+//! non-interactive counterpart of picking it in `wt`.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::action::{self, ShellAction};
+use crate::{hooks, integrations, preview, state};
+
+/// Switch to the worktree matching `target` (branch name or path), or print
+/// its path with `path_only`.
+pub fn switch(target: &str, path_only: bool) -> Result<()> {
+    let path = preview::resolve_target(target)?;
+    let path = path.canonicalize().unwrap_or(path);
+    if path_only {
+        println!("{}", path.display());
+        return Ok(());
+    }
+    cd(path)
+}
+
+/// Change the shell into the worktree at `path` the way the picker does:
+/// count it as a visit, run the `post_switch` hook and hand the shell wrapper
+/// a `cd` action.
+pub fn cd(path: PathBuf) -> Result<()> {
+    state::update_or_warn(|s| s.record_visit(&path, state::now()));
+    integrations::zoxide_add(&path);
+    hooks::post_switch(&path)?;
+    action::emit(ShellAction::Cd(path))
+}
@@ -0,0 +1,255 @@
+//! `wt sync`: fetch, then fast-forward worktrees that are safely behind.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::progress::Progress;
+use crate::worktree::Worktree;
+use crate::{config, discovery, fetch, git, process, worktree};
+
+/// What `wt sync` did with a worktree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Outcome {
+    /// Fast-forwarded to its upstream
+    Updated,
+    /// Already at its upstream
+    UpToDate,
+    /// Has local commits and nothing to pull
+    Ahead,
+    /// Has uncommitted changes to tracked files
+    SkippedDirty,
+    /// Has local commits and is behind; needs a merge or rebase
+    Diverged,
+    /// The branch has no upstream
+    NoUpstream,
+    /// HEAD is detached
+    Detached,
+    /// Reading the status or fast-forwarding failed
+    Failed,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Updated => "updated",
+            Outcome::UpToDate => "up to date",
+            Outcome::Ahead => "ahead",
+            Outcome::SkippedDirty => "skipped (dirty)",
+            Outcome::Diverged => "diverged",
+            Outcome::NoUpstream => "no upstream",
+            Outcome::Detached => "detached",
+            Outcome::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SyncResult {
+    repo: String,
+    path: PathBuf,
+    branch: Option<String>,
+    outcome: Outcome,
+    /// Commits pulled in (updated) or waiting upstream (dirty, diverged)
+    behind: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Fetch the current repository (or all discovered ones with `all`), then
+/// fast-forward every worktree whose branch is behind its upstream and has no
+/// uncommitted changes, and print what happened to each worktree.
+pub fn sync(all: bool, repo: Option<&str>, jobs: usize, json: bool) -> Result<()> {
+    let repos = if all {
+        let config = config::load()
+            .map_err(|e| WtError::config_error_with_source("failed to load config", e))?;
+        discovery::discover_configured_repos(&config, false, repo)?
+    } else {
+        vec![git::repo_root(None)?]
+    };
+
+    // A failed fetch still leaves the last fetched state to fast-forward to
+    for failed in fetch::fetch_repos(&repos, jobs, !json)
+        .iter()
+        .filter(|r| !r.success)
+    {
+        eprintln!(
+            "Warning: fetch failed for {}: {}",
+            failed.repo,
+            failed.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+
+    let targets: Vec<(String, Worktree)> = worktree::repos_worktrees(&repos, false)?
+        .into_iter()
+        .flat_map(|repo| {
+            repo.worktrees
+                .into_iter()
+                .map(move |wt| (repo.name.clone(), wt))
+        })
+        .collect();
+
+    let progress = Progress::new("Syncing", targets.len(), !json);
+    let results = process::parallel_map(&targets, jobs, |(repo, wt)| {
+        let result = sync_worktree(repo, wt);
+        progress.tick();
+        result
+    });
+    progress.finish();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        print_results(&results, all);
+    }
+    Ok(())
+}
+
+/// Fast-forward one worktree if it is clean and strictly behind its upstream.
+fn sync_worktree(repo: &str, wt: &Worktree) -> SyncResult {
+    let mut result = SyncResult {
+        repo: repo.to_string(),
+        path: wt.path.clone(),
+        branch: wt.branch.clone(),
+        outcome: Outcome::Failed,
+        behind: 0,
+        error: None,
+    };
+
+    let status = match git::worktree_status(&wt.path) {
+        Ok(status) => status,
+        Err(e) => {
+            result.error = Some(format!("{:#}", e));
+            return result;
+        }
+    };
+    let (outcome, behind) = classify(&status);
+    result.outcome = outcome;
+    result.behind = behind;
+
+    if outcome == Outcome::Updated
+        && let Err(e) = process::run(
+            "git",
+            &["merge", "--ff-only", "--quiet", "@{u}"],
+            Some(&wt.path),
+        )
+    {
+        result.outcome = Outcome::Failed;
+        result.error = Some(format!("{:#}", e));
+    }
+    result
+}
+
+/// Decide what to do with a worktree from its status. `Updated` means it
+/// should be fast-forwarded. Also returns how many commits it is behind.
+/// Untracked files don't block a fast-forward; git refuses to overwrite them.
+fn classify(status: &git::WorktreeStatus) -> (Outcome, usize) {
+    if status.branch.is_none() {
+        return (Outcome::Detached, 0);
+    }
+    let Some(upstream) = &status.upstream else {
+        return (Outcome::NoUpstream, 0);
+    };
+    let behind = upstream.behind;
+    let outcome = if status.changes.iter().any(|c| !c.starts_with("??")) {
+        Outcome::SkippedDirty
+    } else {
+        match (upstream.ahead, upstream.behind) {
+            (_, 0) if upstream.ahead > 0 => Outcome::Ahead,
+            (_, 0) => Outcome::UpToDate,
+            (0, _) => Outcome::Updated,
+            _ => Outcome::Diverged,
+        }
+    };
+    (outcome, behind)
+}
+
+/// Print one line per worktree and a count of updated worktrees.
+fn print_results(results: &[SyncResult], all: bool) {
+    let names: Vec<String> = results
+        .iter()
+        .map(|r| {
+            let branch = r
+                .branch
+                .as_deref()
+                .map_or("(detached)", git::short_branch)
+                .to_string();
+            if all {
+                format!("{}  {}", r.repo, branch)
+            } else {
+                branch
+            }
+        })
+        .collect();
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0);
+
+    for (result, name) in results.iter().zip(&names) {
+        let mut detail = result.outcome.label().to_string();
+        match result.outcome {
+            Outcome::Updated if result.behind == 1 => detail.push_str(" (1 commit)"),
+            Outcome::Updated => detail.push_str(&format!(" ({} commits)", result.behind)),
+            Outcome::SkippedDirty | Outcome::Diverged if result.behind > 0 => {
+                detail.push_str(&format!(" ({} behind)", result.behind))
+            }
+            _ => {}
+        }
+        if let Some(error) = &result.error {
+            detail.push_str(&format!(": {}", process::error_summary(error)));
+        }
+        println!("{name:<width$}  {detail}");
+    }
+
+    let updated = results
+        .iter()
+        .filter(|r| r.outcome == Outcome::Updated)
+        .count();
+    println!("Updated {} of {} worktrees", updated, results.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(ahead: usize, behind: usize, changes: &[&str]) -> git::WorktreeStatus {
+        git::WorktreeStatus {
+            head: Some("abc".to_string()),
+            branch: Some("feature".to_string()),
+            upstream: Some(git::Upstream {
+                name: "origin/feature".to_string(),
+                ahead,
+                behind,
+            }),
+            changes: changes.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn classify_fast_forwards_only_clean_worktrees_behind() {
+        assert_eq!(classify(&status(0, 3, &[])), (Outcome::Updated, 3));
+        assert_eq!(
+            classify(&status(0, 3, &["?? notes.txt"])),
+            (Outcome::Updated, 3)
+        );
+        assert_eq!(
+            classify(&status(0, 3, &[" M src/main.rs"])),
+            (Outcome::SkippedDirty, 3)
+        );
+        assert_eq!(classify(&status(2, 3, &[])), (Outcome::Diverged, 3));
+        assert_eq!(classify(&status(2, 0, &[])), (Outcome::Ahead, 0));
+        assert_eq!(classify(&status(0, 0, &[])), (Outcome::UpToDate, 0));
+    }
+
+    #[test]
+    fn classify_needs_branch_and_upstream() {
+        let mut detached = status(0, 1, &[]);
+        detached.branch = None;
+        assert_eq!(classify(&detached).0, Outcome::Detached);
+
+        let mut untracked = status(0, 1, &[]);
+        untracked.upstream = None;
+        assert_eq!(classify(&untracked).0, Outcome::NoUpstream);
+    }
+}
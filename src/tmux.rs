@@ -0,0 +1,157 @@
+//! Open worktrees in per-worktree tmux sessions (or a custom tmux command).
+
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+
+use crate::config::{self, TmuxConfig};
+use crate::error::WtError;
+use crate::keymap::shell_quote;
+use crate::{git, process};
+
+/// Open the worktree matching `target` (branch name or path) in tmux.
+pub fn open_target(target: &str) -> Result<()> {
+    let path = crate::preview::resolve_target(target)?;
+    open(&path)
+}
+
+/// Open the worktree at `path` in tmux: switch to (or attach) its session,
+/// creating it with the configured layout first if needed. A custom
+/// `tmux.command` replaces all of this.
+pub fn open(path: &Path) -> Result<()> {
+    let config = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?
+        .tmux;
+    let session = session_name(&repo_name(path), &branch_name(path));
+
+    if !config.command.trim().is_empty() {
+        return run_template(&config.command, path, &session);
+    }
+
+    let exact = format!("={}", session);
+    if process::run("tmux", &["has-session", "-t", &exact], None).is_err() {
+        create_session(&config, path, &session)?;
+    }
+
+    if inside_tmux() {
+        process::run("tmux", &["switch-client", "-t", &exact], None).map_err(|e| {
+            WtError::user_error_with_source(format!("failed to switch to session {}", session), e)
+        })?;
+        return Ok(());
+    }
+
+    // Attaching takes over the terminal until the session is detached; keep
+    // stdout off the shell wrapper's captured output
+    let status = Command::new("tmux")
+        .args(["attach-session", "-t", &exact])
+        .stdin(Stdio::inherit())
+        .stdout(io::stderr())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| WtError::io_error_with_source("failed to execute tmux", e.into()))?;
+    if !status.success() {
+        return Err(WtError::user_error(format!("failed to attach to session {}", session)).into());
+    }
+    Ok(())
+}
+
+/// Create the detached session `session` in `path` and run the layout
+/// commands in it.
+fn create_session(config: &TmuxConfig, path: &Path, session: &str) -> Result<()> {
+    let path_str = path.to_string_lossy();
+    process::run(
+        "tmux",
+        &["new-session", "-d", "-s", session, "-c", path_str.as_ref()],
+        None,
+    )
+    .map_err(|e| {
+        WtError::user_error_with_source(format!("failed to create session {}", session), e)
+    })?;
+    for template in &config.layout {
+        run_template(template, path, session)?;
+    }
+    Ok(())
+}
+
+/// Render `template` (see [`render`]) and run it with `sh -c`.
+fn run_template(template: &str, path: &Path, session: &str) -> Result<()> {
+    let command = render(template, path, session);
+    process::run("sh", &["-c", &command], None).map_err(|e| {
+        WtError::user_error_with_source(format!("tmux command failed: {}", command), e)
+    })?;
+    Ok(())
+}
+
+fn inside_tmux() -> bool {
+    env::var_os("TMUX").is_some_and(|v| !v.is_empty())
+}
+
+/// The repository's name: the main worktree's directory name, or the bare
+/// repository's without `.git`.
+pub fn repo_name(path: &Path) -> String {
+    let main = git::worktrees_porcelain(path)
+        .ok()
+        .and_then(|worktrees| worktrees.into_iter().next())
+        .map(|wt| wt.path)
+        .unwrap_or_else(|| path.to_path_buf());
+    let name = dir_name(&main);
+    name.strip_suffix(".git").unwrap_or(&name).to_string()
+}
+
+/// The branch checked out at `path`, or its directory name on detached HEAD.
+pub fn branch_name(path: &Path) -> String {
+    process::run_stdout(
+        "git",
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+        Some(path),
+    )
+    .map(|b| b.trim().to_string())
+    .unwrap_or_else(|_| dir_name(path))
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Session name "repo/branch"; tmux doesn't allow `.` or `:` in names.
+fn session_name(repo: &str, branch: &str) -> String {
+    format!("{}/{}", repo, branch).replace(['.', ':'], "_")
+}
+
+/// Fill in `{path}` (the worktree path), `{name}` (its directory name) and
+/// `{session}` (its session name), all shell-quoted.
+fn render(template: &str, path: &Path, session: &str) -> String {
+    template
+        .replace("{path}", &shell_quote(&path.to_string_lossy()))
+        .replace("{name}", &shell_quote(&dir_name(path)))
+        .replace("{session}", &shell_quote(session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_fills_path_name_and_session() {
+        let command = render(
+            "tmux new-window -c {path} -n {name} -t {session}",
+            Path::new("/repos/app-feature"),
+            "app/feature",
+        );
+        assert_eq!(
+            command,
+            "tmux new-window -c '/repos/app-feature' -n 'app-feature' -t 'app/feature'"
+        );
+    }
+
+    #[test]
+    fn session_names_avoid_tmux_separators() {
+        assert_eq!(session_name("app", "feature/x"), "app/feature/x");
+        assert_eq!(session_name("site.io", "v1.2:fix"), "site_io/v1_2_fix");
+    }
+}
@@ -0,0 +1,92 @@
+//! `wt --version`: the version, and with `--json` the build metadata that
+//! scripts and agents use to check what this binary supports.
+
+use anyhow::Result;
+use clap::CommandFactory;
+use serde::Serialize;
+
+use crate::cli::Cli;
+use crate::{action, git};
+
+#[derive(Serialize)]
+struct VersionInfo {
+    name: &'static str,
+    version: &'static str,
+    /// Commit the binary was built from; `None` outside a git checkout
+    commit: Option<&'static str>,
+    build_date: &'static str,
+    target: &'static str,
+    /// Cargo features enabled at build time
+    features: Vec<&'static str>,
+    /// Oldest git `wt` runs with, as `major.minor`
+    min_git_version: String,
+    /// Newest shell action protocol this binary speaks (see `wt init`)
+    shell_protocol: u32,
+    /// Subcommands, e.g. to check for `switch` before using it
+    commands: Vec<String>,
+}
+
+/// Print `wt <version>`, or with `json` the build metadata.
+pub fn print_version(json: bool) -> Result<()> {
+    if !json {
+        println!("wt {}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+    println!("{}", serde_json::to_string_pretty(&version_info())?);
+    Ok(())
+}
+
+fn version_info() -> VersionInfo {
+    VersionInfo {
+        name: "wt",
+        version: env!("CARGO_PKG_VERSION"),
+        commit: option_env!("WT_GIT_COMMIT"),
+        build_date: env!("WT_BUILD_DATE"),
+        target: env!("WT_TARGET"),
+        features: env!("WT_FEATURES")
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .collect(),
+        min_git_version: format!("{}.{}", git::MIN_VERSION.0, git::MIN_VERSION.1),
+        shell_protocol: action::PROTOCOL_VERSION,
+        commands: Cli::command()
+            .get_subcommands()
+            .filter(|c| !c.is_hide_set())
+            .map(|c| c.get_name().to_string())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_schema() {
+        let info = serde_json::to_value(version_info()).unwrap();
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("schemas/version.json")).unwrap();
+        let mut keys: Vec<&str> = info
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        let mut required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        keys.sort();
+        required.sort();
+        assert_eq!(keys, required);
+        assert_eq!(info["min_git_version"], "2.17");
+        assert!(
+            info["commands"]
+                .as_array()
+                .unwrap()
+                .contains(&"switch".into())
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! VS Code integration: `wt code` and `.code-workspace` files listing every
+//! worktree of a repository.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::error::WtError;
+use crate::{git, integrations, preview, process};
+
+/// Result of generating a workspace file (for JSON output)
+#[derive(Serialize)]
+struct WorkspaceResult {
+    path: String,
+    folders: usize,
+}
+
+/// Open the worktree matching `target` (branch name or path) in VS Code.
+pub fn open_target(target: &str) -> Result<()> {
+    let path = preview::resolve_target(target)?;
+    let path_str = path.to_string_lossy();
+    process::run("code", &[path_str.as_ref()], None).map_err(|e| {
+        if integrations::is_not_installed(&e) {
+            WtError::not_found(
+                "VS Code's `code` command not found; run \"Shell Command: Install 'code' command in PATH\" in VS Code",
+            )
+        } else {
+            WtError::user_error_with_source("failed to open VS Code", e)
+        }
+    })?;
+    Ok(())
+}
+
+/// Write a `.code-workspace` file with one folder per worktree of the current
+/// repository, to `output` or next to the main worktree as `<repo>.code-workspace`.
+/// Other settings in an existing file are kept.
+pub fn generate(output: Option<&str>, json: bool) -> Result<()> {
+    let repo_root = git::repo_root(None)?;
+    let path = match output {
+        Some(output) => PathBuf::from(output),
+        None => default_path(&repo_root)?,
+    };
+    let folders = write(&repo_root, &path)?;
+
+    if json {
+        let result = WorkspaceResult {
+            path: path.to_string_lossy().to_string(),
+            folders,
+        };
+        println!("{}", serde_json::to_string(&result)?);
+    } else {
+        eprintln!(
+            "Wrote {} with {} folder{}",
+            path.display(),
+            folders,
+            if folders == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
+
+/// Regenerate the repository's default workspace file after worktrees were
+/// added or removed, if one was generated before.
+pub fn refresh(repo_root: &Path) {
+    let Ok(path) = default_path(repo_root) else {
+        return;
+    };
+    if !path.is_file() {
+        return;
+    }
+    if let Err(e) = write(repo_root, &path) {
+        eprintln!("Warning: failed to update {}: {:#}", path.display(), e);
+    }
+}
+
+/// `<repo>.code-workspace` in the directory holding the main worktree (or
+/// the bare repository), where `wt add` puts sibling worktrees.
+fn default_path(repo_root: &Path) -> Result<PathBuf> {
+    let main = git::worktrees_porcelain(repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?
+        .into_iter()
+        .next()
+        .map(|wt| wt.path)
+        .ok_or_else(|| WtError::not_found("no worktrees found"))?;
+    let name = main.file_name().unwrap_or_default().to_string_lossy();
+    let name = name.strip_suffix(".git").unwrap_or(&name);
+    Ok(main.with_file_name(format!("{}.code-workspace", name)))
+}
+
+/// Write the workspace file at `path` and return how many folders it lists.
+fn write(repo_root: &Path, path: &Path) -> Result<usize> {
+    let worktrees = git::worktrees_porcelain(repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+    let base = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let base = base.canonicalize().unwrap_or_else(|_| base.to_path_buf());
+    let folders: Vec<(String, PathBuf)> = worktrees
+        .into_iter()
+        .filter(|wt| !wt.bare && wt.path.is_dir())
+        .map(|wt| {
+            let name = wt.branch_name().map(str::to_string).unwrap_or_else(|| {
+                wt.path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            });
+            let relative = pathdiff::diff_paths(&wt.path, &base).unwrap_or(wt.path);
+            (name, relative)
+        })
+        .collect();
+
+    let existing = match fs::read_to_string(path) {
+        Ok(content) => Some(serde_json::from_str::<Value>(&content).map_err(|e| {
+            WtError::user_error_with_source(
+                format!(
+                    "{} isn't plain JSON (comments aren't supported); fix or delete it",
+                    path.display()
+                ),
+                e,
+            )
+        })?),
+        Err(_) => None,
+    };
+    let workspace = workspace_json(existing, &folders);
+
+    let content = serde_json::to_string_pretty(&workspace)? + "\n";
+    fs::write(path, content).map_err(|e| {
+        WtError::io_error_with_source(format!("failed to write {}", path.display()), e.into())
+    })?;
+    Ok(folders.len())
+}
+
+/// `existing` (or a new workspace) with its folders replaced by `folders`.
+fn workspace_json(existing: Option<Value>, folders: &[(String, PathBuf)]) -> Value {
+    let mut workspace = match existing {
+        Some(Value::Object(map)) => map,
+        _ => {
+            let mut map = Map::new();
+            map.insert("settings".to_string(), json!({}));
+            map
+        }
+    };
+    let folders = folders
+        .iter()
+        .map(|(name, path)| json!({ "name": name, "path": path.to_string_lossy() }))
+        .collect();
+    workspace.insert("folders".to_string(), Value::Array(folders));
+    Value::Object(workspace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_json_replaces_folders_and_keeps_settings() {
+        let existing = json!({
+            "folders": [{ "path": "old" }],
+            "settings": { "editor.tabSize": 4 }
+        });
+        let folders = vec![
+            ("main".to_string(), PathBuf::from("app")),
+            ("feature/x".to_string(), PathBuf::from("app-feature-x")),
+        ];
+        let workspace = workspace_json(Some(existing), &folders);
+        assert_eq!(workspace["settings"]["editor.tabSize"], 4);
+        assert_eq!(
+            workspace["folders"],
+            json!([
+                { "name": "main", "path": "app" },
+                { "name": "feature/x", "path": "app-feature-x" }
+            ])
+        );
+
+        let fresh = workspace_json(None, &folders);
+        assert_eq!(fresh["settings"], json!({}));
+        assert_eq!(fresh["folders"].as_array().unwrap().len(), 2);
+    }
+}
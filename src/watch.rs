@@ -0,0 +1,296 @@
+//! `wt agent watch`: stream worktree events as NDJSON.
+//!
+//! Every `interval` the watcher stats a few git files (the shared
+//! `worktrees` directory and each worktree's `HEAD` and `index`); when one
+//! changes, or every `poll` otherwise, it re-reads `git worktree list` and
+//! each worktree's status and prints an event per difference. Edits to
+//! tracked files don't touch any git file, so dirty changes from them show
+//! up with the periodic poll.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+
+use crate::error::WtError;
+use crate::{git, process, state};
+
+/// How many `git status` calls a poll runs at once.
+const STATUS_JOBS: usize = 8;
+
+/// One NDJSON line: an event and when it was seen.
+#[derive(Serialize)]
+struct Record<'a> {
+    /// Unix timestamp (seconds)
+    time: u64,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event {
+    /// All worktrees when watching starts
+    Snapshot {
+        worktrees: Vec<WorktreeSnapshot>,
+    },
+    WorktreeAdded {
+        path: PathBuf,
+        branch: Option<String>,
+    },
+    WorktreeRemoved {
+        path: PathBuf,
+        branch: Option<String>,
+    },
+    BranchChanged {
+        path: PathBuf,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    DirtyChanged {
+        path: PathBuf,
+        dirty: bool,
+    },
+}
+
+/// What the watcher tracks about one worktree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct WorktreeSnapshot {
+    path: PathBuf,
+    /// Branch name without `refs/heads/`; `None` on detached HEAD
+    branch: Option<String>,
+    dirty: bool,
+}
+
+/// Watch the current repository until interrupted, printing one JSON event
+/// per line. Stops quietly at the first event after stdout is closed (e.g.
+/// when piped into `head`).
+pub fn watch(interval: Duration, poll: Duration) -> Result<(), WtError> {
+    let repo_root = git::repo_root(None)?;
+    let common_dir = git::repo_dirs(&repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to locate the git directory", e))?
+        .common_dir;
+
+    let mut current = scan(&repo_root)?;
+    let mut stamps = fingerprint(&common_dir, &current);
+    let snapshot = Event::Snapshot {
+        worktrees: current.values().cloned().collect(),
+    };
+    if !emit(&snapshot) {
+        return Ok(());
+    }
+
+    let mut last_poll = Instant::now();
+    loop {
+        thread::sleep(interval);
+        let files_changed = fingerprint(&common_dir, &current) != stamps;
+        if !files_changed && last_poll.elapsed() < poll {
+            continue;
+        }
+
+        let next = scan(&repo_root)?;
+        for event in diff(&current, &next) {
+            if !emit(&event) {
+                return Ok(());
+            }
+        }
+        // Taken after the scan: `git status` may refresh the index, which
+        // shouldn't count as a change on the next round
+        stamps = fingerprint(&common_dir, &next);
+        current = next;
+        last_poll = Instant::now();
+    }
+}
+
+/// Print `event` as one JSON line; `false` once stdout is gone.
+fn emit(event: &Event) -> bool {
+    let record = Record {
+        time: state::now(),
+        event,
+    };
+    let Ok(line) = serde_json::to_string(&record) else {
+        return true;
+    };
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", line)
+        .and_then(|_| stdout.flush())
+        .is_ok()
+}
+
+/// Every worktree of the repository with its branch and dirty state, keyed
+/// by path. Bare entries are skipped.
+fn scan(repo_root: &Path) -> Result<BTreeMap<PathBuf, WorktreeSnapshot>, WtError> {
+    let worktrees = git::worktrees_porcelain(repo_root)
+        .map_err(|e| WtError::git_error_with_source("failed to list worktrees", e))?;
+    let worktrees: Vec<_> = worktrees.into_iter().filter(|wt| !wt.bare).collect();
+    let dirty = process::parallel_map(&worktrees, STATUS_JOBS, |wt| {
+        git::worktree_status(&wt.path).is_ok_and(|s| s.is_dirty())
+    });
+
+    Ok(worktrees
+        .into_iter()
+        .zip(dirty)
+        .map(|(wt, dirty)| {
+            let branch = wt.branch_name().map(str::to_string);
+            let snapshot = WorktreeSnapshot {
+                path: wt.path.clone(),
+                branch,
+                dirty,
+            };
+            (wt.path, snapshot)
+        })
+        .collect())
+}
+
+/// Events that turn `before` into `after`.
+fn diff(
+    before: &BTreeMap<PathBuf, WorktreeSnapshot>,
+    after: &BTreeMap<PathBuf, WorktreeSnapshot>,
+) -> Vec<Event> {
+    let mut events = Vec::new();
+    for (path, old) in before {
+        if !after.contains_key(path) {
+            events.push(Event::WorktreeRemoved {
+                path: path.clone(),
+                branch: old.branch.clone(),
+            });
+        }
+    }
+    for (path, new) in after {
+        let Some(old) = before.get(path) else {
+            events.push(Event::WorktreeAdded {
+                path: path.clone(),
+                branch: new.branch.clone(),
+            });
+            if new.dirty {
+                events.push(Event::DirtyChanged {
+                    path: path.clone(),
+                    dirty: true,
+                });
+            }
+            continue;
+        };
+        if old.branch != new.branch {
+            events.push(Event::BranchChanged {
+                path: path.clone(),
+                from: old.branch.clone(),
+                to: new.branch.clone(),
+            });
+        }
+        if old.dirty != new.dirty {
+            events.push(Event::DirtyChanged {
+                path: path.clone(),
+                dirty: new.dirty,
+            });
+        }
+    }
+    events
+}
+
+/// Modification times of the git files that change when worktrees are
+/// added, removed, switched or staged.
+fn fingerprint(
+    common_dir: &Path,
+    worktrees: &BTreeMap<PathBuf, WorktreeSnapshot>,
+) -> Vec<Option<SystemTime>> {
+    let mut files = vec![common_dir.to_path_buf(), common_dir.join("worktrees")];
+    for path in worktrees.keys() {
+        if let Some(git_dir) = git_dir(path) {
+            files.push(git_dir.join("HEAD"));
+            files.push(git_dir.join("index"));
+        }
+    }
+    files
+        .iter()
+        .map(|file| fs::metadata(file).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// The git dir of the worktree at `path`: its `.git` directory, or the one
+/// a linked worktree's `.git` file points to.
+fn git_dir(path: &Path) -> Option<PathBuf> {
+    let dot_git = path.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let target = PathBuf::from(content.strip_prefix("gitdir:")?.trim());
+    Some(if target.is_relative() {
+        path.join(target)
+    } else {
+        target
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(path: &str, branch: Option<&str>, dirty: bool) -> (PathBuf, WorktreeSnapshot) {
+        let path = PathBuf::from(path);
+        let snapshot = WorktreeSnapshot {
+            path: path.clone(),
+            branch: branch.map(str::to_string),
+            dirty,
+        };
+        (path, snapshot)
+    }
+
+    #[test]
+    fn diff_reports_each_change() {
+        let before = BTreeMap::from([
+            snapshot("/repo", Some("main"), false),
+            snapshot("/repo-a", Some("a"), false),
+            snapshot("/repo-b", Some("b"), true),
+        ]);
+        let after = BTreeMap::from([
+            snapshot("/repo", Some("main"), true),
+            snapshot("/repo-b", None, true),
+            snapshot("/repo-c", Some("c"), false),
+        ]);
+        assert_eq!(
+            diff(&before, &after),
+            vec![
+                Event::WorktreeRemoved {
+                    path: "/repo-a".into(),
+                    branch: Some("a".to_string()),
+                },
+                Event::DirtyChanged {
+                    path: "/repo".into(),
+                    dirty: true,
+                },
+                Event::BranchChanged {
+                    path: "/repo-b".into(),
+                    from: Some("b".to_string()),
+                    to: None,
+                },
+                Event::WorktreeAdded {
+                    path: "/repo-c".into(),
+                    branch: Some("c".to_string()),
+                },
+            ]
+        );
+        assert!(diff(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn records_are_flat_json() {
+        let event = Event::DirtyChanged {
+            path: "/repo".into(),
+            dirty: true,
+        };
+        let line = serde_json::to_string(&Record {
+            time: 1700000000,
+            event: &event,
+        })
+        .unwrap();
+        assert_eq!(
+            line,
+            r#"{"time":1700000000,"event":"dirty_changed","path":"/repo","dirty":true}"#
+        );
+    }
+}
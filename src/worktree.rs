@@ -1,8 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 use anyhow::{Context, Result};
 use serde::Serialize;
 
+use crate::error::WtError;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Worktree {
     pub path: PathBuf,
@@ -13,6 +15,13 @@ pub struct Worktree {
     pub bare: bool,
 }
 
+impl Worktree {
+    /// The checked-out branch without `refs/heads/`; `None` on detached HEAD.
+    pub fn branch_name(&self) -> Option<&str> {
+        self.branch.as_deref().map(crate::git::short_branch)
+    }
+}
+
 /// Parse `git worktree list --porcelain` output.
 ///
 /// Format (repeated blocks separated by blank lines):
@@ -23,12 +32,20 @@ pub struct Worktree {
 /// - `prunable <reason>`
 /// - `bare`
 pub fn parse_porcelain(input: &str) -> Result<Vec<Worktree>> {
+    parse_fields(input.lines().map(str::trim_end))
+}
+
+/// Parse `git worktree list --porcelain -z` output: the same fields, each
+/// ending in a NUL rather than a newline, so paths may contain newlines.
+pub fn parse_porcelain_z(input: &str) -> Result<Vec<Worktree>> {
+    parse_fields(input.split('\0'))
+}
+
+fn parse_fields<'a>(fields: impl Iterator<Item = &'a str>) -> Result<Vec<Worktree>> {
     let mut worktrees = Vec::new();
     let mut current: Option<Worktree> = None;
 
-    for raw_line in input.lines() {
-        let line = raw_line.trim_end();
-
+    for line in fields {
         if line.is_empty() {
             if let Some(wt) = current.take() {
                 worktrees.push(wt);
@@ -97,9 +114,251 @@ pub fn parse_porcelain(input: &str) -> Result<Vec<Worktree>> {
     Ok(worktrees)
 }
 
+/// Find a worktree by target: a path (absolute or relative, e.g. `.` or
+/// `../repo-feature/`, symlinks resolved), a branch name, or the name of a
+/// worktree's directory, in that order of preference.
+/// Returns error if no match or multiple matches found.
+pub fn find_worktree<'a>(worktrees: &'a [Worktree], target: &str) -> Result<&'a Worktree> {
+    let target_path = normalize(Path::new(target));
+    let by_path: Vec<_> = worktrees
+        .iter()
+        .filter(|wt| normalize(&wt.path) == target_path)
+        .collect();
+
+    let by_branch: Vec<_> = worktrees
+        .iter()
+        .filter(|wt| {
+            wt.branch.as_deref().is_some_and(|branch| {
+                let branch_name = branch
+                    .strip_prefix("refs/heads/")
+                    .or_else(|| branch.strip_prefix("refs/remotes/"))
+                    .unwrap_or(branch);
+                branch_name == target
+            })
+        })
+        .collect();
+
+    let name = target.trim_end_matches(['/', std::path::MAIN_SEPARATOR]);
+    let by_name: Vec<_> = worktrees
+        .iter()
+        .filter(|wt| wt.path.file_name().is_some_and(|n| n == name))
+        .collect();
+
+    let matches = [by_path, by_branch, by_name]
+        .into_iter()
+        .find(|matches| !matches.is_empty())
+        .unwrap_or_default();
+
+    let paths = |worktrees: &[&Worktree]| -> Vec<String> {
+        worktrees
+            .iter()
+            .map(|wt| wt.path.display().to_string())
+            .collect()
+    };
+    match matches.len() {
+        0 => {
+            let similar = similar(worktrees, target);
+            let mut message = format!("no worktree found matching '{}'", target);
+            if !similar.is_empty() {
+                let names: Vec<_> = similar.iter().map(|wt| display_name(wt)).collect();
+                message.push_str(&format!("; did you mean {}?", names.join(", ")));
+            }
+            Err(WtError::not_found(message)
+                .with_target(target)
+                .with_candidates(paths(&similar))
+                .into())
+        }
+        1 => Ok(matches[0]),
+        _ => Err(WtError::user_error(format!(
+            "target '{}' matches multiple worktrees:\n  {}",
+            target,
+            paths(&matches).join("\n  ")
+        ))
+        .with_reason("ambiguous")
+        .with_target(target)
+        .with_hint("pass the worktree's path instead")
+        .with_candidates(paths(&matches))
+        .into()),
+    }
+}
+
+/// How many suggestions a "did you mean" error offers.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Worktrees whose branch or directory name is close to `target`: names
+/// starting with it, containing it, or a typo or two away (ignoring case),
+/// best first.
+pub fn similar<'a>(worktrees: &'a [Worktree], target: &str) -> Vec<&'a Worktree> {
+    let target = target.to_lowercase();
+    let mut scored: Vec<(usize, &Worktree)> = worktrees
+        .iter()
+        .filter_map(|wt| {
+            let names = [
+                wt.branch_name(),
+                wt.path.file_name().and_then(|n| n.to_str()),
+            ];
+            names
+                .into_iter()
+                .flatten()
+                .filter_map(|name| similarity(&name.to_lowercase(), &target))
+                .min()
+                .map(|score| (score, wt))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| *score);
+    scored
+        .into_iter()
+        .map(|(_, wt)| wt)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// The `names` close to `target` the way [`similar`] matches worktrees, best
+/// first, e.g. branch names for a branch that doesn't exist.
+pub fn similar_names<'a>(names: &'a [String], target: &str) -> Vec<&'a str> {
+    let target = target.to_lowercase();
+    let mut scored: Vec<(usize, &str)> = names
+        .iter()
+        .filter_map(|name| similarity(&name.to_lowercase(), &target).map(|s| (s, name.as_str())))
+        .collect();
+    scored.sort_by_key(|(score, _)| *score);
+    scored
+        .into_iter()
+        .map(|(_, name)| name)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// Lower is closer; `None` if `name` isn't close to `target` at all.
+fn similarity(name: &str, target: &str) -> Option<usize> {
+    if target.is_empty() {
+        return None;
+    }
+    if name.starts_with(target) {
+        return Some(0);
+    }
+    if name.contains(target) {
+        return Some(1);
+    }
+    let distance = levenshtein(name, target);
+    (distance <= (target.chars().count() / 3).max(1)).then_some(2 + distance)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The worktree's branch name, or its path when detached.
+pub fn display_name(wt: &Worktree) -> String {
+    wt.branch_name()
+        .map(str::to_string)
+        .unwrap_or_else(|| wt.path.display().to_string())
+}
+
+/// `path` made absolute against the current directory, with symlinks
+/// resolved. Paths that don't exist (a prunable worktree) are cleaned up
+/// lexically instead: `.` and `..` components are resolved and trailing
+/// slashes dropped.
+fn normalize(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    if let Ok(canonical) = absolute.canonicalize() {
+        return canonical;
+    }
+    let mut clean = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            other => clean.push(other),
+        }
+    }
+    clean
+}
+
+/// Find the worktree containing `dir`. Worktrees can be nested inside each
+/// other (e.g. under the main checkout), so the deepest match wins.
+pub fn containing<'a>(worktrees: &'a [Worktree], dir: &Path) -> Option<&'a Worktree> {
+    worktrees
+        .iter()
+        .filter(|wt| dir.starts_with(&wt.path))
+        .max_by_key(|wt| wt.path.components().count())
+}
+
+/// The worktrees of one repository that commands can run in.
+pub struct RepoWorktrees {
+    /// Where the worktrees were listed from
+    pub root: PathBuf,
+    /// Directory name of the main worktree
+    pub name: String,
+    pub worktrees: Vec<Worktree>,
+}
+
+/// List the worktrees of the repository containing `repo_root`. Bare and
+/// missing worktrees are skipped.
+pub fn repo_worktrees(repo_root: &Path) -> Result<RepoWorktrees> {
+    let worktrees = crate::git::worktrees_porcelain(repo_root)?;
+    // The main worktree is listed first and names the repository
+    let name = worktrees
+        .first()
+        .and_then(|main| main.path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "(unknown)".to_string());
+    Ok(RepoWorktrees {
+        root: repo_root.to_path_buf(),
+        name,
+        worktrees: worktrees
+            .into_iter()
+            .filter(|wt| !wt.bare && wt.path.is_dir())
+            .collect(),
+    })
+}
+
+/// [`repo_worktrees`] of each of `repos`. A repository whose worktrees can't
+/// be listed fails the whole listing when `strict`, and is skipped with a
+/// warning otherwise.
+pub fn repos_worktrees(repos: &[PathBuf], strict: bool) -> Result<Vec<RepoWorktrees>> {
+    let mut listed = Vec::new();
+    for repo_root in repos {
+        match repo_worktrees(repo_root) {
+            Ok(repo) => listed.push(repo),
+            Err(e) if strict => {
+                return Err(WtError::git_error_with_source("failed to list worktrees", e).into());
+            }
+            Err(e) => eprintln!(
+                "Warning: failed to list worktrees for {}: {}",
+                repo_root.display(),
+                e
+            ),
+        }
+    }
+    Ok(listed)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Worktree, parse_porcelain};
+    use super::{
+        MAX_SUGGESTIONS, Worktree, containing, find_worktree, parse_porcelain, parse_porcelain_z,
+        repo_worktrees, similar, similar_names,
+    };
+    use crate::error::WtError;
+    use crate::git::test_support::{git, init_repo};
+    use std::path::Path;
     use std::path::PathBuf;
 
     #[test]
@@ -132,4 +391,197 @@ mod tests {
         assert_eq!(wt.prunable.as_deref(), Some("stale"));
         assert!(wt.bare);
     }
+
+    fn make_worktree(path: &str, branch: Option<&str>) -> Worktree {
+        Worktree {
+            path: PathBuf::from(path),
+            head: Some("abc123".to_string()),
+            branch: branch.map(|b| format!("refs/heads/{}", b)),
+            locked: false,
+            prunable: None,
+            bare: false,
+        }
+    }
+
+    #[test]
+    fn parses_nul_separated_fields() {
+        let input = "worktree /tmp/repo\0HEAD abcdef\0branch refs/heads/main\0\0\
+                     worktree /tmp/new\nline\0HEAD 123456\0detached\0locked\0\0";
+        let got = parse_porcelain_z(input).unwrap();
+        assert_eq!(got.len(), 2);
+        assert_eq!(got[0].branch.as_deref(), Some("refs/heads/main"));
+        assert_eq!(got[1].path, PathBuf::from("/tmp/new\nline"));
+        assert!(got[1].locked);
+    }
+
+    #[test]
+    fn find_by_exact_path() {
+        let worktrees = vec![
+            make_worktree("/tmp/repo", Some("main")),
+            make_worktree("/tmp/repo-feature", Some("feature")),
+        ];
+
+        let found = find_worktree(&worktrees, "/tmp/repo-feature").unwrap();
+        assert_eq!(found.path, PathBuf::from("/tmp/repo-feature"));
+    }
+
+    #[test]
+    fn find_by_branch_name() {
+        let worktrees = vec![
+            make_worktree("/tmp/repo", Some("main")),
+            make_worktree("/tmp/repo-feature", Some("feature")),
+        ];
+
+        let found = find_worktree(&worktrees, "feature").unwrap();
+        assert_eq!(found.path, PathBuf::from("/tmp/repo-feature"));
+    }
+
+    #[test]
+    fn error_on_no_match() {
+        let worktrees = vec![make_worktree("/tmp/repo", Some("main"))];
+
+        let result = find_worktree(&worktrees, "nonexistent");
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("no worktree found")
+        );
+    }
+
+    #[test]
+    fn error_on_multiple_matches() {
+        // Create two worktrees with same branch name (edge case, but possible)
+        let worktrees = vec![
+            Worktree {
+                path: PathBuf::from("/tmp/repo1"),
+                head: Some("abc123".to_string()),
+                branch: Some("refs/heads/feature".to_string()),
+                locked: false,
+                prunable: None,
+                bare: false,
+            },
+            Worktree {
+                path: PathBuf::from("/tmp/repo2"),
+                head: Some("def456".to_string()),
+                branch: Some("refs/heads/feature".to_string()),
+                locked: false,
+                prunable: None,
+                bare: false,
+            },
+        ];
+
+        let result = find_worktree(&worktrees, "feature");
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("multiple worktrees")
+        );
+    }
+
+    #[test]
+    fn find_by_unclean_symlinked_path_or_directory_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(real.join("repo-feature")).unwrap();
+        let worktrees = vec![
+            make_worktree(real.join("repo").to_str().unwrap(), Some("main")),
+            make_worktree(real.join("repo-feature").to_str().unwrap(), Some("feature")),
+        ];
+
+        let unclean = format!("{}/repo/../repo-feature/", real.display());
+        let found = find_worktree(&worktrees, &unclean).unwrap();
+        assert_eq!(found.branch.as_deref(), Some("refs/heads/feature"));
+
+        #[cfg(unix)]
+        {
+            let link = real.join("link");
+            std::os::unix::fs::symlink(real.join("repo-feature"), &link).unwrap();
+            let found = find_worktree(&worktrees, link.to_str().unwrap()).unwrap();
+            assert_eq!(found.branch.as_deref(), Some("refs/heads/feature"));
+        }
+
+        // `repo` doesn't exist on disk, and is also matched by directory name
+        let found = find_worktree(&worktrees, "repo/").unwrap();
+        assert_eq!(found.branch.as_deref(), Some("refs/heads/main"));
+    }
+
+    #[test]
+    fn suggests_close_matches() {
+        let worktrees = vec![
+            make_worktree("/tmp/repo", Some("main")),
+            make_worktree("/tmp/repo-feature-x", Some("feature-x")),
+            make_worktree("/tmp/repo-auth", Some("fix/auth-login")),
+        ];
+
+        let error = find_worktree(&worktrees, "featre-x").unwrap_err();
+        let error = error.downcast_ref::<WtError>().unwrap();
+        assert_eq!(error.candidates(), ["/tmp/repo-feature-x"]);
+        assert!(error.to_string().contains("did you mean feature-x?"));
+        let json = error.to_json();
+        assert_eq!(json["target"], "featre-x");
+        assert_eq!(json["candidates"][0], "/tmp/repo-feature-x");
+        assert!(json.get("reason").is_none());
+
+        let auth: Vec<_> = similar(&worktrees, "AUTH")
+            .iter()
+            .map(|wt| &wt.path)
+            .collect();
+        assert_eq!(auth, [Path::new("/tmp/repo-auth")]);
+        assert!(similar(&worktrees, "unrelated").is_empty());
+
+        let branches: Vec<String> = ["main", "feature-a", "feature-b", "feature-c", "feature-d"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(similar_names(&branches, "mainn"), ["main"]);
+        assert_eq!(similar_names(&branches, "feature").len(), MAX_SUGGESTIONS);
+    }
+
+    #[test]
+    fn strips_refs_heads_prefix() {
+        let worktrees = vec![make_worktree("/tmp/repo", Some("main"))];
+
+        // Should find it by branch name without prefix
+        let found = find_worktree(&worktrees, "main").unwrap();
+        assert_eq!(found.path, PathBuf::from("/tmp/repo"));
+    }
+
+    #[test]
+    fn containing_prefers_nested_worktree() {
+        let worktrees = vec![
+            make_worktree("/tmp/repo", Some("refs/heads/main")),
+            make_worktree("/tmp/repo/.worktrees/feature", Some("refs/heads/feature")),
+        ];
+
+        let found = containing(&worktrees, Path::new("/tmp/repo/.worktrees/feature/src"));
+        assert_eq!(
+            found.unwrap().path,
+            PathBuf::from("/tmp/repo/.worktrees/feature")
+        );
+        let found = containing(&worktrees, Path::new("/tmp/repo/src"));
+        assert_eq!(found.unwrap().path, PathBuf::from("/tmp/repo"));
+        assert!(containing(&worktrees, Path::new("/tmp/other")).is_none());
+    }
+
+    #[test]
+    fn repo_worktrees_skips_missing_worktrees_and_names_the_main_one() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main = tmp.path().join("project");
+        init_repo(&main);
+        git(&main, &["worktree", "add", "-q", "../feature"]);
+        git(&main, &["worktree", "add", "-q", "../gone"]);
+        std::fs::remove_dir_all(tmp.path().join("gone")).unwrap();
+
+        let repo = repo_worktrees(&tmp.path().join("feature")).unwrap();
+        assert_eq!(repo.name, "project");
+        let names: Vec<_> = repo
+            .worktrees
+            .iter()
+            .filter_map(|wt| wt.branch_name())
+            .collect();
+        assert_eq!(names, ["main", "feature"]);
+    }
 }
@@ -0,0 +1,94 @@
+//! Open worktrees in zellij tabs or per-worktree sessions.
+
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+
+use crate::error::WtError;
+use crate::{config, process, tmux};
+
+/// Open the worktree matching `target` (branch name or path) in zellij.
+pub fn open_target(target: &str) -> Result<()> {
+    let path = crate::preview::resolve_target(target)?;
+    open(&path)
+}
+
+/// Open the worktree at `path` in zellij: a new tab inside zellij, otherwise
+/// its own session (attached if it is already running).
+pub fn open(path: &Path) -> Result<()> {
+    let layout = config::load()
+        .map_err(|e| WtError::config_error_with_source("failed to load config", e))?
+        .zellij
+        .layout;
+    let name = session_name(&tmux::repo_name(path), &tmux::branch_name(path));
+    let path_str = path.to_string_lossy();
+
+    if env::var_os("ZELLIJ").is_some() {
+        let mut args = vec![
+            "action",
+            "new-tab",
+            "--cwd",
+            path_str.as_ref(),
+            "--name",
+            &name,
+        ];
+        if !layout.is_empty() {
+            args.extend(["--layout", &layout]);
+        }
+        process::run("zellij", &args, None)
+            .map_err(|e| WtError::user_error_with_source("failed to open a zellij tab", e))?;
+        return Ok(());
+    }
+
+    let args = if session_exists(&name) {
+        vec!["attach", name.as_str()]
+    } else {
+        let mut args = vec!["--session", name.as_str()];
+        if !layout.is_empty() {
+            args.extend(["--layout", &layout]);
+        }
+        args
+    };
+    // zellij takes over the terminal until the session is detached; keep
+    // stdout off the shell wrapper's captured output
+    let status = Command::new("zellij")
+        .args(&args)
+        .current_dir(path)
+        .stdin(Stdio::inherit())
+        .stdout(io::stderr())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| WtError::io_error_with_source("failed to execute zellij", e.into()))?;
+    if !status.success() {
+        return Err(WtError::user_error(format!("zellij session {} failed", name)).into());
+    }
+    Ok(())
+}
+
+fn session_exists(name: &str) -> bool {
+    // Fails when no sessions are running
+    process::run_stdout(
+        "zellij",
+        &["list-sessions", "--short", "--no-formatting"],
+        None,
+    )
+    .is_ok_and(|out| out.lines().any(|line| line.trim() == name))
+}
+
+/// Session name "repo-branch"; zellij session names can't contain `/`.
+fn session_name(repo: &str, branch: &str) -> String {
+    format!("{}-{}", repo, branch).replace('/', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_names_have_no_slashes() {
+        assert_eq!(session_name("app", "feature/x"), "app-feature-x");
+    }
+}